@@ -1,9 +1,18 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
-use crate::license::{hardware, service, storage};
+use crate::license::{hardware, service, storage, token};
 use super::EstadoApp;
 
+/// Quantos dias o modo offline (sem token válido, servidor inalcançável)
+/// confia no registro local antes de exigir uma reverificação online bem-
+/// sucedida — contados a partir de `last_verified_at` (ou `installed_at`
+/// se o registro for anterior a este campo). Sem este limite, um usuário
+/// que nunca mais conecta à internet (ou um `license.dat` forjado com um
+/// fingerprint que bate) teria licença válida para sempre.
+const JANELA_GRACA_OFFLINE_DIAS: i64 = 30;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LicenseStatus {
     pub is_licensed: bool,
@@ -12,12 +21,40 @@ pub struct LicenseStatus {
     pub message: String,
 }
 
+/// Marca `record` como verificado agora e persiste — avança a janela de
+/// graça offline. Falhas ao salvar só geram um log: não verificar de novo
+/// não deveria derrubar uma licença que acabou de ser confirmada.
+fn marcar_verificado_agora(app_data_dir: &str, record: &mut storage::InstallationRecord) {
+    record.last_verified_at = Some(Utc::now().to_rfc3339());
+    if let Err(e) = storage::save(app_data_dir, record) {
+        log::warn!("Falha ao atualizar last_verified_at da licença: {}", e);
+    }
+}
+
+/// Dias corridos desde a última verificação bem-sucedida (`last_verified_at`,
+/// ou `installed_at` para registros salvos antes deste campo existir). Trata
+/// datas malformadas como "graça já esgotada", nunca como "graça infinita".
+fn dias_desde_ultima_verificacao(record: &storage::InstallationRecord) -> i64 {
+    let referencia = record
+        .last_verified_at
+        .as_deref()
+        .unwrap_or(&record.installed_at);
+
+    match DateTime::parse_from_rfc3339(referencia) {
+        Ok(data) => (Utc::now() - data.with_timezone(&Utc)).num_days(),
+        Err(_) => {
+            log::warn!("Data de referência da licença offline malformada: '{}'", referencia);
+            JANELA_GRACA_OFFLINE_DIAS + 1
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn check_license(estado: State<'_, EstadoApp>) -> Result<LicenseStatus, String> {
     let app_data_dir = estado.diretorio_app.to_string_lossy().to_string();
     let hardware_id = hardware::get_hardware_id();
 
-    let record = match storage::load(&app_data_dir) {
+    let mut record = match storage::load(&app_data_dir) {
         Some(r) => r,
         None => {
             return Ok(LicenseStatus {
@@ -29,10 +66,29 @@ pub async fn check_license(estado: State<'_, EstadoApp>) -> Result<LicenseStatus
         }
     };
 
+    if let (Some(payload), Some(signature)) = (&record.token_payload, &record.token_signature) {
+        let token = token::LicenseToken {
+            payload: payload.clone(),
+            signature: signature.clone(),
+        };
+        if let Some(dados) = token::verificar_token(&token, &hardware_id) {
+            log::info!("Licença verificada offline via token para {}", dados.email);
+            marcar_verificado_agora(&app_data_dir, &mut record);
+            return Ok(LicenseStatus {
+                is_licensed: true,
+                email: record.email,
+                hardware_id,
+                message: "Licença ativa (verificada offline).".into(),
+            });
+        }
+        log::warn!("Token de ativação local não verificou — caindo para verificação online");
+    }
+
     let result = service::verify_license(&record.email, &hardware_id).await;
 
     if result.success {
         log::info!("Licença verificada com sucesso para {}", record.email);
+        marcar_verificado_agora(&app_data_dir, &mut record);
         return Ok(LicenseStatus {
             is_licensed: true,
             email: record.email,
@@ -42,7 +98,18 @@ pub async fn check_license(estado: State<'_, EstadoApp>) -> Result<LicenseStatus
     }
 
     if result.code == "TIMEOUT" || result.code == "NETWORK_ERROR" {
-        if record.machine_fingerprint != hardware_id {
+        // Com componentes de hardware gravados, tolera pequenas mudanças
+        // (troca de disco, atualização de BIOS) via comparação ponderada;
+        // sem eles (registros antigos), cai de volta para a comparação
+        // exata por `machine_fingerprint`. Ver `hardware::fingerprint_matches`.
+        let pertence_a_esta_maquina = match &record.machine_components {
+            Some(componentes_gravados) => {
+                hardware::fingerprint_matches(componentes_gravados, &hardware::get_hardware_components())
+            }
+            None => record.machine_fingerprint == hardware_id,
+        };
+
+        if !pertence_a_esta_maquina {
             log::warn!("Licença local não pertence a esta máquina (fingerprint mismatch)");
             storage::clear(&app_data_dir);
             return Ok(LicenseStatus {
@@ -53,15 +120,30 @@ pub async fn check_license(estado: State<'_, EstadoApp>) -> Result<LicenseStatus
             });
         }
 
+        let dias_offline = dias_desde_ultima_verificacao(&record);
+        if dias_offline > JANELA_GRACA_OFFLINE_DIAS {
+            log::warn!(
+                "Janela de graça offline esgotada ({} dias sem verificação online)",
+                dias_offline
+            );
+            return Ok(LicenseStatus {
+                is_licensed: false,
+                email: String::new(),
+                hardware_id,
+                message: "Licença offline expirou. Conecte-se à internet para reverificar.".into(),
+            });
+        }
+
         log::warn!(
             "Verificação online falhou ({}), usando licença local",
             result.code
         );
+        let dias_restantes = JANELA_GRACA_OFFLINE_DIAS - dias_offline;
         return Ok(LicenseStatus {
             is_licensed: true,
             email: record.email,
             hardware_id,
-            message: "Licença ativa (modo offline).".into(),
+            message: format!("Licença ativa (modo offline — expira em {} dias).", dias_restantes),
         });
     }
 
@@ -96,11 +178,17 @@ pub async fn activate_license(
     let result = service::activate_license(&email, &hardware_id).await;
 
     if result.success {
+        let agora = Utc::now().to_rfc3339();
         let record = storage::InstallationRecord {
             email: email.trim().to_lowercase(),
             license_key: result.code.clone(),
             machine_fingerprint: hardware_id.clone(),
-            installed_at: chrono::Utc::now().to_rfc3339(),
+            installed_at: agora.clone(),
+            token_payload: result.token_payload.clone(),
+            token_signature: result.token_signature.clone(),
+            record_signature: result.record_signature.clone(),
+            machine_components: Some(hardware::get_hardware_components()),
+            last_verified_at: Some(agora),
         };
 
         storage::save(&app_data_dir, &record)
@@ -7,9 +7,13 @@
 //
 // Convenções:
 // - Comandos retornam Result<T, String> (Tauri exige String para erros)
-// - O Mutex é travado apenas pelo tempo necessário (lock curto)
+// - Comandos só-leitura usam `estado.banco.leitor()`; comandos que
+//   mutam o cofre usam `estado.banco.escritor()` (ver db::connection::PoolConexoes)
+// - A conexão é travada apenas pelo tempo necessário (lock curto)
 // - Auditoria é registrada após operações de escrita importantes
-// - Nenhum comando faz chamadas de rede (app 100% offline)
+// - Nenhum comando faz chamadas de rede automaticamente; a única exceção é
+//   a seção 13 (Sincronização Remota), que só transmite dados quando o
+//   usuário dispara o comando explicitamente (ver services::sincronizacao)
 //
 // Organização:
 // 1. Pastas
@@ -24,38 +28,67 @@
 // 10. Auditoria
 // 11. Configurações
 // 12. Utilitários
+// 13. Sincronização Remota (SFTP/SCP, opcional)
+// 14. Operações em Lote (Transacional)
+// 15. Recuperação de PIN (códigos de emergência, Shamir Secret Sharing)
+// 16. Cifragem em Repouso (selagem de conteudo_nota/descricao, sessão)
 // =============================================================================
 
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::Arc;
 
 use rusqlite::Connection;
 use tauri::State;
 
+use crate::crypto::{self, shamir};
+use crate::db::connection::PoolConexoes;
 use crate::db::models::*;
 use crate::db::queries;
-use crate::services::{auditoria, backup, armazenamento, exportacao};
+use crate::services::{
+    arquivo, auditoria, backend_remoto, backup, armazenamento, cifragem, exportacao, http_segredos,
+    manutencao, reparo_fts, rpc, sincronizacao,
+};
 
 pub mod license_commands;
 
 // =============================================================================
 // Estado Compartilhado do Aplicativo
 // =============================================================================
-// O banco de dados é envolvido em Mutex para acesso thread-safe.
-// Tauri pode chamar comandos de threads diferentes, então precisamos
-// garantir que apenas uma thread acesse o banco por vez.
+// O banco de dados é acessado através de um PoolConexoes (ver
+// `db::connection`): um pequeno conjunto de conexões de leitura mais uma
+// conexão de escrita dedicada, todas em modo WAL. Tauri pode chamar
+// comandos de threads diferentes; comandos só-leitura pegam uma conexão de
+// leitura (`pool.leitor()`) e rodam em paralelo entre si, enquanto toda
+// mutação passa pela conexão de escrita (`pool.escritor()`), preservando a
+// invariante de escritor único do SQLite.
 //
 // O diretorio_app é o caminho onde o banco e anexos são armazenados.
 // É definido na inicialização e não muda durante a execução.
 // =============================================================================
 
 /// Estado global do aplicativo, gerenciado pelo Tauri.
-/// Contém a conexão com o banco de dados e o diretório de dados.
+/// Contém o pool de conexões com o banco de dados e o diretório de dados.
 pub struct EstadoApp {
-    /// Conexão SQLite protegida por Mutex (acesso thread-safe)
-    pub banco: Mutex<Connection>,
+    /// Pool de conexões SQLite (leitores + escritor), ver `db::connection::PoolConexoes`.
+    /// É um `Arc` (e não um `PoolConexoes` direto) porque as fachadas de rede
+    /// locais opcionais (`servidor_rpc`/`servidor_segredos`) precisam de uma
+    /// referência `'static` para levar para dentro da tarefa em segundo
+    /// plano do listener — `State<'_, EstadoApp>` só vive pela duração do
+    /// comando que o recebeu.
+    pub banco: Arc<PoolConexoes>,
     /// Diretório raiz do aplicativo (onde ficam banco e anexos)
     pub diretorio_app: PathBuf,
+    /// Chave de dados da selagem de campos, se a sessão já tiver sido
+    /// desbloqueada (ver `services::cifragem`). `Arc` pelo mesmo motivo de
+    /// `banco`: `servidor_segredos` precisa levar uma referência `'static`
+    /// para dentro da tarefa do listener HTTP.
+    pub sessao_cifragem: Arc<cifragem::EstadoSessao>,
+    /// Listener WebSocket local da fachada JSON-RPC, ver `services::rpc`.
+    /// Não inicia sozinho — só quando `iniciar_servidor_rpc` é chamado.
+    pub servidor_rpc: rpc::ServidorRpc,
+    /// Listener HTTP local da API REST de segredos, ver `services::http_segredos`.
+    /// Não inicia sozinho — só quando `iniciar_servidor_http_segredos` é chamado.
+    pub servidor_segredos: http_segredos::ServidorHttpSegredos,
 }
 
 /// Macro auxiliar para converter anyhow::Error em String (exigido pelo Tauri).
@@ -71,7 +104,7 @@ fn erro_para_string(e: anyhow::Error) -> String {
 /// Lista todas as pastas do cofre em ordem alfabética pelo caminho.
 #[tauri::command]
 pub fn listar_pastas(estado: State<'_, EstadoApp>) -> Result<Vec<Pasta>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::listar_pastas(&conn).map_err(erro_para_string)
 }
 
@@ -83,7 +116,7 @@ pub fn criar_pasta(
     nome: String,
     pasta_pai_id: Option<String>,
 ) -> Result<Pasta, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let dados = NovaPasta { nome: nome.clone(), pasta_pai_id };
     let pasta = queries::criar_pasta(&conn, &dados).map_err(erro_para_string)?;
@@ -94,6 +127,9 @@ pub fn criar_pasta(
         Some(&pasta.id),
         Some(&format!("{{\"nome\": \"{}\"}}", nome)),
     );
+    if let Ok(payload) = auditoria::serializar_payload(&pasta) {
+        let _ = auditoria::registrar_mutacao(&conn, "pasta", &pasta.id, OperacaoMutacao::Criacao, None, Some(&payload));
+    }
 
     Ok(pasta)
 }
@@ -106,7 +142,7 @@ pub fn renomear_pasta(
     id: String,
     novo_nome: String,
 ) -> Result<Pasta, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let pasta = queries::renomear_pasta(&conn, &id, &novo_nome).map_err(erro_para_string)?;
 
@@ -115,6 +151,9 @@ pub fn renomear_pasta(
         Some(&id),
         Some(&format!("{{\"novo_nome\": \"{}\"}}", novo_nome)),
     );
+    if let Ok(payload) = auditoria::serializar_payload(&pasta) {
+        let _ = auditoria::registrar_mutacao(&conn, "pasta", &id, OperacaoMutacao::Atualizacao, None, Some(&payload));
+    }
 
     Ok(pasta)
 }
@@ -126,7 +165,7 @@ pub fn mover_pasta(
     id: String,
     novo_pai_id: Option<String>,
 ) -> Result<Pasta, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let pasta = queries::mover_pasta(&conn, &id, novo_pai_id.as_deref())
         .map_err(erro_para_string)?;
@@ -136,6 +175,9 @@ pub fn mover_pasta(
         Some(&id),
         Some(&format!("{{\"novo_pai_id\": {:?}}}", novo_pai_id)),
     );
+    if let Ok(payload) = auditoria::serializar_payload(&pasta) {
+        let _ = auditoria::registrar_mutacao(&conn, "pasta", &id, OperacaoMutacao::Atualizacao, None, Some(&payload));
+    }
 
     Ok(pasta)
 }
@@ -147,7 +189,7 @@ pub fn excluir_pasta(
     estado: State<'_, EstadoApp>,
     id: String,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     // Obter nome antes de excluir (para auditoria)
     let pasta = queries::obter_pasta_por_id(&conn, &id).map_err(erro_para_string)?;
@@ -158,6 +200,7 @@ pub fn excluir_pasta(
         Some(&id),
         Some(&format!("{{\"nome\": \"{}\"}}", pasta.nome)),
     );
+    let _ = auditoria::registrar_mutacao(&conn, "pasta", &id, OperacaoMutacao::Exclusao, None, None);
 
     Ok(())
 }
@@ -172,17 +215,33 @@ pub fn listar_itens(
     estado: State<'_, EstadoApp>,
     pasta_id: String,
 ) -> Result<Vec<Item>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::listar_itens_por_pasta(&conn, &pasta_id).map_err(erro_para_string)
 }
 
+/// Versão paginada de `listar_itens`, para pastas com muitos itens.
+/// `limite` padrão é 50; `cursor` é o `proximo_cursor` da página anterior
+/// (omitido na primeira página).
+#[tauri::command]
+pub fn listar_itens_paginado(
+    estado: State<'_, EstadoApp>,
+    pasta_id: String,
+    limite: Option<i64>,
+    cursor: Option<String>,
+) -> Result<Pagina<Item>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    let limite = limite.unwrap_or(50);
+    queries::listar_itens_por_pasta_paginado(&conn, &pasta_id, limite, cursor.as_deref())
+        .map_err(erro_para_string)
+}
+
 /// Obtém um item específico com todos os dados associados.
 #[tauri::command]
 pub fn obter_item(
     estado: State<'_, EstadoApp>,
     id: String,
 ) -> Result<Item, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::obter_item_por_id(&conn, &id).map_err(erro_para_string)
 }
 
@@ -192,7 +251,7 @@ pub fn criar_item(
     estado: State<'_, EstadoApp>,
     dados: NovoItem,
 ) -> Result<Item, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let item = queries::criar_item(&conn, &dados).map_err(erro_para_string)?;
 
@@ -201,6 +260,9 @@ pub fn criar_item(
         Some(&item.id),
         Some(&format!("{{\"titulo\": \"{}\", \"tipo\": \"{}\"}}", dados.titulo, dados.tipo)),
     );
+    if let Ok(payload) = auditoria::serializar_payload(&item) {
+        let _ = auditoria::registrar_mutacao(&conn, "item", &item.id, OperacaoMutacao::Criacao, None, Some(&payload));
+    }
 
     Ok(item)
 }
@@ -212,7 +274,10 @@ pub fn atualizar_item(
     id: String,
     dados: AtualizacaoItem,
 ) -> Result<Item, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let antes = queries::obter_item_por_id(&conn, &id).ok();
+    let payload_antes = antes.and_then(|antes| auditoria::serializar_payload(&antes).ok());
 
     let item = queries::atualizar_item(&conn, &id, &dados).map_err(erro_para_string)?;
 
@@ -220,6 +285,39 @@ pub fn atualizar_item(
         &conn, "atualizacao", "item",
         Some(&id), None,
     );
+    if let Ok(payload) = auditoria::serializar_payload(&item) {
+        let _ = auditoria::registrar_mutacao(
+            &conn, "item", &id, OperacaoMutacao::Atualizacao, payload_antes.as_deref(), Some(&payload),
+        );
+    }
+
+    Ok(item)
+}
+
+/// Avança um item recorrente para sua próxima ocorrência (ver
+/// `services::recorrencia` e `queries::avancar_ocorrencia_item`). Falha se
+/// o item não tiver `regra_recorrencia` ou `data_vencimento`.
+#[tauri::command]
+pub fn avancar_ocorrencia_item(
+    estado: State<'_, EstadoApp>,
+    id: String,
+) -> Result<Item, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let antes = queries::obter_item_por_id(&conn, &id).ok();
+    let payload_antes = antes.and_then(|antes| auditoria::serializar_payload(&antes).ok());
+
+    let item = queries::avancar_ocorrencia_item(&conn, &id).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "atualizacao", "item",
+        Some(&id), Some("{\"acao\": \"avancar_ocorrencia\"}"),
+    );
+    if let Ok(payload) = auditoria::serializar_payload(&item) {
+        let _ = auditoria::registrar_mutacao(
+            &conn, "item", &id, OperacaoMutacao::Atualizacao, payload_antes.as_deref(), Some(&payload),
+        );
+    }
 
     Ok(item)
 }
@@ -230,16 +328,18 @@ pub fn excluir_item(
     estado: State<'_, EstadoApp>,
     id: String,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     // Obter título antes de excluir (para auditoria)
     let item = queries::obter_item_por_id(&conn, &id).map_err(erro_para_string)?;
 
     // Remover anexos físicos do armazenamento antes de excluir do banco
     for anexo in &item.anexos {
-        let _ = armazenamento::remover_anexo(&estado.diretorio_app, &anexo.caminho_interno);
+        let _ = armazenamento::remover_anexo(&estado.diretorio_app, &conn, anexo);
     }
 
+    let payload_antes = auditoria::serializar_payload(&item).ok();
+
     queries::excluir_item(&conn, &id).map_err(erro_para_string)?;
 
     let _ = auditoria::registrar(
@@ -247,10 +347,137 @@ pub fn excluir_item(
         Some(&id),
         Some(&format!("{{\"titulo\": \"{}\"}}", item.titulo)),
     );
+    let _ = auditoria::registrar_mutacao(
+        &conn, "item", &id, OperacaoMutacao::Exclusao, payload_antes.as_deref(), None,
+    );
 
     Ok(())
 }
 
+/// Move vários itens para uma pasta de uma vez, em blocos internamente
+/// para lidar com listas grandes sem estourar o limite de variáveis do
+/// SQLite. Retorna quantos itens foram movidos.
+#[tauri::command]
+pub fn mover_itens_em_lote(
+    estado: State<'_, EstadoApp>,
+    item_ids: Vec<String>,
+    nova_pasta_id: String,
+) -> Result<usize, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let payloads_antes: std::collections::HashMap<String, String> = item_ids.iter()
+        .filter_map(|item_id| {
+            let item = queries::obter_item_por_id(&conn, item_id).ok()?;
+            let payload = auditoria::serializar_payload(&item).ok()?;
+            Some((item_id.clone(), payload))
+        })
+        .collect();
+
+    let total = queries::mover_itens_em_lote(&conn, &item_ids, &nova_pasta_id).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "atualizacao", "item",
+        None,
+        Some(&format!("{{\"acao\": \"mover_em_lote\", \"total\": {}, \"pasta_id\": \"{}\"}}", total, nova_pasta_id)),
+    );
+    for item_id in &item_ids {
+        if let Ok(item) = queries::obter_item_por_id(&conn, item_id) {
+            if let Ok(payload) = auditoria::serializar_payload(&item) {
+                let _ = auditoria::registrar_mutacao(
+                    &conn, "item", item_id, OperacaoMutacao::Atualizacao,
+                    payloads_antes.get(item_id).map(String::as_str), Some(&payload),
+                );
+            }
+        }
+    }
+
+    Ok(total)
+}
+
+/// Exclui vários itens de uma vez (e seus anexos físicos), em blocos
+/// internamente para lidar com listas grandes. Retorna quantos itens
+/// foram excluídos.
+#[tauri::command]
+pub fn excluir_itens_em_lote(
+    estado: State<'_, EstadoApp>,
+    item_ids: Vec<String>,
+) -> Result<usize, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    // Remover anexos físicos do armazenamento antes de excluir do banco
+    let anexos = queries::listar_anexos_por_itens(&conn, &item_ids).map_err(erro_para_string)?;
+    for anexo in &anexos {
+        let _ = armazenamento::remover_anexo(&estado.diretorio_app, &conn, anexo);
+    }
+
+    let payloads_antes: std::collections::HashMap<String, String> = item_ids.iter()
+        .filter_map(|item_id| {
+            let item = queries::obter_item_por_id(&conn, item_id).ok()?;
+            let payload = auditoria::serializar_payload(&item).ok()?;
+            Some((item_id.clone(), payload))
+        })
+        .collect();
+
+    let total = queries::excluir_itens_em_lote(&conn, &item_ids).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "exclusao", "item",
+        None,
+        Some(&format!("{{\"acao\": \"excluir_em_lote\", \"total\": {}}}", total)),
+    );
+    for item_id in &item_ids {
+        let _ = auditoria::registrar_mutacao(
+            &conn, "item", item_id, OperacaoMutacao::Exclusao,
+            payloads_antes.get(item_id).map(String::as_str), None,
+        );
+    }
+
+    Ok(total)
+}
+
+/// Lista o histórico de revisões de um item, da mais recente para a mais
+/// antiga. Uma revisão é criada automaticamente a cada `atualizar_item`
+/// (ver `queries::atualizar_item`); a quantidade retida é limitada pela
+/// configuração `max_revisoes`.
+#[tauri::command]
+pub fn listar_revisoes(
+    estado: State<'_, EstadoApp>,
+    item_id: String,
+) -> Result<Vec<RevisaoItem>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    queries::listar_revisoes(&conn, &item_id).map_err(erro_para_string)
+}
+
+/// Restaura um item para o estado registrado em `revisao`. O estado atual
+/// do item também é snapshotado como uma nova revisão antes da reversão,
+/// então restaurar uma restauração anterior também é possível.
+#[tauri::command]
+pub fn restaurar_revisao(
+    estado: State<'_, EstadoApp>,
+    item_id: String,
+    revisao: i64,
+) -> Result<Item, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let antes = queries::obter_item_por_id(&conn, &item_id).ok();
+    let payload_antes = antes.and_then(|antes| auditoria::serializar_payload(&antes).ok());
+
+    let item = queries::restaurar_revisao(&conn, &item_id, revisao).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "restauracao", "item",
+        Some(&item_id),
+        Some(&format!("{{\"revisao\": {}}}", revisao)),
+    );
+    if let Ok(payload) = auditoria::serializar_payload(&item) {
+        let _ = auditoria::registrar_mutacao(
+            &conn, "item", &item_id, OperacaoMutacao::Atualizacao, payload_antes.as_deref(), Some(&payload),
+        );
+    }
+
+    Ok(item)
+}
+
 // =============================================================================
 // 3. TAGS — CRUD para categorização
 // =============================================================================
@@ -258,7 +485,7 @@ pub fn excluir_item(
 /// Lista todas as tags do cofre.
 #[tauri::command]
 pub fn listar_tags(estado: State<'_, EstadoApp>) -> Result<Vec<Tag>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::listar_tags(&conn).map_err(erro_para_string)
 }
 
@@ -269,7 +496,7 @@ pub fn criar_tag(
     nome: String,
     cor: Option<String>,
 ) -> Result<Tag, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let dados = NovaTag { nome: nome.clone(), cor };
     let tag = queries::criar_tag(&conn, &dados).map_err(erro_para_string)?;
@@ -279,6 +506,9 @@ pub fn criar_tag(
         Some(&tag.id),
         Some(&format!("{{\"nome\": \"{}\"}}", nome)),
     );
+    if let Ok(payload) = auditoria::serializar_payload(&tag) {
+        let _ = auditoria::registrar_mutacao(&conn, "tag", &tag.id, OperacaoMutacao::Criacao, None, Some(&payload));
+    }
 
     Ok(tag)
 }
@@ -291,10 +521,16 @@ pub fn atualizar_tag(
     nome: String,
     cor: Option<String>,
 ) -> Result<Tag, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let dados = NovaTag { nome, cor };
-    queries::atualizar_tag(&conn, &id, &dados).map_err(erro_para_string)
+    let tag = queries::atualizar_tag(&conn, &id, &dados).map_err(erro_para_string)?;
+
+    if let Ok(payload) = auditoria::serializar_payload(&tag) {
+        let _ = auditoria::registrar_mutacao(&conn, "tag", &id, OperacaoMutacao::Atualizacao, None, Some(&payload));
+    }
+
+    Ok(tag)
 }
 
 /// Exclui uma tag. Remove automaticamente as associações com itens.
@@ -303,13 +539,14 @@ pub fn excluir_tag(
     estado: State<'_, EstadoApp>,
     id: String,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     queries::excluir_tag(&conn, &id).map_err(erro_para_string)?;
 
     let _ = auditoria::registrar(
         &conn, "exclusao", "tag", Some(&id), None,
     );
+    let _ = auditoria::registrar_mutacao(&conn, "tag", &id, OperacaoMutacao::Exclusao, None, None);
 
     Ok(())
 }
@@ -326,13 +563,13 @@ pub fn adicionar_anexo(
     caminho_arquivo: String,
     item_id: String,
 ) -> Result<Anexo, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let caminho = std::path::Path::new(&caminho_arquivo);
 
     // Salvar arquivo no armazenamento interno
     let anexo = armazenamento::salvar_anexo(
-        &estado.diretorio_app, caminho, Some(&item_id), None,
+        &estado.diretorio_app, &conn, caminho, Some(&item_id), None,
     ).map_err(erro_para_string)?;
 
     // Registrar no banco de dados
@@ -353,13 +590,13 @@ pub fn remover_anexo(
     estado: State<'_, EstadoApp>,
     id: String,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     // Obter dados do anexo antes de excluir
     let anexo = queries::obter_anexo_por_id(&conn, &id).map_err(erro_para_string)?;
 
     // Remover arquivo físico
-    armazenamento::remover_anexo(&estado.diretorio_app, &anexo.caminho_interno)
+    armazenamento::remover_anexo(&estado.diretorio_app, &conn, &anexo)
         .map_err(erro_para_string)?;
 
     // Remover registro do banco
@@ -381,7 +618,7 @@ pub fn abrir_anexo(
     estado: State<'_, EstadoApp>,
     id: String,
 ) -> Result<String, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
 
     let anexo = queries::obter_anexo_por_id(&conn, &id).map_err(erro_para_string)?;
     let caminho = armazenamento::obter_caminho_completo_anexo(
@@ -397,7 +634,7 @@ pub fn listar_anexos(
     estado: State<'_, EstadoApp>,
     item_id: String,
 ) -> Result<Vec<Anexo>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::listar_anexos_por_item(&conn, &item_id).map_err(erro_para_string)
 }
 
@@ -411,7 +648,7 @@ pub fn listar_tarefas(
     estado: State<'_, EstadoApp>,
     item_id: String,
 ) -> Result<Vec<TarefaChecklist>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::listar_tarefas_por_item(&conn, &item_id).map_err(erro_para_string)
 }
 
@@ -423,7 +660,7 @@ pub fn criar_tarefa(
     titulo: String,
     ordem: Option<i32>,
 ) -> Result<TarefaChecklist, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     let dados = NovaTarefa { item_id, titulo, ordem };
     queries::criar_tarefa(&conn, &dados).map_err(erro_para_string)
@@ -436,7 +673,7 @@ pub fn atualizar_tarefa(
     id: String,
     dados: AtualizacaoTarefa,
 ) -> Result<TarefaChecklist, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
     queries::atualizar_tarefa(&conn, &id, &dados).map_err(erro_para_string)
 }
 
@@ -446,7 +683,7 @@ pub fn excluir_tarefa(
     estado: State<'_, EstadoApp>,
     id: String,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
     queries::excluir_tarefa(&conn, &id).map_err(erro_para_string)
 }
 
@@ -457,7 +694,7 @@ pub fn reordenar_tarefas(
     estado: State<'_, EstadoApp>,
     ordens: Vec<(String, i32)>,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
     queries::reordenar_tarefas(&conn, &ordens).map_err(erro_para_string)
 }
 
@@ -468,26 +705,130 @@ pub fn marcar_tarefa(
     id: String,
     concluida: bool,
 ) -> Result<TarefaChecklist, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
     queries::marcar_tarefa_concluida(&conn, &id, concluida).map_err(erro_para_string)
 }
 
+/// Adiciona uma dependência: `tarefa_id` passa a depender de `depende_de_id`.
+/// Recusa a operação se ela fechar um ciclo entre tarefas.
+#[tauri::command]
+pub fn adicionar_dependencia(
+    estado: State<'_, EstadoApp>,
+    tarefa_id: String,
+    depende_de_id: String,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    queries::adicionar_dependencia(&conn, &tarefa_id, &depende_de_id).map_err(erro_para_string)
+}
+
+/// Remove uma dependência entre duas tarefas.
+#[tauri::command]
+pub fn remover_dependencia(
+    estado: State<'_, EstadoApp>,
+    tarefa_id: String,
+    depende_de_id: String,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    queries::remover_dependencia(&conn, &tarefa_id, &depende_de_id).map_err(erro_para_string)
+}
+
+/// Lista os IDs das tarefas das quais `tarefa_id` depende diretamente.
+#[tauri::command]
+pub fn listar_dependencias(
+    estado: State<'_, EstadoApp>,
+    tarefa_id: String,
+) -> Result<Vec<String>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    queries::listar_dependencias(&conn, &tarefa_id).map_err(erro_para_string)
+}
+
+/// Registra uma entrada de tempo trabalhado em uma tarefa.
+#[tauri::command]
+pub fn registrar_tempo(
+    estado: State<'_, EstadoApp>,
+    tarefa_id: String,
+    data_registro: String,
+    mensagem: Option<String>,
+    duracao: Duracao,
+) -> Result<EntradaTempo, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    let dados = NovaEntradaTempo { tarefa_id, data_registro, mensagem, duracao };
+    queries::registrar_tempo(&conn, &dados).map_err(erro_para_string)
+}
+
+/// Lista as entradas de tempo lançadas em uma tarefa.
+#[tauri::command]
+pub fn listar_tempo_por_tarefa(
+    estado: State<'_, EstadoApp>,
+    tarefa_id: String,
+) -> Result<Vec<EntradaTempo>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    queries::listar_tempo_por_tarefa(&conn, &tarefa_id).map_err(erro_para_string)
+}
+
+/// Soma o tempo lançado em todas as tarefas de um item.
+#[tauri::command]
+pub fn total_tempo_por_item(
+    estado: State<'_, EstadoApp>,
+    item_id: String,
+) -> Result<TotalTempoItem, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    queries::total_tempo_por_item(&conn, &item_id).map_err(erro_para_string)
+}
+
 // =============================================================================
 // 6. BUSCA — Full-Text Search
 // =============================================================================
 
-/// Busca itens no cofre usando Full-Text Search (FTS5).
+/// Busca itens no cofre usando Full-Text Search (FTS5), com relevância
+/// calculada por `bm25()` (ver `PesosBusca`) e trechos destacados nativos
+/// do FTS5 (`snippet()`/`highlight()`).
 /// O termo é pesquisado em título, descrição e conteúdo de notas.
 /// Filtros adicionais podem restringir os resultados.
+/// `pesos`, se omitido, usa `PesosBusca::default()` (título pesa mais que
+/// descrição, que pesa mais que o corpo da nota).
+/// Cada resultado traz `trecho_titulo`/`trecho_conteudo`: excertos em HTML
+/// (já com `<mark>` no termo e já escapados) em torno da ocorrência do
+/// termo buscado no título e no conteúdo da nota, respectivamente, e
+/// `titulo_destacado`: o título inteiro com o termo destacado da mesma
+/// forma. `termo` é interpretado como entrada livre do usuário, não
+/// sintaxe FTS5 crua — ver `db::queries::construir_consulta_fts`, que o
+/// traduz para uma consulta FTS5 segura (aspas em termos soltos, suporte a
+/// `prefixo*`, `"frases exatas"` e `AND`/`OR`/`NOT`).
 #[tauri::command]
 pub fn buscar_itens(
     estado: State<'_, EstadoApp>,
     termo: String,
     filtros: Option<FiltrosBusca>,
+    pesos: Option<PesosBusca>,
 ) -> Result<Vec<ResultadoBusca>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     let filtros = filtros.unwrap_or_default();
-    queries::buscar_fts(&conn, &termo, &filtros).map_err(erro_para_string)
+    let pesos = pesos.unwrap_or_default();
+    let resultados = queries::buscar_fts(&conn, &termo, &filtros, &pesos).map_err(erro_para_string)?;
+
+    Ok(resultados)
+}
+
+/// Versão paginada de `buscar_itens`, ordenada por relevância (bm25).
+/// `limite` padrão é 50; `cursor` é o `proximo_cursor` da página anterior.
+#[tauri::command]
+pub fn buscar_itens_paginado(
+    estado: State<'_, EstadoApp>,
+    termo: String,
+    filtros: Option<FiltrosBusca>,
+    pesos: Option<PesosBusca>,
+    limite: Option<i64>,
+    cursor: Option<String>,
+) -> Result<Pagina<ResultadoBusca>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    let filtros = filtros.unwrap_or_default();
+    let pesos = pesos.unwrap_or_default();
+    let limite = limite.unwrap_or(50);
+    let pagina = queries::buscar_fts_paginado(&conn, &termo, &filtros, &pesos, limite, cursor.as_deref())
+        .map_err(erro_para_string)?;
+
+    Ok(pagina)
 }
 
 // =============================================================================
@@ -501,7 +842,7 @@ pub fn listar_vencimentos(
     estado: State<'_, EstadoApp>,
     periodo: Option<i64>,
 ) -> Result<Vec<Item>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
 
     let dias = periodo.unwrap_or(7);
 
@@ -515,21 +856,43 @@ pub fn listar_vencimentos(
     Ok(atrasados)
 }
 
+/// Converte uma expressão de data relativa em linguagem natural (ex.:
+/// `-1d`, `15 minutes`, `ontem 17:20`, `in 2 fortnights`) para o timestamp
+/// ISO 8601 usado em `data_vencimento`, resolvida contra o instante atual.
+/// Não toca o banco — ver `services::recorrencia::parsear_offset_humano`.
+#[tauri::command]
+pub fn interpretar_data_relativa(texto: String) -> Result<String, String> {
+    crate::services::recorrencia::parsear_offset_humano(&texto, chrono::Utc::now())
+        .map(|data| data.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .map_err(erro_para_string)
+}
+
 // =============================================================================
 // 8. BACKUP E RESTAURACAO
 // =============================================================================
 
 /// Cria um backup completo do cofre no destino especificado.
+/// `compressao` escolhe o codec das entradas (padrão: Deflate, se omitido).
+/// `backup_referencia`, se informado, torna o backup diferencial: anexos
+/// inalterados em relação a esse arquivo não são regravados (ver `criar_backup`
+/// em `services::backup`).
+/// `senha`, se informada, cifra o arquivo inteiro em repouso (ver `crypto::backup`).
 /// Retorna o caminho do arquivo .vaultbackup criado.
 #[tauri::command]
 pub fn criar_backup(
     estado: State<'_, EstadoApp>,
     destino: String,
+    compressao: Option<crate::db::models::CompressaoBackup>,
+    backup_referencia: Option<String>,
+    senha: Option<String>,
 ) -> Result<String, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    let codec = compressao.unwrap_or_default();
+    let referencia = backup_referencia.as_ref().map(PathBuf::from);
 
     let caminho = backup::criar_backup(
-        &estado.diretorio_app, &conn, &PathBuf::from(&destino),
+        &estado.diretorio_app, &conn, &PathBuf::from(&destino), &codec,
+        referencia.as_deref(), senha.as_deref(),
     ).map_err(erro_para_string)?;
 
     let _ = auditoria::registrar(
@@ -541,16 +904,23 @@ pub fn criar_backup(
 }
 
 /// Restaura o cofre a partir de um arquivo .vaultbackup.
+/// `arquivo_pai` é obrigatório se `arquivo` for um backup diferencial
+/// (ver `criar_backup`). `senha` é obrigatória se o backup estiver cifrado —
+/// nesse caso a senha incorreta (ou ausente) faz a função falhar antes do
+/// backup automático de segurança e antes de qualquer alteração no cofre.
 /// CUIDADO: substitui todos os dados atuais (faz backup automático antes).
 #[tauri::command]
 pub fn restaurar_backup(
     estado: State<'_, EstadoApp>,
     arquivo: String,
+    arquivo_pai: Option<String>,
+    senha: Option<String>,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    let pai = arquivo_pai.as_ref().map(PathBuf::from);
 
     backup::restaurar_backup(
-        &estado.diretorio_app, &conn, &PathBuf::from(&arquivo),
+        &estado.diretorio_app, &conn, &PathBuf::from(&arquivo), pai.as_deref(), senha.as_deref(),
     ).map_err(erro_para_string)?;
 
     let _ = auditoria::registrar(
@@ -562,30 +932,134 @@ pub fn restaurar_backup(
 }
 
 /// Exporta uma pasta como pacote .vaultbackup para compartilhamento.
+/// `compressao` escolhe o codec das entradas (padrão: Deflate, se omitido).
+/// `senha`, se informada, cifra o pacote inteiro em repouso (ver `crypto::backup`).
 #[tauri::command]
 pub fn exportar_pacote(
     estado: State<'_, EstadoApp>,
     pasta_id: String,
     destino: String,
+    compressao: Option<crate::db::models::CompressaoBackup>,
+    senha: Option<String>,
 ) -> Result<String, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    let codec = compressao.unwrap_or_default();
 
     let caminho = backup::exportar_pacote_pasta(
-        &estado.diretorio_app, &conn, &pasta_id, &PathBuf::from(&destino),
+        &estado.diretorio_app, &conn, &pasta_id, &PathBuf::from(&destino), &codec, senha.as_deref(),
     ).map_err(erro_para_string)?;
 
     Ok(caminho.to_string_lossy().to_string())
 }
 
-/// Importa um pacote .vaultbackup para o cofre.
+/// Importa um pacote .vaultbackup para o cofre. `senha` é obrigatória se o
+/// pacote tiver sido exportado com senha (ver `exportar_pacote`).
 #[tauri::command]
 pub fn importar_pacote(
     estado: State<'_, EstadoApp>,
     arquivo: String,
+    senha: Option<String>,
 ) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
 
     backup::importar_pacote(
+        &estado.diretorio_app, &conn, &PathBuf::from(&arquivo), senha.as_deref(),
+    ).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "importacao", "sistema", None,
+        Some(&format!("{{\"arquivo\": \"{}\"}}", arquivo)),
+    );
+
+    Ok(())
+}
+
+/// Exporta o cofre inteiro como arquivo de cofre portátil cifrado (.vcarch),
+/// incluindo o banco de dados e a cadeia de auditoria completa (ver
+/// `services::arquivo`). Ao contrário de `.vaultbackup`, é sempre cifrado —
+/// `senha` é obrigatória. Retorna o manifesto da exportação.
+#[tauri::command]
+pub fn exportar_arquivo_cofre(
+    estado: State<'_, EstadoApp>,
+    destino: String,
+    senha: String,
+    codec_banco: Option<CodecArquivo>,
+    codec_auditoria: Option<CodecArquivo>,
+) -> Result<ManifestoArquivo, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+
+    let opcoes = OpcoesArquivoExport {
+        senha,
+        codec_banco: codec_banco.unwrap_or_default(),
+        codec_auditoria: codec_auditoria.unwrap_or_default(),
+    };
+
+    let (caminho, manifesto) = arquivo::exportar(
+        &conn, &PathBuf::from(&destino), &opcoes, &estado.diretorio_app,
+    ).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "exportacao_arquivo", "sistema", None,
+        Some(&format!("{{\"destino\": \"{}\"}}", caminho.to_string_lossy())),
+    );
+
+    Ok(manifesto)
+}
+
+/// Importa um arquivo de cofre portátil (.vcarch) gerado por
+/// `exportar_arquivo_cofre`. A assinatura é verificada antes de tentar
+/// decifrar (ver `services::arquivo::importar`). `senha` é obrigatória.
+/// CUIDADO: substitui todos os dados atuais (faz backup automático antes).
+#[tauri::command]
+pub fn importar_arquivo_cofre(
+    estado: State<'_, EstadoApp>,
+    arquivo: String,
+    senha: String,
+) -> Result<ManifestoArquivo, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let manifesto = crate::services::arquivo::importar(
+        &conn, &PathBuf::from(&arquivo), &senha, &estado.diretorio_app,
+    ).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "importacao_arquivo", "sistema", None,
+        Some(&format!("{{\"arquivo\": \"{}\"}}", arquivo)),
+    );
+
+    Ok(manifesto)
+}
+
+/// Exporta uma pasta como arquivo `.tar` padrão (interoperável com
+/// ferramentas externas), ao contrário de `exportar_pacote` (ZIP de formato
+/// próprio do VaultCraft). `compressao` escolhe como o `.tar` inteiro é
+/// comprimido (padrão: Zstd nível 3, se omitido).
+#[tauri::command]
+pub fn exportar_pasta_tar(
+    estado: State<'_, EstadoApp>,
+    pasta_id: String,
+    destino: String,
+    compressao: Option<crate::db::models::CompressaoTar>,
+) -> Result<String, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    let codec = compressao.unwrap_or_default();
+
+    let caminho = backup::exportar_pasta_tar(
+        &estado.diretorio_app, &conn, &pasta_id, &PathBuf::from(&destino), &codec,
+    ).map_err(erro_para_string)?;
+
+    Ok(caminho.to_string_lossy().to_string())
+}
+
+/// Importa um `.tar` gerado por `exportar_pasta_tar` para o cofre.
+#[tauri::command]
+pub fn importar_pasta_tar(
+    estado: State<'_, EstadoApp>,
+    arquivo: String,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    backup::importar_pasta_tar(
         &estado.diretorio_app, &conn, &PathBuf::from(&arquivo),
     ).map_err(erro_para_string)?;
 
@@ -597,27 +1071,104 @@ pub fn importar_pacote(
     Ok(())
 }
 
+/// Verifica a integridade de um `.vaultbackup` sem restaurá-lo. Veja
+/// `crate::db::models::OpcoesVerificacao` para as opções de escopo/reparo.
+/// `senha` é obrigatória se o backup estiver cifrado.
+#[tauri::command]
+pub fn verificar_backup(
+    estado: State<'_, EstadoApp>,
+    arquivo: String,
+    opcoes: crate::db::models::OpcoesVerificacao,
+    senha: Option<String>,
+) -> Result<crate::db::models::RelatorioIntegridade, String> {
+    backup::verificar_backup(
+        &estado.diretorio_app, &PathBuf::from(&arquivo), &opcoes, senha.as_deref(),
+    ).map_err(erro_para_string)
+}
+
+/// Cria um backup incremental (deduplicado por chunks) do cofre. Mais
+/// econômico em espaço que `criar_backup` quando backups são feitos com
+/// frequência: só o conteúdo que mudou desde o último backup ocupa espaço
+/// novo no diretório `destino/chunks`.
+#[tauri::command]
+pub fn criar_backup_incremental(
+    estado: State<'_, EstadoApp>,
+    destino: String,
+) -> Result<String, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let caminho = backup::criar_backup_incremental(
+        &estado.diretorio_app, &conn, &PathBuf::from(&destino),
+    ).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "backup", "sistema", None,
+        Some(&format!("{{\"destino\": \"{}\", \"tipo\": \"incremental\"}}", destino)),
+    );
+
+    Ok(caminho.to_string_lossy().to_string())
+}
+
+/// Restaura o cofre a partir de um backup incremental criado por
+/// `criar_backup_incremental`. `dir_chunks` é o repositório de chunks
+/// usado na criação (normalmente a pasta `chunks` ao lado do arquivo).
+/// CUIDADO: substitui todos os dados atuais.
+#[tauri::command]
+pub fn restaurar_backup_incremental(
+    estado: State<'_, EstadoApp>,
+    arquivo: String,
+    dir_chunks: String,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    backup::restaurar_backup_incremental(
+        &estado.diretorio_app, &PathBuf::from(&arquivo), &PathBuf::from(&dir_chunks),
+    ).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "restauracao", "sistema", None,
+        Some(&format!("{{\"arquivo\": \"{}\", \"tipo\": \"incremental\"}}", arquivo)),
+    );
+
+    Ok(())
+}
+
 // =============================================================================
 // 9. EXPORTACAO — HTML e CSV
 // =============================================================================
 
 /// Exporta um item como arquivo HTML (para impressão/conversão em PDF).
-/// Retorna o caminho do arquivo HTML gerado.
+/// Se `tema` não for informado, usa a configuração `tema_exportacao` do
+/// usuário (ou Claro, se nunca tiver sido definida). Retorna o caminho do
+/// arquivo HTML gerado.
 #[tauri::command]
 pub fn exportar_item_pdf(
     estado: State<'_, EstadoApp>,
     item_id: String,
     destino: String,
+    tema: Option<TemaExportacao>,
 ) -> Result<String, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
 
     let item = queries::obter_item_por_id(&conn, &item_id).map_err(erro_para_string)?;
-    let caminho = exportacao::exportar_item_html(&item, &PathBuf::from(&destino))
+    let tema = tema.unwrap_or_else(|| tema_exportacao_padrao(&conn));
+    let caminho = exportacao::exportar_item_html(&item, &PathBuf::from(&destino), tema)
         .map_err(erro_para_string)?;
 
     Ok(caminho.to_string_lossy().to_string())
 }
 
+/// Lê o tema de exportação padrão salvo em `configuracoes` (chave
+/// `tema_exportacao`). Usa Claro se a configuração nunca foi definida.
+fn tema_exportacao_padrao(conn: &Connection) -> TemaExportacao {
+    queries::obter_configuracao(conn, "tema_exportacao")
+        .ok()
+        .flatten()
+        .and_then(|c| c.valor)
+        .map(|v| TemaExportacao::de_str(&v))
+        .unwrap_or_default()
+}
+
 /// Exporta uma lista de itens (de uma pasta) como arquivo CSV.
 /// Retorna o caminho do arquivo CSV gerado.
 #[tauri::command]
@@ -626,7 +1177,7 @@ pub fn exportar_lista_csv(
     pasta_id: String,
     destino: String,
 ) -> Result<String, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
 
     let itens = queries::listar_itens_por_pasta(&conn, &pasta_id)
         .map_err(erro_para_string)?;
@@ -636,6 +1187,110 @@ pub fn exportar_lista_csv(
     Ok(caminho.to_string_lossy().to_string())
 }
 
+/// Importa itens de um CSV (ver `exportar_lista_csv`) para a pasta `pasta_id`.
+/// `mapeamento` é uma lista de `(coluna, campo, conversao)`: `coluna` é o
+/// índice 0-based no CSV, `campo` é o campo de destino em `NovoItem`
+/// ("titulo"/"descricao"/"conteudo_nota"/"data_vencimento") e `conversao` é
+/// uma string reconhecida por `exportacao::Conversion` ("bytes"/"string",
+/// "integer", "float", "boolean", "timestamp" ou "timestamp_fmt:<padrão>").
+/// Retorna o total de itens criados; para na primeira célula malformada,
+/// com erro identificando linha e coluna.
+#[tauri::command]
+pub fn importar_lista_csv(
+    estado: State<'_, EstadoApp>,
+    arquivo: String,
+    pasta_id: String,
+    mapeamento: Vec<(usize, String, String)>,
+) -> Result<i64, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let mapeamento_tipado: Vec<(usize, String, exportacao::Conversion)> = mapeamento
+        .into_iter()
+        .map(|(coluna, campo, conversao)| {
+            conversao
+                .parse::<exportacao::Conversion>()
+                .map(|c| (coluna, campo, c))
+                .map_err(|e| format!("Conversão inválida na coluna {}: {}", coluna, e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let total = exportacao::importar_lista_csv(
+        &conn, &PathBuf::from(&arquivo), &pasta_id, &mapeamento_tipado,
+    ).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "importacao", "item", None,
+        Some(&format!("{{\"arquivo\": \"{}\", \"total\": {}}}", arquivo, total)),
+    );
+
+    Ok(total)
+}
+
+/// Exporta o cofre inteiro como JSON compatível com a exportação "sem senha
+/// mestra" do Bitwarden/vaultwarden (ver `exportacao::exportar_lista_bitwarden_json`).
+/// Retorna o caminho do arquivo JSON gerado.
+#[tauri::command]
+pub fn exportar_cofre_bitwarden_json(
+    estado: State<'_, EstadoApp>,
+    destino: String,
+) -> Result<String, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+
+    let pastas = queries::listar_pastas(&conn).map_err(erro_para_string)?;
+    let mut itens = Vec::new();
+    for pasta in &pastas {
+        itens.extend(queries::listar_itens_por_pasta(&conn, &pasta.id).map_err(erro_para_string)?);
+    }
+
+    let caminho = exportacao::exportar_lista_bitwarden_json(&pastas, &itens, &PathBuf::from(&destino))
+        .map_err(erro_para_string)?;
+
+    Ok(caminho.to_string_lossy().to_string())
+}
+
+/// Importa um JSON de exportação do Bitwarden/vaultwarden (ver
+/// `exportacao::importar_lista_bitwarden_json`). Itens sem pasta no arquivo
+/// original vão para `pasta_id_padrao`. Retorna o total de itens criados.
+#[tauri::command]
+pub fn importar_lista_bitwarden_json(
+    estado: State<'_, EstadoApp>,
+    arquivo: String,
+    pasta_id_padrao: String,
+) -> Result<i64, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let total = exportacao::importar_lista_bitwarden_json(&conn, &PathBuf::from(&arquivo), &pasta_id_padrao)
+        .map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "importacao", "item", None,
+        Some(&format!("{{\"arquivo\": \"{}\", \"total\": {}}}", arquivo, total)),
+    );
+
+    Ok(total)
+}
+
+/// Exporta o cofre inteiro como um site estático navegável (ZIP), com
+/// busca client-side embutida. Retorna o caminho do arquivo ZIP gerado.
+#[tauri::command]
+pub fn exportar_cofre_site(
+    estado: State<'_, EstadoApp>,
+    destino: String,
+) -> Result<String, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+
+    let pastas = queries::listar_pastas(&conn).map_err(erro_para_string)?;
+    let mut itens = Vec::new();
+    for pasta in &pastas {
+        itens.extend(queries::listar_itens_por_pasta(&conn, &pasta.id).map_err(erro_para_string)?);
+    }
+
+    let caminho = exportacao::exportar_cofre_site(&pastas, &itens, &PathBuf::from(&destino))
+        .map_err(erro_para_string)?;
+
+    Ok(caminho.to_string_lossy().to_string())
+}
+
 // =============================================================================
 // 10. AUDITORIA — Histórico de eventos
 // =============================================================================
@@ -646,11 +1301,137 @@ pub fn listar_historico(
     estado: State<'_, EstadoApp>,
     filtros: Option<FiltrosAuditoria>,
 ) -> Result<Vec<LogAuditoria>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     let filtros = filtros.unwrap_or_default();
     queries::listar_eventos_auditoria(&conn, &filtros).map_err(erro_para_string)
 }
 
+/// Versão paginada de `listar_historico`, ordenada por data decrescente.
+/// `limite` padrão é 50; `cursor` é o `proximo_cursor` da página anterior.
+/// `filtros.limite`/`.offset` são ignorados — use `limite`/`cursor`.
+#[tauri::command]
+pub fn listar_historico_paginado(
+    estado: State<'_, EstadoApp>,
+    filtros: Option<FiltrosAuditoria>,
+    limite: Option<i64>,
+    cursor: Option<String>,
+) -> Result<Pagina<LogAuditoria>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    let filtros = filtros.unwrap_or_default();
+    let limite = limite.unwrap_or(50);
+    queries::listar_eventos_auditoria_paginado(&conn, &filtros, limite, cursor.as_deref())
+        .map_err(erro_para_string)
+}
+
+/// Recomputa a cadeia de hashes do log de auditoria (ver migração 004) e
+/// retorna a primeira linha adulterada, se houver. `None` significa que o
+/// log inteiro confere.
+#[tauri::command]
+pub fn verificar_integridade_auditoria(
+    estado: State<'_, EstadoApp>,
+) -> Result<Option<QuebraIntegridadeAuditoria>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    auditoria::verificar_integridade(&conn).map_err(erro_para_string)
+}
+
+/// Assina o `entry_hash` do topo atual da cadeia de auditoria (ver
+/// `services::auditoria::assinar_topo`), para que possa ser exportado e
+/// ancorado fora do cofre (anexado a um backup, publicado externamente)
+/// como prova de que o log não foi adulterado depois daquele ponto.
+/// `None` se a cadeia de auditoria ainda não tiver nenhuma linha.
+#[tauri::command]
+pub fn assinar_topo_auditoria(
+    estado: State<'_, EstadoApp>,
+) -> Result<Option<AssinaturaTopoAuditoria>, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    auditoria::assinar_topo(&conn).map_err(erro_para_string)
+}
+
+/// Reconfere uma assinatura produzida por `assinar_topo_auditoria` contra o
+/// `entry_hash`/`chave_publica` que vieram com ela — não depende do cofre
+/// local, então serve para validar uma âncora exportada anteriormente (ex.:
+/// ao restaurar um backup antigo, checar se o topo assinado na época ainda
+/// corresponde ao valor divulgado externamente).
+#[tauri::command]
+pub fn verificar_assinatura_topo_auditoria(
+    entry_hash: String,
+    assinatura: String,
+    chave_publica: String,
+) -> Result<bool, String> {
+    auditoria::verificar_assinatura_topo(&entry_hash, &assinatura, &chave_publica)
+        .map_err(erro_para_string)
+}
+
+/// Reconstrói o estado materializado de pastas/itens/tags a partir do
+/// último checkpoint de auditoria e das operações estruturadas registradas
+/// depois dele (ver `services::auditoria::replay`). Principalmente um
+/// utilitário de diagnóstico por ora — é a base para a futura fusão de
+/// cofres sincronizados.
+#[tauri::command]
+pub fn reconstruir_estado_auditoria(
+    estado: State<'_, EstadoApp>,
+) -> Result<EstadoMaterializado, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    auditoria::replay(&conn).map_err(erro_para_string)
+}
+
+/// Reconstrói um item como ele existia em `instante` (ISO 8601), dobrando
+/// (fold) seu histórico estruturado de auditoria até esse ponto (ver
+/// `services::auditoria::reconstruir_item_em`). Falha se o item ainda não
+/// existisse naquele momento.
+#[tauri::command]
+pub fn reconstruir_item_em(
+    estado: State<'_, EstadoApp>,
+    item_id: String,
+    instante: String,
+) -> Result<Item, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    auditoria::reconstruir_item_em(&conn, &item_id, &instante).map_err(erro_para_string)
+}
+
+/// Lista o histórico de operações estruturadas de um item (os "diffs"
+/// antes/depois de cada criação/atualização/exclusão), em ordem
+/// cronológica — ver `services::auditoria::listar_historico_item`.
+#[tauri::command]
+pub fn listar_historico_item(
+    estado: State<'_, EstadoApp>,
+    item_id: String,
+) -> Result<Vec<OperacaoAuditoriaCompleta>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    auditoria::listar_historico_item(&conn, &item_id).map_err(erro_para_string)
+}
+
+/// Reverte um item para o snapshot de um evento passado de seu histórico
+/// (`evento_id`, de `listar_historico_item`), registrando a reversão como
+/// uma nova mutação — nunca edita o histórico existente (ver
+/// `services::auditoria::reverter_item_para`).
+#[tauri::command]
+pub fn reverter_item_para(
+    estado: State<'_, EstadoApp>,
+    item_id: String,
+    evento_id: String,
+) -> Result<Item, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let antes = queries::obter_item_por_id(&conn, &item_id).ok();
+    let payload_antes = antes.and_then(|antes| auditoria::serializar_payload(&antes).ok());
+
+    let item = auditoria::reverter_item_para(&conn, &item_id, &evento_id).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "restauracao", "item",
+        Some(&item_id),
+        Some(&format!("{{\"evento_id\": \"{}\"}}", evento_id)),
+    );
+    if let Ok(payload) = auditoria::serializar_payload(&item) {
+        let _ = auditoria::registrar_mutacao(
+            &conn, "item", &item_id, OperacaoMutacao::Atualizacao, payload_antes.as_deref(), Some(&payload),
+        );
+    }
+
+    Ok(item)
+}
+
 // =============================================================================
 // 11. CONFIGURACOES — Preferências do aplicativo
 // =============================================================================
@@ -661,7 +1442,7 @@ pub fn obter_configuracao(
     estado: State<'_, EstadoApp>,
     chave: String,
 ) -> Result<Option<Configuracao>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::obter_configuracao(&conn, &chave).map_err(erro_para_string)
 }
 
@@ -672,7 +1453,7 @@ pub fn salvar_configuracao(
     chave: String,
     valor: String,
 ) -> Result<Configuracao, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
     queries::salvar_configuracao(&conn, &chave, &valor).map_err(erro_para_string)
 }
 
@@ -681,7 +1462,7 @@ pub fn salvar_configuracao(
 pub fn obter_todas_configuracoes(
     estado: State<'_, EstadoApp>,
 ) -> Result<Vec<Configuracao>, String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
     queries::listar_configuracoes(&conn).map_err(erro_para_string)
 }
 
@@ -694,14 +1475,357 @@ pub fn obter_todas_configuracoes(
 /// Pode levar alguns segundos em bancos maiores.
 #[tauri::command]
 pub fn compactar_banco(estado: State<'_, EstadoApp>) -> Result<(), String> {
-    let conn = estado.banco.lock().map_err(|e| format!("Erro ao acessar banco: {}", e))?;
+    estado
+        .banco
+        .exclusivo(|conn| {
+            queries::compactar_banco(conn)?;
+            let _ = auditoria::registrar(
+                conn, "manutencao", "sistema", None,
+                Some("{\"acao\": \"vacuum\"}"),
+            );
+            Ok(())
+        })
+        .map_err(erro_para_string)
+}
+
+/// Executa a manutenção programada do cofre: poda o log de auditoria além
+/// da janela de retenção/limite de linhas configurado e roda VACUUM +
+/// `PRAGMA optimize` quando o intervalo configurado já passou (ver
+/// `services::manutencao`). `config` usa `ConfiguracaoManutencao::default()`
+/// se omitido. Cada ação executada já é registrada no próprio log de
+/// auditoria por `manutencao::executar` — este comando não registra de novo.
+#[tauri::command]
+pub fn executar_manutencao(
+    estado: State<'_, EstadoApp>,
+    config: Option<ConfiguracaoManutencao>,
+) -> Result<RelatorioManutencao, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    let config = config.unwrap_or_default();
+
+    manutencao::executar(&conn, &config).map_err(erro_para_string)
+}
+
+/// Verifica se `itens_fts` está em sincronia com `itens` (comparando
+/// `COUNT(*)` e o conjunto de IDs dos dois lados) e, se encontrar IDs
+/// ausentes ou órfãos, reconstrói o índice full-text (ver
+/// `services::reparo_fts`). Seguro de chamar com o app em uso — a busca
+/// continua funcionando (com os dados antigos) até a reconstrução terminar.
+#[tauri::command]
+pub fn reparar_indice_busca(estado: State<'_, EstadoApp>) -> Result<RelatorioReparoFts, String> {
+    let mut conn = estado.banco.escritor().map_err(erro_para_string)?;
+    reparo_fts::executar(&mut conn).map_err(erro_para_string)
+}
+
+/// Retorna a versão atual do schema do banco (`PRAGMA user_version`),
+/// para o frontend exibir em telas de diagnóstico/sobre.
+#[tauri::command]
+pub fn versao_schema(estado: State<'_, EstadoApp>) -> Result<u32, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    crate::db::migrations::versao_schema(&conn).map_err(erro_para_string)
+}
+
+// =============================================================================
+// 13. SINCRONIZACAO REMOTA — SFTP/SCP (opcional, ver services::sincronizacao)
+// =============================================================================
+
+/// Lista os destinos remotos (bookmarks de sincronização) salvos.
+#[tauri::command]
+pub fn listar_destinos_remotos(
+    estado: State<'_, EstadoApp>,
+) -> Result<Vec<DestinoRemoto>, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+    queries::listar_destinos_remotos(&conn).map_err(erro_para_string)
+}
+
+/// Salva (cria ou substitui, por `nome`) um destino remoto de sincronização.
+#[tauri::command]
+pub fn salvar_destino_remoto(
+    estado: State<'_, EstadoApp>,
+    destino: DestinoRemoto,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    queries::salvar_destino_remoto(&conn, &destino).map_err(erro_para_string)
+}
+
+/// Remove um destino remoto pelo nome. Retorna `true` se algo foi removido.
+#[tauri::command]
+pub fn remover_destino_remoto(
+    estado: State<'_, EstadoApp>,
+    nome: String,
+) -> Result<bool, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    queries::remover_destino_remoto(&conn, &nome).map_err(erro_para_string)
+}
+
+/// Envia um arquivo de cofre portátil (.vcarch) já exportado para um destino
+/// remoto salvo, via SFTP ou SCP conforme `destino.protocolo` (ver
+/// `services::sincronizacao::enviar`). Recusa enviar um arquivo cuja
+/// assinatura Ed25519 não confira.
+#[tauri::command]
+pub fn enviar_backup_remoto(
+    estado: State<'_, EstadoApp>,
+    nome_destino: String,
+    arquivo_local: String,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let destino = queries::listar_destinos_remotos(&conn)
+        .map_err(erro_para_string)?
+        .into_iter()
+        .find(|d| d.nome == nome_destino)
+        .ok_or_else(|| format!("Destino remoto não encontrado: {}", nome_destino))?;
+
+    sincronizacao::enviar(&conn, &destino, &PathBuf::from(&arquivo_local))
+        .map_err(erro_para_string)
+}
+
+/// Baixa um arquivo de cofre portátil (.vcarch) de um destino remoto salvo
+/// para `destino_local`, verificando a assinatura Ed25519 antes de gravá-lo
+/// (ver `services::sincronizacao::restaurar`). NÃO importa o arquivo
+/// automaticamente — use `importar_arquivo_cofre` com o caminho retornado.
+#[tauri::command]
+pub fn restaurar_backup_remoto(
+    estado: State<'_, EstadoApp>,
+    nome_destino: String,
+    nome_arquivo: String,
+    destino_local: String,
+) -> Result<String, String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let destino = queries::listar_destinos_remotos(&conn)
+        .map_err(erro_para_string)?
+        .into_iter()
+        .find(|d| d.nome == nome_destino)
+        .ok_or_else(|| format!("Destino remoto não encontrado: {}", nome_destino))?;
+
+    let caminho = sincronizacao::restaurar(
+        &conn, &destino, &nome_arquivo, &PathBuf::from(&destino_local),
+    ).map_err(erro_para_string)?;
+
+    Ok(caminho.to_string_lossy().to_string())
+}
+
+/// Reconcilia o repositório local de chunks de backup incremental
+/// (`dir_chunks`, ver `criar_backup_incremental`) com um destino remoto
+/// HTTP salvo (`nome_destino`, protocolo `Http`), enviando só os chunks que
+/// o remoto ainda não tem (ver `services::backend_remoto::sincronizar`).
+#[tauri::command]
+pub fn sincronizar_chunks_remoto(
+    estado: State<'_, EstadoApp>,
+    nome_destino: String,
+    dir_chunks: String,
+) -> Result<ResumoSincronizacaoRemota, String> {
+    let conn = estado.banco.leitor().map_err(erro_para_string)?;
+
+    let destino = queries::listar_destinos_remotos(&conn)
+        .map_err(erro_para_string)?
+        .into_iter()
+        .find(|d| d.nome == nome_destino)
+        .ok_or_else(|| format!("Destino remoto não encontrado: {}", nome_destino))?;
+
+    let backend = backend_remoto::BackendHttp::novo(&destino).map_err(erro_para_string)?;
+    backend_remoto::sincronizar(&backend, &PathBuf::from(&dir_chunks)).map_err(erro_para_string)
+}
+
+// =============================================================================
+// 14. OPERACOES EM LOTE (TRANSACIONAL)
+// =============================================================================
+
+/// Aplica uma lista de `Operacao` em uma única transação: se qualquer uma
+/// falhar, nenhuma é persistida (rollback automático) — evita que uma ação
+/// multi-etapa do frontend (edição em lote, reorganização por
+/// drag-and-drop) deixe o cofre parcialmente atualizado. Em caso de erro,
+/// a mensagem identifica o índice da operação que falhou. Em sucesso,
+/// retorna um `ResultadoOperacao` por operação (na mesma ordem) e grava uma
+/// única entrada de auditoria consolidada.
+#[tauri::command]
+pub fn executar_lote(
+    estado: State<'_, EstadoApp>,
+    operacoes: Vec<Operacao>,
+) -> Result<Vec<ResultadoOperacao>, String> {
+    let mut conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let resultados = queries::executar_lote(&mut conn, &operacoes).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "lote", "sistema", None,
+        Some(&format!("{{\"total_operacoes\": {}}}", operacoes.len())),
+    );
+
+    Ok(resultados)
+}
+
+// =============================================================================
+// 15. RECUPERAÇÃO DE PIN — Códigos de emergência (Shamir Secret Sharing)
+// =============================================================================
+// O PIN em si nunca é persistido — apenas seu hash+salt (configurações
+// "pin_hash"/"pin_salt", mesmo esquema de `crypto::hash_pin`/`verificar_pin`).
+// Ao definir o PIN com recuperação, os bytes do PIN são divididos em
+// `partes_totais` partes (ver `crypto::shamir`), das quais `limite` são
+// necessárias para reconstruir — os códigos de emergência resultantes são
+// devolvidos ao chamador nesta única resposta e NUNCA gravados em disco;
+// cabe ao usuário anotá-los e guardá-los fora do aplicativo.
+
+/// Define o PIN do cofre e gera `partes_totais` códigos de emergência (dos
+/// quais `limite` bastam para reconstruir o PIN depois, via
+/// `recuperar_com_codigos`). Os códigos só existem nesta resposta — o
+/// backend não guarda nenhuma cópia deles.
+#[tauri::command]
+pub fn definir_pin_com_recuperacao(
+    estado: State<'_, EstadoApp>,
+    pin: String,
+    partes_totais: u8,
+    limite: u8,
+) -> Result<Vec<String>, String> {
+    let partes = shamir::dividir_segredo(pin.as_bytes(), partes_totais, limite)
+        .map_err(erro_para_string)?;
+    let codigos = partes.iter().map(shamir::codificar_parte).collect();
+
+    let salt = crypto::gerar_salt();
+    let hash = crypto::hash_pin(&pin, &salt);
+
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    queries::salvar_configuracao(&conn, "pin_salt", &salt).map_err(erro_para_string)?;
+    queries::salvar_configuracao(&conn, "pin_hash", &hash).map_err(erro_para_string)?;
+
+    let _ = auditoria::registrar(
+        &conn, "pin", "sistema", None,
+        Some("{\"acao\": \"definir_com_recuperacao\"}"),
+    );
+
+    Ok(codigos)
+}
+
+/// Reconstrói o PIN a partir de `limite` ou mais códigos de emergência
+/// válidos e, se a reconstrução confere com o PIN originalmente definido,
+/// redefine o PIN do cofre para `novo_pin`. Falha se menos códigos válidos
+/// que o limite original forem fornecidos (a reconstrução incompleta não
+/// reproduz o PIN correto e a verificação contra o hash salvo não confere).
+#[tauri::command]
+pub fn recuperar_com_codigos(
+    estado: State<'_, EstadoApp>,
+    codigos: Vec<String>,
+    novo_pin: String,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+
+    let salt_atual = queries::obter_configuracao(&conn, "pin_salt")
+        .map_err(erro_para_string)?
+        .and_then(|c| c.valor)
+        .ok_or_else(|| "Nenhum PIN com recuperação configurado neste cofre".to_string())?;
+    let hash_atual = queries::obter_configuracao(&conn, "pin_hash")
+        .map_err(erro_para_string)?
+        .and_then(|c| c.valor)
+        .ok_or_else(|| "Nenhum PIN com recuperação configurado neste cofre".to_string())?;
+
+    let partes: Vec<shamir::ParteSegredo> = codigos
+        .iter()
+        .filter_map(|c| shamir::decodificar_parte(c))
+        .collect();
+    if partes.is_empty() {
+        return Err("Nenhum código de emergência válido foi fornecido".to_string());
+    }
+
+    let segredo = shamir::reconstruir_segredo(&partes).map_err(erro_para_string)?;
+    let pin_reconstruido = String::from_utf8(segredo)
+        .map_err(|_| "Códigos insuficientes ou inválidos para reconstruir o PIN".to_string())?;
+
+    if !crypto::verificar_pin(&pin_reconstruido, &hash_atual, &salt_atual) {
+        return Err("Códigos insuficientes ou inválidos para reconstruir o PIN".to_string());
+    }
 
-    queries::compactar_banco(&conn).map_err(erro_para_string)?;
+    let novo_salt = crypto::gerar_salt();
+    let novo_hash = crypto::hash_pin(&novo_pin, &novo_salt);
+    queries::salvar_configuracao(&conn, "pin_salt", &novo_salt).map_err(erro_para_string)?;
+    queries::salvar_configuracao(&conn, "pin_hash", &novo_hash).map_err(erro_para_string)?;
 
     let _ = auditoria::registrar(
-        &conn, "manutencao", "sistema", None,
-        Some("{\"acao\": \"vacuum\"}"),
+        &conn, "pin", "sistema", None,
+        Some("{\"acao\": \"recuperado_via_codigos_emergencia\"}"),
     );
 
     Ok(())
 }
+
+// =============================================================================
+// 16. CIFRAGEM EM REPOUSO — Selagem de conteudo_nota/descricao (sessão)
+// =============================================================================
+
+/// Desbloqueia a selagem de campos sensíveis para esta sessão (ver
+/// `services::cifragem`): na primeira chamada, configura a selagem com a
+/// passphrase informada e sela todo o conteúdo já existente; nas chamadas
+/// seguintes, confere a mesma passphrase contra o que já foi configurado.
+/// A chave de dados resultante só existe em memória enquanto o processo
+/// não chamar `trancar_cofre`.
+#[tauri::command]
+pub fn desbloquear_cofre(
+    estado: State<'_, EstadoApp>,
+    passphrase: String,
+) -> Result<(), String> {
+    let conn = estado.banco.escritor().map_err(erro_para_string)?;
+    cifragem::unlock(&conn, &estado.sessao_cifragem, &passphrase).map_err(erro_para_string)
+}
+
+/// Tranca a sessão: zera a chave de dados da selagem de campos em memória.
+#[tauri::command]
+pub fn trancar_cofre(estado: State<'_, EstadoApp>) {
+    cifragem::lock(&estado.sessao_cifragem);
+}
+
+/// `true` se a selagem de campos já tiver sido desbloqueada nesta sessão.
+#[tauri::command]
+pub fn cofre_esta_desbloqueado(estado: State<'_, EstadoApp>) -> bool {
+    estado.sessao_cifragem.esta_desbloqueada()
+}
+
+// =============================================================================
+// 17. FACHADAS DE REDE LOCAIS (RPC/HTTP, opcionais e desligadas por padrão)
+// =============================================================================
+// Nenhum dos listeners abaixo inicia sozinho — o app continua offline-first
+// no boot (ver topo de `lib.rs`). Cada um só passa a escutar em
+// `127.0.0.1` depois que o comando `iniciar_servidor_*` correspondente é
+// chamado explicitamente pelo frontend (ex.: um botão em Configurações).
+
+/// Inicia o listener WebSocket da fachada JSON-RPC 2.0 (ver `services::rpc`)
+/// em `127.0.0.1:porta`. Erro se já estiver em execução.
+#[tauri::command]
+pub fn iniciar_servidor_rpc(estado: State<'_, EstadoApp>, porta: u16) -> Result<(), String> {
+    estado
+        .servidor_rpc
+        .iniciar(estado.banco.clone(), estado.diretorio_app.clone(), porta)
+        .map_err(erro_para_string)
+}
+
+/// Encerra o listener WebSocket da fachada JSON-RPC, se estiver em execução.
+#[tauri::command]
+pub fn parar_servidor_rpc(estado: State<'_, EstadoApp>) {
+    estado.servidor_rpc.parar();
+}
+
+/// `true` se o listener WebSocket da fachada JSON-RPC estiver em execução.
+#[tauri::command]
+pub fn servidor_rpc_em_execucao(estado: State<'_, EstadoApp>) -> bool {
+    estado.servidor_rpc.esta_em_execucao()
+}
+
+/// Inicia o listener HTTP da API de segredos (ver `services::http_segredos`)
+/// em `127.0.0.1:porta`. Erro se já estiver em execução.
+#[tauri::command]
+pub fn iniciar_servidor_http_segredos(estado: State<'_, EstadoApp>, porta: u16) -> Result<(), String> {
+    estado
+        .servidor_segredos
+        .iniciar(estado.banco.clone(), estado.sessao_cifragem.clone(), porta)
+        .map_err(erro_para_string)
+}
+
+/// Encerra o listener HTTP da API de segredos, se estiver em execução.
+#[tauri::command]
+pub fn parar_servidor_http_segredos(estado: State<'_, EstadoApp>) {
+    estado.servidor_segredos.parar();
+}
+
+/// `true` se o listener HTTP da API de segredos estiver em execução.
+#[tauri::command]
+pub fn servidor_http_segredos_em_execucao(estado: State<'_, EstadoApp>) -> bool {
+    estado.servidor_segredos.esta_em_execucao()
+}
@@ -0,0 +1,232 @@
+// =============================================================================
+// VaultCraft — Hash de Arquivo em Árvore (BLAKE3)
+// =============================================================================
+// `hash_arquivo` (em `crypto::mod`) usa SHA-256 em chunks de 8KB, de forma
+// single-threaded — adequado para anexos pequenos, mas lento em arquivos
+// grandes e só permite detectar corrupção depois de ler o arquivo inteiro.
+//
+// Este módulo usa BLAKE3, que é internamente uma árvore Merkle: o arquivo é
+// dividido em chunks de `TAMANHO_CHUNK` bytes, cada chunk vira um hash-folha,
+// e pares de hashes são combinados nível a nível até sobrar um único hash
+// raiz. Como a árvore é associativa, `hash_arquivo_paralelo` usa o recurso
+// `rayon` do crate `blake3` para hashear subárvores em threads diferentes.
+//
+// `hash_arquivo_arvore` guarda não só a raiz mas também os hashes de cada
+// chunk-folha (dados "outboard") em `ArvoreHash`, permitindo que
+// `verificar_stream` leia o arquivo incrementalmente e aponte o chunk exato
+// onde a corrupção começa, sem precisar ler o arquivo inteiro antes.
+// =============================================================================
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Tamanho de cada chunk-folha da árvore, em bytes.
+pub const TAMANHO_CHUNK: usize = 1024;
+
+/// Árvore de hashes de um arquivo: `raiz` é o hash final (mesmo valor que
+/// `hash_arquivo_paralelo` retornaria) e `niveis[0]` guarda o hash de cada
+/// chunk-folha, na ordem em que aparecem no arquivo — os dados "outboard"
+/// usados por `verificar_stream` para localizar uma divergência.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArvoreHash {
+    pub raiz: String,
+    pub niveis: Vec<Vec<String>>,
+}
+
+/// Posição onde `verificar_stream` encontrou uma divergência entre o
+/// arquivo e a `ArvoreHash` esperada.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DivergenciaArvore {
+    pub indice_chunk: usize,
+    pub offset_byte: u64,
+}
+
+/// Calcula o hash BLAKE3 de um arquivo usando o recurso `rayon` do crate
+/// `blake3` para hashear em paralelo — muito mais rápido que `hash_arquivo`
+/// (SHA-256 single-threaded) em anexos grandes. Mantém o mesmo formato de
+/// retorno (string hexadecimal) para não quebrar chamadores existentes.
+pub fn hash_arquivo_paralelo(caminho: &Path) -> Result<String> {
+    let bytes = fs::read(caminho)
+        .with_context(|| format!("Falha ao abrir arquivo para hash: {:?}", caminho))?;
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_rayon(&bytes);
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Calcula a árvore de hashes BLAKE3 de um arquivo: o hash de cada chunk de
+/// `TAMANHO_CHUNK` bytes, combinados par a par até a raiz. A raiz é igual ao
+/// valor que `hash_arquivo_paralelo` calcularia para o mesmo arquivo.
+pub fn hash_arquivo_arvore(caminho: &Path) -> Result<ArvoreHash> {
+    let bytes = fs::read(caminho)
+        .with_context(|| format!("Falha ao abrir arquivo para hash: {:?}", caminho))?;
+
+    let folhas: Vec<blake3::Hash> = if bytes.is_empty() {
+        vec![blake3::hash(&[])]
+    } else {
+        bytes.chunks(TAMANHO_CHUNK).map(blake3::hash).collect()
+    };
+
+    let niveis = construir_niveis(folhas);
+    let raiz = niveis
+        .last()
+        .and_then(|ultimo_nivel| ultimo_nivel.first())
+        .context("Árvore de hash vazia")?
+        .clone();
+
+    Ok(ArvoreHash { raiz, niveis })
+}
+
+/// Combina os hashes-folha par a par, subindo nível a nível até restar um
+/// único hash (a raiz). Um nó sem par no fim de um nível sobe sozinho para
+/// o próximo nível, sem se combinar com nada.
+fn construir_niveis(folhas: Vec<blake3::Hash>) -> Vec<Vec<String>> {
+    let mut niveis = vec![hex_de_cada(&folhas)];
+    let mut nivel_atual = folhas;
+
+    while nivel_atual.len() > 1 {
+        let proximo_nivel: Vec<blake3::Hash> = nivel_atual
+            .chunks(2)
+            .map(|par| match par {
+                [esquerda, direita] => {
+                    let mut combinado = Vec::with_capacity(64);
+                    combinado.extend_from_slice(esquerda.as_bytes());
+                    combinado.extend_from_slice(direita.as_bytes());
+                    blake3::hash(&combinado)
+                }
+                [unico] => *unico,
+                _ => unreachable!("chunks(2) nunca produz grupos vazios ou maiores que 2"),
+            })
+            .collect();
+
+        niveis.push(hex_de_cada(&proximo_nivel));
+        nivel_atual = proximo_nivel;
+    }
+
+    niveis
+}
+
+fn hex_de_cada(hashes: &[blake3::Hash]) -> Vec<String> {
+    hashes.iter().map(|h| h.to_hex().to_string()).collect()
+}
+
+/// Lê `caminho` incrementalmente (chunk a chunk, sem carregar o arquivo
+/// inteiro em memória) e recalcula o hash de cada chunk, comparando contra
+/// os hashes-folha armazenados em `arvore`. Retorna a posição da primeira
+/// divergência encontrada (ou `None` se o arquivo confere inteiramente) —
+/// permite detectar corrupção no offset exato sem esperar o arquivo inteiro
+/// ser lido.
+pub fn verificar_stream(caminho: &Path, arvore: &ArvoreHash) -> Result<Option<DivergenciaArvore>> {
+    let folhas_esperadas = arvore
+        .niveis
+        .first()
+        .context("Árvore de hash sem nível de folhas (outboard data incompleta)")?;
+
+    let mut arquivo = fs::File::open(caminho)
+        .with_context(|| format!("Falha ao abrir arquivo para verificação incremental: {:?}", caminho))?;
+    let mut buffer = vec![0u8; TAMANHO_CHUNK];
+    let mut indice_chunk = 0usize;
+
+    loop {
+        let bytes_lidos = arquivo
+            .read(&mut buffer)
+            .context("Falha ao ler arquivo durante verificação incremental")?;
+        if bytes_lidos == 0 {
+            break;
+        }
+
+        let hash_chunk = blake3::hash(&buffer[..bytes_lidos]).to_hex().to_string();
+        let esperado = folhas_esperadas
+            .get(indice_chunk)
+            .context("Arquivo tem mais chunks do que a árvore de hash armazenada (arquivo cresceu?)")?;
+
+        if &hash_chunk != esperado {
+            return Ok(Some(DivergenciaArvore {
+                indice_chunk,
+                offset_byte: (indice_chunk * TAMANHO_CHUNK) as u64,
+            }));
+        }
+
+        indice_chunk += 1;
+    }
+
+    if indice_chunk != folhas_esperadas.len() {
+        bail!("Arquivo tem menos chunks do que a árvore de hash armazenada (arquivo truncado?)");
+    }
+
+    Ok(None)
+}
+
+// =============================================================================
+// TESTES
+// =============================================================================
+#[cfg(test)]
+mod testes {
+    use super::*;
+    use std::io::Write;
+
+    fn escrever_arquivo_temporario(conteudo: &[u8]) -> std::path::PathBuf {
+        let caminho = std::env::temp_dir().join(format!(
+            "vaultcraft_teste_arvore_hash_{}",
+            uuid::Uuid::new_v4()
+        ));
+        let mut arquivo = fs::File::create(&caminho).unwrap();
+        arquivo.write_all(conteudo).unwrap();
+        caminho
+    }
+
+    #[test]
+    fn teste_raiz_da_arvore_igual_ao_hash_paralelo() {
+        let conteudo = vec![7u8; TAMANHO_CHUNK * 3 + 17]; // alguns chunks completos + um parcial
+        let caminho = escrever_arquivo_temporario(&conteudo);
+
+        let arvore = hash_arquivo_arvore(&caminho).unwrap();
+        let hash_paralelo = hash_arquivo_paralelo(&caminho).unwrap();
+
+        assert_eq!(arvore.raiz, hash_paralelo);
+        let _ = fs::remove_file(&caminho);
+    }
+
+    #[test]
+    fn teste_verificar_stream_sem_divergencia() {
+        let conteudo = vec![9u8; TAMANHO_CHUNK * 2];
+        let caminho = escrever_arquivo_temporario(&conteudo);
+        let arvore = hash_arquivo_arvore(&caminho).unwrap();
+
+        assert_eq!(verificar_stream(&caminho, &arvore).unwrap(), None);
+        let _ = fs::remove_file(&caminho);
+    }
+
+    #[test]
+    fn teste_verificar_stream_detecta_chunk_corrompido() {
+        let mut conteudo = vec![1u8; TAMANHO_CHUNK * 3];
+        let caminho = escrever_arquivo_temporario(&conteudo);
+        let arvore = hash_arquivo_arvore(&caminho).unwrap();
+
+        // Corrompe um byte dentro do segundo chunk e reescreve o arquivo.
+        conteudo[TAMANHO_CHUNK + 5] ^= 0xFF;
+        fs::write(&caminho, &conteudo).unwrap();
+
+        let divergencia = verificar_stream(&caminho, &arvore)
+            .unwrap()
+            .expect("deve detectar a divergência no segundo chunk");
+        assert_eq!(divergencia.indice_chunk, 1);
+        assert_eq!(divergencia.offset_byte, TAMANHO_CHUNK as u64);
+
+        let _ = fs::remove_file(&caminho);
+    }
+
+    #[test]
+    fn teste_arquivo_vazio_tem_arvore_de_um_unico_hash() {
+        let caminho = escrever_arquivo_temporario(&[]);
+        let arvore = hash_arquivo_arvore(&caminho).unwrap();
+
+        assert_eq!(arvore.niveis.len(), 1);
+        assert_eq!(arvore.niveis[0].len(), 1);
+        assert_eq!(arvore.raiz, arvore.niveis[0][0]);
+
+        let _ = fs::remove_file(&caminho);
+    }
+}
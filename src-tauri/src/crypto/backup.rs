@@ -0,0 +1,499 @@
+// =============================================================================
+// VaultCraft — Criptografia de Backups (.vaultbackup) em repouso
+// =============================================================================
+// `criar_backup`/`exportar_pacote_pasta` podem receber uma senha opcional
+// (tipicamente o PIN do cofre) para cifrar o conteúdo do arquivo ZIP antes
+// de gravá-lo em disco — sem senha, o comportamento é idêntico ao
+// histórico (ZIP em texto claro).
+//
+// Formato do arquivo cifrado (v2, atual — AES-256-GCM):
+//   MAGIC (8 bytes: "VCBKENC2") |
+//   u32 LE: tamanho do cabeçalho JSON que segue |
+//   cabeçalho JSON (`db::models::ManifestoCifragemBackup`: salt, nonce
+//   base e parâmetros do Argon2id, todos em claro — são dados públicos do
+//   KDF, não segredos) |
+//   stream de chunks: [marca: 1 byte][tamanho do texto cifrado: u32 LE][texto cifrado+tag]*
+//
+// A chave de 256 bits é derivada da senha com Argon2id (salt + parâmetros
+// ficam no cabeçalho). O payload (o ZIP inteiro) é dividido em chunks de
+// `TAMANHO_CHUNK_CLARO` e cada chunk é cifrado com AES-256-GCM, autenticado
+// (AEAD). O nonce de 12 bytes de cada chunk é o nonce-base do arquivo com
+// um contador (big-endian) ou-exclusivo nos últimos 8 bytes, então nunca se
+// repete dentro do arquivo — o pedido original descreve "um nonce de 12
+// bytes único por arquivo"; streamamos em chunks (para não exigir que o
+// ZIP inteiro caiba em memória) derivando um nonce único por chunk a partir
+// desse nonce-base, em vez de cifrar tudo numa única chamada.
+//
+// A "marca" de cada chunk (0 = meio do stream, 1 = último chunk) é passada
+// como dado associado (AAD) ao cifrar/decifrar: um atacante não pode trocar
+// a marca de um chunk (por exemplo para truncar o stream e fazer o último
+// chunk legítimo passar por "completo") sem invalidar a tag de autenticação.
+//
+// `salt`/`nonce`/`kdf_params` pedidos como campos do manifesto vivem em
+// `db::models::ManifestoCifragemBackup` — o cabeçalho acima — e não em
+// `ManifestoBackup` (que é `manifesto.json`, gravado DENTRO do ZIP que este
+// módulo cifra): esses parâmetros precisam estar disponíveis ANTES de
+// decifrar o ZIP, então não podem morar num manifesto que só existe depois
+// de decifrado. Ver doc comment de `ManifestoCifragemBackup`.
+//
+// Formato v1 (legado — XChaCha20-Poly1305, MAGIC "VCBKENC1") continua
+// decifrável por `descifrar` para não quebrar a leitura de backups já
+// gravados antes desta revisão; `cifrar`/`cifrar_com_custo` só produzem o
+// formato v2 (AES-256-GCM) a partir de agora.
+// =============================================================================
+
+use anyhow::{bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+
+use crate::db::models::{ManifestoCifragemBackup, ParametrosKdfBackup};
+
+/// Identifica um backup cifrado pelo formato atual (v2, AES-256-GCM).
+pub const MAGIC: &[u8; 8] = b"VCBKENC2";
+/// Identifica um backup cifrado pelo formato legado (v1, XChaCha20-Poly1305)
+/// — `descifrar` ainda lê este formato; `cifrar`/`cifrar_com_custo` nunca
+/// mais o escrevem.
+const MAGIC_V1_LEGADO: &[u8; 8] = b"VCBKENC1";
+
+const TAMANHO_SALT: usize = 16;
+/// Tamanho do nonce base no formato v2 — o tamanho de nonce nativo do
+/// AES-256-GCM (diferente do v1 legado, que usava XChaCha20 e um
+/// nonce-base de 24 bytes).
+const TAMANHO_NONCE_BASE: usize = 12;
+const TAMANHO_NONCE_BASE_V1_LEGADO: usize = 24;
+/// Tamanho de cada pedaço de texto claro antes de cifrar — grande o
+/// suficiente para não gerar overhead excessivo, pequeno o suficiente para
+/// não exigir que todo o arquivo caiba em memória durante a cifragem.
+const TAMANHO_CHUNK_CLARO: usize = 1024 * 1024; // 1 MiB
+
+const MARCA_MEIO: u8 = 0;
+const MARCA_FINAL: u8 = 1;
+
+// Parâmetros Argon2id — equilibram segurança e tempo de abertura do cofre.
+// Ficam gravados no cabeçalho de cada arquivo cifrado, então podem ser
+// endurecidos no futuro sem quebrar a leitura de backups já cifrados com
+// os valores antigos.
+const ARGON2_MEMORIA_KIB: u32 = 64 * 1024; // 64 MiB
+const ARGON2_ITERACOES: u32 = 3;
+const ARGON2_PARALELISMO: u32 = 1;
+
+/// Custo do Argon2id usado para derivar a chave de um backup cifrado.
+/// `cifrar` usa [`CustoArgon2id::default`]; chamadores que precisem de um
+/// custo diferente (por exemplo, hardware mais fraco, ou para endurecer a
+/// derivação com o tempo) podem chamar [`cifrar_com_custo`] diretamente — o
+/// custo usado fica sempre gravado no cabeçalho do arquivo (ver formato no
+/// topo deste módulo), então arquivos cifrados com custos diferentes
+/// continuam decifráveis pela mesma `descifrar`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustoArgon2id {
+    pub memoria_kib: u32,
+    pub iteracoes: u32,
+    pub paralelismo: u32,
+}
+
+impl Default for CustoArgon2id {
+    fn default() -> Self {
+        Self {
+            memoria_kib: ARGON2_MEMORIA_KIB,
+            iteracoes: ARGON2_ITERACOES,
+            paralelismo: ARGON2_PARALELISMO,
+        }
+    }
+}
+
+impl From<CustoArgon2id> for ParametrosKdfBackup {
+    fn from(custo: CustoArgon2id) -> Self {
+        Self {
+            memoria_kib: custo.memoria_kib,
+            iteracoes: custo.iteracoes,
+            paralelismo: custo.paralelismo,
+        }
+    }
+}
+
+/// `true` se `dados` começam com a marca de um backup cifrado deste módulo
+/// (formato atual v2 ou legado v1).
+pub fn esta_cifrado(dados: &[u8]) -> bool {
+    dados.len() >= MAGIC.len() && (&dados[..MAGIC.len()] == MAGIC || &dados[..MAGIC.len()] == MAGIC_V1_LEGADO)
+}
+
+/// Cifra `dados` (tipicamente o conteúdo completo de um .vaultbackup) com
+/// `senha` (o PIN do cofre, tipicamente), retornando o arquivo final pronto
+/// para ser gravado em disco (cabeçalho + stream de chunks cifrados). Usa
+/// o custo padrão do Argon2id (ver [`CustoArgon2id::default`]) — para
+/// ajustar o custo, use [`cifrar_com_custo`].
+pub fn cifrar(dados: &[u8], senha: &str) -> Result<Vec<u8>> {
+    cifrar_com_custo(dados, senha, CustoArgon2id::default())
+}
+
+/// Como [`cifrar`], mas com o custo do Argon2id explícito em `custo`.
+/// Sempre produz o formato atual (v2, AES-256-GCM, `MAGIC`).
+pub fn cifrar_com_custo(dados: &[u8], senha: &str, custo: CustoArgon2id) -> Result<Vec<u8>> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+
+    let mut salt = [0u8; TAMANHO_SALT];
+    let mut nonce_base = [0u8; TAMANHO_NONCE_BASE];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce_base);
+
+    let bytes_chave = derivar_chave(senha, &salt, custo.memoria_kib, custo.iteracoes, custo.paralelismo)?;
+    let cifra = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes_chave));
+
+    let cabecalho = ManifestoCifragemBackup {
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce_base),
+        kdf_params: custo.into(),
+    };
+    let cabecalho_json = serde_json::to_vec(&cabecalho).context("Falha ao serializar cabeçalho de cifragem")?;
+
+    let mut saida = Vec::with_capacity(dados.len() + 128);
+    saida.extend_from_slice(MAGIC);
+    saida.extend_from_slice(&(cabecalho_json.len() as u32).to_le_bytes());
+    saida.extend_from_slice(&cabecalho_json);
+
+    let chunks: Vec<&[u8]> = if dados.is_empty() {
+        vec![&[][..]]
+    } else {
+        dados.chunks(TAMANHO_CHUNK_CLARO).collect()
+    };
+
+    for (indice, chunk) in chunks.iter().enumerate() {
+        let ultimo = indice == chunks.len() - 1;
+        let marca = if ultimo { MARCA_FINAL } else { MARCA_MEIO };
+        let nonce_bytes = nonce_do_chunk(&nonce_base, indice as u64);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let cifrado = cifra
+            .encrypt(nonce, Payload { msg: chunk, aad: &[marca] })
+            .map_err(|_| anyhow::anyhow!("Falha ao cifrar chunk do backup"))?;
+
+        saida.push(marca);
+        saida.extend_from_slice(&(cifrado.len() as u32).to_le_bytes());
+        saida.extend_from_slice(&cifrado);
+    }
+
+    Ok(saida)
+}
+
+/// Decifra um arquivo produzido por `cifrar`/`cifrar_com_custo` (formato
+/// atual v2, AES-256-GCM) ou por uma versão anterior deste módulo (formato
+/// legado v1, XChaCha20-Poly1305). Retorna erro se a senha estiver
+/// incorreta, se o arquivo não tiver o cabeçalho esperado, ou se o stream
+/// estiver corrompido/truncado — em qualquer caso, nada é gravado em disco
+/// antes que a descriptografia (e sua autenticação) termine com sucesso.
+pub fn descifrar(dados: &[u8], senha: &str) -> Result<Vec<u8>> {
+    if dados.len() >= MAGIC.len() && &dados[..MAGIC.len()] == MAGIC_V1_LEGADO {
+        return descifrar_v1_legado(dados, senha);
+    }
+    if !esta_cifrado(dados) {
+        bail!("Arquivo não está no formato cifrado esperado");
+    }
+
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+
+    let mut cursor = MAGIC.len();
+    let tamanho_cabecalho = dados
+        .get(cursor..cursor + 4)
+        .context("Cabeçalho cifrado truncado (tamanho do cabeçalho)")?;
+    let tamanho_cabecalho = u32::from_le_bytes(tamanho_cabecalho.try_into().unwrap()) as usize;
+    cursor += 4;
+
+    let cabecalho_json = dados
+        .get(cursor..cursor + tamanho_cabecalho)
+        .context("Cabeçalho cifrado truncado (manifesto de cifragem)")?;
+    let cabecalho: ManifestoCifragemBackup =
+        serde_json::from_slice(cabecalho_json).context("Cabeçalho de cifragem em formato inválido")?;
+    cursor += tamanho_cabecalho;
+
+    let salt = hex::decode(&cabecalho.salt).context("Salt do cabeçalho de cifragem inválido")?;
+    let nonce_base = hex::decode(&cabecalho.nonce).context("Nonce do cabeçalho de cifragem inválido")?;
+    if nonce_base.len() != TAMANHO_NONCE_BASE {
+        bail!("Nonce do cabeçalho de cifragem com tamanho inesperado");
+    }
+
+    let bytes_chave = derivar_chave(
+        senha,
+        &salt,
+        cabecalho.kdf_params.memoria_kib,
+        cabecalho.kdf_params.iteracoes,
+        cabecalho.kdf_params.paralelismo,
+    )?;
+    let cifra = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes_chave));
+
+    let mut saida = Vec::with_capacity(dados.len());
+    let mut indice: u64 = 0;
+    let mut viu_marca_final = false;
+
+    while cursor < dados.len() {
+        let marca = *dados.get(cursor).context("Stream cifrado truncado (marca)")?;
+        cursor += 1;
+
+        let tamanho = ler_u32(dados, &mut cursor)? as usize;
+        let cifrado = dados
+            .get(cursor..cursor + tamanho)
+            .context("Stream cifrado truncado (chunk incompleto)")?;
+        cursor += tamanho;
+
+        let nonce_bytes = nonce_do_chunk_slice(&nonce_base, indice);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+        let texto_claro = cifra
+            .decrypt(nonce, Payload { msg: cifrado, aad: &[marca] })
+            .map_err(|_| anyhow::anyhow!("Senha incorreta ou backup corrompido/adulterado"))?;
+
+        saida.extend_from_slice(&texto_claro);
+        indice += 1;
+
+        if marca == MARCA_FINAL {
+            viu_marca_final = true;
+            break;
+        }
+    }
+
+    if !viu_marca_final {
+        bail!("Backup cifrado truncado (fim do stream inesperado antes do chunk final)");
+    }
+    if cursor != dados.len() {
+        bail!("Dados extras após o fim do stream cifrado (arquivo adulterado?)");
+    }
+
+    Ok(saida)
+}
+
+/// Decifra o formato legado v1 (XChaCha20-Poly1305, cabeçalho binário com
+/// salt/nonce-base/parâmetros do Argon2id em claro, sem o manifesto JSON
+/// do formato atual). Mantido só para leitura de backups antigos.
+fn descifrar_v1_legado(dados: &[u8], senha: &str) -> Result<Vec<u8>> {
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+    let mut cursor = MAGIC_V1_LEGADO.len();
+    let salt: [u8; TAMANHO_SALT] = dados
+        .get(cursor..cursor + TAMANHO_SALT)
+        .context("Cabeçalho cifrado truncado (salt)")?
+        .try_into()
+        .unwrap();
+    cursor += TAMANHO_SALT;
+
+    let memoria_kib = ler_u32(dados, &mut cursor)?;
+    let iteracoes = ler_u32(dados, &mut cursor)?;
+    let paralelismo = ler_u32(dados, &mut cursor)?;
+
+    let nonce_base: [u8; TAMANHO_NONCE_BASE_V1_LEGADO] = dados
+        .get(cursor..cursor + TAMANHO_NONCE_BASE_V1_LEGADO)
+        .context("Cabeçalho cifrado truncado (nonce)")?
+        .try_into()
+        .unwrap();
+    cursor += TAMANHO_NONCE_BASE_V1_LEGADO;
+
+    let bytes_chave = derivar_chave(senha, &salt, memoria_kib, iteracoes, paralelismo)?;
+    let cifra = XChaCha20Poly1305::new(Key::from_slice(&bytes_chave));
+
+    let mut saida = Vec::with_capacity(dados.len());
+    let mut indice: u64 = 0;
+    let mut viu_marca_final = false;
+
+    while cursor < dados.len() {
+        let marca = *dados.get(cursor).context("Stream cifrado truncado (marca)")?;
+        cursor += 1;
+
+        let tamanho = ler_u32(dados, &mut cursor)? as usize;
+        let cifrado = dados
+            .get(cursor..cursor + tamanho)
+            .context("Stream cifrado truncado (chunk incompleto)")?;
+        cursor += tamanho;
+
+        let mut nonce = nonce_base;
+        let contador = indice.to_be_bytes();
+        for i in 0..8 {
+            nonce[TAMANHO_NONCE_BASE_V1_LEGADO - 8 + i] ^= contador[i];
+        }
+        let nonce = XNonce::from_slice(&nonce);
+
+        let texto_claro = cifra
+            .decrypt(nonce, chacha20poly1305::aead::Payload { msg: cifrado, aad: &[marca] })
+            .map_err(|_| anyhow::anyhow!("Senha incorreta ou backup corrompido/adulterado"))?;
+
+        saida.extend_from_slice(&texto_claro);
+        indice += 1;
+
+        if marca == MARCA_FINAL {
+            viu_marca_final = true;
+            break;
+        }
+    }
+
+    if !viu_marca_final {
+        bail!("Backup cifrado truncado (fim do stream inesperado antes do chunk final)");
+    }
+    if cursor != dados.len() {
+        bail!("Dados extras após o fim do stream cifrado (arquivo adulterado?)");
+    }
+
+    Ok(saida)
+}
+
+fn ler_u32(dados: &[u8], cursor: &mut usize) -> Result<u32> {
+    let bytes: [u8; 4] = dados
+        .get(*cursor..*cursor + 4)
+        .context("Cabeçalho cifrado truncado (inteiro)")?
+        .try_into()
+        .unwrap();
+    *cursor += 4;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn derivar_chave(senha: &str, salt: &[u8], memoria_kib: u32, iteracoes: u32, paralelismo: u32) -> Result<[u8; 32]> {
+    let parametros = Params::new(memoria_kib, iteracoes, paralelismo, Some(32))
+        .map_err(|e| anyhow::anyhow!("Parâmetros Argon2id inválidos: {}", e))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, parametros);
+
+    let mut chave = [0u8; 32];
+    argon2
+        .hash_password_into(senha.as_bytes(), salt, &mut chave)
+        .map_err(|e| anyhow::anyhow!("Falha ao derivar chave com Argon2id: {}", e))?;
+
+    Ok(chave)
+}
+
+/// Deriva o nonce de 12 bytes de um chunk a partir do nonce-base do
+/// arquivo (formato v2, AES-256-GCM), fazendo XOR do contador
+/// (big-endian) nos últimos 8 bytes — garante um nonce único por chunk sem
+/// precisar gravar um nonce inteiro para cada um.
+fn nonce_do_chunk(nonce_base: &[u8; TAMANHO_NONCE_BASE], indice: u64) -> [u8; TAMANHO_NONCE_BASE] {
+    let mut nonce = *nonce_base;
+    let contador = indice.to_be_bytes();
+    for i in 0..8 {
+        nonce[TAMANHO_NONCE_BASE - 8 + i] ^= contador[i];
+    }
+    nonce
+}
+
+/// Como [`nonce_do_chunk`], mas para um `nonce_base` lido em tempo de
+/// execução (como um `Vec<u8>` já validado com `TAMANHO_NONCE_BASE` bytes).
+fn nonce_do_chunk_slice(nonce_base: &[u8], indice: u64) -> [u8; TAMANHO_NONCE_BASE] {
+    let mut base = [0u8; TAMANHO_NONCE_BASE];
+    base.copy_from_slice(nonce_base);
+    nonce_do_chunk(&base, indice)
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    /// Custo baixo só para os testes rodarem rápido — não usado em produção.
+    const CUSTO_TESTE: CustoArgon2id = CustoArgon2id {
+        memoria_kib: 8 * 1024,
+        iteracoes: 1,
+        paralelismo: 1,
+    };
+
+    #[test]
+    fn cifrar_e_descifrar_preserva_o_conteudo() {
+        let dados = b"conteudo do .vaultbackup de teste".repeat(100);
+        let cifrado = cifrar_com_custo(&dados, "senha-correta", CUSTO_TESTE).unwrap();
+
+        assert!(esta_cifrado(&cifrado));
+        assert_eq!(descifrar(&cifrado, "senha-correta").unwrap(), dados);
+    }
+
+    #[test]
+    fn descifrar_com_senha_errada_falha() {
+        let dados = b"dados sensiveis";
+        let cifrado = cifrar_com_custo(dados, "senha-correta", CUSTO_TESTE).unwrap();
+
+        assert!(descifrar(&cifrado, "senha-errada").is_err());
+    }
+
+    #[test]
+    fn salt_e_nonce_sao_aleatorios_por_arquivo() {
+        let dados = b"mesmo conteudo";
+        let cifrado_a = cifrar_com_custo(dados, "senha", CUSTO_TESTE).unwrap();
+        let cifrado_b = cifrar_com_custo(dados, "senha", CUSTO_TESTE).unwrap();
+
+        // Mesma senha e mesmo conteúdo, mas salt/nonce aleatórios fazem o
+        // cabeçalho (e o stream cifrado) diferirem a cada chamada.
+        assert_ne!(cifrado_a, cifrado_b);
+    }
+
+    #[test]
+    fn custo_gravado_no_cabecalho_e_o_custo_usado_na_derivacao() {
+        // Cifra com um custo diferente do padrão; só decifra corretamente se
+        // `descifrar` ler o custo do cabeçalho em vez de assumir o padrão.
+        let custo_nao_padrao = CustoArgon2id {
+            memoria_kib: 16 * 1024,
+            iteracoes: 2,
+            paralelismo: 1,
+        };
+        assert_ne!(custo_nao_padrao, CustoArgon2id::default());
+
+        let dados = b"dados com custo customizado";
+        let cifrado = cifrar_com_custo(dados, "senha", custo_nao_padrao).unwrap();
+
+        assert_eq!(descifrar(&cifrado, "senha").unwrap(), dados);
+    }
+
+    #[test]
+    fn manifesto_de_cifragem_traz_salt_nonce_e_kdf_params() {
+        let dados = b"dados para inspecionar o cabecalho";
+        let cifrado = cifrar_com_custo(dados, "senha", CUSTO_TESTE).unwrap();
+
+        let tamanho_cabecalho =
+            u32::from_le_bytes(cifrado[MAGIC.len()..MAGIC.len() + 4].try_into().unwrap()) as usize;
+        let inicio_cabecalho = MAGIC.len() + 4;
+        let cabecalho: ManifestoCifragemBackup =
+            serde_json::from_slice(&cifrado[inicio_cabecalho..inicio_cabecalho + tamanho_cabecalho]).unwrap();
+
+        assert_eq!(hex::decode(&cabecalho.salt).unwrap().len(), TAMANHO_SALT);
+        assert_eq!(hex::decode(&cabecalho.nonce).unwrap().len(), TAMANHO_NONCE_BASE);
+        assert_eq!(cabecalho.kdf_params, ParametrosKdfBackup::from(CUSTO_TESTE));
+    }
+
+    #[test]
+    fn descifrar_rejeita_arquivo_sem_cabecalho_esperado() {
+        assert!(descifrar(b"nao e um vaultbackup cifrado", "senha").is_err());
+    }
+
+    #[test]
+    fn descifrar_le_formato_legado_v1_xchacha20() {
+        // Reproduz o formato v1 (anterior a esta revisão) manualmente, para
+        // garantir que backups já gravados com ele continuam restauráveis.
+        use chacha20poly1305::aead::{Aead, KeyInit};
+        use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+        let senha = "senha-legada";
+        let dados = b"conteudo cifrado no formato antigo";
+
+        let mut salt = [0u8; TAMANHO_SALT];
+        let mut nonce_base = [0u8; TAMANHO_NONCE_BASE_V1_LEGADO];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce_base);
+
+        let custo = CUSTO_TESTE;
+        let bytes_chave =
+            derivar_chave(senha, &salt, custo.memoria_kib, custo.iteracoes, custo.paralelismo).unwrap();
+        let cifra = XChaCha20Poly1305::new(Key::from_slice(&bytes_chave));
+
+        let mut arquivo_v1 = Vec::new();
+        arquivo_v1.extend_from_slice(MAGIC_V1_LEGADO);
+        arquivo_v1.extend_from_slice(&salt);
+        arquivo_v1.extend_from_slice(&custo.memoria_kib.to_le_bytes());
+        arquivo_v1.extend_from_slice(&custo.iteracoes.to_le_bytes());
+        arquivo_v1.extend_from_slice(&custo.paralelismo.to_le_bytes());
+        arquivo_v1.extend_from_slice(&nonce_base);
+
+        let cifrado = cifra
+            .encrypt(
+                XNonce::from_slice(&nonce_base),
+                chacha20poly1305::aead::Payload { msg: &dados[..], aad: &[MARCA_FINAL] },
+            )
+            .unwrap();
+        arquivo_v1.push(MARCA_FINAL);
+        arquivo_v1.extend_from_slice(&(cifrado.len() as u32).to_le_bytes());
+        arquivo_v1.extend_from_slice(&cifrado);
+
+        assert!(esta_cifrado(&arquivo_v1));
+        assert_eq!(descifrar(&arquivo_v1, senha).unwrap(), dados.to_vec());
+    }
+}
@@ -0,0 +1,106 @@
+// =============================================================================
+// VaultCraft — Selagem de Campos Sensíveis (sealed blobs)
+// =============================================================================
+// Cifra um único campo de texto (por exemplo `conteudo_nota`/`descricao` de
+// um item) com XChaCha20-Poly1305, autenticado (AEAD) — a mesma primitiva já
+// usada em `crypto::backup`/`license::keystore`, aqui sem chunking porque
+// cada campo é um texto curto que cabe inteiro em memória de uma vez.
+//
+// Cada blob guarda seu próprio nonce aleatório (nunca reusado entre campos,
+// mesmo que dois campos tenham o mesmo texto), então `CampoCifrado` é
+// autocontido e pode ser serializado como uma única coluna TEXT (JSON) sem
+// precisar de nenhum outro dado da linha para ser decifrado — só a chave de
+// sessão (ver `services::cifragem`).
+//
+// A chave usada aqui é sempre a chave de dados de 32 bytes já derivada pelo
+// desbloqueio da sessão (ver `services::cifragem::EstadoSessao`) — este
+// módulo não deriva nada a partir de senha, só cifra/decifra com a chave que
+// recebe.
+// =============================================================================
+
+use anyhow::{anyhow, Context, Result};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+const TAMANHO_NONCE: usize = 24;
+
+/// Um campo de texto cifrado, pronto para ser serializado como uma única
+/// coluna TEXT (JSON) — ver migração 007 (`*_selado`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampoCifrado {
+    /// Texto cifrado (com tag de autenticação do AEAD), em hexadecimal.
+    pub ciphertext: String,
+    /// Nonce do XChaCha20-Poly1305 usado para cifrar este campo, em hexadecimal.
+    pub nonce: String,
+}
+
+/// Cifra `texto_claro` com `chave_dados` (32 bytes), gerando um nonce
+/// aleatório novo para este campo.
+pub fn cifrar(chave_dados: &[u8; 32], texto_claro: &str) -> Result<CampoCifrado> {
+    let mut nonce_bytes = [0u8; TAMANHO_NONCE];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let cifra = XChaCha20Poly1305::new(Key::from_slice(chave_dados));
+    let nonce = XNonce::from_slice(&nonce_bytes);
+    let ciphertext = cifra
+        .encrypt(nonce, texto_claro.as_bytes())
+        .map_err(|_| anyhow!("Falha ao cifrar campo"))?;
+
+    Ok(CampoCifrado {
+        ciphertext: hex::encode(ciphertext),
+        nonce: hex::encode(nonce_bytes),
+    })
+}
+
+/// Decifra um `CampoCifrado` produzido por `cifrar`. Falha se `chave_dados`
+/// estiver errada ou se o blob tiver sido adulterado (tag do AEAD não bate).
+pub fn decifrar(chave_dados: &[u8; 32], campo: &CampoCifrado) -> Result<String> {
+    let nonce_bytes = hex::decode(&campo.nonce).context("Nonce de campo cifrado malformado")?;
+    let ciphertext = hex::decode(&campo.ciphertext).context("Texto cifrado de campo malformado")?;
+
+    let cifra = XChaCha20Poly1305::new(Key::from_slice(chave_dados));
+    let nonce = XNonce::from_slice(&nonce_bytes);
+    let texto_claro = cifra
+        .decrypt(nonce, ciphertext.as_slice())
+        .map_err(|_| anyhow!("Chave de sessão incorreta ou campo cifrado corrompido"))?;
+
+    String::from_utf8(texto_claro).context("Campo decifrado não é UTF-8 válido")
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    #[test]
+    fn teste_cifrar_e_decifrar_preserva_o_texto() {
+        let chave = [7u8; 32];
+        let selado = cifrar(&chave, "conteúdo sensível da nota").unwrap();
+        assert_eq!(decifrar(&chave, &selado).unwrap(), "conteúdo sensível da nota");
+    }
+
+    #[test]
+    fn teste_mesmo_texto_gera_nonces_diferentes() {
+        let chave = [7u8; 32];
+        let selado1 = cifrar(&chave, "mesmo texto").unwrap();
+        let selado2 = cifrar(&chave, "mesmo texto").unwrap();
+        assert_ne!(selado1.nonce, selado2.nonce, "Cada selagem deve usar um nonce novo");
+    }
+
+    #[test]
+    fn teste_chave_errada_falha_ao_decifrar() {
+        let chave_certa = [7u8; 32];
+        let chave_errada = [9u8; 32];
+        let selado = cifrar(&chave_certa, "segredo").unwrap();
+        assert!(decifrar(&chave_errada, &selado).is_err());
+    }
+
+    #[test]
+    fn teste_blob_adulterado_falha_ao_decifrar() {
+        let chave = [7u8; 32];
+        let mut selado = cifrar(&chave, "segredo").unwrap();
+        selado.ciphertext = hex::encode(b"dados adulterados, tamanho diferente do original");
+        assert!(decifrar(&chave, &selado).is_err());
+    }
+}
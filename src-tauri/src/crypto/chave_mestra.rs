@@ -0,0 +1,121 @@
+// =============================================================================
+// VaultCraft — Chave Mestra do Cofre (Argon2id)
+// =============================================================================
+// Deriva a chave de 256 bits usada para cifrar o banco SQLite em repouso
+// (ver `db::connection::PoolConexoes::abrir_com_capacidade_cifrada`) a
+// partir do PIN do usuário, com Argon2id — memory-hard, resistente a
+// ataques de GPU/ASIC — em vez do SHA-256+salt de `crypto::hash_pin`, que
+// continua existindo só como hash de verificação de PIN para cofres que
+// ainda não migraram para a cifragem em repouso.
+//
+// O salt é aleatório (16 bytes) e os parâmetros Argon2id ficam salvos em
+// claro na tabela `configuracoes` ("chave_mestra_salt" em hex,
+// "chave_mestra_argon2_params" como "m_kib|iteracoes|paralelismo") — dados
+// públicos do KDF, não segredos. Guardá-los (em vez de fixá-los em
+// constantes) permite endurecer os parâmetros no futuro sem invalidar
+// cofres já cifrados com os valores antigos, mesma lógica de
+// `crypto::backup`.
+//
+// NOTA DE ESCOPO: o `PRAGMA key` em `db::connection` só cifra de verdade se
+// o binário estiver compilado contra o SQLCipher (feature
+// "bundled-sqlcipher" do rusqlite) — com o SQLite padrão desta árvore, o
+// pragma é apenas ignorado. Falta também, neste repositório, a tela que
+// colete o PIN do usuário *antes* de abrir o cofre (`run()` hoje chama
+// `PoolConexoes::abrir`/`abrir_com_capacidade` sem PIN algum); este módulo e
+// `abrir_com_capacidade_cifrada` são o ponto de entrada pronto para quando
+// esse fluxo de UI existir, mas não substituem o caminho padrão sozinhos.
+// =============================================================================
+
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+
+pub const TAMANHO_SALT: usize = 16;
+pub const TAMANHO_CHAVE: usize = 32;
+
+// Parâmetros Argon2id padrão — equilibram segurança e tempo de
+// desbloqueio do cofre. Ficam salvos em `configuracoes` junto com o salt
+// (ver comentário do módulo), não fixos para sempre neste código.
+pub const ARGON2_MEMORIA_KIB: u32 = 64 * 1024; // 64 MiB
+pub const ARGON2_ITERACOES: u32 = 3;
+pub const ARGON2_PARALELISMO: u32 = 1;
+
+/// Gera um novo salt aleatório para a chave mestra.
+pub fn gerar_salt() -> [u8; TAMANHO_SALT] {
+    let mut salt = [0u8; TAMANHO_SALT];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+/// Deriva a chave mestra de 256 bits a partir do PIN e do salt, usando os
+/// parâmetros Argon2id informados (ver `ARGON2_*` para os valores padrão,
+/// gravados em `configuracoes` na primeira vez que o cofre é cifrado).
+pub fn derivar_chave(
+    pin: &str,
+    salt: &[u8; TAMANHO_SALT],
+    memoria_kib: u32,
+    iteracoes: u32,
+    paralelismo: u32,
+) -> Result<[u8; TAMANHO_CHAVE]> {
+    let parametros = Params::new(memoria_kib, iteracoes, paralelismo, Some(TAMANHO_CHAVE))
+        .map_err(|e| anyhow!("Parâmetros Argon2id inválidos: {}", e))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, parametros);
+
+    let mut chave = [0u8; TAMANHO_CHAVE];
+    argon2
+        .hash_password_into(pin.as_bytes(), salt, &mut chave)
+        .map_err(|e| anyhow!("Falha ao derivar chave mestra: {}", e))?;
+
+    Ok(chave)
+}
+
+/// Formata a chave derivada como o hex esperado por `PRAGMA key = x'...'`
+/// em `db::connection` (só o hex — o `x'...'` é montado lá).
+pub fn chave_para_hex(chave: &[u8; TAMANHO_CHAVE]) -> String {
+    hex::encode(chave)
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    // Parâmetros bem mais baratos que os padrão de produção, só para os
+    // testes não ficarem lentos — o salt e o PIN já garantem variação
+    // suficiente para verificar as propriedades do KDF.
+    const MEMORIA_TESTE_KIB: u32 = 8 * 1024;
+    const ITERACOES_TESTE: u32 = 1;
+
+    #[test]
+    fn teste_mesmo_pin_e_salt_derivam_a_mesma_chave() {
+        let salt = gerar_salt();
+        let chave1 = derivar_chave("1234", &salt, MEMORIA_TESTE_KIB, ITERACOES_TESTE, 1).unwrap();
+        let chave2 = derivar_chave("1234", &salt, MEMORIA_TESTE_KIB, ITERACOES_TESTE, 1).unwrap();
+        assert_eq!(chave1, chave2, "Mesma entrada deve derivar a mesma chave");
+    }
+
+    #[test]
+    fn teste_pin_diferente_deriva_chave_diferente() {
+        let salt = gerar_salt();
+        let chave1 = derivar_chave("1234", &salt, MEMORIA_TESTE_KIB, ITERACOES_TESTE, 1).unwrap();
+        let chave2 = derivar_chave("4321", &salt, MEMORIA_TESTE_KIB, ITERACOES_TESTE, 1).unwrap();
+        assert_ne!(chave1, chave2, "PINs diferentes devem derivar chaves diferentes");
+    }
+
+    #[test]
+    fn teste_salt_diferente_deriva_chave_diferente() {
+        let chave1 = derivar_chave("1234", &gerar_salt(), MEMORIA_TESTE_KIB, ITERACOES_TESTE, 1).unwrap();
+        let chave2 = derivar_chave("1234", &gerar_salt(), MEMORIA_TESTE_KIB, ITERACOES_TESTE, 1).unwrap();
+        assert_ne!(chave1, chave2, "Salts diferentes devem derivar chaves diferentes");
+    }
+
+    #[test]
+    fn teste_salt_gerado_tem_tamanho_esperado() {
+        assert_eq!(gerar_salt().len(), TAMANHO_SALT);
+    }
+
+    #[test]
+    fn teste_chave_para_hex_tem_tamanho_esperado() {
+        let chave = derivar_chave("1234", &gerar_salt(), MEMORIA_TESTE_KIB, ITERACOES_TESTE, 1).unwrap();
+        assert_eq!(chave_para_hex(&chave).len(), TAMANHO_CHAVE * 2);
+    }
+}
@@ -0,0 +1,175 @@
+// =============================================================================
+// VaultCraft — Limpeza Automática de Segredos e Arquivos Temporários
+// =============================================================================
+// Operações que decifram dados (importação de `.vcarch`, restauração de
+// backup, manutenção) mantêm texto plano sensível em memória e, em alguns
+// casos, geram artefatos intermediários em disco. Se o processo encerra
+// antes de sobrescrever esses buffers/arquivos, o texto plano pode continuar
+// acessível depois que a operação termina.
+//
+// `LimpezaAutomatica` é um guarda de escopo: segredos e caminhos registrados
+// nela são apagados quando ela é descartada (via `Drop`), inclusive em
+// retornos antecipados por `?`. `Drop` não roda em `std::process::exit` —
+// chamadores que podem encerrar o processo por esse caminho (ou que
+// precisam do resumo de limpeza antes do fim do escopo, para registrar em
+// auditoria) devem chamar `limpar()` explicitamente.
+// =============================================================================
+
+use std::fs;
+use std::path::PathBuf;
+
+use zeroize::Zeroize;
+
+/// Guarda de limpeza automática. Acumula segredos em memória (texto plano
+/// decifrado) e caminhos de arquivos temporários ao longo de uma operação;
+/// `limpar()` zera os primeiros e remove os segundos, podendo ser chamado
+/// explicitamente ou deixado para o `Drop`.
+///
+/// `descricao` identifica a operação (ex.: "importacao_arquivo_cofre") e
+/// aparece no resumo retornado por `limpar()`, para o chamador repassar a
+/// `auditoria::registrar`.
+pub struct LimpezaAutomatica {
+    descricao: String,
+    segredos: Vec<Vec<u8>>,
+    arquivos_temporarios: Vec<PathBuf>,
+    limpo: bool,
+}
+
+impl LimpezaAutomatica {
+    pub fn nova(descricao: impl Into<String>) -> Self {
+        Self {
+            descricao: descricao.into(),
+            segredos: Vec::new(),
+            arquivos_temporarios: Vec::new(),
+            limpo: false,
+        }
+    }
+
+    /// Registra um buffer sigiloso (ex.: texto plano recém-decifrado) para
+    /// zeragem quando o guarda for limpo. O guarda passa a ser o dono do
+    /// buffer — use o índice retornado com `segredo()` para lê-lo enquanto
+    /// o guarda estiver vivo.
+    pub fn registrar_segredo(&mut self, segredo: Vec<u8>) -> usize {
+        self.segredos.push(segredo);
+        self.segredos.len() - 1
+    }
+
+    /// Lê de volta um segredo registrado por `registrar_segredo`, pelo
+    /// índice retornado na hora do registro.
+    pub fn segredo(&self, indice: usize) -> &[u8] {
+        &self.segredos[indice]
+    }
+
+    /// Registra um arquivo temporário em disco para remoção quando o guarda
+    /// for limpo (ex.: staging de VACUUM, extração intermediária de backup).
+    pub fn registrar_arquivo_temporario(&mut self, caminho: PathBuf) {
+        self.arquivos_temporarios.push(caminho);
+    }
+
+    /// Zera todos os segredos em memória e remove todos os arquivos
+    /// temporários registrados. Idempotente: chamadas repetidas (inclusive
+    /// a disparada por `Drop` depois de uma chamada explícita) não fazem
+    /// nada. Retorna uma descrição de cada artefato limpo, para o chamador
+    /// registrar com `auditoria::registrar` (este módulo, em `crypto`, não
+    /// depende da camada de serviços/auditoria).
+    pub fn limpar(&mut self) -> Vec<String> {
+        if self.limpo {
+            return Vec::new();
+        }
+        self.limpo = true;
+
+        let mut resumo = Vec::new();
+
+        if !self.segredos.is_empty() {
+            for segredo in self.segredos.iter_mut() {
+                segredo.zeroize();
+            }
+            resumo.push(format!(
+                "[{}] {} segredo(s) em memória zerado(s)",
+                self.descricao,
+                self.segredos.len()
+            ));
+        }
+
+        for caminho in &self.arquivos_temporarios {
+            match fs::remove_file(caminho) {
+                Ok(()) => resumo.push(format!(
+                    "[{}] arquivo temporário removido: {:?}",
+                    self.descricao, caminho
+                )),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log::warn!(
+                    "[{}] falha ao remover arquivo temporário {:?}: {}",
+                    self.descricao, caminho, e
+                ),
+            }
+        }
+
+        resumo
+    }
+}
+
+impl Drop for LimpezaAutomatica {
+    fn drop(&mut self) {
+        // Resultado ignorado de propósito: não há para onde propagar um
+        // erro em `Drop`, e `limpar()` já registra avisos via `log::warn!`.
+        let _ = self.limpar();
+    }
+}
+
+// =============================================================================
+// TESTES
+// =============================================================================
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    #[test]
+    fn teste_limpar_zera_segredos() {
+        let mut guarda = LimpezaAutomatica::nova("teste");
+        let indice = guarda.registrar_segredo(vec![1, 2, 3, 4]);
+        assert_eq!(guarda.segredo(indice), &[1, 2, 3, 4]);
+
+        let resumo = guarda.limpar();
+        assert_eq!(resumo.len(), 1);
+        assert_eq!(guarda.segredo(indice), &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn teste_limpar_e_idempotente() {
+        let mut guarda = LimpezaAutomatica::nova("teste");
+        guarda.registrar_segredo(vec![9, 9]);
+
+        let primeiro_resumo = guarda.limpar();
+        let segundo_resumo = guarda.limpar();
+
+        assert_eq!(primeiro_resumo.len(), 1);
+        assert!(segundo_resumo.is_empty());
+    }
+
+    #[test]
+    fn teste_arquivo_temporario_inexistente_nao_gera_resumo() {
+        let mut guarda = LimpezaAutomatica::nova("teste");
+        guarda.registrar_arquivo_temporario(PathBuf::from("/caminho/que/nao/existe.tmp"));
+
+        let resumo = guarda.limpar();
+        assert!(resumo.is_empty());
+    }
+
+    #[test]
+    fn teste_arquivo_temporario_e_removido() {
+        let caminho = std::env::temp_dir().join(format!(
+            "vaultcraft_teste_limpeza_{}.tmp",
+            uuid::Uuid::new_v4()
+        ));
+        fs::write(&caminho, b"segredo temporario").unwrap();
+        assert!(caminho.exists());
+
+        let mut guarda = LimpezaAutomatica::nova("teste");
+        guarda.registrar_arquivo_temporario(caminho.clone());
+        let resumo = guarda.limpar();
+
+        assert_eq!(resumo.len(), 1);
+        assert!(!caminho.exists());
+    }
+}
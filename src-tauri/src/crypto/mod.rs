@@ -6,15 +6,50 @@
 // - Hash SHA-256 de arquivos (verificação de integridade)
 // - Hash de PIN com salt (proteção de acesso)
 //
-// IMPORTANTE: Este módulo NÃO implementa criptografia de dados em repouso.
-// Os dados ficam em texto plano no SQLite local. A criptografia completa
-// (AES-256-GCM para o banco, libsodium para chaves) será adicionada em
-// versão futura quando o modelo de ameaça justificar a complexidade.
+// IMPORTANTE: o banco ainda abre em texto plano por padrão (`run()` chama
+// `PoolConexoes::abrir`, sem PIN) — falta a tela de UI que colete o PIN
+// antes do cofre abrir. `chave_mestra` + `db::connection::
+// abrir_com_capacidade_cifrada` já existem como o caminho de cifragem em
+// repouso (SQLCipher via PRAGMA key, chave derivada com Argon2id), prontos
+// para quando esse fluxo existir — ver módulo `chave_mestra`.
 //
 // O PIN é hashado com SHA-256 + salt aleatório. Para produção, considerar
 // migrar para Argon2id ou bcrypt (resistentes a ataques de força bruta por GPU).
+//
+// `backup`: cifragem opcional (com senha) de arquivos .vaultbackup, com
+// Argon2id para derivação de chave e XChaCha20-Poly1305 em chunks
+// autenticados — essa parte já usa Argon2id, ver módulo `backup`.
+//
+// `limpeza`: guarda de escopo (`LimpezaAutomatica`) que zera segredos em
+// memória e remove arquivos temporários ao final de operações que decifram
+// dados (importação, restauração, manutenção), mesmo em retornos
+// antecipados por `?` — ver módulo `limpeza`.
+//
+// `shamir`: divisão do PIN em partes (Shamir Secret Sharing sobre GF(256))
+// para gerar códigos de emergência — permite redefinir o PIN esquecido sem
+// reter nenhuma cópia do PIN nem das partes em disco, ver módulo `shamir`.
+//
+// `arvore_hash`: hash BLAKE3 em árvore Merkle para anexos grandes — hashing
+// paralelo (via `rayon`) e verificação incremental que aponta o chunk exato
+// onde a corrupção começa, ver módulo `arvore_hash`.
+//
+// `chave_mestra`: deriva (via Argon2id) a chave de 256 bits usada para
+// cifrar o banco SQLite em repouso — ver módulo `chave_mestra` para a nota
+// de escopo sobre o que falta para isso estar totalmente ligado ao `run()`.
+//
+// `campo_cifrado`: sela campos de texto individuais (hoje: `conteudo_nota`/
+// `descricao` de um item) com XChaCha20-Poly1305, para cifragem em repouso
+// no nível de coluna — ver módulo `campo_cifrado` e `services::cifragem`
+// para a chave de sessão que o desbloqueio do cofre mantém em memória.
 // =============================================================================
 
+pub mod arvore_hash;
+pub mod backup;
+pub mod campo_cifrado;
+pub mod chave_mestra;
+pub mod limpeza;
+pub mod shamir;
+
 use anyhow::{Context, Result};
 use sha2::{Sha256, Digest};
 use std::fs;
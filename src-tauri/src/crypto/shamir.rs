@@ -0,0 +1,415 @@
+// =============================================================================
+// VaultCraft — Recuperação de PIN via Shamir Secret Sharing (GF(256))
+// =============================================================================
+// Divide um segredo (aqui, o PIN de acesso ao cofre) em `n` "códigos de
+// emergência" dos quais quaisquer `k` reconstroem o segredo original, mas
+// `k - 1` não revelam absolutamente nada sobre ele (segurança
+// information-theoretic, não computacional).
+//
+// Cada byte do segredo é tratado de forma independente: para cada byte,
+// sorteamos um polinômio de grau `k - 1` sobre GF(256) cujo coeficiente
+// constante é o próprio byte e os demais `k - 1` coeficientes são
+// aleatórios, e avaliamos esse polinômio em `n` pontos `x = 1..=n`
+// (nunca em `x = 0`, que revelaria o segredo). Cada parte `i` carrega o
+// valor do polinômio de TODOS os bytes do segredo avaliados em `x = i`,
+// então reconstruir o segredo completo exige reunir `k` partes e, para
+// cada byte, interpolar Lagrange em `x = 0`.
+//
+// GF(256) aqui é o corpo de Rijndael (polinômio redutor x^8+x^4+x^3+x+1,
+// 0x11B) — o mesmo usado pelo AES, então soma é XOR e multiplicação usa
+// as tabelas de log/antilog padrão, sem depender de uma crate externa de
+// corpos finitos.
+// =============================================================================
+
+use anyhow::{bail, Result};
+use rand::RngCore;
+
+/// Uma parte (código de emergência) da divisão de um segredo: `x` é o ponto
+/// de avaliação (1..=n, nunca 0) e `y` guarda o polinômio avaliado em `x`
+/// para cada byte do segredo, na mesma ordem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParteSegredo {
+    pub x: u8,
+    pub y: Vec<u8>,
+}
+
+/// Divide `segredo` em `partes_totais` partes das quais `limite` reconstroem
+/// o original. Processa byte a byte, como exigido pelo esquema: cada byte do
+/// segredo tem seu próprio polinômio aleatório independente.
+///
+/// Erros se `limite` for 0, maior que `partes_totais`, ou se `partes_totais`
+/// for maior que 255 (só há 255 pontos não-nulos em GF(256)).
+pub fn dividir_segredo(segredo: &[u8], partes_totais: u8, limite: u8) -> Result<Vec<ParteSegredo>> {
+    if limite == 0 {
+        bail!("O limite de partes necessárias para reconstruir não pode ser zero");
+    }
+    if limite > partes_totais {
+        bail!(
+            "O limite ({}) não pode ser maior que o total de partes geradas ({})",
+            limite, partes_totais
+        );
+    }
+
+    let mut rng = rand::thread_rng();
+
+    // Para cada byte do segredo, sorteia os `limite - 1` coeficientes
+    // aleatórios do polinômio (o coeficiente constante é o próprio byte).
+    let coeficientes_por_byte: Vec<Vec<u8>> = segredo
+        .iter()
+        .map(|&byte_segredo| {
+            let mut coeficientes = vec![0u8; limite as usize];
+            coeficientes[0] = byte_segredo;
+            if limite > 1 {
+                rng.fill_bytes(&mut coeficientes[1..]);
+            }
+            coeficientes
+        })
+        .collect();
+
+    let partes = (1..=partes_totais)
+        .map(|x| {
+            let y = coeficientes_por_byte
+                .iter()
+                .map(|coeficientes| avaliar_polinomio(coeficientes, x))
+                .collect();
+            ParteSegredo { x, y }
+        })
+        .collect();
+
+    Ok(partes)
+}
+
+/// Reconstrói o segredo original a partir de `partes` (precisa de pelo menos
+/// `limite` partes distintas usadas em `dividir_segredo`, mas esta função não
+/// sabe qual era o limite original — cabe ao chamador não tentar reconstruir
+/// com menos partes do que exigiu ao dividir).
+///
+/// Erros se `partes` estiver vazio, tiver partes com `x` repetido, ou se o
+/// comprimento de `y` divergir entre partes (sinal de códigos de fontes
+/// diferentes/incompatíveis).
+pub fn reconstruir_segredo(partes: &[ParteSegredo]) -> Result<Vec<u8>> {
+    if partes.is_empty() {
+        bail!("Nenhuma parte fornecida para reconstrução");
+    }
+
+    let tamanho_segredo = partes[0].y.len();
+    for parte in partes {
+        if parte.y.len() != tamanho_segredo {
+            bail!("Partes de tamanhos incompatíveis — códigos de emergência misturados?");
+        }
+    }
+    for i in 0..partes.len() {
+        for j in (i + 1)..partes.len() {
+            if partes[i].x == partes[j].x {
+                bail!("Partes duplicadas (mesmo x={}) — forneça códigos distintos", partes[i].x);
+            }
+        }
+    }
+
+    let mut segredo = Vec::with_capacity(tamanho_segredo);
+    for indice_byte in 0..tamanho_segredo {
+        let pontos: Vec<(u8, u8)> = partes.iter().map(|p| (p.x, p.y[indice_byte])).collect();
+        segredo.push(interpolar_lagrange_em_zero(&pontos));
+    }
+
+    Ok(segredo)
+}
+
+/// Avalia o polinômio com `coeficientes` (índice = grau, `coeficientes[0]` é
+/// o termo constante) em `x`, usando a regra de Horner sobre GF(256).
+fn avaliar_polinomio(coeficientes: &[u8], x: u8) -> u8 {
+    let mut resultado = 0u8;
+    for &coeficiente in coeficientes.iter().rev() {
+        resultado = gf256_mul(resultado, x) ^ coeficiente;
+    }
+    resultado
+}
+
+/// Interpolação de Lagrange em `x = 0`: dado um conjunto de pontos
+/// `(x_i, y_i)` de um polinômio sobre GF(256), retorna o valor do polinômio
+/// em `x = 0` (o termo constante — no nosso caso, o byte do segredo).
+fn interpolar_lagrange_em_zero(pontos: &[(u8, u8)]) -> u8 {
+    let mut resultado = 0u8;
+
+    for (i, &(xi, yi)) in pontos.iter().enumerate() {
+        let mut termo = yi;
+        for (j, &(xj, _)) in pontos.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            // L_i(0) = produto de (0 - xj) / (xi - xj) = produto de xj / (xi XOR xj),
+            // já que em GF(256) a subtração é a mesma operação que a soma (XOR).
+            let numerador = xj;
+            let denominador = xi ^ xj;
+            termo = gf256_mul(termo, gf256_div(numerador, denominador));
+        }
+        resultado ^= termo;
+    }
+
+    resultado
+}
+
+// =============================================================================
+// Aritmética em GF(256) (corpo de Rijndael, polinômio redutor 0x11B)
+// =============================================================================
+
+const GF256_POLINOMIO_REDUTOR: u16 = 0x11B;
+
+fn gf256_mul(a: u8, b: u8) -> u8 {
+    let (mut a, mut b) = (a as u16, b as u16);
+    let mut produto: u16 = 0;
+
+    while b > 0 {
+        if b & 1 != 0 {
+            produto ^= a;
+        }
+        b >>= 1;
+        a <<= 1;
+        if a & 0x100 != 0 {
+            a ^= GF256_POLINOMIO_REDUTOR;
+        }
+    }
+
+    produto as u8
+}
+
+/// Inverso multiplicativo de `a` em GF(256) via exponenciação: todo elemento
+/// não-nulo tem ordem 255, então `a^254 == a^-1`.
+fn gf256_inverso(a: u8) -> Option<u8> {
+    if a == 0 {
+        return None;
+    }
+    let mut resultado = 1u8;
+    let mut base = a;
+    let mut expoente = 254u8;
+    while expoente > 0 {
+        if expoente & 1 != 0 {
+            resultado = gf256_mul(resultado, base);
+        }
+        base = gf256_mul(base, base);
+        expoente >>= 1;
+    }
+    Some(resultado)
+}
+
+fn gf256_div(a: u8, b: u8) -> u8 {
+    match gf256_inverso(b) {
+        Some(inverso) => gf256_mul(a, inverso),
+        None => 0, // só ocorre se dois pontos compartilharem o mesmo x, já rejeitado antes
+    }
+}
+
+/// Soma de verificação (1 byte) de uma parte, anexada ao código de emergência
+/// exportado para detectar erros de transcrição antes de tentar reconstruir.
+pub fn checksum(parte: &ParteSegredo) -> u8 {
+    let mut soma = parte.x;
+    for &byte in &parte.y {
+        soma = soma.wrapping_add(byte).rotate_left(1);
+    }
+    soma
+}
+
+// =============================================================================
+// Códigos de emergência legíveis (Base32 + checksum)
+// =============================================================================
+// Formato de cada código: "VCREC-<x em hex, 2 dígitos>-<payload em Base32
+// com um traço a cada 4 caracteres>-<checksum em Base32, 2 caracteres>".
+// `x` fica em hex separado (não dentro do Base32 do payload) só para que o
+// usuário consiga identificar visualmente qual parte é qual sem precisar
+// decodificar nada.
+
+const PREFIXO_CODIGO: &str = "VCREC";
+const BASE32_ALFABETO: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Converte uma parte em um código de emergência legível, pronto para o
+/// usuário anotar e guardar offline.
+pub fn codificar_parte(parte: &ParteSegredo) -> String {
+    let payload_b32 = base32_encode(&parte.y);
+    let payload_com_tracos = inserir_tracos_a_cada(&payload_b32, 4);
+    let checksum_b32 = base32_encode(&[checksum(parte)]);
+
+    format!("{}-{:02X}-{}-{}", PREFIXO_CODIGO, parte.x, payload_com_tracos, checksum_b32)
+}
+
+/// Decodifica um código de emergência produzido por `codificar_parte`,
+/// validando o checksum. Retorna `None` para código malformado ou com
+/// checksum divergente (erro de transcrição).
+pub fn decodificar_parte(codigo: &str) -> Option<ParteSegredo> {
+    let codigo = codigo.trim();
+    let resto = codigo.strip_prefix(PREFIXO_CODIGO)?.strip_prefix('-')?;
+
+    let mut partes_texto = resto.splitn(3, '-');
+    let x_hex = partes_texto.next()?;
+    let payload_com_tracos = partes_texto.next()?;
+    let checksum_b32 = partes_texto.next()?;
+
+    let x = u8::from_str_radix(x_hex, 16).ok()?;
+    let payload_b32: String = payload_com_tracos.chars().filter(|c| *c != '-').collect();
+    let y = base32_decode(&payload_b32)?;
+    let checksum_bytes = base32_decode(checksum_b32)?;
+    let checksum_lido = *checksum_bytes.first()?;
+
+    let parte = ParteSegredo { x, y };
+    if checksum(&parte) != checksum_lido {
+        return None;
+    }
+
+    Some(parte)
+}
+
+fn inserir_tracos_a_cada(texto: &str, grupo: usize) -> String {
+    texto
+        .as_bytes()
+        .chunks(grupo)
+        .map(|c| std::str::from_utf8(c).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut resultado = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer: u32 = 0;
+    let mut bits_no_buffer = 0u32;
+
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits_no_buffer += 8;
+
+        while bits_no_buffer >= 5 {
+            bits_no_buffer -= 5;
+            let indice = (buffer >> bits_no_buffer) & 0x1F;
+            resultado.push(BASE32_ALFABETO[indice as usize] as char);
+        }
+    }
+
+    if bits_no_buffer > 0 {
+        let indice = (buffer << (5 - bits_no_buffer)) & 0x1F;
+        resultado.push(BASE32_ALFABETO[indice as usize] as char);
+    }
+
+    resultado
+}
+
+fn base32_decode(texto: &str) -> Option<Vec<u8>> {
+    let mut resultado = Vec::with_capacity(texto.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits_no_buffer = 0u32;
+
+    for c in texto.chars() {
+        let valor = base32_valor(c)?;
+        buffer = (buffer << 5) | valor as u32;
+        bits_no_buffer += 5;
+
+        if bits_no_buffer >= 8 {
+            bits_no_buffer -= 8;
+            resultado.push(((buffer >> bits_no_buffer) & 0xFF) as u8);
+        }
+    }
+
+    Some(resultado)
+}
+
+fn base32_valor(c: char) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        'A'..='Z' => Some(c.to_ascii_uppercase() as u8 - b'A'),
+        '2'..='7' => Some(c as u8 - b'2' + 26),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// TESTES
+// =============================================================================
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    #[test]
+    fn teste_gf256_multiplicacao_identidade() {
+        assert_eq!(gf256_mul(0x53, 1), 0x53, "Multiplicar por 1 deve ser identidade");
+        assert_eq!(gf256_mul(0x53, 0), 0, "Multiplicar por 0 deve dar 0");
+    }
+
+    #[test]
+    fn teste_gf256_inverso_e_divisao() {
+        for a in 1..=255u8 {
+            let inverso = gf256_inverso(a).expect("todo elemento não-nulo tem inverso");
+            assert_eq!(gf256_mul(a, inverso), 1, "a * a^-1 deve ser 1 para a={}", a);
+        }
+    }
+
+    #[test]
+    fn teste_dividir_e_reconstruir_com_limite_exato() {
+        let segredo = b"0123456789ABCDEF".to_vec(); // 16 bytes, tamanho típico de PIN hasheado
+        let partes = dividir_segredo(&segredo, 5, 3).unwrap();
+        assert_eq!(partes.len(), 5);
+
+        let reconstruido = reconstruir_segredo(&partes[0..3]).unwrap();
+        assert_eq!(reconstruido, segredo, "3 de 5 partes devem reconstruir o segredo");
+    }
+
+    #[test]
+    fn teste_reconstruir_com_subconjunto_diferente_da_mesma_divisao() {
+        let segredo = b"segredo-de-teste".to_vec();
+        let partes = dividir_segredo(&segredo, 5, 3).unwrap();
+
+        let combinacao_a = vec![partes[0].clone(), partes[1].clone(), partes[2].clone()];
+        let combinacao_b = vec![partes[1].clone(), partes[3].clone(), partes[4].clone()];
+
+        assert_eq!(reconstruir_segredo(&combinacao_a).unwrap(), segredo);
+        assert_eq!(reconstruir_segredo(&combinacao_b).unwrap(), segredo);
+    }
+
+    #[test]
+    fn teste_menos_partes_que_o_limite_nao_reconstroi_corretamente() {
+        let segredo = vec![42u8; 8];
+        let partes = dividir_segredo(&segredo, 5, 3).unwrap();
+
+        // Com só 2 das 3 partes exigidas, a interpolação "reconstrói" um
+        // valor, mas não é o segredo original — é assim que o esquema
+        // garante que k-1 partes não revelam nada.
+        let reconstruido_incompleto = reconstruir_segredo(&partes[0..2]).unwrap();
+        assert_ne!(reconstruido_incompleto, segredo);
+    }
+
+    #[test]
+    fn teste_limite_maior_que_total_de_partes_falha() {
+        assert!(dividir_segredo(b"segredo", 3, 5).is_err());
+    }
+
+    #[test]
+    fn teste_partes_duplicadas_na_reconstrucao_falha() {
+        let segredo = vec![1u8, 2, 3];
+        let partes = dividir_segredo(&segredo, 5, 3).unwrap();
+        let duplicadas = vec![partes[0].clone(), partes[0].clone(), partes[1].clone()];
+        assert!(reconstruir_segredo(&duplicadas).is_err());
+    }
+
+    #[test]
+    fn teste_codificar_e_decodificar_parte_ida_e_volta() {
+        let segredo = b"1234".to_vec();
+        let partes = dividir_segredo(&segredo, 5, 3).unwrap();
+
+        for parte in &partes {
+            let codigo = codificar_parte(parte);
+            assert!(codigo.starts_with("VCREC-"));
+            let decodificada = decodificar_parte(&codigo).expect("código válido deve decodificar");
+            assert_eq!(&decodificada, parte);
+        }
+    }
+
+    #[test]
+    fn teste_decodificar_parte_com_checksum_corrompido_falha() {
+        let segredo = b"1234".to_vec();
+        let partes = dividir_segredo(&segredo, 5, 3).unwrap();
+        let mut codigo = codificar_parte(&partes[0]);
+
+        // Corrompe o último caractere (parte do checksum), simulando erro
+        // de transcrição do usuário.
+        let ultimo = codigo.pop().unwrap();
+        let substituto = if ultimo == 'A' { 'B' } else { 'A' };
+        codigo.push(substituto);
+
+        assert!(decodificar_parte(&codigo).is_none());
+    }
+}
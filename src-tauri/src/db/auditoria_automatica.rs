@@ -0,0 +1,147 @@
+// =============================================================================
+// VaultCraft — Captura Automática de Auditoria via Hooks do SQLite
+// =============================================================================
+// O serviço `services::auditoria` já permite registrar eventos no
+// `log_auditoria`, mas depende de cada command lembrar de chamá-lo. Este
+// módulo usa `update_hook`/`commit_hook`/`rollback_hook` do rusqlite para
+// capturar automaticamente todo INSERT/UPDATE/DELETE nas tabelas de
+// domínio, então a auditoria reflete o que realmente foi persistido —
+// mesmo que um command futuro esqueça de chamar `auditoria::registrar`.
+//
+// Como funciona:
+// 1. `update_hook` é chamado pelo SQLite a cada linha afetada dentro de
+//    uma transação. Aqui apenas bufferizamos o evento em memória (tabela,
+//    ação, rowid) — nada é gravado ainda, e nenhuma query roda na
+//    conexão a partir do hook.
+// 2. `commit_hook` é chamado quando a transação está prestes a ser
+//    confirmada. O SQLite não permite rodar novas statements na mesma
+//    conexão a partir de um commit hook, então abrimos uma conexão
+//    secundária de curta duração para o mesmo arquivo e gravamos os
+//    eventos bufferizados por ela — o modo WAL (já habilitado em
+//    `connection::configurar_pragmas`) permite essa segunda conexão
+//    aguardar no `busy_timeout` e gravar logo que o commit original libere
+//    o banco.
+// 3. `rollback_hook` descarta o buffer: eventos de uma transação desfeita
+//    nunca devem aparecer no log.
+//
+// O toggle `auditoria_automatica` em `configuracoes` (qualquer valor
+// diferente de "false" é tratado como habilitado) é relido a cada
+// commit, então pode ser ligado/desligado em tempo real com o command
+// genérico `salvar_configuracao`, sem reiniciar o aplicativo.
+// =============================================================================
+
+use log::{info, warn};
+use rusqlite::hooks::Action;
+use rusqlite::Connection;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::queries;
+
+/// Tabelas de domínio cujas alterações são auditadas automaticamente.
+const TABELAS_AUDITADAS: &[&str] = &["itens", "pastas", "tags", "anexos"];
+
+/// Um evento de alteração capturado pelo `update_hook`, ainda não persistido.
+#[derive(Debug, Clone)]
+struct EventoPendente {
+    tipo_evento: &'static str,
+    entidade_tipo: &'static str,
+    entidade_id: String,
+}
+
+/// Instala os hooks de auditoria automática na conexão informada.
+/// `caminho_banco` é usado para abrir a conexão secundária de escrita do
+/// log dentro do commit hook.
+pub fn instalar_hooks(conexao: &Connection, caminho_banco: PathBuf) {
+    let buffer: Arc<Mutex<Vec<EventoPendente>>> = Arc::new(Mutex::new(Vec::new()));
+
+    let buffer_update = Arc::clone(&buffer);
+    conexao.update_hook(Some(
+        move |acao: Action, _banco: &str, tabela: &str, rowid: i64| {
+            let Some(entidade_tipo) = nome_entidade_singular(tabela) else {
+                return;
+            };
+            let tipo_evento = match acao {
+                Action::SQLITE_INSERT => "criacao",
+                Action::SQLITE_UPDATE => "atualizacao",
+                Action::SQLITE_DELETE => "exclusao",
+                _ => return,
+            };
+
+            buffer_update.lock().unwrap().push(EventoPendente {
+                tipo_evento,
+                entidade_tipo,
+                entidade_id: rowid.to_string(),
+            });
+        },
+    ));
+
+    let buffer_commit = Arc::clone(&buffer);
+    conexao.commit_hook(Some(move || {
+        let eventos: Vec<EventoPendente> = std::mem::take(&mut *buffer_commit.lock().unwrap());
+        if !eventos.is_empty() {
+            if let Err(erro) = persistir_eventos(&caminho_banco, &eventos) {
+                warn!("Falha ao persistir log de auditoria automático: {}", erro);
+            }
+        }
+        false // nunca aborta o commit original
+    }));
+
+    let buffer_rollback = Arc::clone(&buffer);
+    conexao.rollback_hook(Some(move || {
+        buffer_rollback.lock().unwrap().clear();
+    }));
+
+    info!(
+        "Hooks de auditoria automática instalados ({} tabelas monitoradas).",
+        TABELAS_AUDITADAS.len()
+    );
+}
+
+/// Mapeia o nome da tabela (plural) para o `entidade_tipo` usado no log
+/// de auditoria (singular), ou `None` se a tabela não for auditada.
+fn nome_entidade_singular(tabela: &str) -> Option<&'static str> {
+    if !TABELAS_AUDITADAS.contains(&tabela) {
+        return None;
+    }
+    match tabela {
+        "itens" => Some("item"),
+        "pastas" => Some("pasta"),
+        "tags" => Some("tag"),
+        "anexos" => Some("anexo"),
+        _ => None,
+    }
+}
+
+/// Abre uma conexão secundária de curta duração e grava os eventos
+/// bufferizados em `log_auditoria`, respeitando o toggle `auditoria_automatica`.
+fn persistir_eventos(caminho_banco: &PathBuf, eventos: &[EventoPendente]) -> anyhow::Result<()> {
+    let conexao = Connection::open(caminho_banco)?;
+    conexao.busy_timeout(Duration::from_secs(5))?;
+
+    if !captura_habilitada(&conexao) {
+        return Ok(());
+    }
+
+    for evento in eventos {
+        queries::registrar_evento_auditoria(
+            &conexao,
+            evento.tipo_evento,
+            evento.entidade_tipo,
+            Some(&evento.entidade_id),
+            None,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Lê o toggle `auditoria_automatica` em `configuracoes`. Habilitado por
+/// padrão — só é desativado quando o valor salvo é exatamente "false".
+fn captura_habilitada(conexao: &Connection) -> bool {
+    match queries::obter_configuracao(conexao, "auditoria_automatica") {
+        Ok(Some(config)) => config.valor.as_deref() != Some("false"),
+        _ => true,
+    }
+}
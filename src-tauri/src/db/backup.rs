@@ -0,0 +1,145 @@
+// =============================================================================
+// VaultCraft — Backup Online (Hot Backup) via SQLite Backup API
+// =============================================================================
+// Usa a Online Backup API do SQLite (`rusqlite::backup::Backup`) para
+// copiar o banco de dados página a página enquanto o aplicativo continua
+// rodando, sem bloquear escritas concorrentes e sem o risco de corromper
+// um arquivo que uma cópia simples (`fs::copy`/`fs::read`) teria caso
+// pegasse uma gravação em andamento no meio.
+//
+// Diferente do backup .vaultbackup (ZIP com manifesto e hashes SHA-256,
+// ver `services::backup`), este módulo expõe a primitiva de baixo nível
+// de snapshot do próprio arquivo SQLite, com progresso incremental —
+// útil para o `services::backup` compor em cima, ou para uma restauração
+// rápida sem precisar passar por um pacote ZIP.
+// =============================================================================
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::Connection;
+use std::path::Path;
+use std::time::Duration;
+
+use super::migrations::executar_migracoes;
+
+/// Quantidade de páginas copiadas por chamada de `step` durante o backup
+/// incremental. Valores menores dão um progresso mais granular; valores
+/// maiores terminam mais rápido, mas relatam progresso com menos frequência.
+const PAGINAS_POR_PASSO: i32 = 100;
+
+/// Progresso de um backup/restauração incremental.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressoBackup {
+    pub paginas_restantes: i32,
+    pub paginas_totais: i32,
+}
+
+/// Copia o banco de dados da conexão `origem` (que pode estar em uso
+/// normalmente, inclusive com escritas concorrentes) para o arquivo em
+/// `destino`, usando a Online Backup API do SQLite.
+///
+/// `ao_progredir` é chamado após cada lote de `PAGINAS_POR_PASSO`
+/// páginas copiadas, permitindo à UI mostrar uma barra de progresso.
+pub fn backup_to(
+    origem: &Connection,
+    destino: &Path,
+    mut ao_progredir: impl FnMut(ProgressoBackup),
+) -> Result<()> {
+    info!("Iniciando backup online para: {:?}", destino);
+
+    let mut conexao_destino = Connection::open(destino)
+        .with_context(|| format!("Falha ao abrir/criar arquivo de destino: {:?}", destino))?;
+
+    let backup = Backup::new(origem, &mut conexao_destino)
+        .context("Falha ao iniciar Backup API do SQLite")?;
+
+    copiar_em_passos(&backup, &mut ao_progredir)?;
+
+    info!("Backup online concluído: {:?}", destino);
+    Ok(())
+}
+
+/// Restaura o banco de dados `destino` (a conexão ativa do aplicativo) a
+/// partir do snapshot em `origem`.
+///
+/// Antes de copiar, o snapshot de origem é validado com
+/// `PRAGMA integrity_check` e tem o runner de migrações executado sobre
+/// ele — se vier de uma versão mais antiga do VaultCraft, é atualizado
+/// para o schema atual antes de "trocar de lugar" com o banco ativo.
+/// Isso evita restaurar um arquivo corrompido ou deixar o app rodando
+/// contra um schema desatualizado.
+pub fn restore_from(
+    origem: &Path,
+    destino: &mut Connection,
+    mut ao_progredir: impl FnMut(ProgressoBackup),
+) -> Result<()> {
+    info!("Validando snapshot de origem para restauração: {:?}", origem);
+
+    let conexao_origem = Connection::open(origem)
+        .with_context(|| format!("Falha ao abrir snapshot de origem: {:?}", origem))?;
+
+    verificar_integridade(&conexao_origem)
+        .context("Snapshot de origem reprovado na verificação de integridade")?;
+
+    executar_migracoes(&conexao_origem)
+        .context("Falha ao atualizar schema do snapshot de origem antes de restaurar")?;
+
+    info!("Snapshot validado e atualizado. Restaurando sobre o banco ativo...");
+
+    let backup = Backup::new(&conexao_origem, destino)
+        .context("Falha ao iniciar Backup API para restauração")?;
+
+    copiar_em_passos(&backup, &mut ao_progredir)?;
+
+    info!("Restauração concluída a partir de: {:?}", origem);
+    Ok(())
+}
+
+/// Roda `PRAGMA integrity_check` e falha se o banco relatar qualquer
+/// problema (o pragma retorna exatamente a linha "ok" quando está íntegro;
+/// caso contrário, retorna uma linha por problema encontrado).
+fn verificar_integridade(conexao: &Connection) -> Result<()> {
+    let mut stmt = conexao.prepare("PRAGMA integrity_check")?;
+    let linhas: Vec<String> = stmt
+        .query_map([], |linha| linha.get(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Falha ao executar PRAGMA integrity_check")?;
+
+    if linhas.len() == 1 && linhas[0] == "ok" {
+        Ok(())
+    } else {
+        bail!(
+            "Banco de origem falhou na verificação de integridade: {}",
+            linhas.join("; ")
+        );
+    }
+}
+
+/// Executa `Backup::step` em lotes de `PAGINAS_POR_PASSO` páginas até
+/// terminar, reportando o progresso a cada lote. Se o banco de destino
+/// estiver temporariamente ocupado/travado, aguarda um pouco e tenta de novo.
+fn copiar_em_passos(
+    backup: &Backup<'_, '_>,
+    ao_progredir: &mut dyn FnMut(ProgressoBackup),
+) -> Result<()> {
+    loop {
+        let resultado = backup
+            .step(PAGINAS_POR_PASSO)
+            .context("Falha ao copiar página(s) do banco de dados")?;
+
+        let progresso = backup.progress();
+        ao_progredir(ProgressoBackup {
+            paginas_restantes: progresso.remaining,
+            paginas_totais: progresso.pagecount,
+        });
+
+        match resultado {
+            StepResult::Done => return Ok(()),
+            StepResult::More => continue,
+            StepResult::Busy | StepResult::Locked => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+}
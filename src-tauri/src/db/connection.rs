@@ -11,11 +11,14 @@
 //   (com WAL, NORMAL é seguro contra corrupção em caso de crash)
 // =============================================================================
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use log::info;
 use rusqlite::Connection;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
 
+use super::auditoria_automatica;
 use super::migrations::executar_migracoes;
 
 /// Inicializa o banco de dados SQLite no diretório do aplicativo.
@@ -25,10 +28,24 @@ use super::migrations::executar_migracoes;
 /// 2. Abre/cria o arquivo do banco
 /// 3. Configura pragmas de performance e segurança
 /// 4. Executa migrações pendentes
-/// 5. Retorna a conexão pronta para uso
+/// 5. Instala os hooks de captura automática de auditoria
+/// 6. Retorna a conexão pronta para uso
 ///
 /// O arquivo do banco é criado em `app_dir/vaultcraft.db`.
 pub fn inicializar_banco(diretorio_app: &Path) -> Result<Connection> {
+    inicializar_banco_interno(diretorio_app, None)
+}
+
+/// Mesmo que `inicializar_banco`, mas aplica `PRAGMA key` com `chave_hex`
+/// (chave mestra derivada do PIN, ver `crypto::chave_mestra`) antes de
+/// qualquer outro pragma ou migração — exigido pelo SQLCipher (feature
+/// "bundled-sqlcipher" do rusqlite) para cifrar o arquivo em repouso. Ver
+/// `PoolConexoes::abrir_com_capacidade_cifrada`.
+fn inicializar_banco_cifrado(diretorio_app: &Path, chave_hex: &str) -> Result<Connection> {
+    inicializar_banco_interno(diretorio_app, Some(chave_hex))
+}
+
+fn inicializar_banco_interno(diretorio_app: &Path, chave_hex: Option<&str>) -> Result<Connection> {
     // Garantir que o diretório existe
     std::fs::create_dir_all(diretorio_app)
         .with_context(|| format!("Falha ao criar diretório: {:?}", diretorio_app))?;
@@ -40,6 +57,25 @@ pub fn inicializar_banco(diretorio_app: &Path) -> Result<Connection> {
     let conexao = Connection::open(&caminho_banco)
         .with_context(|| format!("Falha ao abrir banco de dados: {:?}", caminho_banco))?;
 
+    // O PRAGMA key precisa vir antes de QUALQUER outra operação na conexão
+    // (inclusive antes de configurar_pragmas) — o SQLCipher usa a página de
+    // cabeçalho para confirmar a chave, e nada mais no arquivo é legível
+    // até esse pragma rodar com a chave certa.
+    if let Some(chave_hex) = chave_hex {
+        conexao
+            .pragma_update(None, "key", format!("x'{}'", chave_hex))
+            .context("Falha ao aplicar PRAGMA key (chave mestra incorreta ou SQLCipher indisponível)")?;
+
+        // Migração: se o arquivo já existia em texto claro (cofre criado
+        // antes desta funcionalidade), `PRAGMA key` sozinho não cifra nada
+        // — é preciso recifrar o arquivo inteiro com `PRAGMA rekey`, que o
+        // SQLCipher aceita tanto para texto claro -> cifrado quanto para
+        // trocar a chave de um banco já cifrado.
+        conexao
+            .pragma_update(None, "rekey", format!("x'{}'", chave_hex))
+            .context("Falha ao recifrar banco de dados existente (migração para cofre cifrado)")?;
+    }
+
     // Configurar pragmas para performance e integridade
     configurar_pragmas(&conexao)?;
 
@@ -47,6 +83,10 @@ pub fn inicializar_banco(diretorio_app: &Path) -> Result<Connection> {
     executar_migracoes(&conexao)
         .context("Falha ao executar migrações do banco de dados")?;
 
+    // Instalar hooks de captura automática de auditoria (itens, pastas,
+    // tags, anexos). Controlado pelo toggle 'auditoria_automatica'.
+    auditoria_automatica::instalar_hooks(&conexao, caminho_banco.clone());
+
     info!("Banco de dados inicializado com sucesso.");
     Ok(conexao)
 }
@@ -81,3 +121,188 @@ fn configurar_pragmas(conexao: &Connection) -> Result<()> {
     info!("Pragmas do SQLite configurados (WAL, FK, cache 8MB).");
     Ok(())
 }
+
+/// Valor padrão de `max_conexoes_concorrentes` passado a `PoolConexoes::abrir`
+/// pelo `setup()` do Tauri. Pequeno o bastante para não gastar memória/handles
+/// à toa num app desktop single-user, grande o bastante para que uma busca
+/// FTS ampla não deixe as demais leituras concorrentes esperando na fila.
+pub const MAX_CONEXOES_CONCORRENTES_PADRAO: usize = 4;
+
+/// Pool de conexões SQLite usado por `EstadoApp` no lugar de um único
+/// `Mutex<Connection>` compartilhado por todos os comandos.
+///
+/// Mantém um pequeno conjunto de conexões de leitura (cada uma com seu
+/// próprio `Mutex`) mais uma conexão de escrita dedicada, todas abertas
+/// para o mesmo arquivo em modo WAL. Em WAL, leituras podem avançar
+/// concorrentemente com a escrita em andamento — um único mutex global
+/// serializava até leituras entre si, fazendo uma busca ampla ou uma
+/// consulta de histórico bloquear a UI inteira enquanto outro comando
+/// estivesse com o lock.
+///
+/// Comandos só-leitura usam `leitor()`; toda mutação passa por
+/// `escritor()`, preservando a invariante de escritor único do SQLite
+/// (o `busy_timeout` configurado em `configurar_pragmas` cobre o caso raro
+/// de duas escritas colidirem).
+pub struct PoolConexoes {
+    leitores: Vec<Mutex<Connection>>,
+    proximo_leitor: AtomicUsize,
+    escritor: Mutex<Connection>,
+}
+
+impl PoolConexoes {
+    /// Abre o pool para o banco em `diretorio_app` com o tamanho padrão
+    /// (`MAX_CONEXOES_CONCORRENTES_PADRAO`). Ver `abrir_com_capacidade` para
+    /// controlar o número de conexões de leitura.
+    pub fn abrir(diretorio_app: &Path) -> Result<Self> {
+        Self::abrir_com_capacidade(diretorio_app, MAX_CONEXOES_CONCORRENTES_PADRAO)
+    }
+
+    /// Abre o pool para o banco em `diretorio_app`: inicializa o banco
+    /// (migrações + hooks de auditoria, ver `inicializar_banco`) na conexão
+    /// de escrita e abre mais `max_conexoes_concorrentes` conexões de leitura
+    /// para o mesmo arquivo. Como todas compartilham o mesmo WAL, as
+    /// leituras enxergam os commits do escritor sem nenhuma sincronização
+    /// adicional.
+    pub fn abrir_com_capacidade(diretorio_app: &Path, max_conexoes_concorrentes: usize) -> Result<Self> {
+        let escritor = inicializar_banco(diretorio_app)?;
+        let caminho_banco = diretorio_app.join("vaultcraft.db");
+
+        let mut leitores = Vec::with_capacity(max_conexoes_concorrentes);
+        for _ in 0..max_conexoes_concorrentes {
+            leitores.push(Mutex::new(abrir_conexao_leitura(&caminho_banco)?));
+        }
+
+        info!(
+            "Pool de conexões pronto ({} leitores + 1 escritor).",
+            leitores.len()
+        );
+
+        Ok(Self {
+            leitores,
+            proximo_leitor: AtomicUsize::new(0),
+            escritor: Mutex::new(escritor),
+        })
+    }
+
+    /// Mesmo que `abrir_com_capacidade`, mas para um cofre cifrado em
+    /// repouso (SQLCipher): aplica `chave_hex` — a chave mestra derivada do
+    /// PIN via `crypto::chave_mestra::derivar_chave` — em todas as conexões
+    /// do pool, inclusive recifrando um banco em texto claro já existente no
+    /// primeiro desbloqueio (ver `inicializar_banco_cifrado`).
+    ///
+    /// Ainda não é chamada por `run()`: falta, neste repositório, a tela que
+    /// colete o PIN do usuário antes do cofre abrir. Este método é o ponto
+    /// de entrada pronto para quando esse fluxo de UI existir.
+    pub fn abrir_com_capacidade_cifrada(
+        diretorio_app: &Path,
+        max_conexoes_concorrentes: usize,
+        chave_hex: &str,
+    ) -> Result<Self> {
+        let escritor = inicializar_banco_cifrado(diretorio_app, chave_hex)?;
+        let caminho_banco = diretorio_app.join("vaultcraft.db");
+
+        let mut leitores = Vec::with_capacity(max_conexoes_concorrentes);
+        for _ in 0..max_conexoes_concorrentes {
+            leitores.push(Mutex::new(abrir_conexao_leitura_cifrada(&caminho_banco, chave_hex)?));
+        }
+
+        info!(
+            "Pool de conexões (cofre cifrado) pronto ({} leitores + 1 escritor).",
+            leitores.len()
+        );
+
+        Ok(Self {
+            leitores,
+            proximo_leitor: AtomicUsize::new(0),
+            escritor: Mutex::new(escritor),
+        })
+    }
+
+    /// Retorna uma conexão de leitura do pool, escolhida em round-robin.
+    /// Como cada conexão tem seu próprio `Mutex`, comandos de leitura
+    /// concorrentes tendem a cair em conexões diferentes e rodam em
+    /// paralelo (até `TAMANHO_POOL_LEITURA` por vez).
+    pub fn leitor(&self) -> Result<MutexGuard<'_, Connection>> {
+        let indice = self.proximo_leitor.fetch_add(1, Ordering::Relaxed) % self.leitores.len();
+        self.leitores[indice]
+            .lock()
+            .map_err(|_| anyhow!("Mutex de uma conexão de leitura do pool foi envenenado"))
+    }
+
+    /// Retorna a conexão de escrita dedicada. SQLite permite só um
+    /// escritor por vez mesmo em WAL, então esta seção continua
+    /// serializada — a diferença é que ela não bloqueia as leituras do pool.
+    pub fn escritor(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.escritor
+            .lock()
+            .map_err(|_| anyhow!("Mutex da conexão de escrita do pool foi envenenado"))
+    }
+
+    /// Executa `f` com a conexão de escrita, tendo antes travado também
+    /// todas as conexões de leitura do pool — usado por operações como
+    /// `VACUUM` que reescrevem o arquivo inteiro do banco e não podem
+    /// conviver com uma leitura em andamento em outra conexão WAL.
+    /// `escritor()` sozinho bloquearia outras escritas, mas não os
+    /// leitores, que têm suas próprias conexões/mutexes independentes.
+    pub fn exclusivo<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&Connection) -> Result<R>,
+    {
+        let _leitores_travados: Vec<MutexGuard<'_, Connection>> = self
+            .leitores
+            .iter()
+            .map(|leitor| {
+                leitor
+                    .lock()
+                    .map_err(|_| anyhow!("Mutex de uma conexão de leitura do pool foi envenenado"))
+            })
+            .collect::<Result<_>>()?;
+
+        let escritor = self.escritor()?;
+        f(&escritor)
+    }
+}
+
+/// Abre uma conexão de leitura adicional para o arquivo do banco já
+/// existente, com os mesmos pragmas da conexão de escrita. Não executa
+/// migrações nem instala hooks de auditoria — isso já foi feito pela
+/// conexão de escrita via `inicializar_banco`, e hooks de auditoria só
+/// fazem sentido na conexão que efetivamente escreve.
+fn abrir_conexao_leitura(caminho_banco: &PathBuf) -> Result<Connection> {
+    abrir_conexao_leitura_interno(caminho_banco, None)
+}
+
+fn abrir_conexao_leitura_cifrada(caminho_banco: &PathBuf, chave_hex: &str) -> Result<Connection> {
+    abrir_conexao_leitura_interno(caminho_banco, Some(chave_hex))
+}
+
+fn abrir_conexao_leitura_interno(caminho_banco: &PathBuf, chave_hex: Option<&str>) -> Result<Connection> {
+    let conexao = Connection::open(caminho_banco)
+        .with_context(|| format!("Falha ao abrir conexão de leitura: {:?}", caminho_banco))?;
+    if let Some(chave_hex) = chave_hex {
+        conexao
+            .pragma_update(None, "key", format!("x'{}'", chave_hex))
+            .context("Falha ao aplicar PRAGMA key em conexão de leitura")?;
+    }
+    configurar_pragmas(&conexao)?;
+    Ok(conexao)
+}
+
+/// Tenta abrir (conexão descartável, fechada ao sair de escopo) o banco em
+/// `caminho_banco` com `chave_hex` e confirma que a página de cabeçalho
+/// decifra corretamente — uma consulta trivial contra `sqlite_master` falha
+/// com chave errada, já que o SQLCipher não consegue nem ler o schema nesse
+/// caso. Usado no lugar de `crypto::verificar_pin` quando o cofre está
+/// cifrado (ver `crypto::chave_mestra`), já que não há mais um hash de PIN
+/// salvo à parte para comparar.
+pub fn verificar_chave_mestra(caminho_banco: &Path, chave_hex: &str) -> bool {
+    let Ok(conexao) = Connection::open(caminho_banco) else {
+        return false;
+    };
+    if conexao.pragma_update(None, "key", format!("x'{}'", chave_hex)).is_err() {
+        return false;
+    }
+    conexao
+        .query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+        .is_ok()
+}
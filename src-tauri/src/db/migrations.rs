@@ -2,111 +2,418 @@
 // VaultCraft — Sistema de Migrações do Banco de Dados
 // =============================================================================
 // Gerencia a evolução do schema do banco de dados de forma incremental.
-// Cada migração é um arquivo SQL embutido no binário via include_str!.
-// A versão atual é rastreada na tabela 'configuracoes' (chave 'versao_schema').
+// Cada migração é um par de arquivos SQL embutidos no binário via
+// include_str!: `up_sql` aplica a mudança, `down_sql` a desfaz.
+//
+// A versão atual do schema é rastreada via `PRAGMA user_version` (um
+// inteiro no próprio cabeçalho do arquivo SQLite), e não mais em uma
+// linha da tabela `configuracoes` — isso elimina a dependência de
+// `CREATE TABLE IF NOT EXISTS` para simplesmente saber "em que versão
+// estamos", e funciona mesmo antes de qualquer tabela existir.
+//
+// Todas as migrações pendentes rodam dentro de uma única transação
+// `BEGIN IMMEDIATE` / `COMMIT`: se qualquer statement falhar, a
+// transação inteira sofre `ROLLBACK`, então o schema nunca fica
+// parcialmente aplicado. Isso segue o modelo de upgrade stepwise do
+// schema de places do Firefox (uma constante `VERSION` + passos de
+// upgrade) e o padrão "uma transação por padrão" do migra.
 //
 // Por que embutir os SQLs no binário?
 //   - Não depende de arquivos externos em tempo de execução
 //   - Garante que a migração correta está sempre disponível
 //   - Simplifica a distribuição do aplicativo
+//
+// Além da versão, `schema_migracoes` guarda o hash SHA-256 do `up_sql` de
+// cada migração já aplicada — um ledger de integridade que detecta se o
+// SQL embutido no binário foi alterado depois que uma migração já rodou
+// (ver `verificar_historico_migracoes`).
+//
+// Cada passo aplicado (além do 001, que cria a própria tabela de auditoria)
+// também é registrado em `log_auditoria` via
+// `queries::registrar_evento_auditoria` (tipo "migracao_schema"), para que
+// upgrades de schema apareçam no histórico do cofre como qualquer outro
+// evento.
 // =============================================================================
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
 use log::info;
-use rusqlite::Connection;
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
 
 /// SQL da migração 001 — Esquema inicial completo
 /// Inclui todas as tabelas, índices, FTS5 (standalone) e triggers de sincronização.
-const MIGRACAO_001: &str = include_str!("migrations/001_schema_inicial.sql");
+const MIGRACAO_001_UP: &str = include_str!("migrations/001_schema_inicial.sql");
+
+/// Reversão da migração 001 — remove todo o schema inicial.
+const MIGRACAO_001_DOWN: &str = include_str!("migrations/001_schema_inicial_down.sql");
 
 /// SQL da migração 002 — Dados iniciais (seed data)
 /// Insere configurações padrão necessárias para o primeiro uso.
-const MIGRACAO_002: &str = include_str!("migrations/002_dados_iniciais.sql");
-
-/// Lista ordenada de migrações disponíveis.
-/// Cada tupla contém (versão_destino, sql_da_migração).
-/// Novas migrações devem ser adicionadas ao final desta lista.
-const MIGRACOES: &[(i32, &str)] = &[
-    (1, MIGRACAO_001),
-    (2, MIGRACAO_002),
+const MIGRACAO_002_UP: &str = include_str!("migrations/002_dados_iniciais.sql");
+
+/// Reversão da migração 002 — remove os dados iniciais inseridos.
+const MIGRACAO_002_DOWN: &str = include_str!("migrations/002_dados_iniciais_down.sql");
+
+/// SQL da migração 003 — Histórico de revisões de itens
+/// Cria `item_revisoes`, usada por `queries::atualizar_item`/`restaurar_revisao`.
+const MIGRACAO_003_UP: &str = include_str!("migrations/003_revisoes_item.sql");
+
+/// Reversão da migração 003 — remove a tabela de revisões de itens.
+const MIGRACAO_003_DOWN: &str = include_str!("migrations/003_revisoes_item_down.sql");
+
+/// SQL da migração 004 — Cadeia de hashes no log de auditoria
+/// Adiciona `prev_hash`/`entry_hash` a `log_auditoria`, usados por
+/// `services::auditoria::registrar`/`verificar_integridade`.
+const MIGRACAO_004_UP: &str = include_str!("migrations/004_cadeia_auditoria.sql");
+
+/// Reversão da migração 004 — remove as colunas da cadeia de hashes.
+const MIGRACAO_004_DOWN: &str = include_str!("migrations/004_cadeia_auditoria_down.sql");
+
+/// SQL da migração 005 — Armazenamento de anexos endereçado por conteúdo
+/// Cria `blobs`, usada por `services::armazenamento::salvar_anexo`/
+/// `remover_anexo` para deduplicar anexos idênticos por hash SHA-256.
+const MIGRACAO_005_UP: &str = include_str!("migrations/005_armazenamento_blobs.sql");
+
+/// Reversão da migração 005 — remove a tabela de blobs.
+const MIGRACAO_005_DOWN: &str = include_str!("migrations/005_armazenamento_blobs_down.sql");
+
+/// SQL da migração 006 — Log de operações estruturado com checkpoints (HLC)
+/// Adiciona `hlc`/`operacao`/`payload_antes`/`payload_depois` a
+/// `log_auditoria` e cria `checkpoints_auditoria`, usados por
+/// `services::auditoria::registrar_mutacao`/`replay`.
+const MIGRACAO_006_UP: &str = include_str!("migrations/006_log_operacoes.sql");
+
+/// Reversão da migração 006 — remove o log de operações e os checkpoints.
+const MIGRACAO_006_DOWN: &str = include_str!("migrations/006_log_operacoes_down.sql");
+
+/// SQL da migração 007 — Colunas seladas para conteudo_nota/descricao
+/// Adiciona `conteudo_nota_selado`/`descricao_selada` a `itens`, usadas por
+/// `crypto::campo_cifrado` + `services::cifragem` para cifragem em repouso
+/// desses dois campos.
+const MIGRACAO_007_UP: &str = include_str!("migrations/007_selagem_campos.sql");
+
+/// Reversão da migração 007 — remove as colunas seladas.
+const MIGRACAO_007_DOWN: &str = include_str!("migrations/007_selagem_campos_down.sql");
+
+/// SQL da migração 008 — Registro de tempo trabalhado em tarefas
+/// Cria `entradas_tempo`, usada por `queries::registrar_tempo`/
+/// `listar_tempo_por_tarefa`/`total_tempo_por_item` para reportar quanto
+/// tempo foi gasto em cada checklist.
+const MIGRACAO_008_UP: &str = include_str!("migrations/008_entradas_tempo.sql");
+
+/// Reversão da migração 008 — remove a tabela de entradas de tempo.
+const MIGRACAO_008_DOWN: &str = include_str!("migrations/008_entradas_tempo_down.sql");
+
+/// SQL da migração 009 — Dependências entre tarefas de checklist
+/// Cria `dependencias_tarefa`, usada por `queries::adicionar_dependencia`/
+/// `remover_dependencia`/`listar_dependencias`/`tarefa_bloqueada` para dar
+/// ordem de pré-requisitos a checklists.
+const MIGRACAO_009_UP: &str = include_str!("migrations/009_dependencias_tarefa.sql");
+
+/// Reversão da migração 009 — remove o grafo de dependências.
+const MIGRACAO_009_DOWN: &str = include_str!("migrations/009_dependencias_tarefa_down.sql");
+
+/// Migração 010 — adiciona `itens.regra_recorrencia` (ver services::recorrencia).
+const MIGRACAO_010_UP: &str = include_str!("migrations/010_regra_recorrencia.sql");
+
+/// Reversão da migração 010 — remove a coluna de recorrência.
+const MIGRACAO_010_DOWN: &str = include_str!("migrations/010_regra_recorrencia_down.sql");
+
+/// Migração 011 — cria `segredos`/`tokens_segredos` (ver services::api_segredos).
+const MIGRACAO_011_UP: &str = include_str!("migrations/011_segredos_kv.sql");
+
+/// Reversão da migração 011 — remove as tabelas de segredos e tokens.
+const MIGRACAO_011_DOWN: &str = include_str!("migrations/011_segredos_kv_down.sql");
+
+/// Uma migração versionada: `up_sql` aplica a mudança, `down_sql` a desfaz.
+/// `down_sql` é `None` para migrações que não podem (ou ainda não foram
+/// escritas para) ser revertidas — `reverter_para` falha alto em vez de
+/// pular essas migrações silenciosamente. Novas migrações devem ser
+/// adicionadas ao final de `MIGRACOES`.
+struct Migracao {
+    versao: u32,
+    up_sql: &'static str,
+    down_sql: Option<&'static str>,
+}
+
+/// Ledger de integridade: para cada versão já aplicada, guarda o SHA-256 do
+/// `up_sql` exato que rodou e quando. Não é uma migração numerada em si —
+/// precisa existir antes de qualquer migração poder ser verificada, então é
+/// criada diretamente aqui (mesma lógica do `PRAGMA user_version`: não dá
+/// para depender do próprio sistema de migrações para rastrear o sistema de
+/// migrações). Detecta adulteração ou reescrita de histórico: se o SQL
+/// embutido no binário não bater mais com o hash gravado na hora em que a
+/// migração rodou, `executar_migracoes` aborta em vez de seguir num schema
+/// que pode não ser o que o banco realmente tem.
+const SCHEMA_MIGRACOES_TABELA: &str = "
+CREATE TABLE IF NOT EXISTS schema_migracoes (
+    versao      INTEGER NOT NULL PRIMARY KEY,
+    sha256_sql  TEXT NOT NULL,
+    aplicada_em TEXT NOT NULL
+);
+";
+
+/// Registro ordenado de migrações disponíveis.
+const MIGRACOES: &[Migracao] = &[
+    Migracao { versao: 1, up_sql: MIGRACAO_001_UP, down_sql: Some(MIGRACAO_001_DOWN) },
+    Migracao { versao: 2, up_sql: MIGRACAO_002_UP, down_sql: Some(MIGRACAO_002_DOWN) },
+    Migracao { versao: 3, up_sql: MIGRACAO_003_UP, down_sql: Some(MIGRACAO_003_DOWN) },
+    Migracao { versao: 4, up_sql: MIGRACAO_004_UP, down_sql: Some(MIGRACAO_004_DOWN) },
+    Migracao { versao: 5, up_sql: MIGRACAO_005_UP, down_sql: Some(MIGRACAO_005_DOWN) },
+    Migracao { versao: 6, up_sql: MIGRACAO_006_UP, down_sql: Some(MIGRACAO_006_DOWN) },
+    Migracao { versao: 7, up_sql: MIGRACAO_007_UP, down_sql: Some(MIGRACAO_007_DOWN) },
+    Migracao { versao: 8, up_sql: MIGRACAO_008_UP, down_sql: Some(MIGRACAO_008_DOWN) },
+    Migracao { versao: 9, up_sql: MIGRACAO_009_UP, down_sql: Some(MIGRACAO_009_DOWN) },
+    Migracao { versao: 10, up_sql: MIGRACAO_010_UP, down_sql: Some(MIGRACAO_010_DOWN) },
+    Migracao { versao: 11, up_sql: MIGRACAO_011_UP, down_sql: Some(MIGRACAO_011_DOWN) },
 ];
 
 /// Executa todas as migrações pendentes no banco de dados.
 ///
 /// O processo é:
-/// 1. Garante que a tabela de configurações existe (para rastrear versão)
-/// 2. Lê a versão atual do schema
-/// 3. Executa cada migração com versão > atual em ordem
-/// 4. Atualiza a versão do schema após cada migração bem-sucedida
-///
-/// Cada migração roda dentro de uma transação para garantir atomicidade.
-/// Se uma migração falhar, o banco volta ao estado anterior àquela migração.
+/// 1. Garante que `schema_migracoes` existe e confere o hash de cada
+///    migração já aplicada contra o `up_sql` embutido no binário, abortando
+///    se algum divergir (ver `SCHEMA_MIGRACOES_TABELA`)
+/// 2. Lê a versão atual via `PRAGMA user_version`
+/// 3. Seleciona as migrações cuja versão é estritamente maior
+/// 4. Abre uma única transação `BEGIN IMMEDIATE`
+/// 5. Executa cada `up_sql` pendente em ordem crescente, atualizando
+///    `PRAGMA user_version` e gravando seu checksum em `schema_migracoes`
+///    logo após cada uma
+/// 6. `COMMIT` se tudo deu certo; `ROLLBACK` do lote inteiro se algo falhar
 pub fn executar_migracoes(conexao: &Connection) -> Result<()> {
-    // Garantir que a tabela de configurações existe antes de tudo.
-    // Usamos IF NOT EXISTS para que seja idempotente.
-    // A tabela precisa existir antes das migrações para rastrear a versão do schema.
-    // Nota: a coluna atualizado_em não tem DEFAULT porque a migração 001 já
-    // define a tabela sem DEFAULT (mantemos compatibilidade com schema existente).
-    conexao.execute_batch(
-        "CREATE TABLE IF NOT EXISTS configuracoes (
-            chave         TEXT NOT NULL PRIMARY KEY,
-            valor         TEXT,
-            atualizado_em TEXT NOT NULL DEFAULT (datetime('now'))
-        );
-        INSERT OR IGNORE INTO configuracoes (chave, valor, atualizado_em)
-        VALUES ('versao_schema', '0', datetime('now'));",
-    ).context("Falha ao criar tabela de configurações inicial")?;
+    conexao.execute_batch(SCHEMA_MIGRACOES_TABELA)
+        .context("Falha ao criar tabela schema_migracoes")?;
 
     let versao_atual = obter_versao_schema(conexao)?;
-    info!("Versão atual do schema: {}", versao_atual);
-
-    for &(versao_destino, sql) in MIGRACOES {
-        if versao_destino > versao_atual {
-            info!(
-                "Executando migração V{:03} (versão {} -> {})...",
-                versao_destino, versao_atual, versao_destino
-            );
-
-            // Executar o SQL da migração diretamente (sem transação explícita)
-            // porque PRAGMAs como journal_mode e foreign_keys não podem rodar
-            // dentro de transações no SQLite. As DDL statements (CREATE TABLE, etc.)
-            // são implicitamente transacionais no SQLite.
-            conexao.execute_batch(sql)
-                .with_context(|| format!("Falha ao executar migração V{:03}", versao_destino))?;
-
-            // Atualizar a versão do schema após migração bem-sucedida
-            conexao.execute(
-                "UPDATE configuracoes SET valor = ?1, atualizado_em = datetime('now') WHERE chave = 'versao_schema'",
-                rusqlite::params![versao_destino.to_string()],
-            ).context("Falha ao atualizar versão do schema")?;
-
-            info!("Migração V{:03} aplicada com sucesso.", versao_destino);
+    info!("Versão atual do schema (user_version): {}", versao_atual);
+
+    let versao_conhecida = versao_mais_recente() as u32;
+    if versao_atual > versao_conhecida {
+        anyhow::bail!(
+            "O banco está na versão de schema {}, mas esta versão do VaultCraft só conhece até a versão {}. \
+             Atualize o aplicativo antes de abrir este cofre.",
+            versao_atual, versao_conhecida,
+        );
+    }
+
+    verificar_historico_migracoes(conexao, versao_atual)?;
+
+    let pendentes: Vec<&Migracao> = MIGRACOES.iter().filter(|m| m.versao > versao_atual).collect();
+    if pendentes.is_empty() {
+        info!("Schema já está atualizado (versão {}).", versao_atual);
+        return Ok(());
+    }
+
+    conexao.execute_batch("BEGIN IMMEDIATE;")
+        .context("Falha ao iniciar transação de migração")?;
+
+    for migracao in &pendentes {
+        info!(
+            "Executando migração V{:03} (versão {} -> {})...",
+            migracao.versao, versao_atual, migracao.versao
+        );
+
+        if let Err(erro) = conexao.execute_batch(migracao.up_sql) {
+            conexao.execute_batch("ROLLBACK;").ok();
+            return Err(erro)
+                .with_context(|| format!("Falha ao executar migração V{:03}, rollback efetuado", migracao.versao));
         }
+
+        if let Err(erro) = definir_versao_schema(conexao, migracao.versao) {
+            conexao.execute_batch("ROLLBACK;").ok();
+            return Err(erro);
+        }
+
+        if let Err(erro) = registrar_checksum_migracao(conexao, migracao) {
+            conexao.execute_batch("ROLLBACK;").ok();
+            return Err(erro);
+        }
+
+        // A migração 001 cria `log_auditoria`, mas só a 004 adiciona
+        // `prev_hash`/`entry_hash` (ver 004_cadeia_auditoria.sql) — colunas
+        // que `registrar_evento_auditoria`/`ultimo_hash_auditoria` leem e
+        // gravam incondicionalmente. Antes da 004 não há onde registrar o
+        // passo (001) nem como gravar a linha sem essas colunas (002/003):
+        // num banco novo, 1→011 roda inteira numa transação, então auditar
+        // a migração 002 quebraria a inicialização de qualquer vault novo.
+        if migracao.versao > 3 {
+            if let Err(erro) = super::queries::registrar_evento_auditoria(
+                conexao,
+                "migracao_schema",
+                "schema",
+                Some(&migracao.versao.to_string()),
+                Some(&format!("Migração V{:03} aplicada (versão {} -> {})", migracao.versao, versao_atual, migracao.versao)),
+            ) {
+                conexao.execute_batch("ROLLBACK;").ok();
+                return Err(erro).with_context(|| format!("Falha ao registrar auditoria da migração V{:03}", migracao.versao));
+            }
+        }
+
+        info!("Migração V{:03} aplicada com sucesso.", migracao.versao);
     }
 
+    conexao.execute_batch("COMMIT;")
+        .context("Falha ao confirmar transação de migração")?;
+
     let versao_final = obter_versao_schema(conexao)?;
     info!("Schema atualizado. Versão final: {}", versao_final);
 
     Ok(())
 }
 
-/// Lê a versão atual do schema no banco de dados.
-/// Retorna 0 se a configuração não existir (banco novo).
-fn obter_versao_schema(conexao: &Connection) -> Result<i32> {
-    let resultado: Result<String, _> = conexao.query_row(
-        "SELECT valor FROM configuracoes WHERE chave = 'versao_schema'",
-        [],
-        |linha| linha.get(0),
-    );
-
-    match resultado {
-        Ok(valor) => {
-            let versao: i32 = valor.parse().unwrap_or(0);
-            Ok(versao)
+/// Para cada migração com versão <= `versao_atual`, recalcula o SHA-256 do
+/// `up_sql` embutido no binário e confere contra o valor gravado em
+/// `schema_migracoes`. Bancos que já estavam em uma versão antes deste
+/// recurso existir não têm registro prévio — nesse caso o hash atual é
+/// gravado como linha de base (confiança no primeiro uso) em vez de abortar.
+/// Diverge só quando HÁ um registro anterior que não bate mais, o sinal de
+/// que o SQL embutido foi trocado depois que a migração já tinha rodado.
+fn verificar_historico_migracoes(conexao: &Connection, versao_atual: u32) -> Result<()> {
+    for migracao in MIGRACOES.iter().filter(|m| m.versao <= versao_atual) {
+        let digest_atual = sha256_hex(migracao.up_sql);
+
+        let digest_gravado: Option<String> = conexao
+            .query_row(
+                "SELECT sha256_sql FROM schema_migracoes WHERE versao = ?1",
+                params![migracao.versao],
+                |linha| linha.get(0),
+            )
+            .optional()
+            .with_context(|| format!("Falha ao ler schema_migracoes para V{:03}", migracao.versao))?;
+
+        match digest_gravado {
+            Some(hash_gravado) if hash_gravado != digest_atual => {
+                anyhow::bail!(
+                    "Integridade do schema comprometida: o SQL da migração V{:03} embutido neste \
+                     binário não corresponde mais ao hash gravado quando ela foi aplicada \
+                     (gravado={}, atual={}). O histórico de migrações pode ter sido reescrito ou \
+                     o banco foi adulterado — abortando em vez de abrir o cofre num schema incerto.",
+                    migracao.versao, hash_gravado, digest_atual,
+                );
+            }
+            Some(_) => {}
+            None => {
+                registrar_checksum_migracao(conexao, migracao)
+                    .with_context(|| format!("Falha ao gravar linha de base de V{:03} em schema_migracoes", migracao.versao))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Grava o checksum da migração `migracao` (SHA-256 do `up_sql` embutido e
+/// o instante atual) em `schema_migracoes`.
+fn registrar_checksum_migracao(conexao: &Connection, migracao: &Migracao) -> Result<()> {
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    conexao.execute(
+        "INSERT INTO schema_migracoes (versao, sha256_sql, aplicada_em) VALUES (?1, ?2, ?3)",
+        params![migracao.versao, sha256_hex(migracao.up_sql), agora],
+    )?;
+    Ok(())
+}
+
+/// Hash SHA-256 (hex) de um texto — usado para o ledger de integridade das
+/// migrações.
+fn sha256_hex(texto: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(texto.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Reverte o schema para `versao_alvo`, executando `down_sql` de cada
+/// migração aplicada com versão estritamente maior, em ordem decrescente.
+/// Se `versao_alvo` já é >= à versão atual, não faz nada.
+///
+/// Ao contrário de `executar_migracoes` (uma única transação para todo o
+/// lote), cada reversão roda na sua PRÓPRIA transação, decrementando
+/// `PRAGMA user_version` logo em seguida: se uma reversão falhar no meio
+/// do caminho, as que já rodaram continuam valendo — o schema fica na
+/// versão da última reversão bem-sucedida, não de volta na versão de
+/// partida. Isso espelha o comportamento de uma reversão manual passo a
+/// passo, em vez de fingir atomicidade que o `down_sql` de uma migração
+/// no meio do caminho pode não sustentar.
+///
+/// Migrações sem `down_sql` fazem a função falhar alto assim que alcançadas
+/// — reverter "por cima" delas corromperia o schema silenciosamente, então
+/// é melhor parar e deixar quem chamou decidir o próximo passo.
+///
+/// Útil para testes e para recuperação manual em caso de incidente
+/// (ex.: uma migração aplicada por engano precisa ser desfeita).
+pub fn reverter_para(conexao: &Connection, versao_alvo: u32) -> Result<()> {
+    let versao_atual = obter_versao_schema(conexao)?;
+    if versao_alvo >= versao_atual {
+        return Ok(());
+    }
+
+    let mut a_reverter: Vec<&Migracao> = MIGRACOES
+        .iter()
+        .filter(|m| m.versao > versao_alvo && m.versao <= versao_atual)
+        .collect();
+    a_reverter.sort_by(|a, b| b.versao.cmp(&a.versao));
+
+    for migracao in &a_reverter {
+        let down_sql = migracao.down_sql.ok_or_else(|| {
+            anyhow!(
+                "Migração V{:03} não tem script de reversão — não é possível reverter além dela. \
+                 Schema permanece na versão {}.",
+                migracao.versao,
+                migracao.versao,
+            )
+        })?;
+
+        info!("Revertendo migração V{:03}...", migracao.versao);
+
+        conexao.execute_batch("BEGIN IMMEDIATE;")
+            .context("Falha ao iniciar transação de reversão")?;
+
+        if let Err(erro) = conexao.execute_batch(down_sql) {
+            conexao.execute_batch("ROLLBACK;").ok();
+            return Err(erro)
+                .with_context(|| format!("Falha ao reverter migração V{:03}, rollback efetuado", migracao.versao));
+        }
+
+        let versao_apos = migracao.versao - 1;
+        if let Err(erro) = definir_versao_schema(conexao, versao_apos) {
+            conexao.execute_batch("ROLLBACK;").ok();
+            return Err(erro);
+        }
+
+        if let Err(erro) = conexao
+            .execute("DELETE FROM schema_migracoes WHERE versao = ?1", params![migracao.versao])
+            .context("Falha ao remover checksum de schema_migracoes")
+        {
+            conexao.execute_batch("ROLLBACK;").ok();
+            return Err(erro);
         }
-        // Se a tabela ou registro não existe, estamos na versão 0
-        Err(_) => Ok(0),
+
+        conexao.execute_batch("COMMIT;")
+            .context("Falha ao confirmar transação de reversão")?;
+
+        info!("Migração V{:03} revertida com sucesso (versão agora {}).", migracao.versao, versao_apos);
     }
+
+    Ok(())
+}
+
+/// Lê a versão atual do schema via `PRAGMA user_version`.
+/// Bancos novos começam em 0.
+fn obter_versao_schema(conexao: &Connection) -> Result<u32> {
+    let versao: i64 = conexao
+        .query_row("PRAGMA user_version", [], |linha| linha.get(0))
+        .context("Falha ao ler PRAGMA user_version")?;
+    Ok(versao as u32)
+}
+
+/// Define `PRAGMA user_version`. Não pode ser parametrizado via bind
+/// (PRAGMAs não aceitam parâmetros), mas `versao` é sempre controlada
+/// internamente por `MIGRACOES`, nunca por entrada do usuário.
+fn definir_versao_schema(conexao: &Connection, versao: u32) -> Result<()> {
+    conexao
+        .execute_batch(&format!("PRAGMA user_version = {};", versao))
+        .with_context(|| format!("Falha ao definir PRAGMA user_version = {}", versao))
 }
 
 /// Retorna a versão mais recente disponível nas migrações.
@@ -114,6 +421,13 @@ fn obter_versao_schema(conexao: &Connection) -> Result<i32> {
 pub fn versao_mais_recente() -> i32 {
     MIGRACOES
         .last()
-        .map(|&(versao, _)| versao)
+        .map(|m| m.versao as i32)
         .unwrap_or(0)
 }
+
+/// Lê a versão atual do schema (`PRAGMA user_version`) de uma conexão já
+/// aberta. Usado pelo comando `versao_schema` para exibir a versão ao
+/// usuário no frontend.
+pub fn versao_schema(conexao: &Connection) -> Result<u32> {
+    obter_versao_schema(conexao)
+}
@@ -6,9 +6,15 @@
 // - migrations: sistema de migrações incrementais
 // - models: estruturas de dados (DTOs e entidades)
 // - queries: todas as operações CRUD no banco
+// - backup: snapshot/restauração online via SQLite Backup API
+// - auditoria_automatica: captura de eventos de auditoria via hooks do SQLite
+// - sync: sincronização offline entre instalações via session/changeset
 // =============================================================================
 
+pub mod auditoria_automatica;
+pub mod backup;
 pub mod connection;
 pub mod migrations;
 pub mod models;
 pub mod queries;
+pub mod sync;
@@ -49,6 +49,39 @@ impl TipoItem {
     }
 }
 
+// =============================================================================
+// TemaExportacao — Tema visual usado na exportação HTML de itens
+// =============================================================================
+// Controla as variáveis CSS (--bg, --fg, --muted, --accent, --border) do
+// HTML gerado por `servicos::exportacao::exportar_item_html`. O padrão é
+// lido da configuração 'tema_exportacao'; se ausente, usa Claro.
+// =============================================================================
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TemaExportacao {
+    Claro,
+    Escuro,
+    AltoContraste,
+}
+
+impl TemaExportacao {
+    /// Converte uma string (ex.: valor salvo em `configuracoes`) para o enum.
+    /// Retorna Claro como padrão se o valor não for reconhecido.
+    pub fn de_str(valor: &str) -> Self {
+        match valor {
+            "escuro" => TemaExportacao::Escuro,
+            "altocontraste" => TemaExportacao::AltoContraste,
+            _ => TemaExportacao::Claro,
+        }
+    }
+}
+
+impl Default for TemaExportacao {
+    fn default() -> Self {
+        TemaExportacao::Claro
+    }
+}
+
 // =============================================================================
 // Pasta — Representa uma pasta no cofre (hierarquia com auto-referência)
 // =============================================================================
@@ -83,6 +116,10 @@ pub struct Item {
     pub data_vencimento: Option<String>,
     pub criado_em: String,
     pub atualizado_em: String,
+    /// Regra de recorrência no formato `FREQ=DAILY|WEEKLY|MONTHLY|YEARLY;INTERVAL=N`
+    /// (ver `services::recorrencia`). `None` para itens não recorrentes.
+    #[serde(default)]
+    pub regra_recorrencia: Option<String>,
     /// Tags associadas ao item (carregadas via JOIN)
     #[serde(default)]
     pub tags: Vec<Tag>,
@@ -91,6 +128,25 @@ pub struct Item {
     pub anexos: Vec<Anexo>,
 }
 
+// =============================================================================
+// RevisaoItem — Snapshot histórico de um item antes de uma atualização
+// =============================================================================
+// Criada automaticamente por `queries::atualizar_item` antes de sobrescrever
+// titulo/descricao/conteudo_nota. numero_revisao é monotonicamente
+// crescente por item (1, 2, 3, ...); a quantidade retida é limitada pela
+// configuração `max_revisoes` (ver `queries::podar_revisoes`).
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisaoItem {
+    pub id: String,
+    pub item_id: String,
+    pub numero_revisao: i64,
+    pub titulo: String,
+    pub descricao: Option<String>,
+    pub conteudo_nota: Option<String>,
+    pub criado_em: String,
+}
+
 // =============================================================================
 // Tag — Rótulo colorido para categorização de itens
 // =============================================================================
@@ -124,12 +180,32 @@ pub struct Anexo {
     pub criado_em: String,
 }
 
+// =============================================================================
+// Blob — Conteúdo físico único de um ou mais anexos (endereçado por hash)
+// =============================================================================
+// Ver migração 005 e `services::armazenamento`. Vários registros `Anexo`
+// com o mesmo `hash_sha256` compartilham o mesmo blob em disco;
+// `contagem_referencias` rastreia quantos ainda apontam para ele, para que
+// o arquivo físico só seja removido quando o último for excluído.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blob {
+    pub hash_sha256: String,
+    pub tamanho: i64,
+    pub contagem_referencias: i64,
+    pub criado_em: String,
+}
+
 // =============================================================================
 // TarefaChecklist — Tarefa individual de uma checklist
 // =============================================================================
 // concluida: false = pendente, true = concluída
 // ordem: inteiro para controlar posição na lista (drag-and-drop)
 // anexos: tarefas individuais também podem ter anexos
+// bloqueada: computado (não gravado) a partir de `dependencias_tarefa` —
+// true se alguma tarefa da qual esta depende ainda não estiver concluída
+// (ver queries::tarefa_bloqueada). marcar_tarefa_concluida recusa concluir
+// uma tarefa bloqueada.
 // =============================================================================
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TarefaChecklist {
@@ -143,6 +219,74 @@ pub struct TarefaChecklist {
     /// Anexos específicos desta tarefa
     #[serde(default)]
     pub anexos: Vec<Anexo>,
+    #[serde(default)]
+    pub bloqueada: bool,
+}
+
+// =============================================================================
+// EntradaTempo — Lançamento de tempo trabalhado em uma tarefa de checklist
+// =============================================================================
+// Gravada em `entradas_tempo` (migração 008) como `duracao_minutos`
+// (inteiro), mas exposta ao frontend como `Duracao` (horas/minutos) — mais
+// natural para quem está lançando tempo. A conversão acontece em
+// queries::registrar_tempo/listar_tempo_por_tarefa.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntradaTempo {
+    pub id: String,
+    pub tarefa_id: String,
+    pub data_registro: String,
+    pub mensagem: Option<String>,
+    pub duracao: Duracao,
+}
+
+/// Duração expressa em horas/minutos, a forma em que o usuário lança tempo
+/// (em vez do total de minutos gravado no banco). `minutos` deve estar
+/// entre 0 e 59 — ver `validar`, chamado por `queries::registrar_tempo`
+/// antes de gravar.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Duracao {
+    pub horas: i32,
+    pub minutos: i32,
+}
+
+impl Duracao {
+    /// Confere o invariante `0 <= minutos < 60`. `horas` pode ser 0 mas não
+    /// negativo — uma duração não tem sentido negativo.
+    pub fn validar(&self) -> anyhow::Result<()> {
+        if self.horas < 0 {
+            return Err(anyhow::anyhow!("Horas da duração não podem ser negativas (recebido: {})", self.horas));
+        }
+        if !(0..60).contains(&self.minutos) {
+            return Err(anyhow::anyhow!("Minutos da duração devem estar entre 0 e 59 (recebido: {})", self.minutos));
+        }
+        Ok(())
+    }
+
+    pub fn total_minutos(&self) -> i32 {
+        self.horas * 60 + self.minutos
+    }
+
+    pub fn de_minutos_totais(total: i32) -> Self {
+        Self { horas: total / 60, minutos: total % 60 }
+    }
+}
+
+/// DTO para registro de uma nova entrada de tempo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NovaEntradaTempo {
+    pub tarefa_id: String,
+    pub data_registro: String,
+    pub mensagem: Option<String>,
+    pub duracao: Duracao,
+}
+
+/// Soma do tempo lançado em todas as tarefas de um item (ver
+/// `queries::total_tempo_por_item`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotalTempoItem {
+    pub item_id: String,
+    pub duracao_total: Duracao,
 }
 
 // =============================================================================
@@ -163,6 +307,246 @@ pub struct LogAuditoria {
     pub criado_em: String,
 }
 
+// =============================================================================
+// LogAuditoriaCompleto — LogAuditoria incluindo os campos da cadeia de hashes
+// =============================================================================
+// `LogAuditoria` (o DTO exposto ao frontend por `listar_historico`) não
+// inclui `prev_hash`/`entry_hash` — este tipo existe só para
+// `services::arquivo::exportar`, que precisa exportar a cadeia completa de
+// forma independente do banco.sqlite (ver migração 004).
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogAuditoriaCompleto {
+    pub id: String,
+    pub tipo_evento: String,
+    pub entidade_tipo: String,
+    pub entidade_id: Option<String>,
+    pub detalhes: Option<String>,
+    pub criado_em: String,
+    pub prev_hash: Option<String>,
+    pub entry_hash: Option<String>,
+}
+
+// =============================================================================
+// QuebraIntegridadeAuditoria — Resultado de uma cadeia de auditoria quebrada
+// =============================================================================
+// Retornado por `db::queries::verificar_integridade_auditoria` e
+// `services::auditoria::verificar_integridade` quando a cadeia de hashes do
+// log (ver migração 004) não confere. `None` no lugar deste tipo significa
+// que a cadeia inteira foi recomputada com sucesso, sem adulteração.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuebraIntegridadeAuditoria {
+    /// id da primeira linha de log_auditoria cujo hash não confere.
+    pub linha_id: String,
+    /// Explicação de qual verificação falhou (prev_hash ou entry_hash).
+    pub motivo: String,
+}
+
+// =============================================================================
+// AssinaturaTopoAuditoria — Ancoragem Ed25519 do topo da cadeia de auditoria
+// =============================================================================
+// Retornado por `services::auditoria::assinar_topo`. Pensado para ser
+// exportado e guardado fora do cofre (ex.: publicado, enviado por e-mail):
+// qualquer um com `chave_publica` pode confirmar depois, via
+// `services::auditoria::verificar_assinatura_topo`, que este `entry_hash`
+// foi mesmo assinado por este cofre — sem precisar reabrir o banco.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssinaturaTopoAuditoria {
+    pub entry_hash: String,
+    /// Assinatura Ed25519 (64 bytes) sobre `entry_hash`, em hexadecimal.
+    pub assinatura: String,
+    /// Chave pública Ed25519 (32 bytes) do cofre, em hexadecimal.
+    pub chave_publica: String,
+}
+
+// =============================================================================
+// OperacaoMutacao — Tipo de mutação estruturada (ver services::auditoria)
+// =============================================================================
+// Só as mutações de pasta/item/tag registradas por
+// `services::auditoria::registrar_mutacao` carregam uma `OperacaoMutacao` —
+// eventos livres (backup, sincronização, manutenção...) continuam passando
+// por `registrar`/`registrar_evento_auditoria` sem uma delas.
+// =============================================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperacaoMutacao {
+    Criacao,
+    Atualizacao,
+    Exclusao,
+}
+
+impl fmt::Display for OperacaoMutacao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperacaoMutacao::Criacao => write!(f, "criacao"),
+            OperacaoMutacao::Atualizacao => write!(f, "atualizacao"),
+            OperacaoMutacao::Exclusao => write!(f, "exclusao"),
+        }
+    }
+}
+
+impl OperacaoMutacao {
+    /// Converte uma string do banco de dados para o enum correspondente.
+    /// `None` se o valor não for reconhecido (linha fora do log estruturado).
+    pub fn de_str(valor: &str) -> Option<Self> {
+        match valor {
+            "criacao" => Some(OperacaoMutacao::Criacao),
+            "atualizacao" => Some(OperacaoMutacao::Atualizacao),
+            "exclusao" => Some(OperacaoMutacao::Exclusao),
+            _ => None,
+        }
+    }
+}
+
+// =============================================================================
+// OperacaoAuditoria — Linha estruturada do log de operações
+// =============================================================================
+// Subconjunto de `log_auditoria` (ver migração 006) usado por
+// `services::auditoria::replay` para reconstruir o `EstadoMaterializado` —
+// só as colunas necessárias para aplicar a mutação, em ordem de `hlc`.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperacaoAuditoria {
+    pub hlc: String,
+    pub entidade_tipo: String,
+    pub entidade_id: String,
+    pub operacao: OperacaoMutacao,
+    pub payload_depois: Option<String>,
+}
+
+// =============================================================================
+// OperacaoAuditoriaCompleta — Operação estruturada com id e payload_antes
+// =============================================================================
+// Variante de `OperacaoAuditoria` usada pelas consultas "como estava em"
+// (`queries::listar_operacoes_auditoria_da_entidade`,
+// `services::auditoria::reconstruir_item_em`/`listar_historico_item`/
+// `reverter_item_para`), que precisam do `id` da linha (para
+// `reverter_item_para`) e de `payload_antes` (para exibir o diff de cada
+// evento), além de `criado_em` para ordenação/exibição amigável.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperacaoAuditoriaCompleta {
+    pub id: String,
+    pub hlc: String,
+    pub operacao: OperacaoMutacao,
+    pub payload_antes: Option<String>,
+    pub payload_depois: Option<String>,
+    pub criado_em: String,
+}
+
+// =============================================================================
+// EstadoMaterializado / CheckpointAuditoria — Replay do log de operações
+// =============================================================================
+// `EstadoMaterializado` é o resultado de `services::auditoria::replay`:
+// pastas/itens/tags reconstruídos a partir do checkpoint mais recente (ver
+// `CheckpointAuditoria`) mais as operações estruturadas posteriores, em vez
+// de lidos diretamente das tabelas vivas. É a base para o futuro merge
+// conflict-free de dois cofres (ver nota em `services::auditoria`): como
+// cada operação carrega um `hlc` totalmente ordenado entre dispositivos,
+// dois replays podem ser comparados operação a operação.
+// =============================================================================
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EstadoMaterializado {
+    pub pastas: HashMap<String, Pasta>,
+    pub itens: HashMap<String, Item>,
+    pub tags: HashMap<String, Tag>,
+}
+
+/// Um checkpoint gravado a cada `KEEP_STATE_EVERY` mutações estruturadas
+/// (ver `services::auditoria::registrar_mutacao`) — guarda o
+/// `EstadoMaterializado` já serializado e o `hlc` da operação mais recente
+/// incluída nele, para que `replay` saiba a partir de onde continuar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointAuditoria {
+    pub id: String,
+    pub hlc: String,
+    /// `EstadoMaterializado` serializado em JSON.
+    pub estado: String,
+    pub criado_em: String,
+}
+
+// =============================================================================
+// ConfiguracaoManutencao — Política de retenção e manutenção programada
+// =============================================================================
+// Usada por `services::manutencao::executar`. Modelada sobre o `Config` de
+// storage de sistemas externos (que expõe um limite configurável de espaço):
+// aqui os limites são sobre o crescimento do log de auditoria, para que quem
+// opera o cofre ajuste retenção/tamanho em vez de depender só de rodar
+// `compactar_banco` manualmente.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfiguracaoManutencao {
+    /// Linhas de `log_auditoria` com `criado_em` mais antigo que isto (em
+    /// dias) são removidas.
+    pub dias_retencao_auditoria: i64,
+    /// Número máximo de linhas de auditoria mantidas mesmo dentro da janela
+    /// de retenção — o excedente mais antigo é removido primeiro.
+    pub max_linhas_auditoria: i64,
+    /// Intervalo mínimo, em dias, entre execuções automáticas de
+    /// VACUUM + `PRAGMA optimize`. `executar` não roda de novo antes disso.
+    pub intervalo_auto_vacuum_dias: i64,
+}
+
+impl Default for ConfiguracaoManutencao {
+    fn default() -> Self {
+        Self {
+            dias_retencao_auditoria: 365,
+            max_linhas_auditoria: 100_000,
+            intervalo_auto_vacuum_dias: 30,
+        }
+    }
+}
+
+/// Resultado de uma execução de `services::manutencao::executar`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatorioManutencao {
+    pub linhas_auditoria_removidas: u64,
+    pub vacuum_executado: bool,
+    pub optimize_executado: bool,
+}
+
+// =============================================================================
+// RelatorioReparoFts — Resultado de services::reparo_fts::executar
+// =============================================================================
+// `itens_fts` só fica em sincronia com `itens` porque os gatilhos
+// `trg_itens_fts_*` disparam em todo INSERT/UPDATE/DELETE — uma migração
+// manual, uma falha no meio de uma importação em lote, ou uma mudança de
+// schema podem deixá-los dessincronizados, quebrando a busca em silêncio
+// (ver `queries::verificar_integridade_fts`/`reconstruir_fts`).
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatorioReparoFts {
+    /// Total de linhas em `itens` no momento da verificação.
+    pub linhas_escaneadas: i64,
+    /// IDs presentes em `itens` mas ausentes de `itens_fts`.
+    pub ids_ausentes: Vec<String>,
+    /// IDs presentes em `itens_fts` mas sem item correspondente em `itens`.
+    pub ids_orfaos: Vec<String>,
+    /// Se `ids_ausentes`/`ids_orfaos` não estavam vazios, `reconstruido`
+    /// indica se `reconstruir_fts` rodou (sempre `true` quando havia
+    /// divergência — `executar` só pula a reconstrução se já estava tudo ok).
+    pub reconstruido: bool,
+}
+
+// =============================================================================
+// Pagina — Envelope de paginação por keyset (seek) para listagens grandes
+// =============================================================================
+// Usada por `listar_itens`, `buscar_itens` e `listar_historico`: em vez de
+// OFFSET (que degrada conforme a posição avança), a consulta usa
+// `WHERE (chave_ordenacao, id) < (:k, :id) ORDER BY ... LIMIT :n`, então o
+// custo de cada página é O(limite) independente de quão fundo a rolagem foi.
+// `proximo_cursor` é opaco ao frontend: deve ser devolvido como veio na
+// chamada seguinte, e é `None` quando não há mais páginas.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pagina<T> {
+    pub itens: Vec<T>,
+    pub proximo_cursor: Option<String>,
+    pub total_aproximado: i64,
+}
+
 // =============================================================================
 // Configuracao — Par chave/valor para preferências do aplicativo
 // =============================================================================
@@ -176,13 +560,28 @@ pub struct Configuracao {
 // =============================================================================
 // ResultadoBusca — Item retornado pela busca full-text (FTS)
 // =============================================================================
-// relevancia: score do FTS5 (quanto menor, mais relevante —
-// invertemos para que maior = melhor no frontend)
+// relevancia: `bm25(itens_fts, w_titulo, w_descricao, w_conteudo)` (ver
+// `PesosBusca`), negado para que maior = melhor no frontend (mesma
+// convenção de quando isso vinha do `rank` padrão do FTS5).
+// trecho_titulo/trecho_conteudo: excerto em HTML (já com <mark> nos termos
+// buscados e já escapado) da coluna título/conteúdo da nota
+// respectivamente, vindos de `snippet(itens_fts, <col>, ...)` — `None` se
+// o termo não aparecer naquela coluna específica.
+// titulo_destacado: o título do item inteiro (não truncado) com os termos
+// buscados destacados com <mark> (já escapado), vindo de
+// `highlight(itens_fts, ...)` — fica igual ao título sem marcação se o
+// termo não aparecer nele.
 // =============================================================================
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResultadoBusca {
     pub item: Item,
     pub relevancia: f64,
+    #[serde(default)]
+    pub trecho_titulo: Option<String>,
+    #[serde(default)]
+    pub trecho_conteudo: Option<String>,
+    #[serde(default)]
+    pub titulo_destacado: Option<String>,
 }
 
 // =============================================================================
@@ -201,6 +600,293 @@ pub struct ManifestoBackup {
     pub hash_banco: String,
     /// Mapa de caminho_interno -> hash SHA-256 de cada anexo
     pub hashes_anexos: HashMap<String, String>,
+    /// Codec usado para comprimir as entradas deste backup. Ausente em
+    /// manifestos antigos — nesse caso assume-se `Deflate`, o único codec
+    /// usado antes da introdução deste campo.
+    #[serde(default)]
+    pub compressao: CompressaoBackup,
+    /// Presente quando este backup foi criado de forma diferencial, a
+    /// partir de um backup anterior (ver `backup_referencia` em
+    /// `criar_backup`). `None` para backups completos.
+    #[serde(default)]
+    pub backup_pai: Option<BackupPai>,
+    /// Caminhos internos de anexos cujo conteúdo não foi regravado neste
+    /// arquivo por já existir, inalterado, no `backup_pai` — `restaurar_backup`
+    /// precisa do arquivo pai para resolvê-los. Continuam tendo hash em
+    /// `hashes_anexos` normalmente.
+    #[serde(default)]
+    pub anexos_referenciados: Vec<String>,
+}
+
+/// Referência ao backup pai de um backup diferencial (ver `ManifestoBackup::backup_pai`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupPai {
+    /// Nome do arquivo .vaultbackup pai, para exibição/diagnóstico — a
+    /// restauração depende do caminho informado pelo usuário, não deste campo.
+    pub arquivo: String,
+    /// Hash do banco.sqlite do backup pai, no momento em que este backup foi criado.
+    pub hash_banco: String,
+}
+
+/// Parâmetros do Argon2id usados para derivar a chave de um `.vaultbackup`
+/// cifrado com AES-256-GCM (ver `ManifestoCifragemBackup`, `crypto::backup`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ParametrosKdfBackup {
+    pub memoria_kib: u32,
+    pub iteracoes: u32,
+    pub paralelismo: u32,
+}
+
+// =============================================================================
+// ManifestoCifragemBackup — Cabeçalho em claro de um .vaultbackup cifrado
+// =============================================================================
+// Pedido originalmente como novos campos de `ManifestoBackup` (`salt`,
+// `nonce`, `kdf_params`) — mas `ManifestoBackup` vira `manifesto.json`
+// DENTRO do ZIP que `crypto::backup` cifra, e salt/nonce/kdf_params
+// precisam estar disponíveis ANTES de decifrar esse ZIP, já que são os
+// parâmetros da própria decifragem. Por isso moram neste manifesto irmão,
+// gravado em claro como cabeçalho JSON do arquivo (ver formato no topo de
+// `crypto::backup`) em vez de dentro do ZIP — continuam sendo campos de um
+// manifesto, só que do manifesto que descreve a cifragem do arquivo, não o
+// do conteúdo do backup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestoCifragemBackup {
+    /// Salt do Argon2id, em hexadecimal (16 bytes).
+    pub salt: String,
+    /// Nonce base do AES-256-GCM, em hexadecimal (12 bytes) — único por
+    /// arquivo; cada chunk do stream deriva seu próprio nonce a partir
+    /// deste com um contador, ver `crypto::backup::nonce_do_chunk`.
+    pub nonce: String,
+    pub kdf_params: ParametrosKdfBackup,
+}
+
+/// Codec de compressão usado ao gravar um `.vaultbackup`. `restaurar_backup`
+/// e `importar_pacote` leem este valor do manifesto para saber como
+/// descomprimir cada entrada, então backups antigos (sempre `Deflate`)
+/// continuam restauráveis normalmente.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CompressaoBackup {
+    /// Sem compressão — entradas gravadas como estão (ZIP "stored").
+    Nenhuma,
+    /// DEFLATE nativo do ZIP (comportamento histórico, ainda o padrão).
+    Deflate,
+    /// Zstandard, aplicado manualmente antes de gravar a entrada como
+    /// "stored" no ZIP. Nível típico 3-9; maior = mais compressão, mais lento.
+    Zstd { nivel: i32 },
+    /// LZMA2 (xz), aplicado manualmente antes de gravar a entrada como
+    /// "stored". Nível 0-9; maior = mais compressão, mais lento.
+    Xz { nivel: u32 },
+    /// Brotli, aplicado manualmente antes de gravar a entrada como
+    /// "stored". Qualidade 0-11; maior = mais compressão, mais lento.
+    Brotli { qualidade: u32 },
+}
+
+impl Default for CompressaoBackup {
+    fn default() -> Self {
+        CompressaoBackup::Deflate
+    }
+}
+
+// =============================================================================
+// CodecArquivo — Compressão por entrada de um arquivo de cofre portátil
+// =============================================================================
+// Usado por `services::arquivo::exportar`/`importar` (.vcarch). Diferente de
+// `CompressaoBackup`, não carrega Zstd/xz/Deflate nem pressupõe um ZIP por
+// baixo — o .vcarch não é um ZIP, então cada entrada é só bytes comprimidos
+// diretamente, precedidos de um cabeçalho próprio (ver services::arquivo).
+// =============================================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CodecArquivo {
+    /// Sem compressão — bytes da entrada gravados como estão.
+    Armazenar,
+    /// LZ4 — compressão rápida, taxa modesta. Bom padrão para anexos grandes.
+    Lz4,
+    /// Brotli — mais lento, melhor taxa de compressão. Qualidade 0-11.
+    Brotli { qualidade: u32 },
+}
+
+impl Default for CodecArquivo {
+    fn default() -> Self {
+        CodecArquivo::Lz4
+    }
+}
+
+/// Opções de exportação de um arquivo de cofre portátil (.vcarch).
+/// `senha` deriva a chave de cifragem (ver `crypto::backup`) — sem ela o
+/// export não é feito (diferente de `.vaultbackup`, o .vcarch é sempre
+/// cifrado, já que se destina a sair do disco de origem).
+#[derive(Debug, Clone)]
+pub struct OpcoesArquivoExport {
+    pub senha: String,
+    pub codec_banco: CodecArquivo,
+    pub codec_auditoria: CodecArquivo,
+}
+
+/// Metadados de uma entrada dentro de um arquivo de cofre portátil.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntradaArquivoInfo {
+    pub nome: String,
+    pub codec: CodecArquivo,
+    pub tamanho_original: u64,
+    pub tamanho_comprimido: u64,
+}
+
+/// Resumo de um arquivo de cofre portátil (.vcarch) após export ou import
+/// bem-sucedido — devolvido para o frontend exibir ao usuário.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestoArquivo {
+    pub versao_app: String,
+    pub versao_schema: i32,
+    pub criado_em: String,
+    pub entradas: Vec<EntradaArquivoInfo>,
+    /// Chave pública Ed25519 (hex) usada para assinar este arquivo — gerada
+    /// sob demanda a cada export (ver `services::arquivo`), não identifica o
+    /// usuário, só autentica que os bytes cifrados não foram adulterados.
+    pub chave_publica: String,
+}
+
+// =============================================================================
+// CompressaoTar — Compressão do fluxo ao exportar uma pasta como tar
+// =============================================================================
+// Usado por `services::backup::exportar_pasta_tar`/`importar_pasta_tar`.
+// Ao contrário de `CompressaoBackup` (que comprime cada entrada do ZIP
+// individualmente), aqui o `.tar` inteiro é comprimido como um único
+// fluxo — o padrão usual de `.tar.zst`/`.tar.lz4` fora do VaultCraft.
+// `importar_pasta_tar` detecta o codec pela extensão do arquivo, então
+// não precisa ser registrado em nenhum manifesto.
+// =============================================================================
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CompressaoTar {
+    /// `.tar` sem compressão adicional.
+    Nenhuma,
+    /// `.tar.zst`. Nível típico 3-9; maior = mais compressão, mais lento.
+    Zstd { nivel: i32 },
+    /// `.tar.lz4` — mais rápido que Zstd, com menos compressão.
+    Lz4,
+}
+
+impl Default for CompressaoTar {
+    fn default() -> Self {
+        CompressaoTar::Zstd { nivel: 3 }
+    }
+}
+
+// =============================================================================
+// ManifestoBackupFragmentado — Metadados de um backup incremental (chunked)
+// =============================================================================
+// Incluído como manifesto_chunks.json dentro de um .vaultbackup incremental
+// (ver services::cdc e services::backup::criar_backup_incremental). Em vez
+// de conter os bytes do banco/anexos diretamente, lista apenas a sequência
+// ordenada de hashes de chunks necessária para reconstruir cada arquivo —
+// os chunks em si ficam em um repositório content-addressed compartilhado
+// entre backups sucessivos, então só o conteúdo que mudou ocupa espaço novo.
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestoBackupFragmentado {
+    /// Identifica esta versão do backup — usado por `versao_anterior` do
+    /// próximo manifesto para encadear o histórico, e por
+    /// `services::backup::podar_chunks_nao_referenciados` para saber quais
+    /// chunks ainda estão em uso.
+    pub id: String,
+    /// `id` do manifesto do backup incremental anterior no mesmo
+    /// repositório de chunks, se houver. `None` para o primeiro backup de
+    /// um repositório (cadeia começa do zero).
+    pub versao_anterior: Option<String>,
+    pub versao_app: String,
+    pub versao_schema: i32,
+    pub data: String,
+    pub total_itens: i64,
+    pub total_anexos: i64,
+    pub banco: ArquivoFragmentado,
+    /// Mapa de caminho_interno -> fragmentação de cada anexo
+    pub anexos: HashMap<String, ArquivoFragmentado>,
+}
+
+// =============================================================================
+// Poda de backups automáticos (services::backup::podar_backups_automaticos)
+// =============================================================================
+
+/// Política de retenção para `podar_backups_automaticos`, no estilo
+/// keep-last/keep-daily/keep-weekly/keep-monthly do Proxmox: mantém os
+/// `manter_ultimos` snapshots mais recentes, mais o mais recente de cada um
+/// dos `manter_diarios` últimos dias, `manter_semanais` últimas semanas e
+/// `manter_mensais` últimos meses que tiverem algum snapshot. O restante é removido.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoliticaPoda {
+    pub manter_ultimos: usize,
+    pub manter_diarios: usize,
+    pub manter_semanais: usize,
+    pub manter_mensais: usize,
+}
+
+impl Default for PoliticaPoda {
+    fn default() -> Self {
+        Self {
+            manter_ultimos: 5,
+            manter_diarios: 7,
+            manter_semanais: 4,
+            manter_mensais: 6,
+        }
+    }
+}
+
+// =============================================================================
+// Verificação de integridade de backups (services::backup::verificar_backup)
+// =============================================================================
+
+/// Escopo e modo de uma verificação de `.vaultbackup` via `verificar_backup`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpcoesVerificacao {
+    /// Limita a verificação a uma única entrada (ex.: "banco.sqlite" ou um
+    /// `caminho_interno` de anexo). `None` verifica todas as entradas.
+    pub entrada: Option<String>,
+    /// Limita a verificação de anexos aos que começam com este prefixo de
+    /// caminho (útil para verificar só os anexos de uma pasta).
+    pub subpath: Option<String>,
+    pub incluir_banco: bool,
+    pub incluir_anexos: bool,
+    /// Quando `true`, entradas corrompidas cuja cópia no cofre atual ainda
+    /// bate com o hash esperado são reescritas no próprio arquivo de backup.
+    pub reparar: bool,
+}
+
+impl Default for OpcoesVerificacao {
+    fn default() -> Self {
+        Self {
+            entrada: None,
+            subpath: None,
+            incluir_banco: true,
+            incluir_anexos: true,
+            reparar: false,
+        }
+    }
+}
+
+/// Resultado de uma verificação de integridade de `.vaultbackup`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RelatorioIntegridade {
+    /// `true` se nenhuma entrada verificada estava ausente, corrompida
+    /// (e não reparada), ou órfã.
+    pub ok: bool,
+    /// Entradas listadas no manifesto mas ausentes do arquivo ZIP.
+    pub ausentes: Vec<String>,
+    /// Entradas presentes mas cujo hash não confere com o manifesto (e que
+    /// não puderam ou não foram reparadas).
+    pub corrompidos: Vec<String>,
+    /// Entradas corrompidas que foram reparadas com a cópia do cofre atual.
+    pub reparados: Vec<String>,
+    /// Arquivos dentro de `anexos/` no ZIP que não constam no manifesto.
+    pub orfaos: Vec<String>,
+}
+
+/// Descreve como um único arquivo foi dividido em chunks: o tamanho e o
+/// hash SHA-256 do arquivo completo (para verificação pós-reconstrução), e
+/// a sequência ordenada de hashes de chunk que, concatenados, o recompõem.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArquivoFragmentado {
+    pub tamanho: u64,
+    pub hash_completo: String,
+    pub chunks: Vec<String>,
 }
 
 // =============================================================================
@@ -227,6 +913,8 @@ pub struct NovoItem {
     pub conteudo_nota: Option<String>,
     pub data_vencimento: Option<String>,
     pub tag_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub regra_recorrencia: Option<String>,
 }
 
 /// DTO para atualização de um item existente
@@ -238,6 +926,11 @@ pub struct AtualizacaoItem {
     pub data_vencimento: Option<String>,
     pub pasta_id: Option<String>,
     pub tag_ids: Option<Vec<String>>,
+    /// `Some(None)` não é distinguível de `None` aqui (campo simples, não
+    /// `Option<Option<_>>`): enviar `None` preserva a regra atual do item;
+    /// para limpar a recorrência, o chamador deve usar um comando dedicado.
+    #[serde(default)]
+    pub regra_recorrencia: Option<String>,
 }
 
 /// DTO para criação/atualização de tag
@@ -273,6 +966,61 @@ pub struct FiltrosBusca {
     pub data_fim: Option<String>,
 }
 
+/// Pesos das colunas de `itens_fts` usados pela função `bm25()` do FTS5 em
+/// `db::queries::buscar_fts`/`buscar_fts_paginado` — quanto maior o peso,
+/// mais um acerto naquela coluna conta para o score (e o score do `bm25()`
+/// é menor quanto mais relevante, por isso `relevancia` continua sendo o
+/// valor negado, como já era com `rank`). Expostos como parâmetro para o
+/// chamador poder ajustar a ponderação sem mexer na query.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PesosBusca {
+    pub titulo: f64,
+    pub descricao: f64,
+    pub conteudo_nota: f64,
+}
+
+impl Default for PesosBusca {
+    /// Um acerto no título é um sinal de relevância muito mais forte que
+    /// uma menção qualquer no corpo da nota, por isso pesa 10x mais;
+    /// descrição fica no meio do caminho entre os dois.
+    fn default() -> Self {
+        Self {
+            titulo: 10.0,
+            descricao: 4.0,
+            conteudo_nota: 1.0,
+        }
+    }
+}
+
+// =============================================================================
+// Operacao / ResultadoOperacao — Lote transacional (db::queries::executar_lote)
+// =============================================================================
+// Usadas pelo comando `executar_lote`, que aplica uma lista de operações em
+// uma única transação SQLite: se qualquer uma falhar, nenhuma é persistida.
+// =============================================================================
+
+/// Uma operação individual dentro de um lote transacional (ver
+/// `db::queries::executar_lote`). Cada variante espelha os parâmetros do
+/// comando individual equivalente.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Operacao {
+    CriarPasta(NovaPasta),
+    CriarItem(NovoItem),
+    AtualizarItem { id: String, dados: AtualizacaoItem },
+    ExcluirItem { id: String },
+    AdicionarTag { item_id: String, tag_id: String },
+    RemoverTag { item_id: String, tag_id: String },
+    ReordenarTarefas { ordens: Vec<(String, i32)> },
+}
+
+/// Resultado de uma `Operacao` dentro de um lote. `id` é o identificador
+/// criado/afetado, quando a operação produzir um único registro; `None`
+/// para operações que não têm esse conceito (ex.: `ReordenarTarefas`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultadoOperacao {
+    pub id: Option<String>,
+}
+
 /// Filtros para listagem de eventos de auditoria
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FiltrosAuditoria {
@@ -282,3 +1030,95 @@ pub struct FiltrosAuditoria {
     pub limite: Option<i64>,
     pub offset: Option<i64>,
 }
+
+// =============================================================================
+// DestinoRemoto — Bookmark de destino SFTP/SCP/HTTP para cópias fora do cofre
+// =============================================================================
+// Usado por `services::sincronizacao` (Sftp/Scp: envia/busca um arquivo de
+// cofre portátil .vcarch já cifrado e assinado, ver `services::arquivo`) e
+// por `services::backend_remoto` (Http: envia/busca chunks de backup
+// incremental já cifrados, endereçados por hash, ver `services::cdc`). A
+// lista de bookmarks é persistida como JSON em `configuracoes` (ver
+// `db::queries::listar_destinos_remotos`), identificada por `nome`, para os
+// três protocolos igualmente.
+//
+// Guardar credenciais de acesso remoto em claro em `configuracoes` é uma
+// contrapartida aceita aqui (mesmo nível de confiança que o próprio arquivo
+// `vaultcraft.db`, que também não é cifrado em repouso) — quem tiver acesso
+// de leitura ao banco já teria acesso a tudo mais no cofre.
+// =============================================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProtocoloRemoto {
+    Sftp,
+    Scp,
+    /// Backend HTTP/S3-like de `services::backend_remoto`. `host` guarda a
+    /// URL base do endpoint (ex.: "https://backups.exemplo.com"), `porta`/
+    /// `usuario` ficam sem uso, e `autenticacao` deve ser `Token`.
+    Http,
+}
+
+/// Como autenticar com o host remoto. `ChavePrivada` aponta para um arquivo
+/// de chave já existente no disco local (ex.: `~/.ssh/id_ed25519`) — esta
+/// estrutura nunca gera ou guarda uma chave SSH por conta própria. `Token`
+/// é para destinos `ProtocoloRemoto::Http` (enviado como `Authorization:
+/// Bearer <token>`, ver `services::backend_remoto`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AutenticacaoRemota {
+    Senha { senha: String },
+    ChavePrivada {
+        caminho_chave: String,
+        frase_senha: Option<String>,
+    },
+    Token { token: String },
+}
+
+/// Um destino remoto salvo (bookmark), com tudo que `sincronizacao::enviar`/
+/// `::restaurar` (Sftp/Scp) ou `backend_remoto::BackendHttp` (Http)
+/// precisam para conectar sem pedir os dados de novo a cada vez.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DestinoRemoto {
+    /// Nome do bookmark — identifica o destino nas operações de
+    /// salvar/remover/listar (ver `db::queries`).
+    pub nome: String,
+    pub host: String,
+    pub porta: u16,
+    pub protocolo: ProtocoloRemoto,
+    pub usuario: String,
+    pub autenticacao: AutenticacaoRemota,
+    /// Diretório remoto (Sftp/Scp) ou prefixo de namespace dentro do
+    /// endpoint (Http) onde os arquivos/chunks são gravados/lidos.
+    pub caminho_remoto: String,
+}
+
+// =============================================================================
+// ResumoSincronizacaoRemota — Resultado de `services::backend_remoto::sincronizar`
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumoSincronizacaoRemota {
+    /// Total de chunks presentes no repositório local.
+    pub total_local: usize,
+    /// Quantos desses já estavam no remoto (não precisaram ser reenviados).
+    pub ja_no_remoto: usize,
+    /// Quantos chunks foram enviados com sucesso nesta chamada.
+    pub enviados: usize,
+    /// Chunks que falharam mesmo após as tentativas de reenvio, com o erro
+    /// de cada um — `sincronizar` não aborta no primeiro erro, para que uma
+    /// falha isolada não impeça o envio do restante.
+    pub falhas: Vec<(String, String)>,
+}
+
+// =============================================================================
+// TokenSegredo — Token de acesso de curta duração (ver services::api_segredos)
+// =============================================================================
+// Linha de `tokens_segredos` (migração 011). `token_hash` é o SHA-256 do
+// token em si — o token nunca é persistido, só devolvido uma vez no momento
+// em que é criado (mesmo princípio de `pin_hash`).
+// =============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenSegredo {
+    pub id: String,
+    pub token_hash: String,
+    pub criado_em: String,
+    pub expira_em: String,
+    pub revogado: bool,
+}
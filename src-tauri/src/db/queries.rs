@@ -13,13 +13,236 @@
 // - Todos os timestamps são gerados aqui (UTC ISO 8601)
 // =============================================================================
 
+use std::collections::HashSet;
+
 use anyhow::{Context, Result, anyhow};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use log::info;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use super::models::*;
+use crate::services::{exportacao, recorrencia};
+
+// =============================================================================
+// PAGINACAO POR KEYSET (SEEK)
+// =============================================================================
+// Usada por `listar_itens_por_pasta_paginado`, `buscar_fts_paginado` e
+// `listar_eventos_auditoria_paginado`. Em vez de OFFSET, o cursor codifica
+// o último par (chave_ordenacao, id) visto e a próxima página busca
+// `WHERE (chave, id) < (:k, :id) ORDER BY chave DESC, id DESC LIMIT :n` —
+// custo O(limite) independente da profundidade da rolagem.
+// =============================================================================
+
+/// Separador entre a chave de ordenação e o id no cursor codificado.
+/// `\u{1}` (SOH) foi escolhido por não aparecer em timestamps, ids (UUID)
+/// nem em valores de relevância formatados — não precisa de escaping.
+const SEPARADOR_CURSOR: char = '\u{1}';
+
+/// Codifica um cursor opaco a partir da chave de ordenação e do id da
+/// última linha vista em uma página.
+fn codificar_cursor(chave: &str, id: &str) -> String {
+    format!("{}{}{}", chave, SEPARADOR_CURSOR, id)
+}
+
+/// Decodifica um cursor produzido por `codificar_cursor` de volta em
+/// (chave_ordenacao, id).
+fn decodificar_cursor(cursor: &str) -> Result<(String, String)> {
+    cursor
+        .split_once(SEPARADOR_CURSOR)
+        .map(|(chave, id)| (chave.to_string(), id.to_string()))
+        .ok_or_else(|| anyhow!("Cursor de paginação inválido"))
+}
+
+// =============================================================================
+// UTILITARIOS DE CONSULTA EM LOTE
+// =============================================================================
+// O SQLite rejeita uma consulta com mais parâmetros ligados do que
+// SQLITE_MAX_VARIABLE_NUMBER (999 por padrão, mas algumas builds vêm
+// configuradas mais conservadoramente). Operações que recebem uma lista
+// grande de IDs (mover/excluir em lote, buscar anexos de um conjunto de
+// itens) dividem a lista em blocos e rodam a consulta uma vez por bloco,
+// acumulando os resultados — o mesmo espírito dos helpers `repeat`/
+// `each_chunk` do crate sql-support da Mozilla.
+// =============================================================================
+
+/// Tamanho de cada bloco de IDs por execução, com margem de segurança
+/// abaixo do `SQLITE_MAX_VARIABLE_NUMBER` padrão (999).
+const TAMANHO_MAXIMO_LOTE: usize = 900;
+
+/// Gera a lista de placeholders `?, ?, ..., ?` (um por item) usada para
+/// montar uma cláusula `IN (...)` com `quantidade` parâmetros.
+fn placeholders_repetidos(quantidade: usize) -> String {
+    std::iter::repeat("?").take(quantidade).collect::<Vec<_>>().join(", ")
+}
+
+/// Executa `operacao` uma vez para cada bloco de até `TAMANHO_MAXIMO_LOTE`
+/// elementos de `ids`, acumulando os resultados de todos os blocos em um
+/// único `Vec`. `operacao` recebe o bloco atual e os placeholders `IN (...)`
+/// já montados para esse bloco.
+fn each_chunk<T>(
+    ids: &[String],
+    mut operacao: impl FnMut(&[String], &str) -> Result<Vec<T>>,
+) -> Result<Vec<T>> {
+    let mut resultados = Vec::with_capacity(ids.len());
+
+    for bloco in ids.chunks(TAMANHO_MAXIMO_LOTE) {
+        let placeholders = placeholders_repetidos(bloco.len());
+        resultados.extend(operacao(bloco, &placeholders)?);
+    }
+
+    Ok(resultados)
+}
+
+/// Busca todos os anexos pertencentes a qualquer item em `item_ids`, em
+/// blocos para respeitar o limite de variáveis do SQLite.
+pub fn listar_anexos_por_itens(conexao: &Connection, item_ids: &[String]) -> Result<Vec<Anexo>> {
+    if item_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    each_chunk(item_ids, |bloco, placeholders| {
+        let sql = format!(
+            "SELECT id, item_id, tarefa_id, nome_original, caminho_interno,
+                    tamanho, tipo_mime, hash_sha256, criado_em
+             FROM anexos WHERE item_id IN ({})",
+            placeholders
+        );
+        let mut stmt = conexao.prepare(&sql)?;
+        stmt.query_map(rusqlite::params_from_iter(bloco), |linha| {
+            Ok(Anexo {
+                id: linha.get(0)?,
+                item_id: linha.get(1)?,
+                tarefa_id: linha.get(2)?,
+                nome_original: linha.get(3)?,
+                caminho_interno: linha.get(4)?,
+                tamanho: linha.get(5)?,
+                tipo_mime: linha.get(6)?,
+                hash_sha256: linha.get(7)?,
+                criado_em: linha.get(8)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("Falha ao listar anexos em lote")
+    })
+}
+
+/// Move todos os itens em `item_ids` para `nova_pasta_id`, em blocos para
+/// respeitar o limite de variáveis do SQLite. Retorna quantos itens foram movidos.
+pub fn mover_itens_em_lote(conexao: &Connection, item_ids: &[String], nova_pasta_id: &str) -> Result<usize> {
+    if item_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let totais = each_chunk(item_ids, |bloco, placeholders| {
+        let sql = format!(
+            "UPDATE itens SET pasta_id = ?, atualizado_em = ? WHERE id IN ({})",
+            placeholders
+        );
+
+        let mut parametros: Vec<&dyn rusqlite::types::ToSql> = vec![&nova_pasta_id, &agora];
+        for id in bloco {
+            parametros.push(id);
+        }
+
+        let afetados = conexao
+            .execute(&sql, parametros.as_slice())
+            .context("Falha ao mover itens em lote")?;
+        Ok(vec![afetados])
+    })?;
+
+    let total: usize = totais.into_iter().sum();
+    info!("Itens movidos em lote: {} -> pasta {}", total, nova_pasta_id);
+    Ok(total)
+}
+
+/// Exclui todos os itens em `item_ids` (cascade cuida de tags/anexos/tarefas
+/// no banco). Retorna quantos itens foram excluídos.
+pub fn excluir_itens_em_lote(conexao: &Connection, item_ids: &[String]) -> Result<usize> {
+    if item_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let totais = each_chunk(item_ids, |bloco, placeholders| {
+        let sql = format!("DELETE FROM itens WHERE id IN ({})", placeholders);
+        let afetados = conexao
+            .execute(&sql, rusqlite::params_from_iter(bloco))
+            .context("Falha ao excluir itens em lote")?;
+        Ok(vec![afetados])
+    })?;
+
+    let total: usize = totais.into_iter().sum();
+    info!("Itens excluídos em lote: {}", total);
+    Ok(total)
+}
+
+// =============================================================================
+// LOTE TRANSACIONAL
+// =============================================================================
+// Diferente de `mover_itens_em_lote`/`excluir_itens_em_lote` (uma única
+// operação repetida sobre vários ids), `executar_lote` aplica uma sequência
+// heterogênea de operações (criar pasta, criar item, reordenar tarefas...)
+// dentro de uma única transação — se qualquer uma falhar, nenhuma surte
+// efeito, o que os comandos individuais (cada um com seu próprio lock/commit
+// curto) não garantem para uma ação multi-etapa do frontend.
+// =============================================================================
+
+/// Aplica cada `Operacao` de `operacoes`, em ordem, dentro de uma única
+/// transação: se qualquer uma falhar, a transação não é confirmada (rollback
+/// automático ao sair do escopo sem `commit()`) e nenhuma operação anterior
+/// do lote fica persistida. Em caso de erro, o contexto identifica o índice
+/// da operação que falhou.
+pub fn executar_lote(conexao: &mut Connection, operacoes: &[Operacao]) -> Result<Vec<ResultadoOperacao>> {
+    let tx = conexao.transaction().context("Falha ao iniciar transação do lote")?;
+    let mut resultados = Vec::with_capacity(operacoes.len());
+
+    for (indice, operacao) in operacoes.iter().enumerate() {
+        let resultado = aplicar_operacao(&tx, operacao)
+            .with_context(|| format!("Operação {} do lote falhou", indice))?;
+        resultados.push(resultado);
+    }
+
+    tx.commit().context("Falha ao confirmar transação do lote")?;
+    Ok(resultados)
+}
+
+/// Aplica uma única `Operacao` dentro da transação do lote, delegando para
+/// a mesma função de query usada pelo comando individual equivalente.
+fn aplicar_operacao(tx: &rusqlite::Transaction, operacao: &Operacao) -> Result<ResultadoOperacao> {
+    match operacao {
+        Operacao::CriarPasta(dados) => {
+            let pasta = criar_pasta(tx, dados)?;
+            Ok(ResultadoOperacao { id: Some(pasta.id) })
+        }
+        Operacao::CriarItem(dados) => {
+            let item = criar_item(tx, dados)?;
+            Ok(ResultadoOperacao { id: Some(item.id) })
+        }
+        Operacao::AtualizarItem { id, dados } => {
+            let item = atualizar_item(tx, id, dados)?;
+            Ok(ResultadoOperacao { id: Some(item.id) })
+        }
+        Operacao::ExcluirItem { id } => {
+            excluir_item(tx, id)?;
+            Ok(ResultadoOperacao { id: Some(id.clone()) })
+        }
+        Operacao::AdicionarTag { item_id, tag_id } => {
+            vincular_tag_a_item(tx, item_id, tag_id)?;
+            Ok(ResultadoOperacao { id: Some(tag_id.clone()) })
+        }
+        Operacao::RemoverTag { item_id, tag_id } => {
+            desvincular_tag_de_item(tx, item_id, tag_id)?;
+            Ok(ResultadoOperacao { id: Some(tag_id.clone()) })
+        }
+        Operacao::ReordenarTarefas { ordens } => {
+            reordenar_tarefas(tx, ordens)?;
+            Ok(ResultadoOperacao { id: None })
+        }
+    }
+}
 
 // =============================================================================
 // PASTAS — Operações CRUD para a hierarquia de pastas
@@ -247,7 +470,7 @@ pub fn excluir_pasta(conexao: &Connection, id: &str) -> Result<()> {
 pub fn listar_itens_por_pasta(conexao: &Connection, pasta_id: &str) -> Result<Vec<Item>> {
     let mut stmt = conexao.prepare(
         "SELECT id, pasta_id, tipo, titulo, descricao, conteudo_nota,
-                data_vencimento, criado_em, atualizado_em
+                data_vencimento, criado_em, atualizado_em, regra_recorrencia
          FROM itens WHERE pasta_id = ?1 ORDER BY atualizado_em DESC",
     )?;
 
@@ -263,6 +486,7 @@ pub fn listar_itens_por_pasta(conexao: &Connection, pasta_id: &str) -> Result<Ve
             data_vencimento: linha.get(6)?,
             criado_em: linha.get(7)?,
             atualizado_em: linha.get(8)?,
+            regra_recorrencia: linha.get(9)?,
             tags: vec![],
             anexos: vec![],
         })
@@ -279,11 +503,90 @@ pub fn listar_itens_por_pasta(conexao: &Connection, pasta_id: &str) -> Result<Ve
     Ok(itens)
 }
 
+/// Lista os itens de uma pasta por página, usando keyset/seek pagination
+/// (ver seção PAGINACAO POR KEYSET). Ordenado por `atualizado_em DESC, id
+/// DESC`; `cursor`, se fornecido, deve ser o `proximo_cursor` retornado
+/// pela página anterior.
+pub fn listar_itens_por_pasta_paginado(
+    conexao: &Connection,
+    pasta_id: &str,
+    limite: i64,
+    cursor: Option<&str>,
+) -> Result<Pagina<Item>> {
+    let mut sql = String::from(
+        "SELECT id, pasta_id, tipo, titulo, descricao, conteudo_nota,
+                data_vencimento, criado_em, atualizado_em, regra_recorrencia
+         FROM itens WHERE pasta_id = ?1",
+    );
+
+    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(pasta_id.to_string())];
+    let mut idx = 2;
+
+    if let Some(cursor) = cursor {
+        let (chave, id) = decodificar_cursor(cursor)?;
+        sql.push_str(&format!(
+            " AND (atualizado_em < ?{i1} OR (atualizado_em = ?{i1} AND id < ?{i2}))",
+            i1 = idx, i2 = idx + 1,
+        ));
+        params_vec.push(Box::new(chave));
+        params_vec.push(Box::new(id));
+        idx += 2;
+    }
+
+    sql.push_str(&format!(" ORDER BY atualizado_em DESC, id DESC LIMIT ?{}", idx));
+    params_vec.push(Box::new(limite + 1));
+
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+        params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let mut stmt = conexao.prepare(&sql)?;
+    let mut itens: Vec<Item> = stmt.query_map(params_refs.as_slice(), |linha| {
+        let tipo_str: String = linha.get(2)?;
+        Ok(Item {
+            id: linha.get(0)?,
+            pasta_id: linha.get(1)?,
+            tipo: TipoItem::de_str(&tipo_str),
+            titulo: linha.get(3)?,
+            descricao: linha.get(4)?,
+            conteudo_nota: linha.get(5)?,
+            data_vencimento: linha.get(6)?,
+            criado_em: linha.get(7)?,
+            atualizado_em: linha.get(8)?,
+            regra_recorrencia: linha.get(9)?,
+            tags: vec![],
+            anexos: vec![],
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha ao listar página de itens da pasta")?;
+
+    let tem_proxima_pagina = itens.len() as i64 > limite;
+    if tem_proxima_pagina {
+        itens.truncate(limite as usize);
+    }
+    let proximo_cursor = tem_proxima_pagina
+        .then(|| itens.last().map(|i| codificar_cursor(&i.atualizado_em, &i.id)))
+        .flatten();
+
+    for item in &mut itens {
+        item.tags = obter_tags_do_item(conexao, &item.id)?;
+        item.anexos = listar_anexos_por_item(conexao, &item.id)?;
+    }
+
+    let total_aproximado: i64 = conexao.query_row(
+        "SELECT COUNT(*) FROM itens WHERE pasta_id = ?1",
+        params![pasta_id],
+        |linha| linha.get(0),
+    ).context("Falha ao contar itens da pasta")?;
+
+    Ok(Pagina { itens, proximo_cursor, total_aproximado })
+}
+
 /// Obtém um item pelo ID com todos os dados associados (tags, anexos).
 pub fn obter_item_por_id(conexao: &Connection, id: &str) -> Result<Item> {
     let mut item: Item = conexao.query_row(
         "SELECT id, pasta_id, tipo, titulo, descricao, conteudo_nota,
-                data_vencimento, criado_em, atualizado_em
+                data_vencimento, criado_em, atualizado_em, regra_recorrencia
          FROM itens WHERE id = ?1",
         params![id],
         |linha| {
@@ -298,6 +601,7 @@ pub fn obter_item_por_id(conexao: &Connection, id: &str) -> Result<Item> {
                 data_vencimento: linha.get(6)?,
                 criado_em: linha.get(7)?,
                 atualizado_em: linha.get(8)?,
+                regra_recorrencia: linha.get(9)?,
                 tags: vec![],
                 anexos: vec![],
             })
@@ -319,8 +623,8 @@ pub fn criar_item(conexao: &Connection, dados: &NovoItem) -> Result<Item> {
 
     conexao.execute(
         "INSERT INTO itens (id, pasta_id, tipo, titulo, descricao, conteudo_nota,
-                            data_vencimento, criado_em, atualizado_em)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                            data_vencimento, criado_em, atualizado_em, regra_recorrencia)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
         params![
             id,
             dados.pasta_id,
@@ -330,7 +634,8 @@ pub fn criar_item(conexao: &Connection, dados: &NovoItem) -> Result<Item> {
             dados.conteudo_nota,
             dados.data_vencimento,
             agora,
-            agora
+            agora,
+            dados.regra_recorrencia,
         ],
     ).context("Falha ao criar item")?;
 
@@ -349,6 +654,7 @@ pub fn criar_item(conexao: &Connection, dados: &NovoItem) -> Result<Item> {
 /// Apenas os campos fornecidos (Some) são atualizados.
 pub fn atualizar_item(conexao: &Connection, id: &str, dados: &AtualizacaoItem) -> Result<Item> {
     let item_atual = obter_item_por_id(conexao, id)?;
+    criar_revisao(conexao, &item_atual)?;
     let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
 
     let titulo = dados.titulo.as_deref().unwrap_or(&item_atual.titulo);
@@ -356,12 +662,14 @@ pub fn atualizar_item(conexao: &Connection, id: &str, dados: &AtualizacaoItem) -
     let conteudo_nota = dados.conteudo_nota.as_ref().or(item_atual.conteudo_nota.as_ref());
     let data_vencimento = dados.data_vencimento.as_ref().or(item_atual.data_vencimento.as_ref());
     let pasta_id = dados.pasta_id.as_deref().unwrap_or(&item_atual.pasta_id);
+    let regra_recorrencia = dados.regra_recorrencia.as_ref().or(item_atual.regra_recorrencia.as_ref());
 
     conexao.execute(
         "UPDATE itens SET titulo = ?1, descricao = ?2, conteudo_nota = ?3,
-                          data_vencimento = ?4, pasta_id = ?5, atualizado_em = ?6
-         WHERE id = ?7",
-        params![titulo, descricao, conteudo_nota, data_vencimento, pasta_id, agora, id],
+                          data_vencimento = ?4, pasta_id = ?5, atualizado_em = ?6,
+                          regra_recorrencia = ?7
+         WHERE id = ?8",
+        params![titulo, descricao, conteudo_nota, data_vencimento, pasta_id, agora, regra_recorrencia, id],
     ).context("Falha ao atualizar item")?;
 
     // Atualizar tags se fornecidas
@@ -380,6 +688,40 @@ pub fn atualizar_item(conexao: &Connection, id: &str, dados: &AtualizacaoItem) -
     obter_item_por_id(conexao, id)
 }
 
+/// Avança a `data_vencimento` de um item recorrente para a próxima
+/// ocorrência, via `services::recorrencia::proxima_ocorrencia`.
+///
+/// NOTA DE ESCOPO: este schema não tem um conceito de "concluído" para
+/// `Item` (apenas `TarefaChecklist` tem `concluida`) — recorrência de item
+/// não pode, portanto, ser disparada automaticamente ao "marcar como
+/// feito". Esta função existe para ser chamada explicitamente pelo
+/// chamador (ver comando `avancar_ocorrencia_item`) no momento em que ele
+/// considerar o item concluído.
+pub fn avancar_ocorrencia_item(conexao: &Connection, id: &str) -> Result<Item> {
+    let item = obter_item_por_id(conexao, id)?;
+
+    let regra = item.regra_recorrencia.as_deref()
+        .ok_or_else(|| anyhow!("Item {} não tem regra de recorrência configurada", id))?;
+    let data_atual = item.data_vencimento.as_deref()
+        .ok_or_else(|| anyhow!("Item {} não tem data de vencimento para avançar", id))?;
+
+    let data_base = DateTime::parse_from_rfc3339(data_atual)
+        .with_context(|| format!("data_vencimento inválida: '{}'", data_atual))?
+        .with_timezone(&Utc);
+
+    let proxima = recorrencia::proxima_ocorrencia(data_base, regra)?;
+
+    atualizar_item(conexao, id, &AtualizacaoItem {
+        titulo: None,
+        descricao: None,
+        conteudo_nota: None,
+        data_vencimento: Some(proxima.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        pasta_id: None,
+        tag_ids: None,
+        regra_recorrencia: None,
+    })
+}
+
 /// Exclui um item e todos os dados associados (cascade).
 pub fn excluir_item(conexao: &Connection, id: &str) -> Result<()> {
     let item = obter_item_por_id(conexao, id)?;
@@ -393,6 +735,171 @@ pub fn excluir_item(conexao: &Connection, id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Itens que ainda têm `conteudo_nota`/`descricao` em texto claro sem a
+/// selagem correspondente (`conteudo_nota_selado`/`descricao_selada` ainda
+/// NULL, ver migração 007) — usado por
+/// `services::cifragem::selar_itens_existentes` na primeira vez que a
+/// selagem de campos é configurada.
+pub fn itens_pendentes_selagem(conexao: &Connection) -> Result<Vec<(String, Option<String>, Option<String>)>> {
+    let mut stmt = conexao.prepare(
+        "SELECT id, conteudo_nota, descricao FROM itens
+         WHERE (conteudo_nota IS NOT NULL AND conteudo_nota_selado IS NULL)
+            OR (descricao IS NOT NULL AND descricao_selada IS NULL)"
+    )?;
+    let linhas = stmt
+        .query_map([], |linha| {
+            Ok((
+                linha.get::<_, String>(0)?,
+                linha.get::<_, Option<String>>(1)?,
+                linha.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Falha ao listar itens pendentes de selagem")?;
+
+    Ok(linhas)
+}
+
+/// Grava o(s) blob(s) selado(s) (`CampoCifrado` serializado em JSON) de um
+/// item e limpa o texto claro correspondente — `None` deixa o campo
+/// respectivo intocado (nenhum dos dois precisava de selagem).
+pub fn aplicar_selagem_item(
+    conexao: &Connection,
+    id: &str,
+    conteudo_nota_selado: Option<&str>,
+    descricao_selada: Option<&str>,
+) -> Result<()> {
+    if let Some(selado) = conteudo_nota_selado {
+        conexao.execute(
+            "UPDATE itens SET conteudo_nota_selado = ?1, conteudo_nota = NULL WHERE id = ?2",
+            params![selado, id],
+        ).context("Falha ao gravar conteudo_nota selado")?;
+    }
+    if let Some(selada) = descricao_selada {
+        conexao.execute(
+            "UPDATE itens SET descricao_selada = ?1, descricao = NULL WHERE id = ?2",
+            params![selada, id],
+        ).context("Falha ao gravar descricao selada")?;
+    }
+    Ok(())
+}
+
+// =============================================================================
+// REVISOES DE ITEM — Histórico de edições com restauração
+// =============================================================================
+// Cada chamada a `atualizar_item` snapshota o estado anterior em
+// `item_revisoes` antes de sobrescrever (ver chamada a `criar_revisao` logo
+// no início da função). A quantidade retida por item é limitada pela
+// configuração `max_revisoes` (padrão 20, ver `max_revisoes_configurado`).
+// =============================================================================
+
+/// Valor padrão de revisões retidas por item quando a configuração
+/// `max_revisoes` nunca foi definida pelo usuário.
+const MAX_REVISOES_PADRAO: i64 = 20;
+
+/// Lê a configuração `max_revisoes` (ver `obter_configuracao`). Usa o padrão
+/// se a chave não existir ou não for um inteiro válido.
+fn max_revisoes_configurado(conexao: &Connection) -> i64 {
+    obter_configuracao(conexao, "max_revisoes")
+        .ok()
+        .flatten()
+        .and_then(|c| c.valor)
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(MAX_REVISOES_PADRAO)
+}
+
+/// Snapshota o estado atual de `item` em `item_revisoes` e poda revisões
+/// excedentes de acordo com `max_revisoes_configurado`.
+fn criar_revisao(conexao: &Connection, item: &Item) -> Result<()> {
+    let proximo_numero: i64 = conexao.query_row(
+        "SELECT COALESCE(MAX(numero_revisao), 0) + 1 FROM item_revisoes WHERE item_id = ?1",
+        params![item.id],
+        |linha| linha.get(0),
+    ).context("Falha ao calcular o próximo número de revisão")?;
+
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let id = Uuid::new_v4().to_string();
+
+    conexao.execute(
+        "INSERT INTO item_revisoes (id, item_id, numero_revisao, titulo, descricao, conteudo_nota, criado_em)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![id, item.id, proximo_numero, item.titulo, item.descricao, item.conteudo_nota, agora],
+    ).context("Falha ao criar revisão do item")?;
+
+    podar_revisoes(conexao, &item.id, max_revisoes_configurado(conexao))?;
+
+    Ok(())
+}
+
+/// Remove as revisões mais antigas de `item_id` além das `max` mais recentes.
+fn podar_revisoes(conexao: &Connection, item_id: &str, max: i64) -> Result<()> {
+    conexao.execute(
+        "DELETE FROM item_revisoes WHERE item_id = ?1 AND numero_revisao NOT IN (
+             SELECT numero_revisao FROM item_revisoes WHERE item_id = ?1
+             ORDER BY numero_revisao DESC LIMIT ?2
+         )",
+        params![item_id, max],
+    ).context("Falha ao podar revisões antigas")?;
+    Ok(())
+}
+
+/// Lista as revisões de um item, da mais recente para a mais antiga.
+pub fn listar_revisoes(conexao: &Connection, item_id: &str) -> Result<Vec<RevisaoItem>> {
+    let mut stmt = conexao.prepare(
+        "SELECT id, item_id, numero_revisao, titulo, descricao, conteudo_nota, criado_em
+         FROM item_revisoes WHERE item_id = ?1 ORDER BY numero_revisao DESC",
+    )?;
+
+    let revisoes = stmt.query_map(params![item_id], |linha| {
+        Ok(RevisaoItem {
+            id: linha.get(0)?,
+            item_id: linha.get(1)?,
+            numero_revisao: linha.get(2)?,
+            titulo: linha.get(3)?,
+            descricao: linha.get(4)?,
+            conteudo_nota: linha.get(5)?,
+            criado_em: linha.get(6)?,
+        })
+    })?.collect::<rusqlite::Result<Vec<_>>>().context("Falha ao listar revisões do item")?;
+
+    Ok(revisoes)
+}
+
+/// Restaura um item para o estado registrado em `numero_revisao`. O estado
+/// atual do item é snapshotado como uma nova revisão antes da reversão
+/// (via `atualizar_item`), então a restauração em si também é reversível.
+pub fn restaurar_revisao(conexao: &Connection, item_id: &str, numero_revisao: i64) -> Result<Item> {
+    let revisao = conexao.query_row(
+        "SELECT id, item_id, numero_revisao, titulo, descricao, conteudo_nota, criado_em
+         FROM item_revisoes WHERE item_id = ?1 AND numero_revisao = ?2",
+        params![item_id, numero_revisao],
+        |linha| {
+            Ok(RevisaoItem {
+                id: linha.get(0)?,
+                item_id: linha.get(1)?,
+                numero_revisao: linha.get(2)?,
+                titulo: linha.get(3)?,
+                descricao: linha.get(4)?,
+                conteudo_nota: linha.get(5)?,
+                criado_em: linha.get(6)?,
+            })
+        },
+    ).with_context(|| format!("Revisão {} não encontrada para o item {}", numero_revisao, item_id))?;
+
+    let dados = AtualizacaoItem {
+        pasta_id: None,
+        titulo: Some(revisao.titulo),
+        descricao: revisao.descricao,
+        conteudo_nota: revisao.conteudo_nota,
+        data_vencimento: None,
+        tag_ids: None,
+        regra_recorrencia: None,
+    };
+
+    atualizar_item(conexao, item_id, &dados)
+}
+
 // =============================================================================
 // TAGS — Operações CRUD para categorização
 // =============================================================================
@@ -613,6 +1120,74 @@ pub fn excluir_anexo(conexao: &Connection, id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Busca um blob pelo hash SHA-256. `None` se nenhum anexo com esse
+/// conteúdo exato já foi salvo (ver migração 005, `services::armazenamento`).
+pub fn obter_blob(conexao: &Connection, hash_sha256: &str) -> Result<Option<Blob>> {
+    conexao.query_row(
+        "SELECT hash_sha256, tamanho, contagem_referencias, criado_em
+         FROM blobs WHERE hash_sha256 = ?1",
+        params![hash_sha256],
+        |linha| {
+            Ok(Blob {
+                hash_sha256: linha.get(0)?,
+                tamanho: linha.get(1)?,
+                contagem_referencias: linha.get(2)?,
+                criado_em: linha.get(3)?,
+            })
+        },
+    ).optional().context("Falha ao buscar blob")
+}
+
+/// Registra uma nova referência a um blob: cria a linha com
+/// `contagem_referencias = 1` se o blob ainda não existe (novo conteúdo,
+/// gravado fisicamente pelo chamador), ou apenas incrementa a contagem se
+/// já existe (conteúdo idêntico a um anexo já salvo — a cópia física é
+/// dispensada pelo chamador).
+pub fn registrar_referencia_blob(conexao: &Connection, hash_sha256: &str, tamanho: i64) -> Result<()> {
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    conexao.execute(
+        "INSERT INTO blobs (hash_sha256, tamanho, contagem_referencias, criado_em)
+         VALUES (?1, ?2, 1, ?3)
+         ON CONFLICT(hash_sha256) DO UPDATE SET contagem_referencias = contagem_referencias + 1",
+        params![hash_sha256, tamanho, agora],
+    ).context("Falha ao registrar referência de blob")?;
+
+    Ok(())
+}
+
+/// Remove uma referência a um blob (um anexo que apontava para ele foi
+/// excluído). Decrementa `contagem_referencias`; quando chega a zero, a
+/// linha é removida e `true` é retornado — o chamador deve então apagar o
+/// arquivo físico do blob. `false` indica que outros anexos ainda
+/// referenciam o mesmo conteúdo, então o arquivo físico deve ser mantido.
+pub fn remover_referencia_blob(conexao: &Connection, hash_sha256: &str) -> Result<bool> {
+    conexao.execute(
+        "UPDATE blobs SET contagem_referencias = contagem_referencias - 1 WHERE hash_sha256 = ?1",
+        params![hash_sha256],
+    ).context("Falha ao decrementar referência de blob")?;
+
+    let contagem_restante: Option<i64> = conexao
+        .query_row(
+            "SELECT contagem_referencias FROM blobs WHERE hash_sha256 = ?1",
+            params![hash_sha256],
+            |linha| linha.get(0),
+        )
+        .optional()
+        .context("Falha ao ler contagem de referências do blob")?;
+
+    match contagem_restante {
+        Some(contagem) if contagem <= 0 => {
+            conexao.execute(
+                "DELETE FROM blobs WHERE hash_sha256 = ?1",
+                params![hash_sha256],
+            ).context("Falha ao remover blob órfão")?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
 /// Lista todos os anexos de uma tarefa de checklist.
 pub fn listar_anexos_por_tarefa(conexao: &Connection, tarefa_id: &str) -> Result<Vec<Anexo>> {
     let mut stmt = conexao.prepare(
@@ -662,14 +1237,16 @@ pub fn listar_tarefas_por_item(conexao: &Connection, item_id: &str) -> Result<Ve
             criado_em: linha.get(5)?,
             atualizado_em: linha.get(6)?,
             anexos: vec![],
+            bloqueada: false,
         })
     })?
     .collect::<Result<Vec<_>, _>>()
     .context("Falha ao listar tarefas do checklist")?;
 
-    // Carregar anexos de cada tarefa
+    // Carregar anexos e calcular o bloqueio de cada tarefa
     for tarefa in &mut tarefas {
         tarefa.anexos = listar_anexos_por_tarefa(conexao, &tarefa.id)?;
+        tarefa.bloqueada = tarefa_bloqueada(conexao, &tarefa.id)?;
     }
 
     Ok(tarefas)
@@ -721,11 +1298,13 @@ fn obter_tarefa_por_id(conexao: &Connection, id: &str) -> Result<TarefaChecklist
                 criado_em: linha.get(5)?,
                 atualizado_em: linha.get(6)?,
                 anexos: vec![],
+                bloqueada: false,
             })
         },
     ).with_context(|| format!("Tarefa não encontrada: {}", id))?;
 
     tarefa.anexos = listar_anexos_por_tarefa(conexao, &tarefa.id)?;
+    tarefa.bloqueada = tarefa_bloqueada(conexao, &tarefa.id)?;
     Ok(tarefa)
 }
 
@@ -759,8 +1338,16 @@ pub fn excluir_tarefa(conexao: &Connection, id: &str) -> Result<()> {
     Ok(())
 }
 
-/// Marca/desmarca uma tarefa como concluída.
+/// Marca/desmarca uma tarefa como concluída. Recusa concluir (mas permite
+/// desmarcar) uma tarefa cujas dependências ainda não estejam concluídas —
+/// ver `tarefa_bloqueada`.
 pub fn marcar_tarefa_concluida(conexao: &Connection, id: &str, concluida: bool) -> Result<TarefaChecklist> {
+    if concluida && tarefa_bloqueada(conexao, id)? {
+        return Err(anyhow!(
+            "Não é possível concluir a tarefa: existem dependências ainda não concluídas"
+        ));
+    }
+
     let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
 
     conexao.execute(
@@ -789,87 +1376,503 @@ pub fn reordenar_tarefas(conexao: &Connection, ordens: &[(String, i32)]) -> Resu
 }
 
 // =============================================================================
-// BUSCA FULL-TEXT — Pesquisa com FTS5 do SQLite
+// DEPENDÊNCIAS DE TAREFA — Grafo de pré-requisitos entre tarefas de checklist
 // =============================================================================
 
-/// Busca itens usando Full-Text Search (FTS5).
-/// O termo é pesquisado em título, descrição e conteúdo de notas.
-/// Filtros adicionais podem restringir por tipo, pasta, tags e período.
-///
-/// A relevância é calculada pelo FTS5 rank (bm25). Valores mais negativos
-/// indicam maior relevância, então invertemos o sinal para o frontend.
-pub fn buscar_fts(conexao: &Connection, termo: &str, filtros: &FiltrosBusca) -> Result<Vec<ResultadoBusca>> {
-    // Construir a query dinamicamente com base nos filtros.
-    // A FTS5 do VaultCraft é standalone (não content-table), então possui
-    // uma coluna 'id' UNINDEXED para vincular ao item original.
-    // Usamos JOIN em itens_fts.id = i.id para obter os dados completos.
-    let mut sql = String::from(
-        "SELECT i.id, i.pasta_id, i.tipo, i.titulo, i.descricao, i.conteudo_nota,
-                i.data_vencimento, i.criado_em, i.atualizado_em,
-                -itens_fts.rank as relevancia
-         FROM itens_fts
-         INNER JOIN itens i ON itens_fts.id = i.id
-         WHERE itens_fts MATCH ?1"
-    );
-
-    // Adicionar filtros dinamicamente
-    let mut param_index = 2;
-    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = vec![
-        Box::new(termo.to_string()),
-    ];
-
-    if let Some(ref tipo) = filtros.tipo {
-        sql.push_str(&format!(" AND i.tipo = ?{}", param_index));
-        params_vec.push(Box::new(tipo.to_string()));
-        param_index += 1;
+/// Adiciona uma aresta `tarefa_id` depende de `depende_de_id`. Recusa a
+/// aresta se ela fechar um ciclo (percorre o grafo existente a partir de
+/// `depende_de_id` em busca de `tarefa_id` — se alcançável, inserir esta
+/// aresta criaria um ciclo).
+pub fn adicionar_dependencia(conexao: &Connection, tarefa_id: &str, depende_de_id: &str) -> Result<()> {
+    if tarefa_id == depende_de_id {
+        return Err(anyhow!("Uma tarefa não pode depender de si mesma"));
     }
 
-    if let Some(ref pasta_id) = filtros.pasta_id {
-        sql.push_str(&format!(" AND i.pasta_id = ?{}", param_index));
-        params_vec.push(Box::new(pasta_id.clone()));
-        param_index += 1;
+    if alcancavel(conexao, depende_de_id, tarefa_id)? {
+        return Err(anyhow!("Dependência rejeitada: criaria um ciclo entre tarefas"));
     }
 
-    if let Some(ref data_inicio) = filtros.data_inicio {
-        sql.push_str(&format!(" AND i.criado_em >= ?{}", param_index));
-        params_vec.push(Box::new(data_inicio.clone()));
-        param_index += 1;
-    }
+    conexao.execute(
+        "INSERT INTO dependencias_tarefa (tarefa_id, depende_de_id) VALUES (?1, ?2)",
+        params![tarefa_id, depende_de_id],
+    ).context("Falha ao adicionar dependência de tarefa")?;
 
-    if let Some(ref data_fim) = filtros.data_fim {
-        sql.push_str(&format!(" AND i.criado_em <= ?{}", param_index));
-        params_vec.push(Box::new(data_fim.clone()));
-        let _ = param_index; // Suprimir aviso de variável não usada
-    }
+    info!("Dependência adicionada: {} depende de {}", tarefa_id, depende_de_id);
+    Ok(())
+}
 
-    sql.push_str(" ORDER BY relevancia DESC LIMIT 100");
+/// Remove a aresta `tarefa_id` depende de `depende_de_id`, se existir.
+pub fn remover_dependencia(conexao: &Connection, tarefa_id: &str, depende_de_id: &str) -> Result<()> {
+    conexao.execute(
+        "DELETE FROM dependencias_tarefa WHERE tarefa_id = ?1 AND depende_de_id = ?2",
+        params![tarefa_id, depende_de_id],
+    ).context("Falha ao remover dependência de tarefa")?;
 
-    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
-        params_vec.iter().map(|p| p.as_ref()).collect();
+    info!("Dependência removida: {} não depende mais de {}", tarefa_id, depende_de_id);
+    Ok(())
+}
 
-    let mut stmt = conexao.prepare(&sql)?;
+/// Lista os IDs das tarefas das quais `tarefa_id` depende diretamente.
+pub fn listar_dependencias(conexao: &Connection, tarefa_id: &str) -> Result<Vec<String>> {
+    conexao
+        .prepare("SELECT depende_de_id FROM dependencias_tarefa WHERE tarefa_id = ?1")?
+        .query_map(params![tarefa_id], |linha| linha.get(0))?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Falha ao listar dependências da tarefa")
+}
 
-    let mut resultados: Vec<ResultadoBusca> = stmt.query_map(params_refs.as_slice(), |linha| {
-        let tipo_str: String = linha.get(2)?;
-        Ok(ResultadoBusca {
-            item: Item {
-                id: linha.get(0)?,
-                pasta_id: linha.get(1)?,
-                tipo: TipoItem::de_str(&tipo_str),
+/// `true` se `tarefa_id` tiver alguma dependência direta que ainda não
+/// esteja concluída.
+fn tarefa_bloqueada(conexao: &Connection, tarefa_id: &str) -> Result<bool> {
+    conexao.query_row(
+        "SELECT EXISTS(
+             SELECT 1 FROM dependencias_tarefa d
+             INNER JOIN tarefas_checklist t ON t.id = d.depende_de_id
+             WHERE d.tarefa_id = ?1 AND t.concluida = 0
+         )",
+        params![tarefa_id],
+        |linha| linha.get::<_, i64>(0).map(|v| v != 0),
+    ).context("Falha ao verificar bloqueio de dependências da tarefa")
+}
+
+/// Busca em profundidade nas dependências a partir de `origem`: `true` se
+/// `alvo` for alcançável seguindo arestas "depende de". Usada por
+/// `adicionar_dependencia` para detectar ciclos antes do INSERT.
+fn alcancavel(conexao: &Connection, origem: &str, alvo: &str) -> Result<bool> {
+    let mut pilha: Vec<String> = vec![origem.to_string()];
+    let mut visitados: HashSet<String> = HashSet::new();
+
+    while let Some(atual) = pilha.pop() {
+        if atual == alvo {
+            return Ok(true);
+        }
+        if !visitados.insert(atual.clone()) {
+            continue;
+        }
+
+        let proximos: Vec<String> = conexao
+            .prepare("SELECT depende_de_id FROM dependencias_tarefa WHERE tarefa_id = ?1")?
+            .query_map(params![atual], |linha| linha.get(0))?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Falha ao percorrer grafo de dependências de tarefas")?;
+        pilha.extend(proximos);
+    }
+
+    Ok(false)
+}
+
+// =============================================================================
+// ENTRADAS DE TEMPO — Registro de tempo trabalhado em tarefas de checklist
+// =============================================================================
+
+/// Registra uma entrada de tempo trabalhado em uma tarefa. Rejeita
+/// `dados.duracao` fora do invariante `minutos < 60` (ver `Duracao::validar`)
+/// antes de gravar qualquer coisa.
+pub fn registrar_tempo(conexao: &Connection, dados: &NovaEntradaTempo) -> Result<EntradaTempo> {
+    dados.duracao.validar()?;
+
+    let id = Uuid::new_v4().to_string();
+    conexao.execute(
+        "INSERT INTO entradas_tempo (id, tarefa_id, data_registro, mensagem, duracao_minutos)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, dados.tarefa_id, dados.data_registro, dados.mensagem, dados.duracao.total_minutos()],
+    ).context("Falha ao registrar entrada de tempo")?;
+
+    info!("Entrada de tempo registrada: {} ({} min) na tarefa {}", id, dados.duracao.total_minutos(), dados.tarefa_id);
+    obter_entrada_tempo_por_id(conexao, &id)
+}
+
+fn obter_entrada_tempo_por_id(conexao: &Connection, id: &str) -> Result<EntradaTempo> {
+    conexao.query_row(
+        "SELECT id, tarefa_id, data_registro, mensagem, duracao_minutos
+         FROM entradas_tempo WHERE id = ?1",
+        params![id],
+        |linha| {
+            let duracao_minutos: i32 = linha.get(4)?;
+            Ok(EntradaTempo {
+                id: linha.get(0)?,
+                tarefa_id: linha.get(1)?,
+                data_registro: linha.get(2)?,
+                mensagem: linha.get(3)?,
+                duracao: Duracao::de_minutos_totais(duracao_minutos),
+            })
+        },
+    ).with_context(|| format!("Entrada de tempo não encontrada: {}", id))
+}
+
+/// Lista as entradas de tempo de uma tarefa, da mais antiga para a mais
+/// recente.
+pub fn listar_tempo_por_tarefa(conexao: &Connection, tarefa_id: &str) -> Result<Vec<EntradaTempo>> {
+    let mut stmt = conexao.prepare(
+        "SELECT id, tarefa_id, data_registro, mensagem, duracao_minutos
+         FROM entradas_tempo WHERE tarefa_id = ?1 ORDER BY data_registro ASC",
+    )?;
+
+    stmt.query_map(params![tarefa_id], |linha| {
+        let duracao_minutos: i32 = linha.get(4)?;
+        Ok(EntradaTempo {
+            id: linha.get(0)?,
+            tarefa_id: linha.get(1)?,
+            data_registro: linha.get(2)?,
+            mensagem: linha.get(3)?,
+            duracao: Duracao::de_minutos_totais(duracao_minutos),
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha ao listar entradas de tempo da tarefa")
+}
+
+/// Soma a duração de todas as entradas de tempo lançadas nas tarefas de um
+/// item (via JOIN com `tarefas_checklist`).
+pub fn total_tempo_por_item(conexao: &Connection, item_id: &str) -> Result<TotalTempoItem> {
+    let total_minutos: i32 = conexao.query_row(
+        "SELECT COALESCE(SUM(et.duracao_minutos), 0)
+         FROM entradas_tempo et
+         INNER JOIN tarefas_checklist t ON t.id = et.tarefa_id
+         WHERE t.item_id = ?1",
+        params![item_id],
+        |linha| linha.get(0),
+    ).context("Falha ao somar tempo do item")?;
+
+    Ok(TotalTempoItem {
+        item_id: item_id.to_string(),
+        duracao_total: Duracao::de_minutos_totais(total_minutos),
+    })
+}
+
+// =============================================================================
+// BUSCA FULL-TEXT — Pesquisa com FTS5 do SQLite
+// =============================================================================
+
+/// Une segmentos consecutivos de `<mark>`/`</mark>` (já inseridos pelo FTS5
+/// em `snippet()`/`highlight()`) escapando apenas o texto entre eles — as
+/// próprias funções do FTS5 não escapam o texto ao redor do termo buscado,
+/// então confiar cegamente na saída como HTML pronto seria uma brecha de
+/// injeção (um título ou nota contendo `<`/`&` vazaria para o frontend).
+/// As tags de marcação nunca vêm de dados do usuário (são os literais
+/// passados a `snippet`/`highlight` abaixo), então é seguro preservá-las.
+fn escapar_preservando_marcas(bruto: &str) -> String {
+    const ABRE: &str = "<mark>";
+    const FECHA: &str = "</mark>";
+
+    let mut resultado = String::with_capacity(bruto.len());
+    let mut resto = bruto;
+
+    loop {
+        match resto.find(ABRE) {
+            Some(pos) => {
+                resultado.push_str(&exportacao::html_escape(&resto[..pos]));
+                resultado.push_str(ABRE);
+                resto = &resto[pos + ABRE.len()..];
+
+                match resto.find(FECHA) {
+                    Some(pos_fecha) => {
+                        resultado.push_str(&exportacao::html_escape(&resto[..pos_fecha]));
+                        resultado.push_str(FECHA);
+                        resto = &resto[pos_fecha + FECHA.len()..];
+                    }
+                    None => {
+                        resultado.push_str(&exportacao::html_escape(resto));
+                        return resultado;
+                    }
+                }
+            }
+            None => {
+                resultado.push_str(&exportacao::html_escape(resto));
+                return resultado;
+            }
+        }
+    }
+}
+
+/// `None` quando o FTS5 devolve uma string vazia (acontece com
+/// `snippet(..., -1, ...)` quando o termo não casa em nenhuma coluna
+/// indexada) — evita sobrescrever o campo com um trecho vazio.
+fn trecho_ou_none(bruto: String) -> Option<String> {
+    if bruto.is_empty() {
+        None
+    } else {
+        Some(escapar_preservando_marcas(&bruto))
+    }
+}
+
+/// Busca itens usando Full-Text Search (FTS5).
+/// O termo é pesquisado em título, descrição e conteúdo de notas.
+/// Filtros adicionais podem restringir por tipo, pasta, tags e período.
+///
+/// A relevância é calculada por `bm25(itens_fts, ...)`, ponderado por
+/// `pesos` (ver `PesosBusca`). O `bm25()` é menor quanto mais relevante,
+/// então invertemos o sinal para o frontend (mesma convenção de quando
+/// isso vinha do `rank` padrão do FTS5).
+/// `true` se o erro recebido do rusqlite indicar que o módulo FTS5 não está
+/// disponível nesta instalação do SQLite (em vez de algum outro problema
+/// com a consulta). Usado por `buscar_fts` para decidir se cai para
+/// `buscar_like_fallback` em vez de propagar o erro.
+fn fts5_indisponivel(erro: &rusqlite::Error) -> bool {
+    let msg = erro.to_string().to_lowercase();
+    msg.contains("fts5") || msg.contains("no such module")
+}
+
+/// Varredura com `LIKE` sobre `itens.titulo`/`descricao`/`conteudo_nota`,
+/// usada por `buscar_fts` apenas quando o módulo FTS5 não está disponível
+/// em tempo de execução. Sem ranking por bm25 nem trecho/highlight (não há
+/// como computá-los sem FTS5) — `relevancia` fica sempre 0.0 e os
+/// resultados vêm ordenados por `atualizado_em` decrescente.
+fn buscar_like_fallback(
+    conexao: &Connection,
+    termo: &str,
+    filtros: &FiltrosBusca,
+) -> Result<Vec<ResultadoBusca>> {
+    let termo_escapado = termo
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let curinga = format!("%{}%", termo_escapado);
+
+    let mut sql = String::from(
+        "SELECT id, pasta_id, tipo, titulo, descricao, conteudo_nota,
+                data_vencimento, criado_em, atualizado_em, regra_recorrencia
+         FROM itens
+         WHERE (titulo LIKE ?1 ESCAPE '\\' OR descricao LIKE ?1 ESCAPE '\\' OR conteudo_nota LIKE ?1 ESCAPE '\\')"
+    );
+
+    let mut param_index = 2;
+    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(curinga)];
+
+    if let Some(ref tipo) = filtros.tipo {
+        sql.push_str(&format!(" AND tipo = ?{}", param_index));
+        params_vec.push(Box::new(tipo.to_string()));
+        param_index += 1;
+    }
+
+    if let Some(ref pasta_id) = filtros.pasta_id {
+        sql.push_str(&format!(" AND pasta_id = ?{}", param_index));
+        params_vec.push(Box::new(pasta_id.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref data_inicio) = filtros.data_inicio {
+        sql.push_str(&format!(" AND criado_em >= ?{}", param_index));
+        params_vec.push(Box::new(data_inicio.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref data_fim) = filtros.data_fim {
+        sql.push_str(&format!(" AND criado_em <= ?{}", param_index));
+        params_vec.push(Box::new(data_fim.clone()));
+        let _ = param_index;
+    }
+
+    sql.push_str(" ORDER BY atualizado_em DESC LIMIT 100");
+
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+        params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let mut stmt = conexao.prepare(&sql)?;
+    stmt.query_map(params_refs.as_slice(), |linha| {
+        let tipo_str: String = linha.get(2)?;
+        Ok(ResultadoBusca {
+            item: Item {
+                id: linha.get(0)?,
+                pasta_id: linha.get(1)?,
+                tipo: TipoItem::de_str(&tipo_str),
                 titulo: linha.get(3)?,
                 descricao: linha.get(4)?,
                 conteudo_nota: linha.get(5)?,
                 data_vencimento: linha.get(6)?,
                 criado_em: linha.get(7)?,
                 atualizado_em: linha.get(8)?,
+                regra_recorrencia: linha.get(9)?,
                 tags: vec![],
                 anexos: vec![],
             },
-            relevancia: linha.get(9)?,
+            relevancia: 0.0,
+            trecho_titulo: None,
+            trecho_conteudo: None,
+            titulo_destacado: None,
         })
     })?
     .collect::<Result<Vec<_>, _>>()
-    .context("Falha na busca full-text")?;
+    .context("Falha na busca por varredura (fallback sem FTS5)")
+}
+
+/// Traduz uma entrada de busca livre do usuário para sintaxe FTS5 segura.
+/// Hoje, um termo com caracter especial do FTS5 (ex. `:`, `-`, `(`) passado
+/// cru para `MATCH` pode estourar erro de sintaxe ou casar de forma
+/// inesperada — esta função decide, token a token, o que vira operador e o
+/// que vira termo citado:
+/// - `AND`/`OR`/`NOT` (qualquer caixa) viram operadores booleanos FTS5
+/// - `"frases exatas"` já entre aspas são preservadas como estão (aspas
+///   internas são escapadas dobrando, `"` -> `""`, sintaxe de escape do FTS5)
+/// - `prefixo*` (só letras/dígitos/`_` antes do `*`) vira uma prefix query
+/// - qualquer outro token solto é colocado entre aspas duplas, o que o
+///   torna um termo literal para o FTS5 mesmo se tiver pontuação
+pub fn construir_consulta_fts(entrada: &str) -> String {
+    tokenizar_consulta_fts(entrada)
+        .into_iter()
+        .map(|token| match token.to_ascii_uppercase().as_str() {
+            "AND" | "OR" | "NOT" => token.to_ascii_uppercase(),
+            _ => formatar_token_fts(&token),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Divide `entrada` em tokens separados por espaço, tratando
+/// `"frases entre aspas"` (mesmo com espaços dentro) como um único token —
+/// usado por `construir_consulta_fts`.
+fn tokenizar_consulta_fts(entrada: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut restante = entrada.trim();
+
+    while !restante.is_empty() {
+        if let Some(sem_aspas) = restante.strip_prefix('"') {
+            match sem_aspas.find('"') {
+                Some(fim) => {
+                    tokens.push(format!("\"{}\"", &sem_aspas[..fim]));
+                    restante = sem_aspas[fim + 1..].trim_start();
+                }
+                None => {
+                    // Aspa sem fechamento: trata o resto da entrada como a frase.
+                    tokens.push(format!("\"{}\"", sem_aspas));
+                    restante = "";
+                }
+            }
+        } else {
+            let fim = restante.find(char::is_whitespace).unwrap_or(restante.len());
+            tokens.push(restante[..fim].to_string());
+            restante = restante[fim..].trim_start();
+        }
+    }
+
+    tokens
+}
+
+/// Formata um único token não-booleano para sintaxe FTS5 segura (ver
+/// `construir_consulta_fts`): uma frase já entre aspas passa direto (com
+/// aspas internas escapadas), `prefixo*` vira prefix query, e qualquer
+/// outro termo é citado como termo literal.
+fn formatar_token_fts(token: &str) -> String {
+    if token.len() >= 2 && token.starts_with('"') && token.ends_with('"') {
+        let interior = &token[1..token.len() - 1];
+        return format!("\"{}\"", interior.replace('"', "\"\""));
+    }
+
+    if let Some(prefixo) = token.strip_suffix('*') {
+        if !prefixo.is_empty() && prefixo.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return format!("{}*", prefixo);
+        }
+    }
+
+    format!("\"{}\"", token.replace('"', "\"\""))
+}
+
+pub fn buscar_fts(
+    conexao: &Connection,
+    termo: &str,
+    filtros: &FiltrosBusca,
+    pesos: &PesosBusca,
+) -> Result<Vec<ResultadoBusca>> {
+    // Construir a query dinamicamente com base nos filtros.
+    // A FTS5 do VaultCraft é standalone (não content-table), então possui
+    // uma coluna 'id' UNINDEXED para vincular ao item original.
+    // Usamos JOIN em itens_fts.id = i.id para obter os dados completos.
+    //
+    // `itens_fts` tem as colunas, em ordem, `id UNINDEXED, titulo, descricao,
+    // conteudo_nota` — por isso o primeiro peso do bm25() é 0.0 (coluna
+    // 'id', nunca indexada, então seu peso nunca afeta o score), e os
+    // `snippet()`/`highlight()` abaixo usam o índice de coluna 1 (titulo) e
+    // 3 (conteudo_nota).
+    let mut sql = String::from(
+        "SELECT i.id, i.pasta_id, i.tipo, i.titulo, i.descricao, i.conteudo_nota,
+                i.data_vencimento, i.criado_em, i.atualizado_em,
+                -bm25(itens_fts, 0.0, ?2, ?3, ?4) as relevancia,
+                snippet(itens_fts, 1, '<mark>', '</mark>', '…', 10) as trecho_titulo_bruto,
+                snippet(itens_fts, 3, '<mark>', '</mark>', '…', 15) as trecho_conteudo_bruto,
+                highlight(itens_fts, 1, '<mark>', '</mark>') as titulo_destacado_bruto,
+                i.regra_recorrencia
+         FROM itens_fts
+         INNER JOIN itens i ON itens_fts.id = i.id
+         WHERE itens_fts MATCH ?1"
+    );
+
+    // Adicionar filtros dinamicamente
+    let mut param_index = 5;
+    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = vec![
+        Box::new(construir_consulta_fts(termo)),
+        Box::new(pesos.titulo),
+        Box::new(pesos.descricao),
+        Box::new(pesos.conteudo_nota),
+    ];
+
+    if let Some(ref tipo) = filtros.tipo {
+        sql.push_str(&format!(" AND i.tipo = ?{}", param_index));
+        params_vec.push(Box::new(tipo.to_string()));
+        param_index += 1;
+    }
+
+    if let Some(ref pasta_id) = filtros.pasta_id {
+        sql.push_str(&format!(" AND i.pasta_id = ?{}", param_index));
+        params_vec.push(Box::new(pasta_id.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref data_inicio) = filtros.data_inicio {
+        sql.push_str(&format!(" AND i.criado_em >= ?{}", param_index));
+        params_vec.push(Box::new(data_inicio.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref data_fim) = filtros.data_fim {
+        sql.push_str(&format!(" AND i.criado_em <= ?{}", param_index));
+        params_vec.push(Box::new(data_fim.clone()));
+        let _ = param_index; // Suprimir aviso de variável não usada
+    }
+
+    sql.push_str(" ORDER BY relevancia DESC LIMIT 100");
+
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+        params_vec.iter().map(|p| p.as_ref()).collect();
+
+    // A consulta acima depende do módulo FTS5 estar compilado no SQLite em
+    // uso. Isso é verdade para o SQLite empacotado com o app, mas não em
+    // toda instalação do sistema (ex.: feature `bundled` do rusqlite
+    // desligada). Se o FTS5 não estiver disponível em tempo de execução,
+    // caímos para uma varredura LIKE sobre `itens` em vez de propagar o
+    // erro — ver `fts5_indisponivel`/`buscar_like_fallback`.
+    let tentativa_fts: rusqlite::Result<Vec<ResultadoBusca>> = (|| {
+        let mut stmt = conexao.prepare(&sql)?;
+        stmt.query_map(params_refs.as_slice(), |linha| {
+            let tipo_str: String = linha.get(2)?;
+            Ok(ResultadoBusca {
+                item: Item {
+                    id: linha.get(0)?,
+                    pasta_id: linha.get(1)?,
+                    tipo: TipoItem::de_str(&tipo_str),
+                    titulo: linha.get(3)?,
+                    descricao: linha.get(4)?,
+                    conteudo_nota: linha.get(5)?,
+                    data_vencimento: linha.get(6)?,
+                    criado_em: linha.get(7)?,
+                    atualizado_em: linha.get(8)?,
+                    regra_recorrencia: linha.get(13)?,
+                    tags: vec![],
+                    anexos: vec![],
+                },
+                relevancia: linha.get(9)?,
+                trecho_titulo: trecho_ou_none(linha.get(10)?),
+                trecho_conteudo: trecho_ou_none(linha.get(11)?),
+                titulo_destacado: trecho_ou_none(linha.get(12)?),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()
+    })();
+
+    let mut resultados: Vec<ResultadoBusca> = match tentativa_fts {
+        Ok(r) => r,
+        Err(e) if fts5_indisponivel(&e) => buscar_like_fallback(conexao, termo, filtros)?,
+        Err(e) => return Err(e).context("Falha na busca full-text"),
+    };
 
     // Filtrar por tags se especificadas (feito em memória pois é mais simples
     // e o volume de dados é pequeno em um app pessoal)
@@ -895,6 +1898,207 @@ pub fn buscar_fts(conexao: &Connection, termo: &str, filtros: &FiltrosBusca) ->
     Ok(resultados)
 }
 
+/// Versão paginada de `buscar_fts` (ver seção PAGINACAO POR KEYSET).
+/// Ordenado por relevância (bm25) decrescente, com `id` como desempate;
+/// `cursor`, se fornecido, deve ser o `proximo_cursor` da página anterior.
+/// `total_aproximado` conta todos os itens que casam o termo e os filtros,
+/// ignorando a posição da página.
+pub fn buscar_fts_paginado(
+    conexao: &Connection,
+    termo: &str,
+    filtros: &FiltrosBusca,
+    pesos: &PesosBusca,
+    limite: i64,
+    cursor: Option<&str>,
+) -> Result<Pagina<ResultadoBusca>> {
+    // Ver o comentário sobre a ordem das colunas de `itens_fts` em `buscar_fts`.
+    let mut sql = String::from(
+        "SELECT i.id, i.pasta_id, i.tipo, i.titulo, i.descricao, i.conteudo_nota,
+                i.data_vencimento, i.criado_em, i.atualizado_em,
+                -bm25(itens_fts, 0.0, ?2, ?3, ?4) as relevancia,
+                snippet(itens_fts, 1, '<mark>', '</mark>', '…', 10) as trecho_titulo_bruto,
+                snippet(itens_fts, 3, '<mark>', '</mark>', '…', 15) as trecho_conteudo_bruto,
+                highlight(itens_fts, 1, '<mark>', '</mark>') as titulo_destacado_bruto,
+                i.regra_recorrencia
+         FROM itens_fts
+         INNER JOIN itens i ON itens_fts.id = i.id
+         WHERE itens_fts MATCH ?1",
+    );
+
+    let mut param_index = 5;
+    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = vec![
+        Box::new(construir_consulta_fts(termo)),
+        Box::new(pesos.titulo),
+        Box::new(pesos.descricao),
+        Box::new(pesos.conteudo_nota),
+    ];
+
+    if let Some(ref tipo) = filtros.tipo {
+        sql.push_str(&format!(" AND i.tipo = ?{}", param_index));
+        params_vec.push(Box::new(tipo.to_string()));
+        param_index += 1;
+    }
+
+    if let Some(ref pasta_id) = filtros.pasta_id {
+        sql.push_str(&format!(" AND i.pasta_id = ?{}", param_index));
+        params_vec.push(Box::new(pasta_id.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref data_inicio) = filtros.data_inicio {
+        sql.push_str(&format!(" AND i.criado_em >= ?{}", param_index));
+        params_vec.push(Box::new(data_inicio.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref data_fim) = filtros.data_fim {
+        sql.push_str(&format!(" AND i.criado_em <= ?{}", param_index));
+        params_vec.push(Box::new(data_fim.clone()));
+        param_index += 1;
+    }
+
+    // A contagem aproximada usa os mesmos filtros, mas nunca o cursor —
+    // conta todos os resultados que casam a busca, não só os restantes.
+    let sql_contagem = format!(
+        "SELECT COUNT(*) FROM ({}) AS contagem",
+        sql.replacen(
+            "i.id, i.pasta_id, i.tipo, i.titulo, i.descricao, i.conteudo_nota,\n                i.data_vencimento, i.criado_em, i.atualizado_em,\n                -bm25(itens_fts, 0.0, ?2, ?3, ?4) as relevancia,\n                snippet(itens_fts, 1, '<mark>', '</mark>', '…', 10) as trecho_titulo_bruto,\n                snippet(itens_fts, 3, '<mark>', '</mark>', '…', 15) as trecho_conteudo_bruto,\n                highlight(itens_fts, 1, '<mark>', '</mark>') as titulo_destacado_bruto,\n                i.regra_recorrencia",
+            "1",
+            1,
+        )
+    );
+    let params_contagem: Vec<&dyn rusqlite::types::ToSql> =
+        params_vec.iter().map(|p| p.as_ref()).collect();
+    let total_aproximado: i64 = conexao
+        .query_row(&sql_contagem, params_contagem.as_slice(), |linha| linha.get(0))
+        .context("Falha ao contar resultados da busca")?;
+
+    if let Some(cursor) = cursor {
+        let (chave, id) = decodificar_cursor(cursor)?;
+        let relevancia_cursor: f64 = chave.parse()
+            .context("Cursor de paginação com relevância inválida")?;
+        // Repete a expressão do bm25() em vez de referenciar o alias
+        // `relevancia` — aliases da lista SELECT não são confiáveis em
+        // WHERE, mesma razão pela qual a versão anterior (`-itens_fts.rank`)
+        // já não usava o alias aqui. `?2`/`?3`/`?4` são os mesmos pesos já
+        // vinculados acima.
+        sql.push_str(&format!(
+            " AND (-bm25(itens_fts, 0.0, ?2, ?3, ?4) < ?{i1} OR (-bm25(itens_fts, 0.0, ?2, ?3, ?4) = ?{i1} AND i.id < ?{i2}))",
+            i1 = param_index, i2 = param_index + 1,
+        ));
+        params_vec.push(Box::new(relevancia_cursor));
+        params_vec.push(Box::new(id));
+        param_index += 2;
+    }
+
+    sql.push_str(&format!(" ORDER BY relevancia DESC, i.id DESC LIMIT ?{}", param_index));
+    params_vec.push(Box::new(limite + 1));
+
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+        params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let mut stmt = conexao.prepare(&sql)?;
+    let mut resultados: Vec<ResultadoBusca> = stmt.query_map(params_refs.as_slice(), |linha| {
+        let tipo_str: String = linha.get(2)?;
+        Ok(ResultadoBusca {
+            item: Item {
+                id: linha.get(0)?,
+                pasta_id: linha.get(1)?,
+                tipo: TipoItem::de_str(&tipo_str),
+                titulo: linha.get(3)?,
+                descricao: linha.get(4)?,
+                conteudo_nota: linha.get(5)?,
+                data_vencimento: linha.get(6)?,
+                criado_em: linha.get(7)?,
+                atualizado_em: linha.get(8)?,
+                regra_recorrencia: linha.get(13)?,
+                tags: vec![],
+                anexos: vec![],
+            },
+            relevancia: linha.get(9)?,
+            trecho_titulo: trecho_ou_none(linha.get(10)?),
+            trecho_conteudo: trecho_ou_none(linha.get(11)?),
+            titulo_destacado: trecho_ou_none(linha.get(12)?),
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha na busca full-text paginada")?;
+
+    if let Some(ref tag_ids) = filtros.tag_ids {
+        if !tag_ids.is_empty() {
+            for resultado in &mut resultados {
+                resultado.item.tags = obter_tags_do_item(conexao, &resultado.item.id)?;
+            }
+            resultados.retain(|r| r.item.tags.iter().any(|t| tag_ids.contains(&t.id)));
+        }
+    }
+
+    let tem_proxima_pagina = resultados.len() as i64 > limite;
+    if tem_proxima_pagina {
+        resultados.truncate(limite as usize);
+    }
+    let proximo_cursor = tem_proxima_pagina
+        .then(|| resultados.last().map(|r| codificar_cursor(&r.relevancia.to_string(), &r.item.id)))
+        .flatten();
+
+    for resultado in &mut resultados {
+        if resultado.item.tags.is_empty() {
+            resultado.item.tags = obter_tags_do_item(conexao, &resultado.item.id)?;
+        }
+        resultado.item.anexos = listar_anexos_por_item(conexao, &resultado.item.id)?;
+    }
+
+    Ok(Pagina { itens: resultados, proximo_cursor, total_aproximado })
+}
+
+/// Compara o conjunto de `id`s de `itens` com o de `itens_fts`, reportando
+/// quais faltam de um lado e do outro. Não modifica nada — ver
+/// `reconstruir_fts` para a correção. Usado por `services::reparo_fts`.
+pub fn verificar_integridade_fts(conexao: &Connection) -> Result<RelatorioReparoFts> {
+    let linhas_escaneadas: i64 = conexao
+        .query_row("SELECT COUNT(*) FROM itens", [], |linha| linha.get(0))
+        .context("Falha ao contar linhas de itens")?;
+
+    let ids_ausentes: Vec<String> = conexao
+        .prepare("SELECT i.id FROM itens i LEFT JOIN itens_fts f ON f.id = i.id WHERE f.id IS NULL")?
+        .query_map([], |linha| linha.get(0))?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Falha ao detectar IDs ausentes de itens_fts")?;
+
+    let ids_orfaos: Vec<String> = conexao
+        .prepare("SELECT f.id FROM itens_fts f LEFT JOIN itens i ON i.id = f.id WHERE i.id IS NULL")?
+        .query_map([], |linha| linha.get(0))?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Falha ao detectar IDs órfãos em itens_fts")?;
+
+    Ok(RelatorioReparoFts {
+        linhas_escaneadas,
+        ids_ausentes,
+        ids_orfaos,
+        reconstruido: false,
+    })
+}
+
+/// Reconstrói `itens_fts` do zero a partir de `itens`, usando os comandos
+/// de manutenção do FTS5 (`'integrity-check'` antes do `'rebuild'` — o
+/// primeiro só valida a estrutura do índice, o segundo efetivamente
+/// recria as entradas). Roda dentro de uma transação: se o rebuild falhar
+/// no meio, nada fica parcialmente reconstruído. Seguro de chamar com o
+/// app em uso — as tabelas continuam consultáveis durante a transação.
+pub fn reconstruir_fts(conexao: &mut Connection) -> Result<()> {
+    let tx = conexao.transaction().context("Falha ao iniciar transação de reparo do FTS")?;
+
+    tx.execute("INSERT INTO itens_fts(itens_fts) VALUES ('integrity-check')", [])
+        .context("Falha na checagem de integridade do FTS")?;
+    tx.execute("INSERT INTO itens_fts(itens_fts) VALUES ('rebuild')", [])
+        .context("Falha ao reconstruir o índice FTS")?;
+
+    tx.commit().context("Falha ao confirmar reparo do FTS")?;
+
+    info!("Índice itens_fts reconstruído.");
+    Ok(())
+}
+
 // =============================================================================
 // VENCIMENTOS — Consultas por data de vencimento
 // =============================================================================
@@ -909,7 +2113,7 @@ pub fn listar_proximos_vencimentos(conexao: &Connection, dias: i64) -> Result<Ve
 
     let mut stmt = conexao.prepare(
         "SELECT id, pasta_id, tipo, titulo, descricao, conteudo_nota,
-                data_vencimento, criado_em, atualizado_em
+                data_vencimento, criado_em, atualizado_em, regra_recorrencia
          FROM itens
          WHERE data_vencimento IS NOT NULL
            AND data_vencimento >= ?1
@@ -929,6 +2133,7 @@ pub fn listar_proximos_vencimentos(conexao: &Connection, dias: i64) -> Result<Ve
             data_vencimento: linha.get(6)?,
             criado_em: linha.get(7)?,
             atualizado_em: linha.get(8)?,
+            regra_recorrencia: linha.get(9)?,
             tags: vec![],
             anexos: vec![],
         })
@@ -949,7 +2154,7 @@ pub fn listar_vencimentos_atrasados(conexao: &Connection) -> Result<Vec<Item>> {
 
     let mut stmt = conexao.prepare(
         "SELECT id, pasta_id, tipo, titulo, descricao, conteudo_nota,
-                data_vencimento, criado_em, atualizado_em
+                data_vencimento, criado_em, atualizado_em, regra_recorrencia
          FROM itens
          WHERE data_vencimento IS NOT NULL
            AND data_vencimento < ?1
@@ -968,6 +2173,7 @@ pub fn listar_vencimentos_atrasados(conexao: &Connection) -> Result<Vec<Item>> {
             data_vencimento: linha.get(6)?,
             criado_em: linha.get(7)?,
             atualizado_em: linha.get(8)?,
+            regra_recorrencia: linha.get(9)?,
             tags: vec![],
             anexos: vec![],
         })
@@ -986,12 +2192,38 @@ pub fn listar_vencimentos_atrasados(conexao: &Connection) -> Result<Vec<Item>> {
 // AUDITORIA — Log de eventos para rastreabilidade
 // =============================================================================
 
+/// Hash de gênese usado como `prev_hash` da primeira linha da cadeia
+/// (64 zeros — mesmo tamanho de um hash SHA-256 em hexadecimal).
+const HASH_GENESE_AUDITORIA: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Chave de configuração onde fica o "genesis efetivo" da cadeia de
+/// auditoria depois de uma poda (ver `podar_log_auditoria`). Quando
+/// presente, `verificar_integridade_auditoria`/`ultimo_hash_auditoria`
+/// usam este valor no lugar de `HASH_GENESE_AUDITORIA` — a cadeia passa a
+/// ser verificada a partir do ponto podado, não do início real do cofre.
+const CONFIG_GENESE_EFETIVA_AUDITORIA: &str = "auditoria_genese_efetiva";
+
+/// Hash de gênese a usar: o efetivo (gravado pela última poda), se houver,
+/// ou `HASH_GENESE_AUDITORIA` caso a cadeia nunca tenha sido podada.
+fn genese_efetiva_auditoria(conexao: &Connection) -> Result<String> {
+    Ok(obter_configuracao(conexao, CONFIG_GENESE_EFETIVA_AUDITORIA)?
+        .and_then(|config| config.valor)
+        .unwrap_or_else(|| HASH_GENESE_AUDITORIA.to_string()))
+}
+
 /// Registra um evento no log de auditoria.
 /// Chamado internamente pelos serviços após cada operação importante.
 ///
 /// Nota: no schema existente, entidade_id é NOT NULL.
 /// Se nenhum ID de entidade for fornecido, usamos "sistema" como placeholder
 /// para eventos globais (backup, restauração, etc.)
+///
+/// Desde a migração 004, cada linha também guarda `prev_hash`/`entry_hash`,
+/// encadeando este registro ao anterior (ver `verificar_integridade_auditoria`
+/// e `services::auditoria::verificar_integridade`). `entry_hash` é
+/// `SHA-256(prev_hash || criado_em || tipo_evento || entidade_tipo ||
+/// entidade_id || detalhes)`; `prev_hash` é o `entry_hash` da última linha
+/// já encadeada, ou o hash de gênese para a primeira.
 pub fn registrar_evento_auditoria(
     conexao: &Connection,
     tipo_evento: &str,
@@ -1004,15 +2236,479 @@ pub fn registrar_evento_auditoria(
     // entidade_id é NOT NULL no schema, usar "sistema" como fallback
     let entidade_id_valor = entidade_id.unwrap_or("sistema");
 
+    let prev_hash = ultimo_hash_auditoria(conexao)?;
+    let entry_hash = calcular_hash_auditoria(
+        &prev_hash, &agora, tipo_evento, entidade_tipo, entidade_id_valor, detalhes,
+    );
+
     conexao.execute(
-        "INSERT INTO log_auditoria (id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![id, tipo_evento, entidade_tipo, entidade_id_valor, detalhes, agora],
+        "INSERT INTO log_auditoria (id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em, prev_hash, entry_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![id, tipo_evento, entidade_tipo, entidade_id_valor, detalhes, agora, prev_hash, entry_hash],
     ).context("Falha ao registrar evento de auditoria")?;
 
     Ok(())
 }
 
+/// Registra uma mutação estruturada de pasta/item/tag — variante de
+/// `registrar_evento_auditoria` que também grava `hlc`/`operacao`/
+/// `payload_antes`/`payload_depois` (ver migração 006), usada por
+/// `services::auditoria::registrar_mutacao`.
+///
+/// O `entry_hash` é calculado com a mesma fórmula de `registrar_evento_auditoria`
+/// (`tipo_evento` = `operacao.to_string()`, `detalhes` = `payload_depois`),
+/// então a cadeia de hashes continua uma única sequência contígua
+/// independente de a linha ter vindo de `registrar` ou `registrar_mutacao`.
+pub fn registrar_operacao_auditoria(
+    conexao: &Connection,
+    entidade_tipo: &str,
+    entidade_id: &str,
+    operacao: OperacaoMutacao,
+    hlc: &str,
+    payload_antes: Option<&str>,
+    payload_depois: Option<&str>,
+) -> Result<()> {
+    let id = Uuid::new_v4().to_string();
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let operacao_str = operacao.to_string();
+
+    let prev_hash = ultimo_hash_auditoria(conexao)?;
+    let entry_hash = calcular_hash_auditoria(
+        &prev_hash, &agora, &operacao_str, entidade_tipo, entidade_id, payload_depois,
+    );
+
+    conexao.execute(
+        "INSERT INTO log_auditoria
+            (id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em, prev_hash, entry_hash,
+             hlc, operacao, payload_antes, payload_depois)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            id, operacao_str, entidade_tipo, entidade_id, payload_depois, agora, prev_hash, entry_hash,
+            hlc, operacao_str, payload_antes, payload_depois,
+        ],
+    ).context("Falha ao registrar operação estruturada de auditoria")?;
+
+    Ok(())
+}
+
+/// Número de linhas estruturadas (`operacao IS NOT NULL`) registradas desde
+/// o `hlc` do último checkpoint (todas, se não houver nenhum) — usado por
+/// `services::auditoria::registrar_mutacao` para decidir quando gravar o
+/// próximo checkpoint (ver `KEEP_STATE_EVERY`).
+pub fn contar_operacoes_desde_ultimo_checkpoint(conexao: &Connection) -> Result<i64> {
+    let ultimo_hlc: Option<String> = conexao
+        .query_row(
+            "SELECT hlc FROM checkpoints_auditoria ORDER BY hlc DESC LIMIT 1",
+            [],
+            |linha| linha.get(0),
+        )
+        .optional()
+        .context("Falha ao buscar o checkpoint mais recente de auditoria")?;
+
+    match ultimo_hlc {
+        Some(hlc) => conexao
+            .query_row(
+                "SELECT COUNT(*) FROM log_auditoria WHERE operacao IS NOT NULL AND hlc > ?1",
+                params![hlc],
+                |linha| linha.get(0),
+            )
+            .context("Falha ao contar operações desde o último checkpoint"),
+        None => conexao
+            .query_row(
+                "SELECT COUNT(*) FROM log_auditoria WHERE operacao IS NOT NULL",
+                [],
+                |linha| linha.get(0),
+            )
+            .context("Falha ao contar operações estruturadas de auditoria"),
+    }
+}
+
+/// Grava um novo checkpoint com o `EstadoMaterializado` já serializado
+/// (ver `services::auditoria::registrar_mutacao`) e o `hlc` da operação
+/// mais recente incluída nele — `replay` parte daqui em vez da história
+/// inteira.
+pub fn criar_checkpoint_auditoria(conexao: &Connection, hlc: &str, estado_json: &str) -> Result<()> {
+    let id = Uuid::new_v4().to_string();
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    conexao.execute(
+        "INSERT INTO checkpoints_auditoria (id, hlc, estado, criado_em) VALUES (?1, ?2, ?3, ?4)",
+        params![id, hlc, estado_json, agora],
+    ).context("Falha ao gravar checkpoint de auditoria")?;
+
+    Ok(())
+}
+
+/// Checkpoint mais recente (maior `hlc`), se houver algum.
+pub fn obter_ultimo_checkpoint_auditoria(conexao: &Connection) -> Result<Option<CheckpointAuditoria>> {
+    conexao
+        .query_row(
+            "SELECT id, hlc, estado, criado_em FROM checkpoints_auditoria ORDER BY hlc DESC LIMIT 1",
+            [],
+            |linha| {
+                Ok(CheckpointAuditoria {
+                    id: linha.get(0)?,
+                    hlc: linha.get(1)?,
+                    estado: linha.get(2)?,
+                    criado_em: linha.get(3)?,
+                })
+            },
+        )
+        .optional()
+        .context("Falha ao buscar o checkpoint mais recente de auditoria")
+}
+
+/// Operações estruturadas (`operacao IS NOT NULL`) registradas após
+/// `apos_hlc` (todas, se `None`), em ordem crescente de `hlc` — a ordem em
+/// que `services::auditoria::replay` precisa aplicá-las para reconstruir o
+/// estado corretamente.
+pub fn listar_operacoes_auditoria_apos(
+    conexao: &Connection,
+    apos_hlc: Option<&str>,
+) -> Result<Vec<OperacaoAuditoria>> {
+    let sql = "SELECT hlc, entidade_tipo, entidade_id, operacao, payload_depois
+               FROM log_auditoria
+               WHERE operacao IS NOT NULL AND (?1 IS NULL OR hlc > ?1)
+               ORDER BY hlc ASC";
+
+    let mut stmt = conexao.prepare(sql)?;
+    let linhas = stmt.query_map(params![apos_hlc], |linha| {
+        let operacao_str: String = linha.get(3)?;
+        Ok((
+            linha.get::<_, String>(0)?,
+            linha.get::<_, String>(1)?,
+            linha.get::<_, String>(2)?,
+            operacao_str,
+            linha.get::<_, Option<String>>(4)?,
+        ))
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha ao listar operações estruturadas de auditoria")?;
+
+    Ok(linhas
+        .into_iter()
+        .filter_map(|(hlc, entidade_tipo, entidade_id, operacao_str, payload_depois)| {
+            OperacaoMutacao::de_str(&operacao_str).map(|operacao| OperacaoAuditoria {
+                hlc, entidade_tipo, entidade_id, operacao, payload_depois,
+            })
+        })
+        .collect())
+}
+
+/// Operações estruturadas (`operacao IS NOT NULL`) de uma entidade
+/// específica, em ordem crescente de `hlc` — a trilha usada por
+/// `services::auditoria::reconstruir_item_em`/`listar_historico_item` para
+/// dobrar (fold) o histórico de um único item. `ate_instante` (ISO 8601),
+/// quando informado, restringe às operações com `criado_em <= ate_instante`
+/// (ver `reconstruir_item_em`); `None` traz o histórico completo.
+pub fn listar_operacoes_auditoria_da_entidade(
+    conexao: &Connection,
+    entidade_tipo: &str,
+    entidade_id: &str,
+    ate_instante: Option<&str>,
+) -> Result<Vec<OperacaoAuditoriaCompleta>> {
+    let sql = "SELECT id, hlc, operacao, payload_antes, payload_depois, criado_em
+               FROM log_auditoria
+               WHERE operacao IS NOT NULL AND entidade_tipo = ?1 AND entidade_id = ?2
+                 AND (?3 IS NULL OR criado_em <= ?3)
+               ORDER BY hlc ASC";
+
+    let mut stmt = conexao.prepare(sql)?;
+    let linhas = stmt.query_map(params![entidade_tipo, entidade_id, ate_instante], |linha| {
+        Ok((
+            linha.get::<_, String>(0)?,
+            linha.get::<_, String>(1)?,
+            linha.get::<_, String>(2)?,
+            linha.get::<_, Option<String>>(3)?,
+            linha.get::<_, Option<String>>(4)?,
+            linha.get::<_, String>(5)?,
+        ))
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha ao listar operações de auditoria da entidade")?;
+
+    linhas
+        .into_iter()
+        .map(|(id, hlc, operacao_str, payload_antes, payload_depois, criado_em)| {
+            let operacao = OperacaoMutacao::de_str(&operacao_str)
+                .ok_or_else(|| anyhow!("Operação de auditoria com tipo desconhecido: '{}'", operacao_str))?;
+            Ok(OperacaoAuditoriaCompleta { id, hlc, operacao, payload_antes, payload_depois, criado_em })
+        })
+        .collect()
+}
+
+/// Busca uma única operação estruturada pelo `id` da linha em
+/// `log_auditoria` — usado por `services::auditoria::reverter_item_para`
+/// para buscar o snapshot de um evento específico do histórico de um item.
+pub fn obter_operacao_auditoria_por_id(conexao: &Connection, id: &str) -> Result<Option<OperacaoAuditoriaCompleta>> {
+    let linha = conexao.query_row(
+        "SELECT id, hlc, operacao, payload_antes, payload_depois, criado_em
+         FROM log_auditoria WHERE id = ?1 AND operacao IS NOT NULL",
+        params![id],
+        |linha| Ok((
+            linha.get::<_, String>(0)?,
+            linha.get::<_, String>(1)?,
+            linha.get::<_, String>(2)?,
+            linha.get::<_, Option<String>>(3)?,
+            linha.get::<_, Option<String>>(4)?,
+            linha.get::<_, String>(5)?,
+        )),
+    ).optional().context("Falha ao buscar operação de auditoria por id")?;
+
+    let Some((id, hlc, operacao_str, payload_antes, payload_depois, criado_em)) = linha else {
+        return Ok(None);
+    };
+
+    let operacao = OperacaoMutacao::de_str(&operacao_str)
+        .ok_or_else(|| anyhow!("Operação de auditoria com tipo desconhecido: '{}'", operacao_str))?;
+
+    Ok(Some(OperacaoAuditoriaCompleta { id, hlc, operacao, payload_antes, payload_depois, criado_em }))
+}
+
+/// Busca o `entry_hash` da última linha já encadeada (maior `rowid`), ou o
+/// hash de gênese se a cadeia ainda não tiver nenhuma linha (banco novo, ou
+/// nenhum evento registrado desde a migração 004).
+fn ultimo_hash_auditoria(conexao: &Connection) -> Result<String> {
+    let resultado = conexao.query_row(
+        "SELECT entry_hash FROM log_auditoria WHERE entry_hash IS NOT NULL ORDER BY rowid DESC LIMIT 1",
+        [],
+        |linha| linha.get::<_, String>(0),
+    );
+
+    match resultado {
+        Ok(hash) => Ok(hash),
+        Err(rusqlite::Error::QueryReturnedNoRows) => genese_efetiva_auditoria(conexao),
+        Err(e) => Err(anyhow!(e).context("Falha ao buscar último hash da cadeia de auditoria")),
+    }
+}
+
+fn calcular_hash_auditoria(
+    prev_hash: &str,
+    criado_em: &str,
+    tipo_evento: &str,
+    entidade_tipo: &str,
+    entidade_id: &str,
+    detalhes: Option<&str>,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(criado_em.as_bytes());
+    hasher.update(tipo_evento.as_bytes());
+    hasher.update(entidade_tipo.as_bytes());
+    hasher.update(entidade_id.as_bytes());
+    hasher.update(detalhes.unwrap_or("").as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Percorre `log_auditoria` em ordem de inserção (`rowid`) recomputando
+/// cada `entry_hash` e conferindo que `prev_hash` bate com o `entry_hash`
+/// da linha anterior. Retorna a primeira linha adulterada, se houver.
+///
+/// Linhas anteriores à migração 004 (sem `entry_hash`) são ignoradas — a
+/// cadeia só cobre o que foi registrado depois que ela passou a existir.
+///
+/// Se o log já foi podado (ver `podar_log_auditoria`), a verificação parte
+/// do genesis efetivo gravado na poda, não de `HASH_GENESE_AUDITORIA` — ela
+/// só garante que a cadeia não foi adulterada a partir do ponto preservado.
+pub fn verificar_integridade_auditoria(conexao: &Connection) -> Result<Option<QuebraIntegridadeAuditoria>> {
+    let mut stmt = conexao.prepare(
+        "SELECT id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em, prev_hash, entry_hash
+         FROM log_auditoria
+         WHERE entry_hash IS NOT NULL
+         ORDER BY rowid ASC",
+    )?;
+
+    let linhas = stmt.query_map([], |linha| {
+        Ok((
+            linha.get::<_, String>(0)?,
+            linha.get::<_, String>(1)?,
+            linha.get::<_, String>(2)?,
+            linha.get::<_, String>(3)?,
+            linha.get::<_, Option<String>>(4)?,
+            linha.get::<_, String>(5)?,
+            linha.get::<_, Option<String>>(6)?,
+            linha.get::<_, String>(7)?,
+        ))
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha ao ler log de auditoria para verificação de integridade")?;
+
+    let mut hash_esperado = genese_efetiva_auditoria(conexao)?;
+
+    for (id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em, prev_hash, entry_hash) in linhas {
+        if prev_hash.as_deref() != Some(hash_esperado.as_str()) {
+            return Ok(Some(QuebraIntegridadeAuditoria {
+                linha_id: id,
+                motivo: format!(
+                    "prev_hash não confere com o entry_hash da linha anterior (esperado {})",
+                    hash_esperado
+                ),
+            }));
+        }
+
+        let hash_recomputado = calcular_hash_auditoria(
+            &hash_esperado, &criado_em, &tipo_evento, &entidade_tipo, &entidade_id, detalhes.as_deref(),
+        );
+
+        if hash_recomputado != entry_hash {
+            return Ok(Some(QuebraIntegridadeAuditoria {
+                linha_id: id,
+                motivo: "entry_hash não confere com o conteúdo da linha (dado alterado)".to_string(),
+            }));
+        }
+
+        hash_esperado = entry_hash;
+    }
+
+    Ok(None)
+}
+
+/// Retorna o `entry_hash` do topo atual da cadeia de auditoria (a última
+/// linha encadeada), ou `None` se a cadeia ainda não tiver nenhuma linha.
+/// Usado por `services::auditoria::assinar_topo` para ancorar a cadeia.
+pub fn topo_cadeia_auditoria(conexao: &Connection) -> Result<Option<String>> {
+    let hash = ultimo_hash_auditoria(conexao)?;
+    if hash == genese_efetiva_auditoria(conexao)? {
+        Ok(None)
+    } else {
+        Ok(Some(hash))
+    }
+}
+
+/// Remove linhas de `log_auditoria` com `criado_em` anterior a
+/// `cutoff_criado_em`, e ainda mais linhas (as mais antigas) se sobrarem
+/// mais que `max_linhas` depois disso. Linhas sem `entry_hash` (anteriores
+/// à migração 004, fora da cadeia) são removidas livremente.
+///
+/// Se a primeira linha que sobra depois da poda fizer parte da cadeia de
+/// hashes, seu `prev_hash` é gravado como o novo "genesis efetivo" (ver
+/// `CONFIG_GENESE_EFETIVA_AUDITORIA`) antes de qualquer DELETE — é assim
+/// que `verificar_integridade_auditoria` continua confirmando a cadeia a
+/// partir do ponto preservado, em vez de quebrar porque o início real do
+/// cofre não existe mais. Retorna o número de linhas removidas.
+pub fn podar_log_auditoria(
+    conexao: &Connection,
+    cutoff_criado_em: &str,
+    max_linhas: i64,
+) -> Result<u64> {
+    let total: i64 = conexao
+        .query_row("SELECT COUNT(*) FROM log_auditoria", [], |linha| linha.get(0))
+        .context("Falha ao contar linhas de auditoria")?;
+
+    // `MIN(rowid) WHERE criado_em >= cutoff` retorna NULL tanto quando a
+    // tabela está vazia quanto quando TODAS as linhas são mais antigas que
+    // o corte — os dois casos precisam de tratamento diferente, então o
+    // segundo é resolvido comparando com `MAX(rowid)` (nada sobrevive por
+    // idade = corte fica depois da última linha existente).
+    let max_rowid: Option<i64> = conexao
+        .query_row("SELECT MAX(rowid) FROM log_auditoria", [], |linha| linha.get(0))
+        .context("Falha ao buscar o maior rowid de auditoria")?;
+
+    let rowid_corte_idade: Option<i64> = match max_rowid {
+        None => None,
+        Some(max_rowid) => {
+            let sobrevivente_por_idade: Option<i64> = conexao
+                .query_row(
+                    "SELECT MIN(rowid) FROM log_auditoria WHERE criado_em >= ?1",
+                    params![cutoff_criado_em],
+                    |linha| linha.get(0),
+                )
+                .context("Falha ao calcular corte de retenção por idade")?;
+            Some(sobrevivente_por_idade.unwrap_or(max_rowid + 1))
+        }
+    };
+
+    let rowid_corte_quantidade: Option<i64> = if total > max_linhas {
+        conexao
+            .query_row(
+                "SELECT rowid FROM log_auditoria ORDER BY rowid DESC LIMIT 1 OFFSET ?1",
+                params![(max_linhas.max(1) - 1)],
+                |linha| linha.get(0),
+            )
+            .context("Falha ao calcular corte de retenção por quantidade")?
+    } else {
+        None
+    };
+
+    let rowid_sobrevivente = match (rowid_corte_idade, rowid_corte_quantidade) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (Some(a), None) => Some(a),
+        (None, Some(b)) => Some(b),
+        (None, None) => None,
+    };
+
+    let Some(rowid_sobrevivente) = rowid_sobrevivente else {
+        return Ok(0);
+    };
+
+    // Se `rowid_sobrevivente` ficou além da última linha existente, a poda
+    // remove a tabela inteira — não há linha sobrevivente para extrair um
+    // novo genesis efetivo (o atual continua válido para quando a cadeia
+    // recomeçar, ver `ultimo_hash_auditoria`).
+    let prev_hash_sobrevivente: Option<String> = conexao
+        .query_row(
+            "SELECT prev_hash FROM log_auditoria WHERE rowid = ?1",
+            params![rowid_sobrevivente],
+            |linha| linha.get(0),
+        )
+        .optional()
+        .context("Falha ao ler prev_hash da linha sobrevivente da poda")?
+        .flatten();
+
+    if let Some(prev_hash) = prev_hash_sobrevivente {
+        salvar_configuracao(conexao, CONFIG_GENESE_EFETIVA_AUDITORIA, &prev_hash)?;
+    }
+
+    let removidas = conexao
+        .execute(
+            "DELETE FROM log_auditoria WHERE rowid < ?1",
+            params![rowid_sobrevivente],
+        )
+        .context("Falha ao podar log de auditoria")?;
+
+    Ok(removidas as u64)
+}
+
+/// Executa `PRAGMA optimize`, que atualiza estatísticas de índices para o
+/// planner de consultas do SQLite — recomendado periodicamente, sobretudo
+/// depois de uma poda grande (ver `podar_log_auditoria`). Mais barato que
+/// `VACUUM` (não reescreve o arquivo inteiro), então pode rodar com mais
+/// frequência.
+pub fn otimizar_banco(conexao: &Connection) -> Result<()> {
+    conexao
+        .execute_batch("PRAGMA optimize;")
+        .context("Falha ao executar PRAGMA optimize")?;
+
+    info!("PRAGMA optimize executado.");
+    Ok(())
+}
+
+/// Lista todo o log de auditoria em ordem de inserção (`rowid`), incluindo
+/// `prev_hash`/`entry_hash`. Usado por `services::arquivo::exportar` para
+/// exportar a cadeia de forma independente do banco.sqlite.
+pub fn listar_log_auditoria_completo(conexao: &Connection) -> Result<Vec<LogAuditoriaCompleto>> {
+    let mut stmt = conexao.prepare(
+        "SELECT id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em, prev_hash, entry_hash
+         FROM log_auditoria
+         ORDER BY rowid ASC",
+    )?;
+
+    stmt.query_map([], |linha| {
+        Ok(LogAuditoriaCompleto {
+            id: linha.get(0)?,
+            tipo_evento: linha.get(1)?,
+            entidade_tipo: linha.get(2)?,
+            entidade_id: linha.get(3)?,
+            detalhes: linha.get(4)?,
+            criado_em: linha.get(5)?,
+            prev_hash: linha.get(6)?,
+            entry_hash: linha.get(7)?,
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha ao listar log de auditoria completo")
+}
+
 /// Lista eventos de auditoria com filtros opcionais.
 pub fn listar_eventos_auditoria(conexao: &Connection, filtros: &FiltrosAuditoria) -> Result<Vec<LogAuditoria>> {
     let mut sql = String::from(
@@ -1068,6 +2764,94 @@ pub fn listar_eventos_auditoria(conexao: &Connection, filtros: &FiltrosAuditoria
     Ok(eventos)
 }
 
+/// Versão paginada de `listar_eventos_auditoria` (ver seção PAGINACAO POR
+/// KEYSET). Ordenado por `criado_em DESC, id DESC`; `filtros.limite`/
+/// `.offset` são ignorados aqui — `limite` e `cursor` tomam seu lugar.
+pub fn listar_eventos_auditoria_paginado(
+    conexao: &Connection,
+    filtros: &FiltrosAuditoria,
+    limite: i64,
+    cursor: Option<&str>,
+) -> Result<Pagina<LogAuditoria>> {
+    let mut sql = String::from(
+        "SELECT id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em
+         FROM log_auditoria WHERE 1=1"
+    );
+
+    let mut param_index = 1;
+    let mut params_vec: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
+
+    if let Some(ref tipo) = filtros.tipo_evento {
+        sql.push_str(&format!(" AND tipo_evento = ?{}", param_index));
+        params_vec.push(Box::new(tipo.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref entidade_tipo) = filtros.entidade_tipo {
+        sql.push_str(&format!(" AND entidade_tipo = ?{}", param_index));
+        params_vec.push(Box::new(entidade_tipo.clone()));
+        param_index += 1;
+    }
+
+    if let Some(ref entidade_id) = filtros.entidade_id {
+        sql.push_str(&format!(" AND entidade_id = ?{}", param_index));
+        params_vec.push(Box::new(entidade_id.clone()));
+        param_index += 1;
+    }
+
+    let sql_contagem = format!("SELECT COUNT(*) FROM ({}) AS contagem", sql.replacen(
+        "id, tipo_evento, entidade_tipo, entidade_id, detalhes, criado_em",
+        "1",
+        1,
+    ));
+    let params_contagem: Vec<&dyn rusqlite::types::ToSql> =
+        params_vec.iter().map(|p| p.as_ref()).collect();
+    let total_aproximado: i64 = conexao
+        .query_row(&sql_contagem, params_contagem.as_slice(), |linha| linha.get(0))
+        .context("Falha ao contar eventos de auditoria")?;
+
+    if let Some(cursor) = cursor {
+        let (criado_em_cursor, id_cursor) = decodificar_cursor(cursor)?;
+        sql.push_str(&format!(
+            " AND (criado_em < ?{i1} OR (criado_em = ?{i1} AND id < ?{i2}))",
+            i1 = param_index, i2 = param_index + 1,
+        ));
+        params_vec.push(Box::new(criado_em_cursor));
+        params_vec.push(Box::new(id_cursor));
+        param_index += 2;
+    }
+
+    sql.push_str(&format!(" ORDER BY criado_em DESC, id DESC LIMIT ?{}", param_index));
+    params_vec.push(Box::new(limite + 1));
+
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+        params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let mut stmt = conexao.prepare(&sql)?;
+    let mut eventos = stmt.query_map(params_refs.as_slice(), |linha| {
+        Ok(LogAuditoria {
+            id: linha.get(0)?,
+            tipo_evento: linha.get(1)?,
+            entidade_tipo: linha.get(2)?,
+            entidade_id: linha.get(3)?,
+            detalhes: linha.get(4)?,
+            criado_em: linha.get(5)?,
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Falha ao listar eventos de auditoria paginados")?;
+
+    let tem_proxima_pagina = eventos.len() as i64 > limite;
+    if tem_proxima_pagina {
+        eventos.truncate(limite as usize);
+    }
+    let proximo_cursor = tem_proxima_pagina
+        .then(|| eventos.last().map(|e| codificar_cursor(&e.criado_em, &e.id)))
+        .flatten();
+
+    Ok(Pagina { itens: eventos, proximo_cursor, total_aproximado })
+}
+
 // =============================================================================
 // CONFIGURACOES — Pares chave/valor para preferências
 // =============================================================================
@@ -1130,6 +2914,58 @@ pub fn listar_configuracoes(conexao: &Connection) -> Result<Vec<Configuracao>> {
     Ok(configs)
 }
 
+// =============================================================================
+// DESTINOS REMOTOS — Bookmarks de sincronização SFTP/SCP
+// =============================================================================
+// Persistidos como uma lista JSON em `configuracoes` (ver `DestinoRemoto`),
+// a mesma abordagem de chave/valor usada para as demais preferências —
+// não há tabela dedicada para isso, já que a lista de destinos costuma ser
+// pequena e não precisa de consultas relacionais.
+// =============================================================================
+
+const CONFIG_DESTINOS_REMOTOS: &str = "sincronizacao_destinos_remotos";
+
+/// Lista todos os destinos remotos salvos (bookmarks), na ordem em que
+/// foram adicionados. Lista vazia se nenhum foi salvo ainda.
+pub fn listar_destinos_remotos(conexao: &Connection) -> Result<Vec<DestinoRemoto>> {
+    match obter_configuracao(conexao, CONFIG_DESTINOS_REMOTOS)? {
+        Some(config) => match config.valor {
+            Some(valor) if !valor.is_empty() => serde_json::from_str(&valor)
+                .context("Lista de destinos remotos corrompida"),
+            _ => Ok(Vec::new()),
+        },
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Salva (cria ou atualiza, por `nome`) um destino remoto na lista de
+/// bookmarks.
+pub fn salvar_destino_remoto(conexao: &Connection, destino: &DestinoRemoto) -> Result<()> {
+    let mut destinos = listar_destinos_remotos(conexao)?;
+    destinos.retain(|d| d.nome != destino.nome);
+    destinos.push(destino.clone());
+
+    let json = serde_json::to_string(&destinos).context("Falha ao serializar destinos remotos")?;
+    salvar_configuracao(conexao, CONFIG_DESTINOS_REMOTOS, &json)?;
+    Ok(())
+}
+
+/// Remove um destino remoto da lista de bookmarks por `nome`. Retorna se
+/// algum destino com esse nome existia.
+pub fn remover_destino_remoto(conexao: &Connection, nome: &str) -> Result<bool> {
+    let mut destinos = listar_destinos_remotos(conexao)?;
+    let total_antes = destinos.len();
+    destinos.retain(|d| d.nome != nome);
+    let removido = destinos.len() != total_antes;
+
+    if removido {
+        let json = serde_json::to_string(&destinos).context("Falha ao serializar destinos remotos")?;
+        salvar_configuracao(conexao, CONFIG_DESTINOS_REMOTOS, &json)?;
+    }
+
+    Ok(removido)
+}
+
 // =============================================================================
 // UTILITARIOS — Funções auxiliares para contagens e manutenção
 // =============================================================================
@@ -1209,3 +3045,114 @@ pub fn listar_subpastas_recursivas(conexao: &Connection, pasta_id: &str) -> Resu
 
     Ok(pastas)
 }
+
+// =============================================================================
+// SEGREDOS — KV cifrado e tokens de acesso (ver services::api_segredos)
+// =============================================================================
+
+/// Grava (cria ou substitui) o valor de `caminho`. `valor_selado` já deve
+/// vir cifrado (JSON de `crypto::campo_cifrado::CampoCifrado`) — esta
+/// função não sabe nada sobre cifragem, só persiste o que recebe.
+pub fn salvar_segredo(conexao: &Connection, caminho: &str, valor_selado: &str) -> Result<()> {
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    conexao.execute(
+        "INSERT INTO segredos (caminho, valor_selado, criado_em, atualizado_em)
+         VALUES (?1, ?2, ?3, ?3)
+         ON CONFLICT(caminho) DO UPDATE SET valor_selado = excluded.valor_selado, atualizado_em = excluded.atualizado_em",
+        params![caminho, valor_selado, agora],
+    ).with_context(|| format!("Falha ao salvar segredo: {}", caminho))?;
+
+    info!("Segredo salvo: {}", caminho);
+    Ok(())
+}
+
+/// Lê o valor selado de `caminho`, se existir.
+pub fn obter_segredo(conexao: &Connection, caminho: &str) -> Result<Option<String>> {
+    conexao.query_row(
+        "SELECT valor_selado FROM segredos WHERE caminho = ?1",
+        params![caminho],
+        |linha| linha.get(0),
+    )
+    .optional()
+    .with_context(|| format!("Falha ao obter segredo: {}", caminho))
+}
+
+/// Remove o segredo em `caminho`. Retorna `true` se algo foi removido.
+pub fn excluir_segredo(conexao: &Connection, caminho: &str) -> Result<bool> {
+    let linhas = conexao.execute("DELETE FROM segredos WHERE caminho = ?1", params![caminho])
+        .with_context(|| format!("Falha ao excluir segredo: {}", caminho))?;
+
+    if linhas > 0 {
+        info!("Segredo excluído: {}", caminho);
+    }
+    Ok(linhas > 0)
+}
+
+/// Cria um novo token de acesso com validade até `expira_em` (RFC 3339).
+/// `token_hash` é o SHA-256 do token em texto claro — o chamador (serviço)
+/// gera o token, devolve-o uma única vez e só guarda o hash aqui.
+pub fn criar_token_segredo(conexao: &Connection, id: &str, token_hash: &str, expira_em: &str) -> Result<()> {
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    conexao.execute(
+        "INSERT INTO tokens_segredos (id, token_hash, criado_em, expira_em, revogado)
+         VALUES (?1, ?2, ?3, ?4, 0)",
+        params![id, token_hash, agora, expira_em],
+    ).context("Falha ao criar token de acesso a segredos")?;
+
+    info!("Token de acesso a segredos criado: {}", id);
+    Ok(())
+}
+
+/// Busca um token pelo hash, desde que não esteja revogado nem expirado
+/// (comparação textual de timestamps RFC 3339, mesmo truque usado em
+/// `listar_vencimentos`: o formato ordena lexicograficamente igual a
+/// cronologicamente).
+pub fn obter_token_segredo_valido(conexao: &Connection, token_hash: &str) -> Result<Option<TokenSegredo>> {
+    let agora = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    conexao.query_row(
+        "SELECT id, token_hash, criado_em, expira_em, revogado
+         FROM tokens_segredos
+         WHERE token_hash = ?1 AND revogado = 0 AND expira_em > ?2",
+        params![token_hash, agora],
+        |linha| {
+            let revogado_int: i32 = linha.get(4)?;
+            Ok(TokenSegredo {
+                id: linha.get(0)?,
+                token_hash: linha.get(1)?,
+                criado_em: linha.get(2)?,
+                expira_em: linha.get(3)?,
+                revogado: revogado_int != 0,
+            })
+        },
+    )
+    .optional()
+    .context("Falha ao validar token de acesso a segredos")
+}
+
+/// Estende a validade de um token existente (não revogado) para `nova_expira_em`.
+pub fn renovar_token_segredo(conexao: &Connection, id: &str, nova_expira_em: &str) -> Result<()> {
+    let linhas = conexao.execute(
+        "UPDATE tokens_segredos SET expira_em = ?1 WHERE id = ?2 AND revogado = 0",
+        params![nova_expira_em, id],
+    ).context("Falha ao renovar token de acesso a segredos")?;
+
+    if linhas == 0 {
+        return Err(anyhow!("Token não encontrado ou já revogado: {}", id));
+    }
+
+    info!("Token de acesso a segredos renovado: {}", id);
+    Ok(())
+}
+
+/// Revoga um token — `obter_token_segredo_valido` para de aceitá-lo
+/// imediatamente, mesmo que `expira_em` ainda não tenha passado.
+pub fn revogar_token_segredo(conexao: &Connection, id: &str) -> Result<()> {
+    conexao.execute("UPDATE tokens_segredos SET revogado = 1 WHERE id = ?1", params![id])
+        .context("Falha ao revogar token de acesso a segredos")?;
+
+    info!("Token de acesso a segredos revogado: {}", id);
+    Ok(())
+}
@@ -0,0 +1,191 @@
+// =============================================================================
+// VaultCraft — Sincronização Offline via Session/Changeset Extension
+// =============================================================================
+// Permite mover edições entre duas instalações offline do VaultCraft sem
+// servidor algum: uma sessão do SQLite (`rusqlite` com a feature "session")
+// grava todas as alterações em `itens`/`pastas`/`tags` feitas em um período
+// de trabalho, serializa isso em um changeset portátil (um blob que pode
+// ser copiado via pendrive, e-mail, etc.), e a outra instalação reaplica
+// esse changeset sobre o próprio banco.
+//
+// Conflitos (a mesma linha editada nos dois lados) são resolvidos por uma
+// estratégia explícita — "last-writer-wins" comparando `atualizado_em`, ou
+// abortar a aplicação inteira ao primeiro conflito.
+//
+// Por que isso não quebra a FTS nem o log de auditoria automático?
+//   `sqlite3changeset_apply` aplica cada mudança como um INSERT/UPDATE/
+//   DELETE normal através do mecanismo de execução do SQLite — os mesmos
+//   triggers `trg_itens_fts_*` (migração 001) e os hooks de
+//   `db::auditoria_automatica` (chunk1-3) disparam exatamente como
+//   disparariam para uma escrita comum feita pelo próprio aplicativo. Não
+//   há necessidade de nenhuma lógica especial: basta aplicar o changeset
+//   na conexão normal (nunca em uma conexão com os hooks desativados).
+// =============================================================================
+
+use anyhow::{Context, Result};
+use log::info;
+use rusqlite::session::{ChangesetItem, ConflictAction, ConflictType, Session};
+use rusqlite::Connection;
+use std::io::Cursor;
+
+/// Tabelas de domínio cujas alterações entram na sincronização. Ficam de
+/// fora: `anexos` (os arquivos em si não viajam no changeset, só os
+/// metadados relevantes ficariam defasados) e `log_auditoria`/`configuracoes`
+/// (locais por natureza a cada instalação).
+const TABELAS_SINCRONIZADAS: &[&str] = &["itens", "pastas", "tags"];
+
+/// Uma sessão de captura de alterações, anexada a uma conexão aberta.
+/// Mantenha-a viva durante o período de trabalho que deve ser exportado;
+/// ao finalizar, chame `exportar_changeset`.
+pub struct SessaoSincronizacao<'conn> {
+    sessao: Session<'conn>,
+}
+
+impl<'conn> SessaoSincronizacao<'conn> {
+    /// Inicia a captura de alterações nas tabelas sincronizadas.
+    pub fn iniciar(conexao: &'conn Connection) -> Result<Self> {
+        let mut sessao = Session::new(conexao).context("Falha ao criar sessão de sincronização")?;
+
+        for tabela in TABELAS_SINCRONIZADAS {
+            sessao
+                .attach(Some(tabela))
+                .with_context(|| format!("Falha ao anexar tabela '{}' à sessão de sincronização", tabela))?;
+        }
+
+        info!(
+            "Sessão de sincronização iniciada ({} tabelas monitoradas).",
+            TABELAS_SINCRONIZADAS.len()
+        );
+
+        Ok(Self { sessao })
+    }
+
+    /// Serializa todas as alterações capturadas até agora em um changeset
+    /// portátil. A sessão continua ativa e pode ser exportada novamente
+    /// mais tarde (o changeset acumula desde o início da sessão).
+    pub fn exportar_changeset(&mut self) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.sessao
+            .changeset_strm(&mut buffer)
+            .context("Falha ao serializar changeset de sincronização")?;
+
+        info!("Changeset exportado: {} bytes.", buffer.len());
+        Ok(buffer)
+    }
+}
+
+/// Estratégia de resolução de conflitos ao aplicar um changeset de outra
+/// instalação sobre este banco.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EstrategiaConflito {
+    /// Mantém a versão com `atualizado_em` mais recente entre a linha
+    /// local (em conflito) e a linha trazida pelo changeset. Quando a
+    /// tabela não tem `atualizado_em` (ex.: `tags`), a versão do
+    /// changeset remoto prevalece.
+    UltimaEscritaVence,
+    /// Aborta a aplicação inteira (rollback) ao primeiro conflito.
+    AbortarNoConflito,
+}
+
+/// Aplica um changeset exportado por outra instalação do VaultCraft sobre
+/// a conexão ativa, usando `estrategia` para resolver conflitos linha a linha.
+pub fn aplicar_changeset(
+    conexao: &Connection,
+    changeset: &[u8],
+    estrategia: EstrategiaConflito,
+) -> Result<()> {
+    let mut leitor = Cursor::new(changeset);
+
+    conexao
+        .apply_strm(
+            &mut leitor,
+            None::<fn(&str) -> bool>,
+            |tipo_conflito, item| resolver_conflito(estrategia, tipo_conflito, &item),
+        )
+        .context("Falha ao aplicar changeset de sincronização")?;
+
+    info!(
+        "Changeset aplicado ({} bytes, estratégia {:?}).",
+        changeset.len(),
+        estrategia
+    );
+
+    Ok(())
+}
+
+/// Inverte um changeset já aplicado, produzindo outro changeset que
+/// desfaz exatamente aquelas alterações. Útil para desfazer uma
+/// sincronização indesejada sem precisar restaurar um backup inteiro.
+pub fn inverter_changeset(changeset: &[u8]) -> Result<Vec<u8>> {
+    let mut leitor = Cursor::new(changeset);
+    let mut invertido = Vec::new();
+
+    rusqlite::session::invert_strm(&mut leitor, &mut invertido)
+        .context("Falha ao inverter changeset de sincronização")?;
+
+    info!(
+        "Changeset invertido: {} bytes -> {} bytes.",
+        changeset.len(),
+        invertido.len()
+    );
+
+    Ok(invertido)
+}
+
+/// Decide como resolver um conflito reportado durante `apply_strm`,
+/// conforme a `estrategia` escolhida.
+fn resolver_conflito(
+    estrategia: EstrategiaConflito,
+    tipo_conflito: ConflictType,
+    item: &ChangesetItem,
+) -> ConflictAction {
+    // Conflitos de chave estrangeira e "não encontrado" (a linha-alvo já
+    // foi removida localmente) não têm uma versão "mais nova" sensata
+    // para comparar — sempre pulamos a mudança conflitante nesses casos.
+    if !tipo_conflict_eh_dados(tipo_conflito) {
+        return ConflictAction::SQLITE_CHANGESET_OMIT;
+    }
+
+    match estrategia {
+        EstrategiaConflito::AbortarNoConflito => ConflictAction::SQLITE_CHANGESET_ABORT,
+        EstrategiaConflito::UltimaEscritaVence => decidir_por_atualizado_em(item),
+    }
+}
+
+/// `true` apenas para conflitos de dados (linha existe nos dois lados com
+/// valores diferentes) — os únicos em que comparar `atualizado_em` faz sentido.
+fn tipo_conflict_eh_dados(tipo_conflito: ConflictType) -> bool {
+    matches!(tipo_conflito, ConflictType::SQLITE_CHANGESET_DATA)
+}
+
+/// Compara o valor de `atualizado_em` entre a linha local em conflito e a
+/// linha trazida pelo changeset, mantendo a mais recente. Datas seguem o
+/// formato ISO 8601 (`%Y-%m-%dT%H:%M:%SZ`), então a comparação lexicográfica
+/// de string já corresponde à ordem cronológica.
+fn decidir_por_atualizado_em(item: &ChangesetItem) -> ConflictAction {
+    let Some(indice) = indice_coluna_atualizado_em(item) else {
+        // Tabela sem atualizado_em (ex.: tags): changeset remoto prevalece.
+        return ConflictAction::SQLITE_CHANGESET_REPLACE;
+    };
+
+    let local = item.conflict(indice).ok().and_then(|v| v.as_str().ok().map(str::to_string));
+    let remoto = item.new_value(indice).ok().flatten().and_then(|v| v.as_str().ok().map(str::to_string));
+
+    match (local, remoto) {
+        (Some(local), Some(remoto)) if remoto > local => ConflictAction::SQLITE_CHANGESET_REPLACE,
+        (Some(_), Some(_)) => ConflictAction::SQLITE_CHANGESET_OMIT,
+        // Sem dado suficiente para comparar: preferimos manter a versão local.
+        _ => ConflictAction::SQLITE_CHANGESET_OMIT,
+    }
+}
+
+/// Índice (0-based) da coluna `atualizado_em` no changeset desta linha,
+/// conforme a tabela de origem. `None` se a tabela não tiver essa coluna.
+fn indice_coluna_atualizado_em(item: &ChangesetItem) -> Option<usize> {
+    let operacao = item.op().ok()?;
+    match operacao.table_name() {
+        "itens" => Some(8),
+        "pastas" => Some(5),
+        _ => None,
+    }
+}
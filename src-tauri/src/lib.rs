@@ -11,9 +11,13 @@
 // 4. Configurar o banco de dados na inicialização
 // 5. Gerenciar o estado global do aplicativo (EstadoApp)
 //
-// IMPORTANTE: Este aplicativo é 100% offline.
-// Nenhuma chamada de rede é feita em nenhum momento.
-// Todos os dados ficam no disco local do usuário.
+// IMPORTANTE: Este aplicativo é offline-first.
+// Nenhuma chamada de rede é feita automaticamente ou em segundo plano —
+// toda a funcionalidade principal opera inteiramente sobre o disco local.
+// A única exceção é a sincronização remota opcional (services::sincronizacao),
+// que só transmite dados quando o usuário configura um destino e dispara
+// um envio/recebimento explicitamente, e sempre sobre um arquivo de cofre
+// portátil (.vcarch) já cifrado e assinado (ver services::arquivo).
 // =============================================================================
 
 // Módulos do projeto
@@ -22,18 +26,19 @@ pub mod services;   // Serviços (backup, armazenamento, exportação, auditoria
 pub mod commands;   // Comandos Tauri (interface frontend <-> backend)
 pub mod storage;    // Utilitários de armazenamento (re-exportação)
 pub mod crypto;     // Criptografia (hashes, PIN)
-pub mod license;    // Sistema de licenciamento (hardware, serviço, armazenamento)
+pub mod license;    // Sistema de licenciamento (hardware, serviço, armazenamento, token offline)
 
 use commands::EstadoApp;
-use std::sync::Mutex;
+use db::connection::PoolConexoes;
 use tauri::Manager;
 
 /// Ponto de entrada principal do aplicativo Tauri.
 ///
 /// Configura e executa o aplicativo:
 /// 1. Registra os plugins (shell para abrir arquivos, dialog para seletores)
-/// 2. Inicializa o banco de dados SQLite no diretório de dados do app
-/// 3. Cria o estado compartilhado (EstadoApp) com a conexão protegida por Mutex
+/// 2. Abre o pool de conexões SQLite (leitores + escritor, ver
+///    `db::connection::PoolConexoes`) no diretório de dados do app
+/// 3. Cria o estado compartilhado (EstadoApp) com o pool
 /// 4. Registra todos os comandos que o frontend pode invocar
 /// 5. Inicia o loop de eventos do Tauri
 ///
@@ -62,16 +67,20 @@ pub fn run() {
 
             log::info!("Diretório de dados: {:?}", diretorio_app);
 
-            // Inicializar o banco de dados SQLite.
-            // Cria o arquivo se não existir, executa migrações pendentes.
-            let conexao = db::connection::inicializar_banco(&diretorio_app)
+            // Abrir o pool de conexões SQLite (modo WAL).
+            // Cria o arquivo se não existir, executa migrações pendentes
+            // na conexão de escrita e abre `MAX_CONEXOES_CONCORRENTES_PADRAO`
+            // conexões de leitura do pool.
+            let pool = PoolConexoes::abrir(&diretorio_app)
                 .expect("Falha crítica ao inicializar banco de dados");
 
             // Criar e registrar o estado global do aplicativo.
-            // O Mutex garante acesso thread-safe à conexão do banco.
             let estado = EstadoApp {
-                banco: Mutex::new(conexao),
+                banco: std::sync::Arc::new(pool),
                 diretorio_app,
+                sessao_cifragem: std::sync::Arc::new(services::cifragem::EstadoSessao::nova()),
+                servidor_rpc: services::rpc::ServidorRpc::novo(),
+                servidor_segredos: services::http_segredos::ServidorHttpSegredos::novo(),
             };
 
             app.manage(estado);
@@ -91,10 +100,16 @@ pub fn run() {
 
             // === Itens (Notas, Documentos, Checklists) ===
             commands::listar_itens,
+            commands::listar_itens_paginado,
             commands::obter_item,
             commands::criar_item,
             commands::atualizar_item,
+            commands::avancar_ocorrencia_item,
             commands::excluir_item,
+            commands::mover_itens_em_lote,
+            commands::excluir_itens_em_lote,
+            commands::listar_revisoes,
+            commands::restaurar_revisao,
 
             // === Tags ===
             commands::listar_tags,
@@ -115,25 +130,52 @@ pub fn run() {
             commands::excluir_tarefa,
             commands::reordenar_tarefas,
             commands::marcar_tarefa,
+            commands::adicionar_dependencia,
+            commands::remover_dependencia,
+            commands::listar_dependencias,
+            commands::registrar_tempo,
+            commands::listar_tempo_por_tarefa,
+            commands::total_tempo_por_item,
 
             // === Busca Full-Text ===
             commands::buscar_itens,
+            commands::buscar_itens_paginado,
 
             // === Vencimentos ===
             commands::listar_vencimentos,
+            commands::interpretar_data_relativa,
 
             // === Backup e Restauração ===
             commands::criar_backup,
             commands::restaurar_backup,
+            commands::verificar_backup,
+            commands::criar_backup_incremental,
+            commands::restaurar_backup_incremental,
             commands::exportar_pacote,
             commands::importar_pacote,
+            commands::exportar_arquivo_cofre,
+            commands::importar_arquivo_cofre,
+            commands::exportar_pasta_tar,
+            commands::importar_pasta_tar,
 
             // === Exportação ===
             commands::exportar_item_pdf,
             commands::exportar_lista_csv,
+            commands::importar_lista_csv,
+            commands::exportar_cofre_bitwarden_json,
+            commands::importar_lista_bitwarden_json,
+            commands::exportar_cofre_site,
 
             // === Auditoria ===
             commands::listar_historico,
+            commands::listar_historico_paginado,
+            commands::verificar_integridade_auditoria,
+            commands::assinar_topo_auditoria,
+            commands::verificar_assinatura_topo_auditoria,
+            commands::reconstruir_estado_auditoria,
+            commands::reconstruir_item_em,
+            commands::listar_historico_item,
+            commands::reverter_item_para,
 
             // === Configurações ===
             commands::obter_configuracao,
@@ -142,6 +184,37 @@ pub fn run() {
 
             // === Utilitários ===
             commands::compactar_banco,
+            commands::executar_manutencao,
+            commands::reparar_indice_busca,
+            commands::versao_schema,
+
+            // === Sincronização Remota (SFTP/SCP, opcional) ===
+            commands::listar_destinos_remotos,
+            commands::salvar_destino_remoto,
+            commands::remover_destino_remoto,
+            commands::enviar_backup_remoto,
+            commands::restaurar_backup_remoto,
+            commands::sincronizar_chunks_remoto,
+
+            // === Operações em Lote (Transacional) ===
+            commands::executar_lote,
+
+            // === Recuperação de PIN (códigos de emergência) ===
+            commands::definir_pin_com_recuperacao,
+            commands::recuperar_com_codigos,
+
+            // === Cifragem em Repouso (selagem de campos) ===
+            commands::desbloquear_cofre,
+            commands::trancar_cofre,
+            commands::cofre_esta_desbloqueado,
+
+            // === Fachadas de Rede Locais (RPC/HTTP, opcionais) ===
+            commands::iniciar_servidor_rpc,
+            commands::parar_servidor_rpc,
+            commands::servidor_rpc_em_execucao,
+            commands::iniciar_servidor_http_segredos,
+            commands::parar_servidor_http_segredos,
+            commands::servidor_http_segredos_em_execucao,
 
             // === Licença ===
             commands::license_commands::check_license,
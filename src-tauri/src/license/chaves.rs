@@ -0,0 +1,46 @@
+// =============================================================================
+// VaultCraft — Chave Pública Ed25519 do Fornecedor (Licenciamento Offline)
+// =============================================================================
+// Par de chaves compartilhado por tudo que verifica uma assinatura Ed25519
+// do fornecedor offline: `license::validator` (chaves de licença no formato
+// VLTCR-...) e `license::token` (token de ativação assinado pelo backend).
+// Centralizado aqui para que as duas verificações nunca fiquem fora de
+// sincronia sobre qual é a chave pública vigente.
+// =============================================================================
+
+use ed25519_dalek::VerifyingKey;
+
+/// Chave pública Ed25519 do fornecedor, embutida no binário de produção.
+/// PLACEHOLDER: substituir por bytes reais gerados com a chave privada do
+/// fornecedor (mantida fora deste repositório) antes de um build de release.
+#[cfg(not(debug_assertions))]
+const VERIFYING_KEY_BYTES: [u8; 32] = [
+    0x7a, 0x3f, 0x91, 0xc2, 0x5d, 0x8e, 0x14, 0x6b, 0x2a, 0xf0, 0x63, 0xd9, 0x48, 0xb7, 0x1e, 0x55,
+    0x9c, 0x02, 0x3a, 0x7d, 0xe1, 0x4f, 0x88, 0x36, 0xb4, 0xfa, 0x0e, 0x6c, 0x21, 0x59, 0xd8, 0x43,
+];
+
+/// Em builds de debug, usamos um par de chaves fixo e conhecido só para
+/// permitir gerar e validar chaves/tokens de teste localmente, sem depender
+/// da chave privada real do fornecedor. A chave pública é derivada desta
+/// semente em tempo de execução (nunca embutida separadamente), então as
+/// duas nunca podem ficar fora de sincronia.
+#[cfg(debug_assertions)]
+pub(super) const TEST_SIGNING_KEY_BYTES: [u8; 32] = [
+    0xd1, 0x5e, 0x42, 0x0a, 0x9b, 0x6c, 0x33, 0x87, 0x1f, 0x5a, 0xc4, 0x0d, 0x72, 0xe9, 0x18, 0x3b,
+    0x64, 0xfd, 0x29, 0x51, 0xa7, 0x0e, 0x8c, 0x36, 0xb9, 0x12, 0x4f, 0xd8, 0x63, 0x2a, 0x7e, 0x05,
+];
+
+/// Chave pública Ed25519 usada para verificar tanto chaves de licença
+/// quanto tokens de ativação offline.
+pub(super) fn chave_publica() -> VerifyingKey {
+    #[cfg(debug_assertions)]
+    {
+        use ed25519_dalek::SigningKey;
+        SigningKey::from_bytes(&TEST_SIGNING_KEY_BYTES).verifying_key()
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        VerifyingKey::from_bytes(&VERIFYING_KEY_BYTES)
+            .expect("chave pública de licença embutida é inválida")
+    }
+}
@@ -1,8 +1,84 @@
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::process::Command;
 
+/// Um componente individual de identidade de hardware, com seu peso
+/// relativo na comparação de `fingerprint_matches` e o SHA-256 hex do seu
+/// valor bruto (nunca o valor em claro — evita gravar números de série reais
+/// em `license.dat`). Ausente de `get_hardware_components()` quando a
+/// consulta ao sistema operacional retornou vazio (ver `compute_sha256`
+/// abaixo) — um componente indisponível não entra na comparação, em vez de
+/// contar como "não bate".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Component {
+    pub name: &'static str,
+    pub weight: u32,
+    pub hash: String,
+}
+
+/// Fração do peso total que precisa corresponder para `fingerprint_matches`
+/// considerar que é a mesma máquina. 0.6 tolera, por exemplo, a perda de um
+/// componente de peso médio (troca de disco, atualização de BIOS que muda o
+/// serial da motherboard) sem derrubar a licença, mas rejeita uma máquina
+/// genuinamente diferente (que normalmente não bate em nenhum componente).
+pub const LIMIAR_PESO_FRACAO: f64 = 0.6;
+
+/// Coleta os componentes de identidade de hardware desta máquina, cada um
+/// com seu peso e o hash (nunca o valor bruto) — ver `Component`. Os pesos
+/// somam 100: cpu_id 30, board_serial 25, machine_id 25, primary_mac 10,
+/// disk_serial 10 — os dois primeiros são os mesmos usados historicamente
+/// por `get_hardware_id`, os demais são sinais adicionais só usados pela
+/// comparação tolerante de `fingerprint_matches`.
+pub fn get_hardware_components() -> Vec<Component> {
+    let brutos: [(&'static str, u32, String); 5] = [
+        ("cpu_id", 30, get_processor_id()),
+        ("board_serial", 25, get_motherboard_serial()),
+        ("machine_id", 25, get_machine_id()),
+        ("primary_mac", 10, get_primary_mac_address()),
+        ("disk_serial", 10, get_disk_serial()),
+    ];
+
+    brutos
+        .into_iter()
+        .filter(|(_, _, valor)| !valor.is_empty())
+        .map(|(nome, peso, valor)| Component {
+            name: nome,
+            weight: peso,
+            hash: compute_sha256(&valor),
+        })
+        .collect()
+}
+
+/// Compara os componentes gravados (`stored`, de quando a licença foi
+/// ativada) com os componentes atuais (`current`, lidos agora) e retorna
+/// `true` se pelo menos `LIMIAR_PESO_FRACAO` do peso total de `stored` ainda
+/// corresponder. Um componente só conta como correspondente se o mesmo nome
+/// aparecer em `current` com o mesmo hash — trocas de hardware que mudam
+/// menos que o limiar configurado não derrubam a licença; uma máquina
+/// totalmente diferente, que normalmente não bate em nenhum componente, é
+/// rejeitada.
+pub fn fingerprint_matches(stored: &[Component], current: &[Component]) -> bool {
+    let peso_total: u32 = stored.iter().map(|c| c.weight).sum();
+    if peso_total == 0 {
+        return false;
+    }
+
+    let peso_correspondente: u32 = stored
+        .iter()
+        .filter(|s| current.iter().any(|c| c.name == s.name && c.hash == s.hash))
+        .map(|s| s.weight)
+        .sum();
+
+    (peso_correspondente as f64 / peso_total as f64) >= LIMIAR_PESO_FRACAO
+}
+
 /// Computa um fingerprint unico do hardware da maquina.
 /// Usa processor ID + motherboard serial, hash com SHA-256.
+///
+/// Mantido como estava (mesmo formato `PROC=...;MB=...`) por compatibilidade
+/// com `machine_fingerprint` já gravado em registros de licença existentes
+/// — use `get_hardware_components`/`fingerprint_matches` para a comparação
+/// tolerante a pequenas mudanças de hardware (ver `license::storage`).
 pub fn get_hardware_id() -> String {
     let processor_id = get_processor_id();
     let motherboard_serial = get_motherboard_serial();
@@ -75,6 +151,56 @@ fn run_wmic_query(component: &str, field: &str) -> String {
     }
 }
 
+#[cfg(target_os = "windows")]
+fn get_machine_id() -> String {
+    let script = "(Get-ItemProperty 'HKLM:\\SOFTWARE\\Microsoft\\Cryptography').MachineGuid";
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", script])
+        .output();
+
+    if let Ok(out) = output {
+        let val = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        if !val.is_empty() {
+            return val;
+        }
+    }
+
+    // Fallback: UUID do produto via wmic, outro identificador estável do SO.
+    let output = Command::new("wmic")
+        .args(["csproduct", "get", "UUID"])
+        .output();
+
+    match output {
+        Ok(out) => String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .nth(1)
+            .unwrap_or("")
+            .trim()
+            .to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn get_primary_mac_address() -> String {
+    let output = Command::new("getmac").args(["/NH", "/FO", "CSV"]).output();
+
+    match output {
+        Ok(out) => String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .next()
+            .and_then(|linha| linha.split(',').next())
+            .map(|campo| campo.trim_matches('"').to_string())
+            .unwrap_or_default(),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn get_disk_serial() -> String {
+    run_wmic_query("diskdrive", "SerialNumber")
+}
+
 #[cfg(target_os = "windows")]
 fn try_powershell_query(component: &str, field: &str) -> Option<String> {
     let class = match component {
@@ -138,6 +264,62 @@ fn get_motherboard_serial() -> String {
     }
 }
 
+#[cfg(target_os = "macos")]
+fn get_machine_id() -> String {
+    let output = Command::new("ioreg")
+        .args(["-d2", "-c", "IOPlatformExpertDevice"])
+        .output();
+
+    match output {
+        Ok(out) => {
+            let text = String::from_utf8_lossy(&out.stdout);
+            for line in text.lines() {
+                if line.contains("IOPlatformUUID") {
+                    if let Some(val) = line.split('"').nth(3) {
+                        return val.to_string();
+                    }
+                }
+            }
+            String::new()
+        }
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn get_primary_mac_address() -> String {
+    let output = Command::new("ifconfig").args(["en0"]).output();
+
+    match output {
+        Ok(out) => {
+            let text = String::from_utf8_lossy(&out.stdout);
+            text.lines()
+                .find(|linha| linha.trim_start().starts_with("ether "))
+                .and_then(|linha| linha.trim_start().strip_prefix("ether "))
+                .map(|mac| mac.trim().to_string())
+                .unwrap_or_default()
+        }
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn get_disk_serial() -> String {
+    let output = Command::new("diskutil").args(["info", "/"]).output();
+
+    match output {
+        Ok(out) => {
+            let text = String::from_utf8_lossy(&out.stdout);
+            text.lines()
+                .find(|linha| linha.trim_start().starts_with("Volume UUID:"))
+                .and_then(|linha| linha.split(':').nth(1))
+                .map(|val| val.trim().to_string())
+                .unwrap_or_default()
+        }
+        Err(_) => String::new(),
+    }
+}
+
 // ─── Linux ─────────────────────────────────────────────────────────────
 
 #[cfg(target_os = "linux")]
@@ -155,3 +337,132 @@ fn get_motherboard_serial() -> String {
         .trim()
         .to_string()
 }
+
+#[cfg(target_os = "linux")]
+fn get_machine_id() -> String {
+    std::fs::read_to_string("/etc/machine-id")
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+#[cfg(target_os = "linux")]
+fn get_primary_mac_address() -> String {
+    let Ok(entradas) = std::fs::read_dir("/sys/class/net") else {
+        return String::new();
+    };
+
+    let mut interfaces: Vec<String> = entradas
+        .filter_map(|entrada| entrada.ok())
+        .map(|entrada| entrada.file_name().to_string_lossy().to_string())
+        .filter(|nome| nome != "lo")
+        .collect();
+    interfaces.sort();
+
+    for interface in interfaces {
+        let caminho = format!("/sys/class/net/{}/address", interface);
+        if let Ok(mac) = std::fs::read_to_string(&caminho) {
+            let mac = mac.trim().to_string();
+            if !mac.is_empty() && mac != "00:00:00:00:00:00" {
+                return mac;
+            }
+        }
+    }
+
+    String::new()
+}
+
+#[cfg(target_os = "linux")]
+fn get_disk_serial() -> String {
+    let Ok(entradas) = std::fs::read_dir("/sys/block") else {
+        return String::new();
+    };
+
+    let mut discos: Vec<String> = entradas
+        .filter_map(|entrada| entrada.ok())
+        .map(|entrada| entrada.file_name().to_string_lossy().to_string())
+        .filter(|nome| !nome.starts_with("loop") && !nome.starts_with("sr"))
+        .collect();
+    discos.sort();
+
+    for disco in discos {
+        let caminho = format!("/sys/block/{}/device/serial", disco);
+        if let Ok(serial) = std::fs::read_to_string(&caminho) {
+            let serial = serial.trim().to_string();
+            if !serial.is_empty() {
+                return serial;
+            }
+        }
+    }
+
+    String::new()
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    fn componente(nome: &'static str, peso: u32, valor: &str) -> Component {
+        Component {
+            name: nome,
+            weight: peso,
+            hash: compute_sha256(valor),
+        }
+    }
+
+    fn componentes_de_referencia() -> Vec<Component> {
+        vec![
+            componente("cpu_id", 30, "cpu-abc"),
+            componente("board_serial", 25, "board-123"),
+            componente("machine_id", 25, "machine-xyz"),
+            componente("primary_mac", 10, "aa:bb:cc:dd:ee:ff"),
+            componente("disk_serial", 10, "disk-789"),
+        ]
+    }
+
+    #[test]
+    fn teste_fingerprint_identico_corresponde() {
+        let stored = componentes_de_referencia();
+        let current = componentes_de_referencia();
+        assert!(fingerprint_matches(&stored, &current));
+    }
+
+    #[test]
+    fn teste_fingerprint_tolera_troca_de_componente_leve() {
+        let stored = componentes_de_referencia();
+        let mut current = componentes_de_referencia();
+        // Troca só o disco (peso 10) — ainda deve bater, pela tolerância.
+        current[4] = componente("disk_serial", 10, "disk-outro");
+        assert!(fingerprint_matches(&stored, &current));
+    }
+
+    #[test]
+    fn teste_fingerprint_rejeita_maquina_totalmente_diferente() {
+        let stored = componentes_de_referencia();
+        let current = vec![
+            componente("cpu_id", 30, "outra-cpu"),
+            componente("board_serial", 25, "outra-board"),
+            componente("machine_id", 25, "outra-machine"),
+            componente("primary_mac", 10, "11:22:33:44:55:66"),
+            componente("disk_serial", 10, "outro-disco"),
+        ];
+        assert!(!fingerprint_matches(&stored, &current));
+    }
+
+    #[test]
+    fn teste_fingerprint_rejeita_quando_componentes_pesados_mudam() {
+        let stored = componentes_de_referencia();
+        let mut current = componentes_de_referencia();
+        // Troca cpu_id (30) + board_serial (25) + machine_id (25) = 80 de
+        // peso perdido, restando só 20 — abaixo do limiar de 60%.
+        current[0] = componente("cpu_id", 30, "outra-cpu");
+        current[1] = componente("board_serial", 25, "outra-board");
+        current[2] = componente("machine_id", 25, "outra-machine");
+        assert!(!fingerprint_matches(&stored, &current));
+    }
+
+    #[test]
+    fn teste_fingerprint_vazio_nao_corresponde() {
+        assert!(!fingerprint_matches(&[], &componentes_de_referencia()));
+    }
+}
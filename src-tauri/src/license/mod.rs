@@ -0,0 +1,25 @@
+// =============================================================================
+// VaultCraft — Licenciamento
+// =============================================================================
+// `hardware` identifica a máquina; `service` fala com o backend remoto;
+// `storage` persiste o registro de instalação local; `validator` valida
+// chaves de licença offline (formato VLTCR-...) assinadas em Ed25519;
+// `token` valida o token de ativação (também assinado em Ed25519) que
+// permite `check_license` funcionar sem rede — ver `chaves` para a chave
+// pública compartilhada pelas duas verificações Ed25519.
+//
+// `keystore`: guarda secretos (hoje, a chave de dados do cofre) em um
+// arquivo cifrado separado de `license.dat`, com a chave mestra derivada do
+// PIN combinado com `hardware::get_hardware_id()` — um arquivo copiado para
+// outra máquina não abre nem com o PIN certo. Ainda não substitui
+// `storage`/`license.dat` (falta UI de PIN na ativação — ver nota de escopo
+// no próprio módulo), mas já existe pronto e testado. Ver módulo `keystore`.
+// =============================================================================
+
+mod chaves;
+pub mod hardware;
+pub mod keystore;
+pub mod service;
+pub mod storage;
+pub mod token;
+pub mod validator;
@@ -11,11 +11,22 @@ const ACTIVATE_ENDPOINT: &str =
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
 
 /// Resultado de uma chamada da API de licenca.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LicenseCheckResult {
     pub success: bool,
     pub message: String,
     pub code: String,
+    /// Payload canônico assinado (Base64) do token de ativação offline —
+    /// presente só na resposta de sucesso de `activate_license`, quando o
+    /// backend o devolve. Ver `license::token`.
+    pub token_payload: Option<String>,
+    /// Assinatura Ed25519 (Base64) sobre `token_payload`.
+    pub token_signature: Option<String>,
+    /// Assinatura Ed25519 (Base64) do backend sobre a serialização canônica
+    /// de `{email, license_key, machine_fingerprint, installed_at}` — salva
+    /// em `InstallationRecord::record_signature` para detectar edição
+    /// manual de `license.dat`. Ver `license::storage`.
+    pub record_signature: Option<String>,
 }
 
 /// Verifica uma licenca existente com a API remota.
@@ -25,6 +36,7 @@ pub async fn verify_license(email: &str, hardware_id: &str) -> LicenseCheckResul
             success: false,
             message: "E-mail não informado.".into(),
             code: "INVALID_EMAIL".into(),
+            ..Default::default()
         };
     }
 
@@ -47,12 +59,14 @@ pub async fn verify_license(email: &str, hardware_id: &str) -> LicenseCheckResul
                     success: false,
                     message: "Tempo esgotado ao verificar licença.".into(),
                     code: "TIMEOUT".into(),
+                    ..Default::default()
                 };
             }
             return LicenseCheckResult {
                 success: false,
                 message: format!("Erro de conexão: {}", e),
                 code: "NETWORK_ERROR".into(),
+                ..Default::default()
             };
         }
     };
@@ -65,6 +79,7 @@ pub async fn verify_license(email: &str, hardware_id: &str) -> LicenseCheckResul
                 success: false,
                 message: "Resposta inválida do servidor.".into(),
                 code: "INVALID_RESPONSE".into(),
+                ..Default::default()
             };
         }
     };
@@ -73,12 +88,19 @@ pub async fn verify_license(email: &str, hardware_id: &str) -> LicenseCheckResul
 }
 
 /// Ativa uma licenca para este dispositivo.
+///
+/// Em caso de sucesso, o backend devolve, além de `license_key`, um token
+/// assinado (`payload`/`signature`, ambos em Base64) que `token_payload`/
+/// `token_signature` repassam para o chamador persistir — é esse token que
+/// permite `check_license` verificar a licença offline depois (ver
+/// `license::token`), sem repetir esta chamada HTTP a cada abertura do cofre.
 pub async fn activate_license(email: &str, hardware_id: &str) -> LicenseCheckResult {
     if email.trim().is_empty() {
         return LicenseCheckResult {
             success: false,
             message: "E-mail não informado.".into(),
             code: "INVALID_EMAIL".into(),
+            ..Default::default()
         };
     }
 
@@ -101,12 +123,14 @@ pub async fn activate_license(email: &str, hardware_id: &str) -> LicenseCheckRes
                     success: false,
                     message: "Tempo esgotado ao ativar licença.".into(),
                     code: "TIMEOUT".into(),
+                    ..Default::default()
                 };
             }
             return LicenseCheckResult {
                 success: false,
                 message: format!("Erro de conexão: {}", e),
                 code: "NETWORK_ERROR".into(),
+                ..Default::default()
             };
         }
     };
@@ -118,6 +142,7 @@ pub async fn activate_license(email: &str, hardware_id: &str) -> LicenseCheckRes
                 success: false,
                 message: "Resposta inválida do servidor.".into(),
                 code: "INVALID_RESPONSE".into(),
+                ..Default::default()
             };
         }
     };
@@ -141,6 +166,7 @@ fn parse_verify_response(text: &str, status_code: u16) -> LicenseCheckResult {
                         success: valid,
                         message: msg,
                         code: if valid { "VALID".into() } else { "INVALID".into() },
+                        ..Default::default()
                     };
                 }
             }
@@ -155,6 +181,7 @@ fn parse_verify_response(text: &str, status_code: u16) -> LicenseCheckResult {
                     success: valid,
                     message: msg,
                     code: if valid { "VALID".into() } else { "INVALID".into() },
+                    ..Default::default()
                 };
             }
 
@@ -173,6 +200,7 @@ fn parse_verify_response(text: &str, status_code: u16) -> LicenseCheckResult {
                     success: false,
                     message: msg,
                     code,
+                    ..Default::default()
                 };
             }
 
@@ -187,6 +215,7 @@ fn parse_verify_response(text: &str, status_code: u16) -> LicenseCheckResult {
                         success: false,
                         message: msg,
                         code: "FAILED".into(),
+                        ..Default::default()
                     };
                 }
             }
@@ -195,6 +224,7 @@ fn parse_verify_response(text: &str, status_code: u16) -> LicenseCheckResult {
                 success: false,
                 message: "Resposta inesperada do servidor.".into(),
                 code: "UNEXPECTED_RESPONSE".into(),
+                ..Default::default()
             }
         }
         Err(_) => {
@@ -203,12 +233,14 @@ fn parse_verify_response(text: &str, status_code: u16) -> LicenseCheckResult {
                     success: false,
                     message: format!("Servidor retornou erro HTTP {}.", status_code),
                     code: "HTTP_ERROR".into(),
+                    ..Default::default()
                 }
             } else {
                 LicenseCheckResult {
                     success: false,
                     message: "Não foi possível interpretar a resposta do servidor.".into(),
                     code: "PARSE_ERROR".into(),
+                    ..Default::default()
                 }
             }
         }
@@ -233,10 +265,25 @@ fn parse_activate_response(text: &str) -> LicenseCheckResult {
                         .and_then(|m| m.as_str())
                         .unwrap_or("Licença ativada com sucesso!")
                         .to_string();
+                    let token_payload = data
+                        .and_then(|d| d.get("payload"))
+                        .and_then(|p| p.as_str())
+                        .map(|p| p.to_string());
+                    let token_signature = data
+                        .and_then(|d| d.get("signature"))
+                        .and_then(|s| s.as_str())
+                        .map(|s| s.to_string());
+                    let record_signature = data
+                        .and_then(|d| d.get("record_signature"))
+                        .and_then(|s| s.as_str())
+                        .map(|s| s.to_string());
                     return LicenseCheckResult {
                         success: true,
                         message,
                         code: license_key,
+                        token_payload,
+                        token_signature,
+                        record_signature,
                     };
                 }
             }
@@ -256,6 +303,7 @@ fn parse_activate_response(text: &str) -> LicenseCheckResult {
                     success: false,
                     message: msg,
                     code,
+                    ..Default::default()
                 };
             }
 
@@ -269,12 +317,14 @@ fn parse_activate_response(text: &str) -> LicenseCheckResult {
                 success: false,
                 message: msg,
                 code: "NO_LICENSE".into(),
+                ..Default::default()
             }
         }
         Err(_) => LicenseCheckResult {
             success: false,
             message: "Resposta inválida do servidor.".into(),
             code: "PARSE_ERROR".into(),
+            ..Default::default()
         },
     }
 }
@@ -1,7 +1,11 @@
+use ed25519_dalek::{Signature, Verifier};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::chaves::chave_publica;
+use super::hardware::Component;
+
 /// Registro local de instalacao, salvo apos ativacao bem-sucedida.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallationRecord {
@@ -9,6 +13,84 @@ pub struct InstallationRecord {
     pub license_key: String,
     pub machine_fingerprint: String,
     pub installed_at: String,
+    /// Token de ativação assinado (Base64) devolvido pelo backend, quando
+    /// disponível — permite `license::token::verificar_token` confirmar a
+    /// licença sem rede. `#[serde(default)]` porque registros salvos antes
+    /// da introdução do token offline não têm estes dois campos.
+    #[serde(default)]
+    pub token_payload: Option<String>,
+    /// Assinatura Ed25519 (Base64) sobre `token_payload`.
+    #[serde(default)]
+    pub token_signature: Option<String>,
+    /// Assinatura Ed25519 (Base64) do backend sobre a serialização canônica
+    /// (`payload_canonico`) de `{email, license_key, machine_fingerprint,
+    /// installed_at}` — detecta edição manual de `license.dat` (antes disso,
+    /// o arquivo só era Base64, que qualquer um pode reescrever e
+    /// recodificar). `#[serde(default)]` por compatibilidade com registros
+    /// salvos antes desta verificação existir; `load` trata a ausência como
+    /// adulteração (ver `verificar_assinatura_registro`).
+    #[serde(default)]
+    pub record_signature: Option<String>,
+    /// Componentes de hardware pesados (`hardware::get_hardware_components`)
+    /// no momento da ativação — permite `hardware::fingerprint_matches`
+    /// tolerar pequenas mudanças de hardware (troca de disco, atualização de
+    /// BIOS) sem invalidar a licença, ao contrário da comparação exata contra
+    /// `machine_fingerprint`. Não faz parte de `payload_canonico`: é um
+    /// sinal de tolerância local, não algo que o backend assina ou verifica.
+    /// `#[serde(default)]` por compatibilidade com registros salvos antes
+    /// desta comparação existir — `None` faz o chamador cair de volta para a
+    /// comparação exata por `machine_fingerprint`.
+    #[serde(default)]
+    pub machine_components: Option<Vec<Component>>,
+    /// Data/hora (RFC3339) da última verificação bem-sucedida — por token
+    /// offline ou por chamada online a `service::verify_license` — usada por
+    /// `commands::license_commands::check_license` para limitar por quantos
+    /// dias o modo offline (sem token válido, servidor inalcançável) confia
+    /// no registro local antes de exigir reverificação online. Sem isso, o
+    /// fallback de rede indefinidamente confiava em qualquer registro local
+    /// com fingerprint batendo, mesmo anos após a última verificação real.
+    /// `#[serde(default)]` por compatibilidade com registros salvos antes
+    /// desta janela existir; `None` é tratado como `installed_at`.
+    #[serde(default)]
+    pub last_verified_at: Option<String>,
+}
+
+/// Serialização canônica dos campos que a assinatura do registro cobre —
+/// usada tanto para assinar (no backend) quanto para verificar (aqui).
+fn payload_canonico(record: &InstallationRecord) -> String {
+    format!(
+        "{}|{}|{}|{}",
+        record.email, record.license_key, record.machine_fingerprint, record.installed_at
+    )
+}
+
+/// Verifica `record.record_signature` contra a chave pública do fornecedor.
+/// Retorna `false` se a assinatura estiver ausente, malformada, ou não
+/// corresponder ao conteúdo do registro (arquivo editado manualmente).
+fn verificar_assinatura_registro(record: &InstallationRecord) -> bool {
+    let Some(assinatura_b64) = &record.record_signature else {
+        log::warn!("Registro de licença sem assinatura — tratando como adulterado");
+        return false;
+    };
+
+    let Some(assinatura_bytes) = base64_decode(assinatura_b64) else {
+        log::warn!("Assinatura do registro de licença malformada (Base64 inválido)");
+        return false;
+    };
+    let Ok(assinatura_bytes): Result<[u8; 64], _> = assinatura_bytes.try_into() else {
+        log::warn!("Assinatura do registro de licença com tamanho inválido");
+        return false;
+    };
+    let assinatura = Signature::from_bytes(&assinatura_bytes);
+
+    let payload = payload_canonico(record);
+    match chave_publica().verify(payload.as_bytes(), &assinatura) {
+        Ok(()) => true,
+        Err(_) => {
+            log::warn!("Assinatura do registro de licença não confere — arquivo adulterado?");
+            false
+        }
+    }
 }
 
 fn license_path(app_data_dir: &str) -> PathBuf {
@@ -47,6 +129,10 @@ pub fn load(app_data_dir: &str) -> Option<InstallationRecord> {
     let json = String::from_utf8(decoded).ok()?;
     let record: InstallationRecord = serde_json::from_str(&json).ok()?;
 
+    if !verificar_assinatura_registro(&record) {
+        return None;
+    }
+
     Some(record)
 }
 
@@ -58,7 +144,22 @@ pub fn clear(app_data_dir: &str) {
     }
 }
 
-fn base64_encode(data: &[u8]) -> String {
+/// Assina `record` com o par de chaves fixo de debug (`TEST_SIGNING_KEY_BYTES`)
+/// e preenche `record_signature` — só funciona em builds de debug, já que o
+/// build de release embute a chave pública real do fornecedor. Usado para
+/// testar `save`/`load` localmente sem depender do backend.
+#[cfg(debug_assertions)]
+pub fn assinar_registro_teste(record: &mut InstallationRecord) {
+    use ed25519_dalek::{Signer, SigningKey};
+    use super::chaves::TEST_SIGNING_KEY_BYTES;
+
+    let payload = payload_canonico(record);
+    let chave_privada = SigningKey::from_bytes(&TEST_SIGNING_KEY_BYTES);
+    let assinatura = chave_privada.sign(payload.as_bytes());
+    record.record_signature = Some(base64_encode(&assinatura.to_bytes()));
+}
+
+pub(super) fn base64_encode(data: &[u8]) -> String {
     const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
     let mut result = String::new();
     let chunks = data.chunks(3);
@@ -88,7 +189,7 @@ fn base64_encode(data: &[u8]) -> String {
     result
 }
 
-fn base64_decode(input: &str) -> Option<Vec<u8>> {
+pub(super) fn base64_decode(input: &str) -> Option<Vec<u8>> {
     let input = input.trim();
     if input.is_empty() {
         return None;
@@ -0,0 +1,98 @@
+// =============================================================================
+// VaultCraft — Verificação Offline de Token de Ativação (Ed25519)
+// =============================================================================
+// Diferente de `license::validator` (chaves "VLTCR-..." digitadas à mão pelo
+// usuário), o token deste módulo é emitido pelo backend no momento da
+// ativação (`service::activate_license`) e persistido em
+// `storage::InstallationRecord` — `check_license` o reverifica localmente a
+// cada abertura do cofre, sem precisar repetir a chamada HTTP.
+//
+// Payload canônico (texto, antes do Base64): "<app_id>|<email>|<hardware_id>|<expira_em>"
+//   expira_em = "YYYY-MM-DD", ou o literal "PERPETUAL" para licença vitalícia
+//   (mesma convenção de `license::validator::LicensePayload`)
+//
+// A assinatura é Ed25519 sobre os bytes crus do payload (não do texto em
+// Base64), verificada com a mesma chave pública de `license::chaves`.
+// =============================================================================
+
+use chrono::{NaiveDate, Utc};
+use ed25519_dalek::{Signature, Verifier};
+
+use super::chaves::chave_publica;
+use super::storage::{base64_decode, base64_encode};
+
+/// Token de ativação assinado, como devolvido pelo backend e persistido em
+/// `InstallationRecord`.
+#[derive(Debug, Clone)]
+pub struct LicenseToken {
+    pub payload: String,
+    pub signature: String,
+}
+
+/// Dados embutidos no payload de um token de ativação, já verificado.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DadosToken {
+    pub app_id: String,
+    pub email: String,
+    pub hardware_id: String,
+    pub expires_at: String,
+}
+
+/// Verifica um token de ativação offline, checando a assinatura Ed25519, o
+/// hardware vinculado e a validade.
+///
+/// Retorna `None` para token malformado, assinatura inválida, hardware
+/// diferente do informado, ou data de expiração já passada.
+pub fn verificar_token(token: &LicenseToken, hardware_id_atual: &str) -> Option<DadosToken> {
+    let payload_bytes = base64_decode(&token.payload)?;
+    let assinatura_bytes = base64_decode(&token.signature)?;
+    let assinatura_bytes: [u8; 64] = assinatura_bytes.try_into().ok()?;
+    let assinatura = Signature::from_bytes(&assinatura_bytes);
+
+    chave_publica().verify(&payload_bytes, &assinatura).ok()?;
+
+    let dados = parsear_payload(&payload_bytes)?;
+
+    if dados.hardware_id != hardware_id_atual.trim() {
+        log::warn!("Token de ativação pertence a outro hardware_id — ignorando.");
+        return None;
+    }
+
+    if !dentro_da_validade(&dados.expires_at) {
+        return None;
+    }
+
+    Some(dados)
+}
+
+/// Monta o payload canônico em Base64, pronto para ser assinado pelo backend.
+/// Usado só em testes/ferramentas locais — o backend de produção gera o seu
+/// próprio token.
+#[cfg(debug_assertions)]
+pub fn montar_payload_base64(app_id: &str, email: &str, hardware_id: &str, expires_at: &str) -> String {
+    let texto = format!("{}|{}|{}|{}", app_id, email, hardware_id, expires_at);
+    base64_encode(texto.as_bytes())
+}
+
+fn parsear_payload(bytes: &[u8]) -> Option<DadosToken> {
+    let texto = std::str::from_utf8(bytes).ok()?;
+    let mut campos = texto.splitn(4, '|');
+    let app_id = campos.next()?.to_string();
+    let email = campos.next()?.to_string();
+    let hardware_id = campos.next()?.to_string();
+    let expires_at = campos.next()?.to_string();
+    Some(DadosToken { app_id, email, hardware_id, expires_at })
+}
+
+fn dentro_da_validade(expires_at: &str) -> bool {
+    if expires_at == "PERPETUAL" {
+        return true;
+    }
+    match NaiveDate::parse_from_str(expires_at, "%Y-%m-%d") {
+        Ok(data) => Utc::now().date_naive() <= data,
+        Err(_) => {
+            log::warn!("Formato inválido de expires_at no token: '{}'", expires_at);
+            false
+        }
+    }
+}
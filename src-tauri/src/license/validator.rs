@@ -1,55 +1,184 @@
-use chrono::{NaiveDateTime, Utc};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+// =============================================================================
+// VaultCraft — Validação de Chaves de Licença Offline (Ed25519)
+// =============================================================================
+// Versão anterior: o bloco de verificação de 5 caracteres era derivado de
+// `std::collections::hash_map::DefaultHasher` — um hash não-criptográfico,
+// sem seed estável entre versões do Rust, e trivialmente forjável por
+// qualquer um que lesse `LICENSE_SALT` no binário. Um usuário determinado
+// conseguia gerar chaves "VLTCR-" válidas offline sem nunca ter comprado a
+// licença.
+//
+// Este módulo substitui aquele esquema por assinatura Ed25519 de verdade:
+// a chave pública do fornecedor fica embutida no binário, mas só quem tem a
+// chave privada correspondente (fora deste repositório) consegue produzir
+// uma assinatura que `validate_key` aceite.
+//
+// Formato da chave: VLTCR-<payload em Base32>-<assinatura em Base32>
+//   payload    = "<edição>|<expira_em>|<id_licença>" (texto, antes do Base32)
+//   expira_em  = "YYYY-MM-DD", ou o literal "PERPETUAL" para licença vitalícia
+//   assinatura = 64 bytes Ed25519 sobre os bytes crus do payload (não do
+//                texto em Base32)
+// =============================================================================
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use ed25519_dalek::{Signature, Verifier};
+use sha2::{Digest, Sha256};
+
+use super::chaves::chave_publica;
 
 const TRIAL_DURATION_DAYS: i64 = 14;
-const LICENSE_SALT: &str = "VaultCraft-2025-License-Salt";
 
-/// Valida uma chave de licenca offline.
-/// Formato: VLTCR-XXXXX-XXXXX-XXXXX-CHECK
-pub fn validate_key(key: &str) -> bool {
+/// Segredo local usado para selar (HMAC-SHA256) o carimbo de início do
+/// trial salvo em disco. Diferente da chave de licença, este segredo fica
+/// embutido no próprio binário e por isso é extraível por um usuário bastante
+/// determinado — mas já fecha os dois contornos triviais: editar o timestamp
+/// salvo em um editor de texto, ou resetar o relógio do sistema sem também
+/// recalcular a assinatura (que exige extrair o segredo do binário).
+const TRIAL_SEAL_SECRET: &[u8] = b"VaultCraft-2025-Trial-Seal-Secret";
+
+/// Dados embutidos no payload assinado de uma chave de licença.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicensePayload {
+    pub edition: String,
+    pub expires_at: String,
+    pub license_id: String,
+}
+
+/// Valida uma chave de licença offline assinada.
+///
+/// Retorna o payload (edição, expiração, id) quando a assinatura Ed25519
+/// confere e a data de expiração ainda não passou. Retorna `None` para
+/// qualquer chave malformada, com assinatura inválida, ou expirada.
+pub fn validate_key(key: &str) -> Option<LicensePayload> {
     let key = key.trim();
+    let resto = key.strip_prefix("VLTCR-")?;
 
-    if !key.starts_with("VLTCR-") {
-        return false;
+    let mut partes = resto.splitn(2, '-');
+    let payload_b32 = partes.next()?;
+    let assinatura_b32 = partes.next()?;
+
+    let payload_bytes = base32_decode(payload_b32)?;
+    let assinatura_bytes = base32_decode(assinatura_b32)?;
+    let assinatura_bytes: [u8; 64] = assinatura_bytes.try_into().ok()?;
+    let assinatura = Signature::from_bytes(&assinatura_bytes);
+
+    chave_publica().verify(&payload_bytes, &assinatura).ok()?;
+
+    let payload = parsear_payload(&payload_bytes)?;
+
+    if !payload_dentro_da_validade(&payload.expires_at) {
+        return None;
     }
 
-    let parts: Vec<&str> = key.split('-').collect();
-    if parts.len() != 5 {
-        return false;
+    Some(payload)
+}
+
+/// Gera uma chave de licença assinada para testes locais.
+///
+/// Usa o par de chaves fixo de debug (`TEST_SIGNING_KEY_BYTES`) — chaves
+/// geradas assim só validam em builds de debug, já que o build de release
+/// embute a chave pública real do fornecedor.
+#[cfg(debug_assertions)]
+pub fn generate_test_key(edition: &str, expires_at: &str, license_id: &str) -> String {
+    use ed25519_dalek::{Signer, SigningKey};
+    use super::chaves::TEST_SIGNING_KEY_BYTES;
+
+    let payload = format!("{}|{}|{}", edition, expires_at, license_id);
+    let chave_privada = SigningKey::from_bytes(&TEST_SIGNING_KEY_BYTES);
+    let assinatura = chave_privada.sign(payload.as_bytes());
+
+    format!(
+        "VLTCR-{}-{}",
+        base32_encode(payload.as_bytes()),
+        base32_encode(&assinatura.to_bytes())
+    )
+}
+
+fn parsear_payload(bytes: &[u8]) -> Option<LicensePayload> {
+    let texto = std::str::from_utf8(bytes).ok()?;
+    let mut campos = texto.splitn(3, '|');
+    let edition = campos.next()?.to_string();
+    let expires_at = campos.next()?.to_string();
+    let license_id = campos.next()?.to_string();
+    Some(LicensePayload { edition, expires_at, license_id })
+}
+
+fn payload_dentro_da_validade(expires_at: &str) -> bool {
+    if expires_at == "PERPETUAL" {
+        return true;
+    }
+    match NaiveDate::parse_from_str(expires_at, "%Y-%m-%d") {
+        Ok(data) => Utc::now().date_naive() <= data,
+        Err(_) => {
+            log::warn!("Formato inválido de expires_at na licença: '{}'", expires_at);
+            false
+        }
     }
+}
+
+/// Sela um carimbo de início de trial com um HMAC simples (SHA-256 do
+/// segredo local concatenado com o timestamp), para que editar o valor
+/// salvo em disco sem recalcular o selo seja detectável.
+///
+/// Formato persistido: "<trial_started>|<selo em hex>".
+pub fn selar_inicio_trial(trial_started: &str) -> String {
+    format!("{}|{}", trial_started, calcular_selo(trial_started))
+}
 
-    let check = parts[4];
-    let payload = format!("{}-{}-{}-{}-{}", parts[0], parts[1], parts[2], parts[3], LICENSE_SALT);
-    let expected_check = compute_hash_check(&payload);
+/// Abre um carimbo selado por `selar_inicio_trial`, retornando o timestamp
+/// original. Retorna `None` se o selo não confere (arquivo editado
+/// manualmente) ou se o valor não está no formato selado.
+fn abrir_selo_trial(selado: &str) -> Option<String> {
+    let (trial_started, selo) = selado.rsplit_once('|')?;
+    if calcular_selo(trial_started) == selo {
+        Some(trial_started.to_string())
+    } else {
+        log::warn!("Selo de início de trial inválido — tratando trial como expirado.");
+        None
+    }
+}
 
-    check == expected_check
+fn calcular_selo(trial_started: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(TRIAL_SEAL_SECRET);
+    hasher.update(trial_started.as_bytes());
+    hex::encode(hasher.finalize())
 }
 
-pub fn is_trial_active(trial_started: &str) -> bool {
-    if trial_started.is_empty() {
+/// `trial_started_selado` deve ter sido produzido por `selar_inicio_trial`.
+pub fn is_trial_active(trial_started_selado: &str) -> bool {
+    if trial_started_selado.is_empty() {
         return false;
     }
 
-    match NaiveDateTime::parse_from_str(trial_started, "%Y-%m-%d %H:%M:%S") {
+    let Some(trial_started) = abrir_selo_trial(trial_started_selado) else {
+        return false;
+    };
+
+    match NaiveDateTime::parse_from_str(&trial_started, "%Y-%m-%d %H:%M:%S") {
         Ok(start) => {
             let now = Utc::now().naive_utc();
             let elapsed = now.signed_duration_since(start);
             elapsed.num_days() < TRIAL_DURATION_DAYS
         }
         Err(_) => {
-            log::warn!("Formato invalido de trial_started: '{}'", trial_started);
+            log::warn!("Formato inválido de trial_started: '{}'", trial_started);
             false
         }
     }
 }
 
-pub fn trial_days_remaining(trial_started: &str) -> i64 {
-    if trial_started.is_empty() {
+/// `trial_started_selado` deve ter sido produzido por `selar_inicio_trial`.
+pub fn trial_days_remaining(trial_started_selado: &str) -> i64 {
+    if trial_started_selado.is_empty() {
         return 0;
     }
 
-    match NaiveDateTime::parse_from_str(trial_started, "%Y-%m-%d %H:%M:%S") {
+    let Some(trial_started) = abrir_selo_trial(trial_started_selado) else {
+        return 0;
+    };
+
+    match NaiveDateTime::parse_from_str(&trial_started, "%Y-%m-%d %H:%M:%S") {
         Ok(start) => {
             let now = Utc::now().naive_utc();
             let elapsed = now.signed_duration_since(start);
@@ -60,28 +189,60 @@ pub fn trial_days_remaining(trial_started: &str) -> i64 {
     }
 }
 
-#[cfg(debug_assertions)]
-pub fn generate_test_key(part1: &str, part2: &str, part3: &str) -> String {
-    let payload = format!("VLTCR-{}-{}-{}-{}", part1, part2, part3, LICENSE_SALT);
-    let check = compute_hash_check(&payload);
-    format!("VLTCR-{}-{}-{}-{}", part1, part2, part3, check)
+// =============================================================================
+// Base32 (RFC 4648, sem padding) — mesma abordagem "sem dependência extra"
+// já usada para Base64 em license::storage.
+// =============================================================================
+
+const BASE32_ALFABETO: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut resultado = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer: u32 = 0;
+    let mut bits_no_buffer = 0u32;
+
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits_no_buffer += 8;
+
+        while bits_no_buffer >= 5 {
+            bits_no_buffer -= 5;
+            let indice = (buffer >> bits_no_buffer) & 0x1F;
+            resultado.push(BASE32_ALFABETO[indice as usize] as char);
+        }
+    }
+
+    if bits_no_buffer > 0 {
+        let indice = (buffer << (5 - bits_no_buffer)) & 0x1F;
+        resultado.push(BASE32_ALFABETO[indice as usize] as char);
+    }
+
+    resultado
 }
 
-fn compute_hash_check(payload: &str) -> String {
-    let mut hasher = DefaultHasher::new();
-    payload.hash(&mut hasher);
-    let hash = hasher.finish();
+fn base32_decode(texto: &str) -> Option<Vec<u8>> {
+    let mut resultado = Vec::with_capacity(texto.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits_no_buffer = 0u32;
 
-    let chars: Vec<char> = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789".chars().collect();
-    let base = chars.len() as u64;
+    for c in texto.chars() {
+        let valor = base32_valor(c)?;
+        buffer = (buffer << 5) | valor as u32;
+        bits_no_buffer += 5;
 
-    let mut result = String::with_capacity(5);
-    let mut remaining = hash;
-    for _ in 0..5 {
-        let idx = (remaining % base) as usize;
-        result.push(chars[idx]);
-        remaining /= base;
+        if bits_no_buffer >= 8 {
+            bits_no_buffer -= 8;
+            resultado.push(((buffer >> bits_no_buffer) & 0xFF) as u8);
+        }
     }
 
-    result
+    Some(resultado)
+}
+
+fn base32_valor(c: char) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        'A'..='Z' => Some(c.to_ascii_uppercase() as u8 - b'A'),
+        '2'..='7' => Some(c as u8 - b'2' + 26),
+        _ => None,
+    }
 }
@@ -0,0 +1,125 @@
+// =============================================================================
+// VaultCraft — Motor de Segredos Chave/Valor (services::api_segredos)
+// =============================================================================
+// CRUD de segredos endereçados por caminho ("time/prod/db_password") mais
+// um modelo de token de vida curta (criar/renovar/revogar), pensado para
+// scripts/CI autenticarem sem passar pela UI interativa a cada chamada.
+//
+// Cada valor é cifrado com `crypto::campo_cifrado` usando a chave de dados
+// da sessão de selagem (`services::cifragem::EstadoSessao`) — a mesma
+// primitiva e a mesma sessão já usadas para selar `conteudo_nota`/
+// `descricao` (ver migração 007). Toda função aqui que lê ou escreve um
+// valor de segredo recebe essa sessão e recusa operar se ela estiver
+// trancada: "gatear atrás da chave mestra desbloqueada", na prática deste
+// cofre, é isto — `crypto::chave_mestra` existe mas ainda não está ligada a
+// nenhum fluxo de execução real (ver sua própria NOTA no topo de
+// `crypto::mod`), então não há uma "chave mestra" de runtime diferente
+// desta para gatear contra.
+//
+// Este módulo implementa o motor de segredos e o modelo de token inteiros —
+// cifragem, armazenamento, emissão/renovação/revogação — como funções
+// síncronas sobre `rusqlite::Connection`. A superfície HTTP
+// `GET/PUT/DELETE /v1/secret/<path>` pedida originalmente é
+// `services::http_segredos::ServidorHttpSegredos` (ver DECISÃO DE
+// TRANSPORTE em `services::rpc`, a mesma decisão usada aqui): um listener
+// axum local que chama as funções abaixo por trás de autenticação por
+// token bearer, iniciado só sob demanda por `commands::iniciar_servidor_http_segredos`.
+// =============================================================================
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::crypto::campo_cifrado;
+use crate::db::models::TokenSegredo;
+use crate::db::queries;
+use crate::services::cifragem::EstadoSessao;
+
+/// Validade padrão de um token recém-criado, se o chamador não pedir outra.
+const VALIDADE_PADRAO_TOKEN_HORAS: i64 = 24;
+
+/// Grava `valor` em `caminho`, cifrado com a chave de dados da sessão.
+/// Falha se a sessão de selagem estiver trancada.
+pub fn escrever_segredo(conexao: &Connection, sessao: &EstadoSessao, caminho: &str, valor: &str) -> Result<()> {
+    let chave = sessao
+        .chave_atual()
+        .ok_or_else(|| anyhow!("Cofre de segredos trancado — desbloqueie a sessão antes de escrever"))?;
+
+    let selado = campo_cifrado::cifrar(&chave, valor).context("Falha ao cifrar segredo")?;
+    let selado_json = serde_json::to_string(&selado).context("Falha ao serializar segredo cifrado")?;
+
+    queries::salvar_segredo(conexao, caminho, &selado_json)
+}
+
+/// Lê e decifra o valor em `caminho`. `Ok(None)` se não houver nada
+/// gravado nesse caminho. Falha se a sessão estiver trancada ou se o
+/// blob estiver corrompido/adulterado.
+pub fn ler_segredo(conexao: &Connection, sessao: &EstadoSessao, caminho: &str) -> Result<Option<String>> {
+    let chave = sessao
+        .chave_atual()
+        .ok_or_else(|| anyhow!("Cofre de segredos trancado — desbloqueie a sessão antes de ler"))?;
+
+    let Some(selado_json) = queries::obter_segredo(conexao, caminho)? else {
+        return Ok(None);
+    };
+
+    let selado = serde_json::from_str(&selado_json).context("Segredo gravado em formato inválido")?;
+    let valor = campo_cifrado::decifrar(&chave, &selado).context("Falha ao decifrar segredo")?;
+    Ok(Some(valor))
+}
+
+/// Remove o segredo em `caminho`. Não exige sessão desbloqueada — apagar
+/// não precisa ler nem escrever nada cifrado, só a linha em si — mas ainda
+/// assim é um endpoint autenticado (gate fica no lado do token/RPC que
+/// despacha para cá, ver `commands`).
+pub fn excluir_segredo(conexao: &Connection, caminho: &str) -> Result<bool> {
+    queries::excluir_segredo(conexao, caminho)
+}
+
+/// Gera um novo token de acesso (32 bytes aleatórios, hex), válido por
+/// `validade_horas` (ou `VALIDADE_PADRAO_TOKEN_HORAS` se `None`). Retorna o
+/// token em texto claro — esta é a única vez que ele existe fora do
+/// cabeçalho de autorização do chamador; só o hash fica no banco.
+pub fn criar_token(conexao: &Connection, validade_horas: Option<i64>) -> Result<String> {
+    let mut bytes_token = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes_token);
+    let token = hex::encode(bytes_token);
+
+    let id = Uuid::new_v4().to_string();
+    let hash = hash_token(&token);
+    let expira_em = (Utc::now() + Duration::hours(validade_horas.unwrap_or(VALIDADE_PADRAO_TOKEN_HORAS)))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+
+    queries::criar_token_segredo(conexao, &id, &hash, &expira_em)?;
+    Ok(token)
+}
+
+/// Confere se `token` é válido (existe, não revogado, não expirado).
+pub fn validar_token(conexao: &Connection, token: &str) -> Result<Option<TokenSegredo>> {
+    queries::obter_token_segredo_valido(conexao, &hash_token(token))
+}
+
+/// Estende a validade do token `id` por mais `validade_horas` a partir de
+/// agora (ou `VALIDADE_PADRAO_TOKEN_HORAS` se `None`).
+pub fn renovar_token(conexao: &Connection, id: &str, validade_horas: Option<i64>) -> Result<()> {
+    let nova_expira_em = (Utc::now() + Duration::hours(validade_horas.unwrap_or(VALIDADE_PADRAO_TOKEN_HORAS)))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+
+    queries::renovar_token_segredo(conexao, id, &nova_expira_em)
+}
+
+/// Revoga o token `id` imediatamente.
+pub fn revogar_token(conexao: &Connection, id: &str) -> Result<()> {
+    queries::revogar_token_segredo(conexao, id)
+}
+
+fn hash_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    hex::encode(hasher.finalize())
+}
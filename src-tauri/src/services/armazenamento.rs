@@ -2,25 +2,42 @@
 // VaultCraft — Serviço de Armazenamento de Arquivos
 // =============================================================================
 // Gerencia os arquivos físicos (anexos) no disco local.
-// Os anexos são armazenados em: {app_dir}/storage/anexos/{id}/{nome_original}
+//
+// Desde a migração 005, anexos são armazenados de forma endereçada por
+// conteúdo (mesmo princípio do armazenamento de objetos do pict-rs): o
+// SHA-256 do arquivo determina onde o blob físico mora —
+// {app_dir}/storage/anexos/blobs/{hash[0..2]}/{hash} — e cada linha
+// `anexos` só guarda metadados (`nome_original`, `item_id`/`tarefa_id`,
+// `hash_sha256`) apontando para esse blob. Dois anexos com o mesmo
+// conteúdo (ex.: o mesmo PDF anexado a dois itens) compartilham o mesmo
+// blob em disco; `blobs.contagem_referencias` (ver `db::queries`) rastreia
+// quantos anexos ainda apontam para cada um, para que `remover_anexo` só
+// apague o arquivo físico quando a última referência for removida.
+//
+// Anexos salvos antes da migração 005 continuam no layout antigo
+// (storage/anexos/{id}/{nome_original}) e permanecem acessíveis pelo
+// `caminho_interno` que já tinham gravado — não há migração retroativa.
 //
 // Decisões de design:
-// - Cada anexo fica em sua própria subpasta (pelo ID) para evitar colisões de nome
 // - O hash SHA-256 é calculado na gravação para verificação posterior (backups)
-// - O tipo MIME é detectado pela extensão do arquivo (simples e suficiente para MVP)
+// - O tipo MIME é detectado pela assinatura binária do arquivo (magic bytes),
+//   caindo para a extensão apenas quando nenhuma assinatura conhecida bate
 // - Nenhuma rede é usada — tudo é local e offline
 // =============================================================================
 
 use anyhow::{Context, Result};
 use chrono::Utc;
 use log::info;
+use rusqlite::Connection;
 use sha2::{Sha256, Digest};
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::crypto::limpeza::LimpezaAutomatica;
 use crate::db::models::Anexo;
+use crate::db::queries;
 
 /// Retorna o diretório raiz de armazenamento de anexos.
 /// Cria o diretório se não existir.
@@ -31,20 +48,39 @@ pub fn obter_diretorio_armazenamento(diretorio_app: &Path) -> PathBuf {
     dir
 }
 
-/// Salva um arquivo como anexo no armazenamento interno do cofre.
+/// Caminho interno (relativo ao diretório de armazenamento) de um blob,
+/// particionado pelos dois primeiros caracteres do hash para não acumular
+/// milhares de arquivos numa única pasta.
+fn caminho_interno_blob(hash_sha256: &str) -> String {
+    format!("blobs/{}/{}", &hash_sha256[0..2], hash_sha256)
+}
+
+/// Salva um arquivo como anexo no armazenamento interno do cofre,
+/// deduplicado por conteúdo (ver cabeçalho do módulo).
 ///
 /// Processo:
-/// 1. Gera um UUID único para o anexo
-/// 2. Cria subpasta com o UUID: storage/anexos/{uuid}/
-/// 3. Copia o arquivo para a subpasta mantendo o nome original
-/// 4. Calcula o hash SHA-256 do conteúdo para integridade
-/// 5. Detecta o tipo MIME pela extensão
-/// 6. Retorna o struct Anexo pronto para inserção no banco
+/// 1. Copia o arquivo de origem para um arquivo de staging enquanto calcula
+///    o hash SHA-256, em uma única passada (`copiar_e_hashear`) — como o
+///    caminho final endereçado por conteúdo só é conhecido depois do hash,
+///    não dá para copiar direto para o destino
+/// 2. Se já existe um blob com esse hash (`db::queries::obter_blob`), o
+///    staging é descartado (via `LimpezaAutomatica`) e só a contagem de
+///    referências é incrementada
+/// 3. Caso contrário, o staging é promovido (rename) para
+///    storage/anexos/blobs/{hash[0..2]}/{hash} e o blob é registrado com
+///    contagem de referências 1
+/// 4. Detecta o tipo MIME pela assinatura binária do primeiro chunk (já em
+///    mãos por causa do passo 1), caindo para a extensão só se nenhuma
+///    assinatura bater
+/// 5. Retorna o struct Anexo pronto para inserção no banco
 ///
-/// O registro no banco de dados NÃO é feito aqui — é responsabilidade
-/// do chamador (command) inserir via queries::criar_anexo().
+/// O registro do anexo em si (tabela `anexos`) NÃO é feito aqui — é
+/// responsabilidade do chamador (command) inserir via queries::criar_anexo().
+/// A referência ao blob (`db::queries::registrar_referencia_blob`), porém,
+/// É feita aqui, já que decidir se a cópia física acontece depende dela.
 pub fn salvar_anexo(
     diretorio_app: &Path,
+    conexao: &Connection,
     arquivo_origem: &Path,
     item_id: Option<&str>,
     tarefa_id: Option<&str>,
@@ -59,35 +95,50 @@ pub fn salvar_anexo(
         .unwrap_or("arquivo_desconhecido")
         .to_string();
 
-    // Criar diretório do anexo: storage/anexos/{id}/
     let dir_armazenamento = obter_diretorio_armazenamento(diretorio_app);
-    let dir_anexo = dir_armazenamento.join(&id);
-    fs::create_dir_all(&dir_anexo)
-        .with_context(|| format!("Falha ao criar diretório do anexo: {:?}", dir_anexo))?;
-
-    // Caminho de destino: storage/anexos/{id}/{nome_original}
-    let caminho_destino = dir_anexo.join(&nome_original);
-
-    // Copiar arquivo para o armazenamento interno
-    fs::copy(arquivo_origem, &caminho_destino)
-        .with_context(|| format!(
-            "Falha ao copiar arquivo {:?} para {:?}",
-            arquivo_origem, caminho_destino
-        ))?;
-
-    // Calcular hash SHA-256 do arquivo copiado
-    let hash = calcular_hash_arquivo(&caminho_destino)?;
-
-    // Obter tamanho do arquivo
-    let metadados = fs::metadata(&caminho_destino)
-        .context("Falha ao obter metadados do arquivo copiado")?;
-    let tamanho = metadados.len() as i64;
+    let dir_staging = dir_armazenamento.join(".staging");
+    fs::create_dir_all(&dir_staging)
+        .with_context(|| format!("Falha ao criar diretório de staging: {:?}", dir_staging))?;
+    let caminho_staging = dir_staging.join(Uuid::new_v4().to_string());
+
+    // Descarta o staging automaticamente se algo falhar (ou se o blob já
+    // existir e o rename abaixo nunca acontecer) — ver crypto::limpeza.
+    let mut limpeza = LimpezaAutomatica::nova("salvar_anexo");
+    limpeza.registrar_arquivo_temporario(caminho_staging.clone());
+
+    // Copia e calcula o hash em uma única passada pelo arquivo de origem
+    let (hash, tamanho_copiado, primeiros_bytes) = copiar_e_hashear(arquivo_origem, &caminho_staging)?;
+    let caminho_interno = caminho_interno_blob(&hash);
+    let caminho_destino = dir_armazenamento.join(&caminho_interno);
+
+    let tamanho = match queries::obter_blob(conexao, &hash)? {
+        Some(blob) => {
+            // Conteúdo idêntico já salvo por outro anexo: reusar o blob e
+            // deixar o `LimpezaAutomatica` remover o staging não utilizado.
+            info!("Anexo deduplicado (blob já existente): hash={}", &hash[..16]);
+            blob.tamanho
+        }
+        None => {
+            // Conteúdo novo: promover o staging para o caminho do blob.
+            if let Some(dir_blob) = caminho_destino.parent() {
+                fs::create_dir_all(dir_blob)
+                    .with_context(|| format!("Falha ao criar diretório do blob: {:?}", dir_blob))?;
+            }
+            fs::rename(&caminho_staging, &caminho_destino)
+                .with_context(|| format!(
+                    "Falha ao mover staging {:?} para {:?}",
+                    caminho_staging, caminho_destino
+                ))?;
+
+            tamanho_copiado
+        }
+    };
 
-    // Detectar tipo MIME pela extensão
-    let tipo_mime = detectar_tipo_mime(&nome_original);
+    queries::registrar_referencia_blob(conexao, &hash, tamanho)?;
 
-    // Caminho interno relativo (para portabilidade entre sistemas)
-    let caminho_interno = format!("{}/{}", id, nome_original);
+    // Preferir a assinatura binária; só cair para a extensão se nenhuma bater
+    let tipo_mime = detectar_tipo_mime_por_assinatura(&primeiros_bytes)
+        .unwrap_or_else(|| detectar_tipo_mime(&nome_original));
 
     info!(
         "Anexo salvo: {} ({}) tamanho={} hash={}",
@@ -107,24 +158,38 @@ pub fn salvar_anexo(
     })
 }
 
-/// Remove um anexo do armazenamento (arquivo físico e diretório).
-/// O registro no banco deve ser removido separadamente pelo chamador.
-pub fn remover_anexo(diretorio_app: &Path, caminho_interno: &str) -> Result<()> {
+/// Remove um anexo do armazenamento: libera a referência ao blob
+/// (`db::queries::remover_referencia_blob`) e só apaga o arquivo físico se
+/// essa era a última referência. O registro do anexo na tabela `anexos`
+/// deve ser removido separadamente pelo chamador.
+pub fn remover_anexo(diretorio_app: &Path, conexao: &Connection, anexo: &Anexo) -> Result<()> {
     let dir_armazenamento = obter_diretorio_armazenamento(diretorio_app);
-    let caminho_completo = dir_armazenamento.join(caminho_interno);
+    let caminho_completo = dir_armazenamento.join(&anexo.caminho_interno);
+
+    let pode_remover_arquivo = match anexo.hash_sha256.as_deref() {
+        Some(hash) if !hash.is_empty() => queries::remover_referencia_blob(conexao, hash)?,
+        // Anexos sem hash registrado (não deveria acontecer na prática,
+        // mas o schema permite) não têm uma linha em `blobs` para
+        // decrementar — o arquivo não é compartilhado, então é seguro remover.
+        _ => true,
+    };
 
-    // Remover o arquivo
-    if caminho_completo.exists() {
+    if pode_remover_arquivo && caminho_completo.exists() {
         fs::remove_file(&caminho_completo)
             .with_context(|| format!("Falha ao remover arquivo: {:?}", caminho_completo))?;
-    }
 
-    // Tentar remover o diretório pai (subpasta do UUID) se estiver vazio
-    if let Some(dir_pai) = caminho_completo.parent() {
-        let _ = fs::remove_dir(dir_pai); // Ignora erro se não estiver vazio
+        // Tentar remover o diretório pai se estiver vazio (subpasta do
+        // UUID no layout antigo, ou partição de hash no layout de blobs).
+        if let Some(dir_pai) = caminho_completo.parent() {
+            let _ = fs::remove_dir(dir_pai); // Ignora erro se não estiver vazio
+        }
     }
 
-    info!("Anexo removido do armazenamento: {}", caminho_interno);
+    info!(
+        "Anexo removido do armazenamento: {} (arquivo físico {})",
+        anexo.caminho_interno,
+        if pode_remover_arquivo { "removido" } else { "mantido (outras referências)" }
+    );
     Ok(())
 }
 
@@ -142,26 +207,89 @@ pub fn obter_caminho_completo_anexo(diretorio_app: &Path, caminho_interno: &str)
     Ok(caminho_completo)
 }
 
-/// Calcula o hash SHA-256 de um arquivo.
-/// Lê o arquivo em chunks de 8KB para não consumir muita memória.
-fn calcular_hash_arquivo(caminho: &Path) -> Result<String> {
-    let mut arquivo = fs::File::open(caminho)
-        .with_context(|| format!("Falha ao abrir arquivo para hash: {:?}", caminho))?;
+/// Copia `origem` para `destino` e calcula o SHA-256 do conteúdo em uma
+/// única passada: o mesmo buffer de 8KB lido da origem é usado tanto para
+/// atualizar o hasher quanto para escrever no destino, então a origem só é
+/// lida uma vez (antes, a origem era lida duas vezes: uma em `fs::copy`,
+/// outra em `calcular_hash_arquivo`). Retorna o hash (hex), o tamanho em
+/// bytes (acumulado do próprio laço em vez de uma chamada extra a
+/// `fs::metadata`) e os bytes do primeiro chunk lido, reaproveitados por
+/// `detectar_tipo_mime_por_assinatura` para não precisar reabrir o arquivo.
+fn copiar_e_hashear(origem: &Path, destino: &Path) -> Result<(String, i64, Vec<u8>)> {
+    let mut leitor = fs::File::open(origem)
+        .with_context(|| format!("Falha ao abrir arquivo de origem: {:?}", origem))?;
+    let mut escritor = fs::File::create(destino)
+        .with_context(|| format!("Falha ao criar arquivo de destino: {:?}", destino))?;
 
     let mut hasher = Sha256::new();
     let mut buffer = [0u8; 8192]; // 8KB por chunk
+    let mut tamanho: i64 = 0;
+    let mut primeiros_bytes: Option<Vec<u8>> = None;
 
     loop {
-        let bytes_lidos = arquivo.read(&mut buffer)
-            .context("Falha ao ler arquivo para hash")?;
+        let bytes_lidos = leitor.read(&mut buffer)
+            .context("Falha ao ler arquivo de origem")?;
         if bytes_lidos == 0 {
             break;
         }
+        if primeiros_bytes.is_none() {
+            primeiros_bytes = Some(buffer[..bytes_lidos].to_vec());
+        }
         hasher.update(&buffer[..bytes_lidos]);
+        escritor.write_all(&buffer[..bytes_lidos])
+            .context("Falha ao escrever no arquivo de destino")?;
+        tamanho += bytes_lidos as i64;
     }
 
     let hash = hasher.finalize();
-    Ok(hex::encode(hash))
+    Ok((hex::encode(hash), tamanho, primeiros_bytes.unwrap_or_default()))
+}
+
+/// Detecta o tipo MIME pela assinatura binária (magic bytes) do início do
+/// arquivo, preferida sobre a extensão — um `.txt` renomeado que na
+/// verdade é um PNG, ou um export sem extensão, não engana o sniffing.
+/// Retorna `None` quando nenhuma assinatura conhecida bate, e quem chama
+/// cai de volta para `detectar_tipo_mime` (extensão).
+fn detectar_tipo_mime_por_assinatura(bytes: &[u8]) -> Option<String> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png".to_string());
+    }
+    if bytes.starts_with(b"\xFF\xD8\xFF") {
+        return Some("image/jpeg".to_string());
+    }
+    if bytes.starts_with(b"%PDF") {
+        return Some("application/pdf".to_string());
+    }
+    if bytes.starts_with(b"GIF8") {
+        return Some("image/gif".to_string());
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+        return Some(detectar_tipo_mime_zip(bytes));
+    }
+    None
+}
+
+/// Assinatura `PK\x03\x04` cobre tanto ZIP comuns quanto os formatos
+/// Office Open XML (docx/xlsx/pptx), que são ZIPs por dentro. Para não
+/// rotular todo docx como "application/zip", inspeciona os nomes de
+/// entrada já presentes no primeiro chunk lido — as pastas `word/`, `xl/`
+/// e `ppt/` são exclusivas de cada formato OOXML.
+fn detectar_tipo_mime_zip(bytes: &[u8]) -> String {
+    if contem_subsequencia(bytes, b"word/") {
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document".to_string()
+    } else if contem_subsequencia(bytes, b"xl/") {
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string()
+    } else if contem_subsequencia(bytes, b"ppt/") {
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation".to_string()
+    } else {
+        "application/zip".to_string()
+    }
+}
+
+/// Busca ingênua de `agulha` em `haystack` (arquivos pequenos, chunk único
+/// de no máximo 8KB — não vale a pena puxar um crate de busca de padrão).
+fn contem_subsequencia(haystack: &[u8], agulha: &[u8]) -> bool {
+    haystack.windows(agulha.len()).any(|janela| janela == agulha)
 }
 
 /// Detecta o tipo MIME com base na extensão do arquivo.
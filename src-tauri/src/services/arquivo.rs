@@ -0,0 +1,460 @@
+// =============================================================================
+// VaultCraft — Arquivo de Cofre Portátil Cifrado (.vcarch)
+// =============================================================================
+// Formato alternativo ao `.vaultbackup` (ver services::backup), pensado para
+// sair do disco de origem (pendrive, nuvem de terceiros, e-mail): sempre
+// cifrado, com compressão por entrada independente de ZIP, e autenticado por
+// assinatura Ed25519 ANTES de tentar decifrar (gate barato contra bytes
+// corrompidos/adulterados, sem precisar rodar Argon2id primeiro).
+//
+// Conteúdo de um .vcarch:
+//   - banco.sqlite: cópia completa do banco de dados
+//   - auditoria.jsonl: despejo JSON-lines da cadeia de auditoria completa
+//     (ver migração 004 e `queries::listar_log_auditoria_completo`),
+//     verificável de forma independente, sem precisar abrir o SQLite
+//
+// Formato do arquivo em disco:
+//   MAGIC (8 bytes: "VCARCH01") | chave_publica Ed25519 (32 bytes) |
+//   assinatura Ed25519 (64 bytes, sobre os bytes do corpo cifrado) |
+//   corpo cifrado (ver crypto::backup::cifrar)
+//
+// A chave Ed25519 é EFÊMERA: gerada de novo a cada export, com a chave
+// pública embutida no próprio cabeçalho (ao contrário da chave de ancoragem
+// de `services::auditoria`, que é persistida por cofre). Ela não identifica
+// o usuário — só prova que os bytes cifrados não mudaram depois de assinados.
+//
+// Corpo (antes de cifrar), uma sequência de entradas:
+//   num_entradas: u32 LE
+//   por entrada:
+//     nome_len: u16 LE | nome (UTF-8, nome_len bytes)
+//     codec: u8 (0=Armazenar, 1=Lz4, 2=Brotli) [+ qualidade: u32 LE se Brotli]
+//     tamanho_original: u64 LE
+//     tamanho_comprimido: u32 LE
+//     bytes comprimidos (tamanho_comprimido bytes)
+//
+// `importar` verifica a assinatura sobre o corpo cifrado antes de chamar
+// `crypto::backup::descifrar` — uma senha errada já falha ali, e bytes
+// adulterados em trânsito falham antes mesmo disso.
+// =============================================================================
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use log::info;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::crypto::backup as cripto_backup;
+use crate::crypto::limpeza::LimpezaAutomatica;
+use crate::db::migrations::versao_mais_recente;
+use crate::db::models::{
+    CodecArquivo, CompressaoBackup, EntradaArquivoInfo, ManifestoArquivo, OpcoesArquivoExport,
+    PoliticaPoda,
+};
+use crate::db::queries;
+use crate::services::auditoria;
+use crate::services::backup::{criar_backup, podar_backups_automaticos};
+
+/// Identifica um arquivo de cofre portátil gerado por este módulo.
+const MAGIC: &[u8; 8] = b"VCARCH01";
+
+const TAMANHO_CHAVE_PUBLICA: usize = 32;
+const TAMANHO_ASSINATURA: usize = 64;
+
+// =============================================================================
+// EXPORTACAO
+// =============================================================================
+
+/// Exporta o cofre inteiro como um arquivo `.vcarch` cifrado e assinado.
+///
+/// As duas entradas gravadas são `banco.sqlite` (cópia integral do banco) e
+/// `auditoria.jsonl` (a cadeia de auditoria completa, com `prev_hash`/
+/// `entry_hash` — ver migração 004), cada uma comprimida de acordo com
+/// `opcoes.codec_banco`/`opcoes.codec_auditoria`.
+///
+/// O corpo (ambas entradas serializadas) é cifrado inteiro com
+/// `opcoes.senha` (ver `crypto::backup::cifrar`) e assinado com uma chave
+/// Ed25519 gerada na hora só para este export — a chave pública vai embutida
+/// no cabeçalho do arquivo, para que `importar` possa autenticar o corpo
+/// cifrado antes de tentar decifrá-lo.
+pub fn exportar(
+    conexao: &Connection,
+    destino: &Path,
+    opcoes: &OpcoesArquivoExport,
+    diretorio_app: &Path,
+) -> Result<(PathBuf, ManifestoArquivo)> {
+    let agora = Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let nome_arquivo = format!("vaultcraft_{}.vcarch", agora);
+    let caminho_arquivo = destino.join(&nome_arquivo);
+
+    info!("Exportando arquivo de cofre portátil em: {:?}", caminho_arquivo);
+
+    if let Some(dir_pai) = caminho_arquivo.parent() {
+        fs::create_dir_all(dir_pai)
+            .context("Falha ao criar diretório de destino do arquivo de cofre")?;
+    }
+
+    // 1. banco.sqlite
+    let caminho_banco = diretorio_app.join("vaultcraft.db");
+    let conteudo_banco =
+        fs::read(&caminho_banco).context("Falha ao ler banco de dados para exportação")?;
+
+    // 2. auditoria.jsonl
+    let eventos = queries::listar_log_auditoria_completo(conexao)?;
+    let mut auditoria_jsonl = String::new();
+    for evento in &eventos {
+        auditoria_jsonl.push_str(
+            &serde_json::to_string(evento).context("Falha ao serializar evento de auditoria")?,
+        );
+        auditoria_jsonl.push('\n');
+    }
+
+    // 3. Comprimir e serializar as duas entradas no corpo do arquivo
+    let mut corpo = Vec::new();
+    corpo.extend_from_slice(&2u32.to_le_bytes());
+
+    let mut entradas_info = Vec::new();
+    escrever_entrada(
+        &mut corpo,
+        &mut entradas_info,
+        "banco.sqlite",
+        &conteudo_banco,
+        &opcoes.codec_banco,
+    )?;
+    escrever_entrada(
+        &mut corpo,
+        &mut entradas_info,
+        "auditoria.jsonl",
+        auditoria_jsonl.as_bytes(),
+        &opcoes.codec_auditoria,
+    )?;
+
+    // 4. Cifrar o corpo inteiro com a senha do export
+    let corpo_cifrado = cripto_backup::cifrar(&corpo, &opcoes.senha)
+        .context("Falha ao cifrar arquivo de cofre portátil")?;
+
+    // 5. Gerar uma chave Ed25519 efêmera e assinar o corpo cifrado
+    let chave = gerar_chave_efemera();
+    let assinatura = chave.sign(&corpo_cifrado);
+    let chave_publica_bytes = chave.verifying_key().to_bytes();
+
+    // 6. Gravar MAGIC | chave pública | assinatura | corpo cifrado
+    let mut bytes_finais = Vec::with_capacity(
+        MAGIC.len() + TAMANHO_CHAVE_PUBLICA + TAMANHO_ASSINATURA + corpo_cifrado.len(),
+    );
+    bytes_finais.extend_from_slice(MAGIC);
+    bytes_finais.extend_from_slice(&chave_publica_bytes);
+    bytes_finais.extend_from_slice(&assinatura.to_bytes());
+    bytes_finais.extend_from_slice(&corpo_cifrado);
+
+    fs::write(&caminho_arquivo, &bytes_finais)
+        .with_context(|| format!("Falha ao gravar arquivo de cofre portátil: {:?}", caminho_arquivo))?;
+
+    let manifesto = ManifestoArquivo {
+        versao_app: env!("CARGO_PKG_VERSION").to_string(),
+        versao_schema: versao_mais_recente(),
+        criado_em: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        entradas: entradas_info,
+        chave_publica: hex::encode(chave_publica_bytes),
+    };
+
+    info!(
+        "Arquivo de cofre portátil exportado com sucesso: {:?} ({} eventos de auditoria)",
+        caminho_arquivo, eventos.len()
+    );
+
+    Ok((caminho_arquivo, manifesto))
+}
+
+// =============================================================================
+// IMPORTACAO
+// =============================================================================
+
+/// Importa um arquivo `.vcarch` gerado por `exportar`, restaurando o banco
+/// de dados do cofre atual.
+///
+/// Ordem das verificações (importante: autentica antes de decifrar):
+/// 1. Valida MAGIC e extrai chave pública + assinatura do cabeçalho
+/// 2. Verifica a assinatura Ed25519 sobre o corpo cifrado — bytes corrompidos
+///    ou adulterados em trânsito falham aqui, sem gastar Argon2id
+/// 3. Decifra o corpo com `senha` (ver `crypto::backup::descifrar`) — uma
+///    senha errada falha aqui
+/// 4. Descomprime e reconstrói as entradas `banco.sqlite`/`auditoria.jsonl`
+/// 5. Cria um backup automático do estado atual (mesma rede de segurança de
+///    `backup::restaurar_backup`) e então sobrescreve `vaultcraft.db`
+///
+/// `auditoria.jsonl` não é reimportado para dentro do log de auditoria do
+/// cofre atual (os dois teriam cadeias de hash distintas) — fica apenas
+/// disponível no manifesto devolvido para quem quiser inspecioná-lo à parte.
+///
+/// O corpo decifrado (texto plano do banco e da auditoria) fica retido em
+/// um guarda `LimpezaAutomatica` e é zerado assim que a restauração termina
+/// (ou, em caso de retorno antecipado por `?`, quando o guarda sai de
+/// escopo) — ver `crypto::limpeza`.
+pub fn importar(
+    conexao: &Connection,
+    arquivo: &Path,
+    senha: &str,
+    diretorio_app: &Path,
+) -> Result<ManifestoArquivo> {
+    info!("Importando arquivo de cofre portátil de: {:?}", arquivo);
+
+    let mut limpeza = LimpezaAutomatica::nova("importacao_arquivo_cofre");
+
+    let bytes = fs::read(arquivo)
+        .with_context(|| format!("Falha ao ler arquivo de cofre portátil: {:?}", arquivo))?;
+
+    // 1. Autenticar o corpo ANTES de decifrar
+    verificar_assinatura(&bytes)?;
+    let chave_publica_bytes: [u8; TAMANHO_CHAVE_PUBLICA] = bytes
+        [MAGIC.len()..MAGIC.len() + TAMANHO_CHAVE_PUBLICA]
+        .try_into()
+        .expect("fatiamento com tamanho fixo");
+    let corpo_cifrado = &bytes[MAGIC.len() + TAMANHO_CHAVE_PUBLICA + TAMANHO_ASSINATURA..];
+
+    // 2. Decifrar (corpo em texto plano registrado na guarda de limpeza)
+    let corpo = cripto_backup::descifrar(corpo_cifrado, senha)
+        .context("Falha ao decifrar arquivo de cofre portátil (senha incorreta?)")?;
+    let indice_corpo = limpeza.registrar_segredo(corpo);
+
+    // 3. Ler as entradas
+    let (entradas, entradas_info) = ler_entradas(limpeza.segredo(indice_corpo))?;
+    let conteudo_banco = entradas
+        .get("banco.sqlite")
+        .context("Entrada banco.sqlite não encontrada no arquivo de cofre portátil")?;
+
+    // 4. Backup automático do estado atual antes de sobrescrever (mesma
+    // rede de segurança de `backup::restaurar_backup`)
+    let dir_backups_auto = diretorio_app.join("backups_automaticos");
+    fs::create_dir_all(&dir_backups_auto)
+        .context("Falha ao criar diretório de backups automáticos")?;
+
+    match criar_backup(diretorio_app, conexao, &dir_backups_auto, &CompressaoBackup::default(), None, None) {
+        Ok(caminho) => {
+            info!("Backup automático criado: {:?}", caminho);
+            match podar_backups_automaticos(&dir_backups_auto, &PoliticaPoda::default()) {
+                Ok(removidos) if !removidos.is_empty() => {
+                    info!("Poda de backups automáticos removeu {} arquivo(s) antigos", removidos.len())
+                }
+                Ok(_) => {}
+                Err(e) => info!("Aviso: falha ao podar backups automáticos antigos: {}", e),
+            }
+        }
+        Err(e) => info!("Aviso: não foi possível criar backup automático: {}", e),
+    }
+
+    // 5. Restaurar o banco
+    let caminho_banco = diretorio_app.join("vaultcraft.db");
+    fs::write(&caminho_banco, conteudo_banco).context("Falha ao restaurar banco de dados")?;
+
+    let manifesto = ManifestoArquivo {
+        versao_app: env!("CARGO_PKG_VERSION").to_string(),
+        versao_schema: versao_mais_recente(),
+        criado_em: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        entradas: entradas_info,
+        chave_publica: hex::encode(chave_publica_bytes),
+    };
+
+    // 6. Zerar o corpo decifrado agora que já foi gravado — não esperar o
+    // guarda sair de escopo no fim da função.
+    let resumo_limpeza = limpeza.limpar();
+    if !resumo_limpeza.is_empty() {
+        let _ = auditoria::registrar(
+            conexao, "limpeza_segura", "sistema", None,
+            Some(&format!(
+                "{{\"artefatos\": {}}}",
+                serde_json::to_string(&resumo_limpeza).unwrap_or_default()
+            )),
+        );
+    }
+
+    info!("Arquivo de cofre portátil importado com sucesso: {:?}", arquivo);
+
+    Ok(manifesto)
+}
+
+/// Verifica a assinatura Ed25519 embutida no cabeçalho de um `.vcarch` sem
+/// decifrar nada — gate barato para confirmar que os bytes não foram
+/// corrompidos/adulterados depois do export, usado tanto por `importar`
+/// quanto por `services::sincronizacao` (que verifica um `.vcarch` recém
+/// baixado antes de aceitar trazê-lo para perto do cofre local).
+pub fn verificar_assinatura(bytes: &[u8]) -> Result<()> {
+    let tamanho_cabecalho = MAGIC.len() + TAMANHO_CHAVE_PUBLICA + TAMANHO_ASSINATURA;
+    if bytes.len() < tamanho_cabecalho || &bytes[..MAGIC.len()] != MAGIC {
+        bail!("Arquivo não é um arquivo de cofre portátil VaultCraft válido (.vcarch)");
+    }
+
+    let chave_publica_bytes: [u8; TAMANHO_CHAVE_PUBLICA] = bytes
+        [MAGIC.len()..MAGIC.len() + TAMANHO_CHAVE_PUBLICA]
+        .try_into()
+        .expect("fatiamento com tamanho fixo");
+    let assinatura_bytes: [u8; TAMANHO_ASSINATURA] = bytes[MAGIC.len() + TAMANHO_CHAVE_PUBLICA
+        ..tamanho_cabecalho]
+        .try_into()
+        .expect("fatiamento com tamanho fixo");
+    let corpo_cifrado = &bytes[tamanho_cabecalho..];
+
+    let chave_publica = VerifyingKey::from_bytes(&chave_publica_bytes)
+        .context("Chave pública Ed25519 do arquivo inválida")?;
+    let assinatura = Signature::from_bytes(&assinatura_bytes);
+    chave_publica
+        .verify(corpo_cifrado, &assinatura)
+        .map_err(|_| anyhow!("Assinatura do arquivo inválida — os dados foram corrompidos ou adulterados"))?;
+
+    Ok(())
+}
+
+// =============================================================================
+// FUNCOES AUXILIARES
+// =============================================================================
+
+/// Comprime `dados` com `codec` e anexa a entrada (cabeçalho + bytes) a
+/// `corpo`, registrando seus metadados em `entradas_info`.
+fn escrever_entrada(
+    corpo: &mut Vec<u8>,
+    entradas_info: &mut Vec<EntradaArquivoInfo>,
+    nome: &str,
+    dados: &[u8],
+    codec: &CodecArquivo,
+) -> Result<()> {
+    let comprimido = comprimir(codec, dados)?;
+
+    let nome_bytes = nome.as_bytes();
+    corpo.extend_from_slice(&(nome_bytes.len() as u16).to_le_bytes());
+    corpo.extend_from_slice(nome_bytes);
+
+    match codec {
+        CodecArquivo::Armazenar => corpo.push(0),
+        CodecArquivo::Lz4 => corpo.push(1),
+        CodecArquivo::Brotli { qualidade } => {
+            corpo.push(2);
+            corpo.extend_from_slice(&qualidade.to_le_bytes());
+        }
+    }
+
+    corpo.extend_from_slice(&(dados.len() as u64).to_le_bytes());
+    corpo.extend_from_slice(&(comprimido.len() as u32).to_le_bytes());
+    corpo.extend_from_slice(&comprimido);
+
+    entradas_info.push(EntradaArquivoInfo {
+        nome: nome.to_string(),
+        codec: *codec,
+        tamanho_original: dados.len() as u64,
+        tamanho_comprimido: comprimido.len() as u64,
+    });
+
+    Ok(())
+}
+
+/// Lê todas as entradas do corpo decifrado, devolvendo os bytes de cada uma
+/// (por nome) e seus metadados, na ordem em que aparecem no arquivo.
+fn ler_entradas(corpo: &[u8]) -> Result<(std::collections::HashMap<String, Vec<u8>>, Vec<EntradaArquivoInfo>)> {
+    let mut cursor = 0usize;
+    let mut ler = |tamanho: usize| -> Result<&[u8]> {
+        let fatia = corpo
+            .get(cursor..cursor + tamanho)
+            .context("Arquivo de cofre portátil corrompido (corpo truncado)")?;
+        cursor += tamanho;
+        Ok(fatia)
+    };
+
+    let num_entradas = u32::from_le_bytes(ler(4)?.try_into().unwrap());
+
+    let mut entradas = std::collections::HashMap::new();
+    let mut entradas_info = Vec::new();
+
+    for _ in 0..num_entradas {
+        let nome_len = u16::from_le_bytes(ler(2)?.try_into().unwrap()) as usize;
+        let nome = String::from_utf8(ler(nome_len)?.to_vec())
+            .context("Nome de entrada inválido (não é UTF-8)")?;
+
+        let tag = ler(1)?[0];
+        let codec = match tag {
+            0 => CodecArquivo::Armazenar,
+            1 => CodecArquivo::Lz4,
+            2 => {
+                let qualidade = u32::from_le_bytes(ler(4)?.try_into().unwrap());
+                CodecArquivo::Brotli { qualidade }
+            }
+            outro => bail!("Codec de entrada desconhecido: {}", outro),
+        };
+
+        let tamanho_original = u64::from_le_bytes(ler(8)?.try_into().unwrap());
+        let tamanho_comprimido = u32::from_le_bytes(ler(4)?.try_into().unwrap()) as usize;
+        let comprimido = ler(tamanho_comprimido)?;
+
+        let dados = descomprimir(&codec, comprimido)?;
+        if dados.len() as u64 != tamanho_original {
+            bail!("Entrada {} com tamanho inconsistente após descompressão", nome);
+        }
+
+        entradas_info.push(EntradaArquivoInfo {
+            nome: nome.clone(),
+            codec,
+            tamanho_original,
+            tamanho_comprimido: tamanho_comprimido as u64,
+        });
+        entradas.insert(nome, dados);
+    }
+
+    Ok((entradas, entradas_info))
+}
+
+/// Comprime `dados` de acordo com `codec` (ver `CodecArquivo`).
+fn comprimir(codec: &CodecArquivo, dados: &[u8]) -> Result<Vec<u8>> {
+    match codec {
+        CodecArquivo::Armazenar => Ok(dados.to_vec()),
+        CodecArquivo::Lz4 => {
+            let mut saida = Vec::new();
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(&mut saida);
+            encoder.write_all(dados).context("Falha ao comprimir entrada com LZ4")?;
+            encoder.finish().context("Falha ao finalizar compressão LZ4")?;
+            Ok(saida)
+        }
+        CodecArquivo::Brotli { qualidade } => {
+            let mut saida = Vec::new();
+            let parametros = brotli::enc::BrotliEncoderParams {
+                quality: (*qualidade).min(11) as i32,
+                ..Default::default()
+            };
+            brotli::BrotliCompress(&mut &dados[..], &mut saida, &parametros)
+                .context("Falha ao comprimir entrada com Brotli")?;
+            Ok(saida)
+        }
+    }
+}
+
+/// Descomprime bytes de uma entrada de acordo com `codec`.
+fn descomprimir(codec: &CodecArquivo, dados: &[u8]) -> Result<Vec<u8>> {
+    match codec {
+        CodecArquivo::Armazenar => Ok(dados.to_vec()),
+        CodecArquivo::Lz4 => {
+            let mut saida = Vec::new();
+            lz4_flex::frame::FrameDecoder::new(dados)
+                .read_to_end(&mut saida)
+                .context("Falha ao descomprimir entrada com LZ4")?;
+            Ok(saida)
+        }
+        CodecArquivo::Brotli { .. } => {
+            let mut saida = Vec::new();
+            brotli::BrotliDecompress(&mut &dados[..], &mut saida)
+                .context("Falha ao descomprimir entrada com Brotli")?;
+            Ok(saida)
+        }
+    }
+}
+
+/// Gera uma chave Ed25519 efêmera para assinar um export. Mesma técnica de
+/// `services::auditoria::obter_ou_criar_chave_assinatura` (semente via
+/// SHA-256 de dois UUIDs) — aceitável aqui porque esta chave não protege
+/// segredo nenhum, só assina bytes já cifrados para detectar corrupção.
+fn gerar_chave_efemera() -> SigningKey {
+    let entropia = format!("{}{}", uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    let mut hasher = Sha256::new();
+    hasher.update(entropia.as_bytes());
+    let semente: [u8; 32] = hasher.finalize().into();
+    SigningKey::from_bytes(&semente)
+}
@@ -10,9 +10,20 @@
 //   - Permite que os commands chamem uma função simples sem saber dos detalhes
 // =============================================================================
 
-use anyhow::Result;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use rusqlite::Connection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
 
+use crate::db::models::{
+    AssinaturaTopoAuditoria, AtualizacaoItem, EstadoMaterializado, Item, OperacaoAuditoriaCompleta,
+    OperacaoMutacao, QuebraIntegridadeAuditoria,
+};
 use crate::db::queries;
 
 /// Registra um evento no log de auditoria do cofre.
@@ -37,3 +48,372 @@ pub fn registrar(
 ) -> Result<()> {
     queries::registrar_evento_auditoria(conexao, tipo_evento, entidade_tipo, entidade_id, detalhes)
 }
+
+/// Recomputa a cadeia de hashes do log de auditoria (ver migração 004) e
+/// aponta a primeira linha adulterada, se houver.
+///
+/// `None` significa que a cadeia inteira confere: cada `entry_hash` bate
+/// com o conteúdo recomputado da linha, e cada `prev_hash` bate com o
+/// `entry_hash` da linha anterior. Qualquer edição ou remoção manual de uma
+/// linha já encadeada (inclusive a entrada "vacuum" de manutenção) quebra a
+/// cadeia a partir desse ponto.
+pub fn verificar_integridade(conexao: &Connection) -> Result<Option<QuebraIntegridadeAuditoria>> {
+    queries::verificar_integridade_auditoria(conexao)
+}
+
+/// Chave de configuração onde a chave privada de ancoragem da auditoria
+/// fica guardada (ver `obter_ou_criar_chave_assinatura`).
+const CONFIG_CHAVE_ASSINATURA_AUDITORIA: &str = "auditoria_chave_assinatura_privada";
+
+/// Assina o `entry_hash` do topo atual da cadeia de auditoria com a chave
+/// Ed25519 local (gerada e persistida na primeira chamada), para que o tip
+/// possa ser exportado e ancorado fora do cofre como prova de que o log
+/// não foi adulterado depois daquele ponto.
+///
+/// Retorna `None` se a cadeia ainda não tiver nenhuma linha.
+pub fn assinar_topo(conexao: &Connection) -> Result<Option<AssinaturaTopoAuditoria>> {
+    let Some(entry_hash) = queries::topo_cadeia_auditoria(conexao)? else {
+        return Ok(None);
+    };
+
+    let chave = obter_ou_criar_chave_assinatura(conexao)?;
+    let assinatura = chave.sign(entry_hash.as_bytes());
+
+    Ok(Some(AssinaturaTopoAuditoria {
+        entry_hash,
+        assinatura: hex::encode(assinatura.to_bytes()),
+        chave_publica: hex::encode(chave.verifying_key().to_bytes()),
+    }))
+}
+
+/// Verifica uma assinatura produzida por `assinar_topo` contra o
+/// `entry_hash` e a `chave_publica` que vieram com ela (por exemplo, ao
+/// reconferir um tip exportado anteriormente). Não depende do banco local.
+pub fn verificar_assinatura_topo(
+    entry_hash: &str,
+    assinatura_hex: &str,
+    chave_publica_hex: &str,
+) -> Result<bool> {
+    let chave_publica_bytes: [u8; 32] = hex::decode(chave_publica_hex)
+        .context("Chave pública em formato hexadecimal inválido")?
+        .try_into()
+        .map_err(|_| anyhow!("Chave pública de auditoria com tamanho inválido"))?;
+    let assinatura_bytes: [u8; 64] = hex::decode(assinatura_hex)
+        .context("Assinatura em formato hexadecimal inválido")?
+        .try_into()
+        .map_err(|_| anyhow!("Assinatura de auditoria com tamanho inválido"))?;
+
+    let chave_publica = VerifyingKey::from_bytes(&chave_publica_bytes)
+        .context("Chave pública Ed25519 de auditoria inválida")?;
+    let assinatura = Signature::from_bytes(&assinatura_bytes);
+
+    Ok(chave_publica.verify(entry_hash.as_bytes(), &assinatura).is_ok())
+}
+
+/// Obtém a chave de assinatura Ed25519 usada para ancorar o topo da cadeia
+/// de auditoria, gerando-a e persistindo em `configuracoes` na primeira
+/// chamada. É uma chave própria deste cofre — não a mesma usada para
+/// validar licenças (ver `license::validator`) — e só serve para provar
+/// que um `entry_hash` exportado veio mesmo dele.
+///
+/// A semente é derivada de UUIDs via SHA-256, na mesma linha de
+/// `crypto::gerar_salt`: evita puxar uma dependência extra de RNG só para
+/// isso, o que é aceitável aqui porque essa chave não protege segredos do
+/// usuário — só assina um hash que já é público dentro do próprio cofre.
+fn obter_ou_criar_chave_assinatura(conexao: &Connection) -> Result<SigningKey> {
+    if let Some(config) = queries::obter_configuracao(conexao, CONFIG_CHAVE_ASSINATURA_AUDITORIA)? {
+        let valor = config.valor.context("Chave de assinatura de auditoria sem valor gravado")?;
+        let bytes = hex::decode(&valor)
+            .context("Chave de assinatura de auditoria corrompida (não é hexadecimal)")?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Chave de assinatura de auditoria com tamanho inválido"))?;
+        return Ok(SigningKey::from_bytes(&bytes));
+    }
+
+    let entropia = format!("{}{}", uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    let mut hasher = Sha256::new();
+    hasher.update(entropia.as_bytes());
+    let semente: [u8; 32] = hasher.finalize().into();
+
+    queries::salvar_configuracao(conexao, CONFIG_CHAVE_ASSINATURA_AUDITORIA, &hex::encode(semente))?;
+    Ok(SigningKey::from_bytes(&semente))
+}
+
+// =============================================================================
+// Log de operações com HLC e checkpoints (event sourcing)
+// =============================================================================
+// `registrar` acima continua existindo para eventos livres (backup, sync,
+// manutenção, reparo do FTS). `registrar_mutacao` é a variante estruturada
+// para pasta/item/tag: grava o payload antes/depois e um timestamp lógico
+// híbrido (HLC) que ordena totalmente operações vindas de cofres
+// diferentes, e serve de base para o `replay` que reconstrói o estado
+// materializado a partir do log (ver requests.jsonl chunk8-3).
+// =============================================================================
+
+/// Chave de configuração onde o identificador deste cofre/instalação fica
+/// guardado — compõe o HLC (ver `proximo_hlc`) para que operações
+/// concorrentes em dois dispositivos nunca colidam mesmo no mesmo
+/// milissegundo.
+const CONFIG_NODE_ID: &str = "auditoria_node_id";
+
+/// Quantas operações estruturadas entre um checkpoint e o próximo —
+/// replay() nunca precisa refazer mais que isso a partir do checkpoint mais
+/// recente.
+const KEEP_STATE_EVERY: i64 = 64;
+
+/// Contador monotônico do HLC deste processo: (último milissegundo de
+/// parede observado, contador usado dentro desse milissegundo). Reinicia a
+/// cada processo, o que é aceitável porque o relógio de parede só anda para
+/// frente entre reinícios — a monotonicidade só precisa valer dentro do
+/// processo em execução.
+static CONTADOR_HLC: OnceLock<Mutex<(i64, u64)>> = OnceLock::new();
+
+/// Obtém o identificador deste cofre, gerando-o e persistindo em
+/// `configuracoes` na primeira chamada (mesmo padrão de
+/// `obter_ou_criar_chave_assinatura`).
+fn obter_ou_criar_node_id(conexao: &Connection) -> Result<String> {
+    if let Some(config) = queries::obter_configuracao(conexao, CONFIG_NODE_ID)? {
+        return config.valor.context("Node id de auditoria sem valor gravado");
+    }
+
+    let node_id = Uuid::new_v4().to_string();
+    queries::salvar_configuracao(conexao, CONFIG_NODE_ID, &node_id)?;
+    Ok(node_id)
+}
+
+/// Gera o próximo timestamp lógico híbrido: `"<ms>-<contador>-<node_id>"`.
+/// `<ms>` são os milissegundos de parede (13 dígitos, zero-padded) e
+/// `<contador>` é um contador monotônico (6 dígitos, zero-padded) que só
+/// avança dentro do mesmo milissegundo — juntos garantem que dois HLCs
+/// gerados neste processo nunca empatam, e a string ordena lexicograficamente
+/// na mesma ordem que cronologicamente.
+fn proximo_hlc(conexao: &Connection) -> Result<String> {
+    let node_id = obter_ou_criar_node_id(conexao)?;
+    let agora_ms = Utc::now().timestamp_millis();
+
+    let mutex = CONTADOR_HLC.get_or_init(|| Mutex::new((0, 0)));
+    let mut estado = mutex.lock().map_err(|_| anyhow!("Mutex do contador HLC envenenado"))?;
+
+    let contador = if agora_ms > estado.0 {
+        estado.0 = agora_ms;
+        estado.1 = 0;
+        0
+    } else {
+        estado.1 += 1;
+        estado.1
+    };
+
+    Ok(format!("{:013}-{:06}-{}", estado.0, contador, node_id))
+}
+
+/// Registra uma mutação estruturada de pasta/item/tag: grava `payload_depois`
+/// (serializado em JSON pelo chamador) com HLC e, a cada `KEEP_STATE_EVERY`
+/// operações, materializa e grava um novo checkpoint para limitar o custo de
+/// `replay`.
+///
+/// `payload_antes`/`payload_depois` já devem vir serializados (normalmente
+/// via `serde_json::to_string`), para que o chamador controle exatamente o
+/// que entra no log sem acoplar este serviço ao formato de cada entidade.
+pub fn registrar_mutacao(
+    conexao: &Connection,
+    entidade_tipo: &str,
+    entidade_id: &str,
+    operacao: OperacaoMutacao,
+    payload_antes: Option<&str>,
+    payload_depois: Option<&str>,
+) -> Result<()> {
+    let hlc = proximo_hlc(conexao)?;
+    queries::registrar_operacao_auditoria(
+        conexao, entidade_tipo, entidade_id, operacao, &hlc, payload_antes, payload_depois,
+    )?;
+    talvez_criar_checkpoint(conexao, &hlc)?;
+    Ok(())
+}
+
+/// Se já se passaram `KEEP_STATE_EVERY` operações estruturadas desde o
+/// último checkpoint, materializa o estado atual e grava um novo. Chamado
+/// ao final de `registrar_mutacao` — o checkpoint sempre reflete um `hlc`
+/// real já gravado no log, nunca um estado "no meio" de uma operação.
+fn talvez_criar_checkpoint(conexao: &Connection, hlc_atual: &str) -> Result<()> {
+    let pendentes = queries::contar_operacoes_desde_ultimo_checkpoint(conexao)?;
+    if pendentes < KEEP_STATE_EVERY {
+        return Ok(());
+    }
+
+    let estado = materializar_estado_atual(conexao)?;
+    let estado_json = serde_json::to_string(&estado)
+        .context("Falha ao serializar estado materializado para checkpoint")?;
+    queries::criar_checkpoint_auditoria(conexao, hlc_atual, &estado_json)
+}
+
+/// Monta o `EstadoMaterializado` completo a partir das tabelas vivas —
+/// usado para gravar um checkpoint "do zero" a partir do estado atual do
+/// cofre, em vez de reconstruí-lo operação por operação.
+fn materializar_estado_atual(conexao: &Connection) -> Result<EstadoMaterializado> {
+    let mut estado = EstadoMaterializado::default();
+
+    for pasta in queries::listar_pastas(conexao)? {
+        for item in queries::listar_itens_por_pasta(conexao, &pasta.id)? {
+            estado.itens.insert(item.id.clone(), item);
+        }
+        estado.pastas.insert(pasta.id.clone(), pasta);
+    }
+
+    for tag in queries::listar_tags(conexao)? {
+        estado.tags.insert(tag.id.clone(), tag);
+    }
+
+    Ok(estado)
+}
+
+/// Reconstrói o `EstadoMaterializado` a partir do checkpoint mais recente
+/// (ou do zero, se não houver nenhum) e das operações estruturadas
+/// registradas depois dele, aplicadas em ordem de `hlc`. É a base para a
+/// futura fusão sem conflitos de dois cofres: como o HLC ordena
+/// totalmente, o replay produz o mesmo resultado não importa de qual
+/// dispositivo cada operação veio.
+pub fn replay(conexao: &Connection) -> Result<EstadoMaterializado> {
+    let checkpoint = queries::obter_ultimo_checkpoint_auditoria(conexao)?;
+
+    let (mut estado, desde_hlc) = match checkpoint {
+        Some(cp) => {
+            let estado: EstadoMaterializado = serde_json::from_str(&cp.estado)
+                .context("Checkpoint de auditoria corrompido (JSON inválido)")?;
+            (estado, Some(cp.hlc))
+        }
+        None => (EstadoMaterializado::default(), None),
+    };
+
+    let operacoes = queries::listar_operacoes_auditoria_apos(conexao, desde_hlc.as_deref())?;
+    for op in operacoes {
+        aplicar_operacao(&mut estado, &op.entidade_tipo, &op.entidade_id, op.operacao, op.payload_depois.as_deref())?;
+    }
+
+    Ok(estado)
+}
+
+/// Aplica uma única operação estruturada sobre o `EstadoMaterializado` em
+/// construção — usado por `replay`. "Exclusao" remove a entidade
+/// independente de `payload_depois`; "criacao"/"atualizacao" exigem o
+/// payload (o item/pasta/tag serializado) para inserir/substituir a
+/// entrada correspondente.
+fn aplicar_operacao(
+    estado: &mut EstadoMaterializado,
+    entidade_tipo: &str,
+    entidade_id: &str,
+    operacao: OperacaoMutacao,
+    payload_depois: Option<&str>,
+) -> Result<()> {
+    if operacao == OperacaoMutacao::Exclusao {
+        match entidade_tipo {
+            "pasta" => { estado.pastas.remove(entidade_id); }
+            "item" => { estado.itens.remove(entidade_id); }
+            "tag" => { estado.tags.remove(entidade_id); }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    let Some(payload) = payload_depois else {
+        return Ok(());
+    };
+
+    match entidade_tipo {
+        "pasta" => {
+            estado.pastas.insert(entidade_id.to_string(), serde_json::from_str(payload)
+                .context("Payload de pasta corrompido no log de auditoria")?);
+        }
+        "item" => {
+            estado.itens.insert(entidade_id.to_string(), serde_json::from_str(payload)
+                .context("Payload de item corrompido no log de auditoria")?);
+        }
+        "tag" => {
+            estado.tags.insert(entidade_id.to_string(), serde_json::from_str(payload)
+                .context("Payload de tag corrompido no log de auditoria")?);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// Point-in-time — reconstrução "como estava em" a partir do log de operações
+// =============================================================================
+// `replay` acima reconstrói o estado mais recente inteiro. As três funções
+// abaixo são o equivalente por item e por instante: em vez de aplicar cada
+// operação até o fim do log, `reconstruir_item_em` para no primeiro
+// `criado_em` após o instante pedido; `listar_historico_item` expõe a
+// trilha bruta de operações (os "diffs") para navegação; e
+// `reverter_item_para` reaplica um snapshot passado como uma *nova*
+// operação — o histórico nunca é editado, só estendido.
+// =============================================================================
+
+/// Reconstrói o `Item` como ele existia no instante `instante` (ISO 8601),
+/// dobrando (fold) as operações estruturadas do item registradas até esse
+/// ponto: cada "criacao"/"atualizacao" substitui o estado corrente pelo seu
+/// `payload_depois`; "exclusao" zera o estado (o item não existia naquele
+/// momento). Erra se o item nunca existiu até `instante`.
+pub fn reconstruir_item_em(conexao: &Connection, item_id: &str, instante: &str) -> Result<Item> {
+    let operacoes = queries::listar_operacoes_auditoria_da_entidade(conexao, "item", item_id, Some(instante))?;
+
+    let mut estado: Option<Item> = None;
+    for operacao in &operacoes {
+        match operacao.operacao {
+            OperacaoMutacao::Exclusao => estado = None,
+            OperacaoMutacao::Criacao | OperacaoMutacao::Atualizacao => {
+                let Some(payload) = &operacao.payload_depois else { continue };
+                estado = Some(serde_json::from_str(payload)
+                    .context("Payload de item corrompido no log de auditoria")?);
+            }
+        }
+    }
+
+    estado.ok_or_else(|| anyhow!("Item {} não existia em {}", item_id, instante))
+}
+
+/// Lista as operações estruturadas registradas para um item, em ordem
+/// cronológica — a trilha de diffs (`payload_antes`/`payload_depois` de
+/// cada evento) usada para navegar "como estava em" qualquer ponto do
+/// histórico do item.
+pub fn listar_historico_item(conexao: &Connection, item_id: &str) -> Result<Vec<OperacaoAuditoriaCompleta>> {
+    queries::listar_operacoes_auditoria_da_entidade(conexao, "item", item_id, None)
+}
+
+/// Reaplica o snapshot de um evento passado (`evento_id`, obtido de
+/// `listar_historico_item`) como o estado atual do item — uma nova
+/// mutação é registrada com esse snapshot como `payload_depois`; nenhuma
+/// linha de histórico existente é alterada ou removida.
+pub fn reverter_item_para(conexao: &Connection, item_id: &str, evento_id: &str) -> Result<Item> {
+    let operacao = queries::obter_operacao_auditoria_por_id(conexao, evento_id)?
+        .ok_or_else(|| anyhow!("Evento de auditoria '{}' não encontrado", evento_id))?;
+
+    let payload = operacao.payload_depois.ok_or_else(|| anyhow!(
+        "Evento de auditoria '{}' não tem um snapshot para reverter (provavelmente uma exclusão)", evento_id,
+    ))?;
+
+    let snapshot: Item = serde_json::from_str(&payload)
+        .context("Payload de item corrompido no log de auditoria")?;
+
+    if snapshot.id != item_id {
+        return Err(anyhow!("Evento de auditoria '{}' não pertence ao item {}", evento_id, item_id));
+    }
+
+    queries::atualizar_item(conexao, item_id, &AtualizacaoItem {
+        titulo: Some(snapshot.titulo),
+        descricao: snapshot.descricao,
+        conteudo_nota: snapshot.conteudo_nota,
+        data_vencimento: snapshot.data_vencimento,
+        pasta_id: Some(snapshot.pasta_id),
+        tag_ids: None,
+        regra_recorrencia: snapshot.regra_recorrencia,
+    })
+}
+
+/// Serializa qualquer entidade para uso como `payload_antes`/`payload_depois`
+/// em `registrar_mutacao` — atalho para os commands não precisarem importar
+/// `serde_json` só para isso.
+pub fn serializar_payload<T: Serialize>(valor: &T) -> Result<String> {
+    serde_json::to_string(valor).context("Falha ao serializar payload de auditoria")
+}
@@ -0,0 +1,251 @@
+// =============================================================================
+// VaultCraft — Backend Remoto HTTP para Backups Fragmentados (Chunks)
+// =============================================================================
+// Complementa `services::sincronizacao` (SFTP/SCP de um `.vcarch` inteiro)
+// com um backend HTTP/S3-like para o modo de backup incremental (ver
+// `services::backup::criar_backup_incremental`/`services::cdc`): em vez de
+// reenviar o backup inteiro a cada vez, envia só os chunks de conteúdo
+// endereçados por hash que o destino ainda não tem.
+//
+// Este módulo NUNCA transmite texto claro: os chunks em `dir_chunks` já são
+// fatias de um `.vaultbackup` (o banco SQLite e os anexos, como estão em
+// disco) — qualquer cifragem em repouso configurada (ver
+// `services::cifragem`) já está aplicada antes do conteúdo ser fragmentado,
+// e o nome de cada chunk é o SHA-256 do seu próprio conteúdo (ver
+// `services::cdc::fragmentar_arquivo`), então o remoto só enxerga blobs
+// opacos endereçados por hash, nunca um caminho ou nome de item do cofre.
+//
+// `BackendRemoto` é o ponto de extensão: `BackendHttp` é a única
+// implementação por ora (autenticação por bearer token, ver
+// `db::models::AutenticacaoRemota::Token`), mas qualquer outro backend
+// (outro provedor de objeto remoto) só precisa implementar os três métodos
+// para reusar `sincronizar`.
+//
+// "Resumível" aqui vem de graça da fragmentação em chunks pequenos
+// (`cdc::TAMANHO_MAXIMO` = 64KiB): `sincronizar` só envia os chunks que o
+// remoto ainda não tem, então uma sincronização interrompida a qualquer
+// momento (inclusive no meio do envio de um chunk) simplesmente retoma do
+// zero só os chunks faltantes na próxima chamada — não há necessidade de
+// range requests HTTP nem de acompanhar posição de byte dentro de um chunk
+// individual, já que cada chunk é pequeno o bastante para ser reenviado
+// inteiro sem custo real. Cada envio de chunk ainda tenta novamente algumas
+// vezes (`com_novas_tentativas`) antes de desistir, para tolerar falhas de
+// rede transitórias sem precisar de uma nova chamada a `sincronizar`.
+// =============================================================================
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+
+use crate::db::models::{AutenticacaoRemota, DestinoRemoto, ProtocoloRemoto, ResumoSincronizacaoRemota};
+
+const TIMEOUT_REQUISICAO: Duration = Duration::from_secs(30);
+const MAX_TENTATIVAS_ENVIO: u32 = 3;
+const ESPERA_ENTRE_TENTATIVAS: Duration = Duration::from_millis(500);
+
+/// Operações que um provedor de armazenamento remoto de chunks precisa
+/// oferecer para `sincronizar` funcionar. `BackendHttp` é a implementação
+/// padrão; um backend diferente (outro provedor de objeto remoto) só
+/// precisa implementar estes três métodos.
+pub trait BackendRemoto {
+    /// Envia o chunk de conteúdo `hash` (já o nome de arquivo em
+    /// `dir_chunks`, ver `cdc::fragmentar_arquivo`). Idempotente: enviar o
+    /// mesmo hash duas vezes deve ser um no-op do lado do remoto.
+    fn enviar_chunk(&self, hash: &str, dados: &[u8]) -> Result<()>;
+
+    /// Lista os hashes de chunk que o remoto já possui.
+    fn listar_chunks(&self) -> Result<HashSet<String>>;
+
+    /// Busca o conteúdo de um chunk pelo hash.
+    #[allow(dead_code)]
+    fn buscar_chunk(&self, hash: &str) -> Result<Vec<u8>>;
+}
+
+/// Backend HTTP/S3-like: fala REST com um endpoint configurado em
+/// `DestinoRemoto` (protocolo `Http`), autenticado por bearer token
+/// (`AutenticacaoRemota::Token`). Espera do endpoint três rotas, sob
+/// `{host}/{caminho_remoto}`:
+///   - `PUT  /chunks/<hash>`  — grava o chunk (corpo = bytes brutos)
+///   - `GET  /chunks`         — lista hashes já presentes (JSON: `["hash", ...]`)
+///   - `GET  /chunks/<hash>`  — lê o chunk (corpo = bytes brutos)
+pub struct BackendHttp {
+    endpoint_base: String,
+    prefixo: String,
+    token: String,
+    cliente: Client,
+}
+
+impl BackendHttp {
+    /// Constrói um backend a partir de um `DestinoRemoto` com
+    /// `protocolo: Http` e `autenticacao: Token`. Falha cedo se o bookmark
+    /// não for desse tipo — não faz sentido tentar adivinhar.
+    pub fn novo(destino: &DestinoRemoto) -> Result<Self> {
+        if destino.protocolo != ProtocoloRemoto::Http {
+            bail!("Destino '{}' não é um destino HTTP (protocolo: {:?})", destino.nome, destino.protocolo);
+        }
+        let AutenticacaoRemota::Token { token } = &destino.autenticacao else {
+            bail!("Destino HTTP '{}' requer autenticação por token", destino.nome);
+        };
+
+        let cliente = Client::builder()
+            .timeout(TIMEOUT_REQUISICAO)
+            .build()
+            .context("Falha ao criar cliente HTTP")?;
+
+        Ok(Self {
+            endpoint_base: destino.host.trim_end_matches('/').to_string(),
+            prefixo: destino.caminho_remoto.trim_matches('/').to_string(),
+            token: token.clone(),
+            cliente,
+        })
+    }
+
+    fn url_chunks(&self) -> String {
+        if self.prefixo.is_empty() {
+            format!("{}/chunks", self.endpoint_base)
+        } else {
+            format!("{}/{}/chunks", self.endpoint_base, self.prefixo)
+        }
+    }
+
+    fn url_chunk(&self, hash: &str) -> String {
+        format!("{}/{}", self.url_chunks(), hash)
+    }
+}
+
+impl BackendRemoto for BackendHttp {
+    fn enviar_chunk(&self, hash: &str, dados: &[u8]) -> Result<()> {
+        let resposta = self
+            .cliente
+            .put(self.url_chunk(hash))
+            .bearer_auth(&self.token)
+            .body(dados.to_vec())
+            .send()
+            .with_context(|| format!("Falha ao enviar chunk {} ao destino remoto", hash))?;
+
+        if !resposta.status().is_success() {
+            bail!("Destino remoto recusou o chunk {} (HTTP {})", hash, resposta.status());
+        }
+        Ok(())
+    }
+
+    fn listar_chunks(&self) -> Result<HashSet<String>> {
+        let resposta = self
+            .cliente
+            .get(self.url_chunks())
+            .bearer_auth(&self.token)
+            .send()
+            .context("Falha ao listar chunks no destino remoto")?;
+
+        if !resposta.status().is_success() {
+            bail!("Destino remoto recusou listar chunks (HTTP {})", resposta.status());
+        }
+
+        resposta
+            .json::<Vec<String>>()
+            .context("Resposta de listagem de chunks em formato inesperado")
+            .map(|hashes| hashes.into_iter().collect())
+    }
+
+    fn buscar_chunk(&self, hash: &str) -> Result<Vec<u8>> {
+        let resposta = self
+            .cliente
+            .get(self.url_chunk(hash))
+            .bearer_auth(&self.token)
+            .send()
+            .with_context(|| format!("Falha ao buscar chunk {} no destino remoto", hash))?;
+
+        if resposta.status() == StatusCode::NOT_FOUND {
+            bail!("Chunk {} não encontrado no destino remoto", hash);
+        }
+        if !resposta.status().is_success() {
+            bail!("Destino remoto recusou o chunk {} (HTTP {})", hash, resposta.status());
+        }
+
+        resposta.bytes().map(|b| b.to_vec()).context("Falha ao ler corpo da resposta do chunk")
+    }
+}
+
+/// Repete `operacao` até `tentativas` vezes, com uma pequena espera fixa
+/// entre tentativas, desistindo e propagando o último erro se nenhuma
+/// funcionar.
+fn com_novas_tentativas<T>(mut operacao: impl FnMut() -> Result<T>, tentativas: u32) -> Result<T> {
+    let mut ultimo_erro = None;
+    for tentativa in 1..=tentativas {
+        match operacao() {
+            Ok(valor) => return Ok(valor),
+            Err(erro) => {
+                ultimo_erro = Some(erro);
+                if tentativa < tentativas {
+                    thread::sleep(ESPERA_ENTRE_TENTATIVAS);
+                }
+            }
+        }
+    }
+    Err(ultimo_erro.expect("com_novas_tentativas sempre executa ao menos uma tentativa"))
+}
+
+/// Lista os hashes de todos os chunks presentes localmente em `dir_chunks`
+/// (layout em subdiretórios de 2 caracteres, ver `cdc::fragmentar_arquivo`).
+fn listar_chunks_locais(dir_chunks: &Path) -> Result<HashSet<String>> {
+    let mut hashes = HashSet::new();
+    if !dir_chunks.exists() {
+        return Ok(hashes);
+    }
+
+    for subdir in fs::read_dir(dir_chunks).context("Falha ao listar repositório de chunks")? {
+        let subdir = subdir.context("Falha ao ler subdiretório de chunks")?;
+        if !subdir.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        for arquivo in fs::read_dir(subdir.path()).context("Falha ao listar subdiretório de chunks")? {
+            let arquivo = arquivo.context("Falha ao ler chunk")?;
+            hashes.insert(arquivo.file_name().to_string_lossy().to_string());
+        }
+    }
+
+    Ok(hashes)
+}
+
+/// Reconcilia o repositório local de chunks (`dir_chunks`, ver
+/// `services::backup::criar_backup_incremental`) com o que `backend` já
+/// possui, enviando só os que faltam. Não apaga nada local nem remoto —
+/// isso é responsabilidade de `backup::podar_chunks_nao_referenciados`.
+pub fn sincronizar(backend: &dyn BackendRemoto, dir_chunks: &Path) -> Result<ResumoSincronizacaoRemota> {
+    let locais = listar_chunks_locais(dir_chunks)?;
+    let remotos = backend.listar_chunks().context("Falha ao reconciliar com o destino remoto")?;
+
+    let faltantes: Vec<&String> = locais.iter().filter(|hash| !remotos.contains(*hash)).collect();
+
+    let mut enviados = 0usize;
+    let mut falhas = Vec::new();
+
+    for hash in &faltantes {
+        let caminho = dir_chunks.join(&hash[0..2]).join(hash.as_str());
+        let dados = match fs::read(&caminho) {
+            Ok(dados) => dados,
+            Err(erro) => {
+                falhas.push(((*hash).clone(), erro.to_string()));
+                continue;
+            }
+        };
+
+        match com_novas_tentativas(|| backend.enviar_chunk(hash, &dados), MAX_TENTATIVAS_ENVIO) {
+            Ok(()) => enviados += 1,
+            Err(erro) => falhas.push(((*hash).clone(), format!("{:#}", erro))),
+        }
+    }
+
+    Ok(ResumoSincronizacaoRemota {
+        total_local: locais.len(),
+        ja_no_remoto: locais.len() - faltantes.len(),
+        enviados,
+        falhas,
+    })
+}
@@ -12,24 +12,48 @@
 // - O manifesto permite validar a integridade antes de restaurar
 // - Antes de restaurar, um backup automático do estado atual é criado
 // - Pacotes de pasta permitem exportar/importar partes do cofre
+// - `criar_backup_incremental`/`restaurar_backup_incremental` oferecem uma
+//   variante deduplicada por chunks (ver services::cdc) para quem faz
+//   backups frequentes do mesmo cofre
+// - `criar_backup` aceita opcionalmente um backup de referência (diferencial):
+//   anexos inalterados não são regravados, só referenciados no manifesto
+// - `criar_backup`/`exportar_pacote_pasta` aceitam uma senha opcional para
+//   cifrar o ZIP inteiro em repouso (ver crypto::backup); o ZIP é montado em
+//   memória e só então, opcionalmente, cifrado e gravado em disco
+// - `exportar_pasta_tar`/`importar_pasta_tar` exportam uma pasta como `.tar`
+//   padrão (em vez do ZIP de formato próprio de `exportar_pacote_pasta`),
+//   para interoperar com ferramentas externas
 // =============================================================================
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{Datelike, NaiveDateTime, Utc};
 use log::info;
 use rusqlite::Connection;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{Read, Write};
+use std::io::{Cursor, Read, Write};
 use std::path::{Path, PathBuf};
+use tar::{Archive, Builder, Header};
+use uuid::Uuid;
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
+use crate::crypto::backup as cripto_backup;
 use crate::db::migrations::versao_mais_recente;
-use crate::db::models::ManifestoBackup;
+use crate::db::models::{
+    BackupPai, CompressaoBackup, CompressaoTar, ManifestoBackup, ManifestoBackupFragmentado,
+    OpcoesVerificacao, PoliticaPoda, RelatorioIntegridade,
+};
 use crate::db::queries;
 use crate::services::armazenamento;
+use crate::services::cdc;
+use crate::services::compressao;
+
+/// ZIP já lido (e decifrado, se necessário) inteiramente em memória —
+/// todas as leituras de `.vaultbackup` passam por aqui para que arquivos
+/// cifrados possam ser decifrados de uma vez antes do `zip` crate os abrir.
+type LeitorZip = zip::ZipArchive<Cursor<Vec<u8>>>;
 
 // =============================================================================
 // CRIACAO DE BACKUP
@@ -42,17 +66,46 @@ use crate::services::armazenamento;
 /// - anexos/{caminho_interno}: todos os arquivos anexos
 /// - manifesto.json: metadados com hashes SHA-256 para verificação
 ///
-/// O backup é atômico: criamos em um arquivo temporário e renomeamos ao final.
+/// `codec` escolhe a compressão das entradas `banco.sqlite`/`anexos/*`
+/// (ver `services::compressao`); a escolha fica registrada no manifesto
+/// para que `restaurar_backup` saiba como descomprimir de volta.
+///
+/// `backup_referencia`, se informado, torna este um backup diferencial:
+/// anexos cujo hash SHA-256 já existe, inalterado, no manifesto do backup
+/// referenciado não têm seus bytes regravados — apenas uma referência ao
+/// caminho interno é registrada em `ManifestoBackup::anexos_referenciados`.
+/// `restaurar_backup` precisa receber o mesmo arquivo pai para resolvê-los.
+/// Se `backup_referencia` estiver cifrado, `senha` também é usada para abri-lo.
+/// `banco.sqlite` é sempre incluído por completo (não é deduplicado).
+///
+/// `senha`, se informada, cifra o ZIP inteiro em repouso com
+/// XChaCha20-Poly1305 (chave derivada com Argon2id) — ver `crypto::backup`.
+/// Sem senha, o arquivo é gravado como ZIP em texto claro (comportamento
+/// histórico). O ZIP é montado em memória antes de ser (opcionalmente)
+/// cifrado e só então gravado em disco.
 pub fn criar_backup(
     diretorio_app: &Path,
     conexao: &Connection,
     destino: &Path,
+    codec: &CompressaoBackup,
+    backup_referencia: Option<&Path>,
+    senha: Option<&str>,
 ) -> Result<PathBuf> {
     let agora = Utc::now().format("%Y%m%d_%H%M%S").to_string();
     let nome_arquivo = format!("vaultcraft_backup_{}.vaultbackup", agora);
     let caminho_backup = destino.join(&nome_arquivo);
 
-    info!("Criando backup em: {:?}", caminho_backup);
+    info!(
+        "Criando backup em: {:?} (compressão: {:?}, cifrado: {})",
+        caminho_backup, codec, senha.is_some()
+    );
+
+    // Se informado, carregar o manifesto do backup pai para saber quais
+    // anexos já estão presentes lá (e podem ser apenas referenciados).
+    let manifesto_pai = backup_referencia
+        .map(|caminho_pai| ler_manifesto_de_arquivo(caminho_pai, senha))
+        .transpose()
+        .context("Falha ao ler manifesto do backup de referência")?;
 
     // Garantir que o diretório de destino existe
     if let Some(dir_pai) = caminho_backup.parent() {
@@ -60,11 +113,11 @@ pub fn criar_backup(
             .context("Falha ao criar diretório de destino do backup")?;
     }
 
-    // Criar arquivo ZIP
-    let arquivo_zip = fs::File::create(&caminho_backup)
-        .context("Falha ao criar arquivo de backup")?;
-    let mut zip = ZipWriter::new(arquivo_zip);
+    // Montar o ZIP em memória — só é gravado em disco (cifrado ou não) ao final
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
     let opcoes = SimpleFileOptions::default()
+        .compression_method(compressao::metodo_zip(codec));
+    let opcoes_manifesto = SimpleFileOptions::default()
         .compression_method(zip::CompressionMethod::Deflated);
 
     // 1. Adicionar o banco de dados ao backup
@@ -72,18 +125,20 @@ pub fn criar_backup(
     let conteudo_banco = fs::read(&caminho_banco)
         .context("Falha ao ler banco de dados para backup")?;
 
-    // Calcular hash do banco
+    // Calcular hash do banco (sobre o conteúdo original, antes de comprimir)
     let hash_banco = calcular_hash_bytes(&conteudo_banco);
+    let conteudo_banco_comprimido = compressao::comprimir(codec, &conteudo_banco)?;
 
     zip.start_file("banco.sqlite", opcoes)
         .context("Falha ao iniciar arquivo banco.sqlite no ZIP")?;
-    zip.write_all(&conteudo_banco)
+    zip.write_all(&conteudo_banco_comprimido)
         .context("Falha ao escrever banco.sqlite no ZIP")?;
 
     // 2. Adicionar todos os anexos
     let dir_anexos = armazenamento::obter_diretorio_armazenamento(diretorio_app);
     let caminhos_anexos = queries::listar_caminhos_anexos(conexao)?;
     let mut hashes_anexos: HashMap<String, String> = HashMap::new();
+    let mut anexos_referenciados: Vec<String> = Vec::new();
 
     for caminho_interno in &caminhos_anexos {
         let caminho_completo = dir_anexos.join(caminho_interno);
@@ -92,12 +147,25 @@ pub fn criar_backup(
                 .with_context(|| format!("Falha ao ler anexo: {:?}", caminho_completo))?;
 
             let hash = calcular_hash_bytes(&conteudo);
-            hashes_anexos.insert(caminho_interno.clone(), hash);
+            hashes_anexos.insert(caminho_interno.clone(), hash.clone());
+
+            // Se o pai já tem este anexo com o mesmo hash, apenas referenciar.
+            let ja_existe_no_pai = manifesto_pai
+                .as_ref()
+                .and_then(|m| m.hashes_anexos.get(caminho_interno))
+                .is_some_and(|hash_pai| hash_pai == &hash);
+
+            if ja_existe_no_pai {
+                anexos_referenciados.push(caminho_interno.clone());
+                continue;
+            }
+
+            let conteudo_comprimido = compressao::comprimir(codec, &conteudo)?;
 
             let caminho_no_zip = format!("anexos/{}", caminho_interno);
             zip.start_file(&caminho_no_zip, opcoes)
                 .with_context(|| format!("Falha ao adicionar anexo ao ZIP: {}", caminho_interno))?;
-            zip.write_all(&conteudo)
+            zip.write_all(&conteudo_comprimido)
                 .context("Falha ao escrever anexo no ZIP")?;
         }
     }
@@ -106,6 +174,17 @@ pub fn criar_backup(
     let total_itens = queries::contar_itens(conexao)?;
     let total_anexos = queries::contar_anexos(conexao)?;
 
+    let backup_pai = match (backup_referencia, &manifesto_pai) {
+        (Some(caminho_pai), Some(manifesto_pai)) => Some(BackupPai {
+            arquivo: caminho_pai
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            hash_banco: manifesto_pai.hash_banco.clone(),
+        }),
+        _ => None,
+    };
+
     let manifesto = ManifestoBackup {
         versao_app: env!("CARGO_PKG_VERSION").to_string(),
         versao_schema: versao_mais_recente(),
@@ -114,23 +193,27 @@ pub fn criar_backup(
         total_anexos,
         hash_banco,
         hashes_anexos,
+        compressao: codec.clone(),
+        backup_pai,
+        anexos_referenciados,
     };
 
     let manifesto_json = serde_json::to_string_pretty(&manifesto)
         .context("Falha ao serializar manifesto")?;
 
-    zip.start_file("manifesto.json", opcoes)
+    zip.start_file("manifesto.json", opcoes_manifesto)
         .context("Falha ao adicionar manifesto ao ZIP")?;
     zip.write_all(manifesto_json.as_bytes())
         .context("Falha ao escrever manifesto no ZIP")?;
 
-    // Finalizar o ZIP
-    zip.finish()
+    // Finalizar o ZIP em memória e gravar (cifrado, se houver senha) em disco
+    let cursor_zip = zip.finish()
         .context("Falha ao finalizar arquivo ZIP de backup")?;
+    escrever_arquivo_backup(&caminho_backup, cursor_zip.into_inner(), senha)?;
 
     info!(
-        "Backup criado com sucesso: {:?} ({} itens, {} anexos)",
-        caminho_backup, total_itens, total_anexos
+        "Backup criado com sucesso: {:?} ({} itens, {} anexos, {} referenciados do pai)",
+        caminho_backup, total_itens, total_anexos, manifesto.anexos_referenciados.len()
     );
 
     Ok(caminho_backup)
@@ -143,26 +226,33 @@ pub fn criar_backup(
 /// Restaura um backup do cofre a partir de um arquivo .vaultbackup.
 ///
 /// Processo:
-/// 1. Abre e valida o arquivo ZIP
+/// 1. Abre o arquivo (decifrando-o primeiro, se protegido por senha) e valida o ZIP
 /// 2. Lê e valida o manifesto
 /// 3. Cria backup automático do estado atual (segurança)
 /// 4. Restaura o banco de dados
 /// 5. Restaura os anexos
 /// 6. Verifica hashes de integridade
 ///
-/// Se qualquer etapa falhar, o backup automático permite recuperação.
+/// Se qualquer etapa falhar, o backup automático permite recuperação. Em
+/// particular, se `arquivo` estiver cifrado e a senha estiver incorreta (ou
+/// ausente), a função retorna erro na etapa 1 — antes do backup automático
+/// e de qualquer alteração no cofre atual.
+///
+/// `arquivo_pai` deve ser informado quando `arquivo` é um backup diferencial
+/// (`ManifestoBackup::anexos_referenciados` não vazio) — é de lá que os
+/// anexos não regravados neste arquivo são extraídos. Ignorado para backups
+/// completos. Se o arquivo pai também estiver cifrado, usa-se a mesma `senha`.
 pub fn restaurar_backup(
     diretorio_app: &Path,
     conexao: &Connection,
     arquivo: &Path,
+    arquivo_pai: Option<&Path>,
+    senha: Option<&str>,
 ) -> Result<()> {
     info!("Restaurando backup de: {:?}", arquivo);
 
-    // Abrir o arquivo ZIP
-    let arquivo_zip = fs::File::open(arquivo)
-        .context("Falha ao abrir arquivo de backup")?;
-    let mut zip = zip::ZipArchive::new(arquivo_zip)
-        .context("Arquivo de backup inválido (não é ZIP válido)")?;
+    // Abrir o arquivo (decifra primeiro, se necessário) e validar o ZIP
+    let mut zip = abrir_leitura_zip(arquivo, senha)?;
 
     // 1. Ler e validar manifesto
     let manifesto = ler_manifesto_do_zip(&mut zip)?;
@@ -179,8 +269,19 @@ pub fn restaurar_backup(
 
     // Tentar criar backup do estado atual (não falhar se não conseguir —
     // pode ser a primeira execução sem dados)
-    match criar_backup(diretorio_app, conexao, &dir_backups_auto) {
-        Ok(caminho) => info!("Backup automático criado: {:?}", caminho),
+    match criar_backup(diretorio_app, conexao, &dir_backups_auto, &CompressaoBackup::default(), None, None) {
+        Ok(caminho) => {
+            info!("Backup automático criado: {:?}", caminho);
+            // Remover backups automáticos antigos além da política de retenção
+            // padrão, para que este diretório não cresça sem limite.
+            match podar_backups_automaticos(&dir_backups_auto, &PoliticaPoda::default()) {
+                Ok(removidos) if !removidos.is_empty() => {
+                    info!("Poda de backups automáticos removeu {} arquivo(s) antigos", removidos.len())
+                }
+                Ok(_) => {}
+                Err(e) => info!("Aviso: falha ao podar backups automáticos antigos: {}", e),
+            }
+        }
         Err(e) => info!("Aviso: não foi possível criar backup automático: {}", e),
     }
 
@@ -188,11 +289,13 @@ pub fn restaurar_backup(
     let mut banco_arquivo = zip.by_name("banco.sqlite")
         .context("Arquivo banco.sqlite não encontrado no backup")?;
 
-    let mut conteudo_banco = Vec::new();
-    banco_arquivo.read_to_end(&mut conteudo_banco)
+    let mut conteudo_banco_bruto = Vec::new();
+    banco_arquivo.read_to_end(&mut conteudo_banco_bruto)
         .context("Falha ao ler banco.sqlite do backup")?;
     drop(banco_arquivo); // Liberar empréstimo do zip
 
+    let conteudo_banco = compressao::descomprimir(&manifesto.compressao, &conteudo_banco_bruto)?;
+
     // Verificar hash do banco
     let hash_banco = calcular_hash_bytes(&conteudo_banco);
     if hash_banco != manifesto.hash_banco {
@@ -202,15 +305,61 @@ pub fn restaurar_backup(
         );
     }
 
-    // Escrever o banco restaurado
+    // 4. Verificar integridade de banco e anexos ANTES de tocar no cofre
+    // atual — reaproveita `verificar_backup` (mesma lógica usada para checar
+    // um backup sem restaurar) em vez de duplicar a comparação de hashes.
+    // Se algo estiver corrompido ou faltando, o backup inteiro é rejeitado e
+    // nada no cofre atual chega a ser sobrescrito.
+    let relatorio = verificar_backup(diretorio_app, arquivo, &OpcoesVerificacao::default(), senha)?;
+
+    if !relatorio.ok {
+        let dir_quarentena = diretorio_app
+            .join("quarentena")
+            .join(Utc::now().format("%Y%m%d_%H%M%S").to_string());
+        fs::create_dir_all(&dir_quarentena)
+            .context("Falha ao criar diretório de quarentena")?;
+
+        for nome_no_zip in &relatorio.corrompidos {
+            if let Some(caminho_relativo) = nome_no_zip.strip_prefix("anexos/") {
+                if let Ok(mut entrada) = zip.by_name(nome_no_zip) {
+                    let mut conteudo_bruto = Vec::new();
+                    if entrada.read_to_end(&mut conteudo_bruto).is_ok() {
+                        drop(entrada);
+                        if let Ok(conteudo) = compressao::descomprimir(&manifesto.compressao, &conteudo_bruto) {
+                            let destino = dir_quarentena.join(caminho_relativo);
+                            if let Some(dir_pai) = destino.parent() {
+                                let _ = fs::create_dir_all(dir_pai);
+                            }
+                            let _ = fs::write(&destino, &conteudo);
+                        }
+                    }
+                }
+            }
+        }
+
+        anyhow::bail!(
+            "Backup rejeitado por falha de integridade: {} anexo(s) ausente(s), \
+             {} anexo(s) corrompido(s) (copiados para {:?} para inspeção). \
+             Ausentes: {:?}. Corrompidos: {:?}",
+            relatorio.ausentes.len(), relatorio.corrompidos.len(), dir_quarentena,
+            relatorio.ausentes, relatorio.corrompidos
+        );
+    }
+
+    if !relatorio.orfaos.is_empty() {
+        info!(
+            "Aviso: {} anexo(s) no backup não estavam listados no manifesto (restaurados mesmo assim): {:?}",
+            relatorio.orfaos.len(), relatorio.orfaos
+        );
+    }
+
+    // Integridade confirmada — agora sim pode sobrescrever o cofre atual.
     let caminho_banco = diretorio_app.join("vaultcraft.db");
     fs::write(&caminho_banco, &conteudo_banco)
         .context("Falha ao restaurar banco de dados")?;
 
-    // 4. Restaurar anexos
     let dir_anexos = armazenamento::obter_diretorio_armazenamento(diretorio_app);
 
-    // Limpar anexos existentes antes de restaurar
     if dir_anexos.exists() {
         fs::remove_dir_all(&dir_anexos)
             .context("Falha ao limpar diretório de anexos")?;
@@ -218,41 +367,80 @@ pub fn restaurar_backup(
     fs::create_dir_all(&dir_anexos)
         .context("Falha ao recriar diretório de anexos")?;
 
-    // Extrair cada anexo do ZIP
     for i in 0..zip.len() {
-        let mut entrada = zip.by_index(i)
-            .context("Falha ao ler entrada do ZIP")?;
-
+        let mut entrada = zip.by_index(i).context("Falha ao ler entrada do ZIP")?;
         let nome = entrada.name().to_string();
-        if nome.starts_with("anexos/") && !entrada.is_dir() {
-            // Extrair o caminho relativo (sem o prefixo "anexos/")
-            let caminho_relativo = nome.strip_prefix("anexos/").unwrap_or(&nome);
-            let caminho_destino = dir_anexos.join(caminho_relativo);
+        if !nome.starts_with("anexos/") || entrada.is_dir() {
+            continue;
+        }
+        let caminho_relativo = nome.strip_prefix("anexos/").unwrap_or(&nome);
+        let caminho_destino = dir_anexos.join(caminho_relativo);
+
+        if let Some(dir_pai) = caminho_destino.parent() {
+            fs::create_dir_all(dir_pai)
+                .with_context(|| format!("Falha ao criar diretório: {:?}", dir_pai))?;
+        }
+
+        let mut conteudo_bruto = Vec::new();
+        entrada.read_to_end(&mut conteudo_bruto)
+            .with_context(|| format!("Falha ao ler anexo do ZIP: {}", nome))?;
+        let conteudo = compressao::descomprimir(&manifesto.compressao, &conteudo_bruto)?;
+
+        fs::write(&caminho_destino, &conteudo)
+            .with_context(|| format!("Falha ao restaurar anexo: {:?}", caminho_destino))?;
+    }
 
-            // Criar diretórios necessários
-            if let Some(dir_pai) = caminho_destino.parent() {
-                fs::create_dir_all(dir_pai)
-                    .with_context(|| format!("Falha ao criar diretório: {:?}", dir_pai))?;
+    // 5. Resolver anexos referenciados (backup diferencial): não estão
+    // neste arquivo, precisam ser extraídos do backup pai.
+    if !manifesto.anexos_referenciados.is_empty() {
+        let caminho_pai = arquivo_pai.context(
+            "Este backup é diferencial e referencia anexos de um backup pai, \
+             mas nenhum arquivo pai foi informado para restauração",
+        )?;
+
+        let mut zip_pai = abrir_leitura_zip(caminho_pai, senha)
+            .context("Falha ao abrir backup pai")?;
+        let manifesto_pai = ler_manifesto_do_zip(&mut zip_pai)?;
+
+        if let Some(esperado) = &manifesto.backup_pai {
+            if esperado.hash_banco != manifesto_pai.hash_banco {
+                anyhow::bail!(
+                    "O arquivo pai informado não corresponde ao backup pai original \
+                     (hash do banco diverge)"
+                );
             }
+        }
 
-            // Escrever o arquivo
-            let mut conteudo = Vec::new();
-            entrada.read_to_end(&mut conteudo)
-                .with_context(|| format!("Falha ao ler anexo do ZIP: {}", nome))?;
+        for caminho_relativo in &manifesto.anexos_referenciados {
+            let caminho_no_zip = format!("anexos/{}", caminho_relativo);
+            let mut entrada = zip_pai.by_name(&caminho_no_zip).with_context(|| {
+                format!("Anexo referenciado '{}' não encontrado no backup pai", caminho_relativo)
+            })?;
+
+            let mut conteudo_bruto = Vec::new();
+            entrada.read_to_end(&mut conteudo_bruto)
+                .with_context(|| format!("Falha ao ler anexo do backup pai: {}", caminho_relativo))?;
+            drop(entrada);
+            let conteudo = compressao::descomprimir(&manifesto_pai.compressao, &conteudo_bruto)?;
 
-            // Verificar hash se disponível no manifesto
             if let Some(hash_esperado) = manifesto.hashes_anexos.get(caminho_relativo) {
                 let hash_obtido = calcular_hash_bytes(&conteudo);
                 if &hash_obtido != hash_esperado {
-                    info!(
-                        "Aviso: hash do anexo {} não confere (esperado: {}, obtido: {})",
+                    anyhow::bail!(
+                        "Backup rejeitado: anexo referenciado '{}' do backup pai está corrompido \
+                         (esperado: {}, obtido: {})",
                         caminho_relativo, hash_esperado, hash_obtido
                     );
                 }
             }
 
+            let caminho_destino = dir_anexos.join(caminho_relativo);
+            if let Some(dir_pai_destino) = caminho_destino.parent() {
+                fs::create_dir_all(dir_pai_destino)
+                    .with_context(|| format!("Falha ao criar diretório: {:?}", dir_pai_destino))?;
+            }
             fs::write(&caminho_destino, &conteudo)
-                .with_context(|| format!("Falha ao restaurar anexo: {:?}", caminho_destino))?;
+                .with_context(|| format!("Falha ao restaurar anexo referenciado: {:?}", caminho_destino))?;
         }
     }
 
@@ -267,11 +455,17 @@ pub fn restaurar_backup(
 /// Exporta uma pasta específica como pacote .vaultbackup.
 /// Inclui a pasta, seus itens e anexos associados.
 /// Diferente do backup completo, exporta apenas parte do cofre.
+///
+/// `codec` escolhe a compressão dos anexos (ver `services::compressao`);
+/// a escolha fica registrada no manifesto para `importar_pacote`.
+/// `senha`, se informada, cifra o pacote inteiro (ver `criar_backup`).
 pub fn exportar_pacote_pasta(
     diretorio_app: &Path,
     conexao: &Connection,
     pasta_id: &str,
     destino: &Path,
+    codec: &CompressaoBackup,
+    senha: Option<&str>,
 ) -> Result<PathBuf> {
     let pasta = queries::obter_pasta_por_id(conexao, pasta_id)?;
     let agora = Utc::now().format("%Y%m%d_%H%M%S").to_string();
@@ -283,11 +477,11 @@ pub fn exportar_pacote_pasta(
 
     info!("Exportando pacote da pasta '{}' para: {:?}", pasta.nome, caminho_pacote);
 
-    let arquivo_zip = fs::File::create(&caminho_pacote)
-        .context("Falha ao criar arquivo de pacote")?;
-    let mut zip = ZipWriter::new(arquivo_zip);
-    let opcoes = SimpleFileOptions::default()
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let opcoes_manifesto = SimpleFileOptions::default()
         .compression_method(zip::CompressionMethod::Deflated);
+    let opcoes_anexos = SimpleFileOptions::default()
+        .compression_method(compressao::metodo_zip(codec));
 
     // Coletar todos os itens da pasta (e subpastas)
     let itens = queries::listar_itens_completos_da_pasta(conexao, pasta_id)?;
@@ -305,7 +499,7 @@ pub fn exportar_pacote_pasta(
     let dados_json = serde_json::to_string_pretty(&dados_pasta)
         .context("Falha ao serializar dados da pasta")?;
 
-    zip.start_file("dados.json", opcoes)?;
+    zip.start_file("dados.json", opcoes_manifesto)?;
     zip.write_all(dados_json.as_bytes())?;
 
     // Adicionar anexos dos itens
@@ -316,10 +510,11 @@ pub fn exportar_pacote_pasta(
                 let conteudo = fs::read(&caminho_completo)?;
                 let hash = calcular_hash_bytes(&conteudo);
                 hashes_anexos.insert(anexo.caminho_interno.clone(), hash);
+                let conteudo_comprimido = compressao::comprimir(codec, &conteudo)?;
 
                 let caminho_no_zip = format!("anexos/{}", anexo.caminho_interno);
-                zip.start_file(&caminho_no_zip, opcoes)?;
-                zip.write_all(&conteudo)?;
+                zip.start_file(&caminho_no_zip, opcoes_anexos)?;
+                zip.write_all(&conteudo_comprimido)?;
             }
         }
     }
@@ -333,13 +528,17 @@ pub fn exportar_pacote_pasta(
         total_anexos: hashes_anexos.len() as i64,
         hash_banco: String::new(), // Pacotes não incluem banco completo
         hashes_anexos,
+        compressao: codec.clone(),
+        backup_pai: None,
+        anexos_referenciados: Vec::new(),
     };
 
     let manifesto_json = serde_json::to_string_pretty(&manifesto)?;
-    zip.start_file("manifesto.json", opcoes)?;
+    zip.start_file("manifesto.json", opcoes_manifesto)?;
     zip.write_all(manifesto_json.as_bytes())?;
 
-    zip.finish()?;
+    let cursor_zip = zip.finish()?;
+    escrever_arquivo_backup(&caminho_pacote, cursor_zip.into_inner(), senha)?;
 
     info!(
         "Pacote exportado: {:?} ({} itens)",
@@ -351,17 +550,20 @@ pub fn exportar_pacote_pasta(
 
 /// Importa um pacote de pasta para o cofre.
 /// Se houver conflitos de nome, adiciona sufixo "(importado)".
+/// `senha` é necessária se o pacote foi exportado com `senha` (ver `exportar_pacote_pasta`).
 pub fn importar_pacote(
     diretorio_app: &Path,
     conexao: &Connection,
     arquivo: &Path,
+    senha: Option<&str>,
 ) -> Result<()> {
     info!("Importando pacote de: {:?}", arquivo);
 
-    let arquivo_zip = fs::File::open(arquivo)
+    let mut zip = abrir_leitura_zip(arquivo, senha)
         .context("Falha ao abrir arquivo de pacote")?;
-    let mut zip = zip::ZipArchive::new(arquivo_zip)
-        .context("Arquivo de pacote inválido")?;
+
+    // Ler manifesto.json para saber qual codec de compressão os anexos usam
+    let manifesto = ler_manifesto_do_zip(&mut zip)?;
 
     // Ler dados.json do pacote
     let mut dados_arquivo = zip.by_name("dados.json")
@@ -403,6 +605,7 @@ pub fn importar_pacote(
                 conteudo_nota: item_json["conteudo_nota"].as_str().map(|s| s.to_string()),
                 data_vencimento: item_json["data_vencimento"].as_str().map(|s| s.to_string()),
                 tag_ids: None,
+                regra_recorrencia: item_json["regra_recorrencia"].as_str().map(|s| s.to_string()),
             };
 
             let item_criado = queries::criar_item(conexao, &novo_item)?;
@@ -419,9 +622,21 @@ pub fn importar_pacote(
 
                         // Extrair anexo do ZIP para armazenamento
                         if let Ok(mut entrada) = zip.by_name(&caminho_no_zip) {
-                            let mut conteudo = Vec::new();
-                            entrada.read_to_end(&mut conteudo)?;
+                            let mut conteudo_bruto = Vec::new();
+                            entrada.read_to_end(&mut conteudo_bruto)?;
                             drop(entrada);
+                            let conteudo = compressao::descomprimir(&manifesto.compressao, &conteudo_bruto)?;
+
+                            if let Some(hash_esperado) = manifesto.hashes_anexos.get(caminho_interno_original) {
+                                let hash_obtido = calcular_hash_bytes(&conteudo);
+                                if &hash_obtido != hash_esperado {
+                                    anyhow::bail!(
+                                        "Pacote rejeitado: anexo '{}' está corrompido \
+                                         (esperado: {}, obtido: {})",
+                                        caminho_interno_original, hash_esperado, hash_obtido
+                                    );
+                                }
+                            }
 
                             // Salvar no armazenamento com novo UUID
                             let id_anexo = uuid::Uuid::new_v4().to_string();
@@ -454,6 +669,12 @@ pub fn importar_pacote(
                             };
 
                             queries::criar_anexo(conexao, &anexo)?;
+                        } else {
+                            anyhow::bail!(
+                                "Pacote rejeitado: anexo '{}' referenciado em dados.json \
+                                 não foi encontrado no arquivo",
+                                caminho_interno_original
+                            );
                         }
                     }
                 }
@@ -465,6 +686,881 @@ pub fn importar_pacote(
     Ok(())
 }
 
+// =============================================================================
+// EXPORTACAO/IMPORTACAO DE PASTA COMO TAR (INTEROPERAVEL)
+// =============================================================================
+// `exportar_pacote_pasta`/`importar_pacote` usam um ZIP com `dados.json` +
+// `anexos/`: um formato próprio que só o próprio VaultCraft sabe ler de
+// volta. `exportar_pasta_tar`/`importar_pasta_tar` produzem/consomem um
+// `.tar` padrão (opcionalmente comprimido com Zstandard ou LZ4) em que cada
+// item vira arquivos legíveis (`item.json` + `nota.md`, quando aplicável) e
+// cada anexo é gravado sob seu nome original — qualquer ferramenta de
+// arquivo do sistema ou outro pipeline de backup consegue abrir o export
+// sem precisar entender o formato do VaultCraft.
+//
+// Diferente do pacote ZIP, não há manifesto de hashes nem cifragem: o
+// objetivo aqui é interoperabilidade, não verificação de integridade.
+// =============================================================================
+
+/// Exporta uma pasta (e seus itens/anexos) como um `.tar` padrão, legível
+/// por qualquer ferramenta de arquivo — ao contrário de `exportar_pacote_pasta`,
+/// que usa um ZIP de formato próprio só lido de volta por `importar_pacote`.
+///
+/// Cada item vira uma entrada `itens/<slug>/item.json` com seus metadados,
+/// mais `itens/<slug>/nota.md` com o conteúdo quando o item é uma Nota.
+/// Cada anexo é gravado em `itens/<slug>/anexos/<nome_original>`.
+///
+/// `codec` comprime o `.tar` inteiro como um único fluxo (resultando em
+/// `.tar`/`.tar.zst`/`.tar.lz4`), ao contrário de `CompressaoBackup`, que
+/// comprime cada entrada do ZIP individualmente.
+pub fn exportar_pasta_tar(
+    diretorio_app: &Path,
+    conexao: &Connection,
+    pasta_id: &str,
+    destino: &Path,
+    codec: &CompressaoTar,
+) -> Result<PathBuf> {
+    let pasta = queries::obter_pasta_por_id(conexao, pasta_id)?;
+    let agora = Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let nome_limpo = pasta.nome.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_");
+    let nome_arquivo = format!("vaultcraft_pasta_{}_{}.{}", nome_limpo, agora, extensao_tar(codec));
+    let caminho_tar = destino.join(&nome_arquivo);
+
+    info!("Exportando pasta '{}' como tar para: {:?}", pasta.nome, caminho_tar);
+
+    if let Some(dir_pai) = caminho_tar.parent() {
+        fs::create_dir_all(dir_pai).context("Falha ao criar diretório de destino do tar")?;
+    }
+
+    let itens = queries::listar_itens_completos_da_pasta(conexao, pasta_id)?;
+    let dir_anexos = armazenamento::obter_diretorio_armazenamento(diretorio_app);
+
+    let mut buffer_tar = Vec::new();
+    {
+        let mut construtor = Builder::new(&mut buffer_tar);
+
+        adicionar_arquivo_tar(
+            &mut construtor,
+            "pasta.json",
+            serde_json::to_string_pretty(&pasta)?.as_bytes(),
+        )?;
+
+        for item in &itens {
+            let slug = slug_item(item);
+            let prefixo = format!("itens/{}/", slug);
+
+            let metadados = serde_json::json!({
+                "id": item.id,
+                "tipo": item.tipo,
+                "titulo": item.titulo,
+                "descricao": item.descricao,
+                "data_vencimento": item.data_vencimento,
+                "regra_recorrencia": item.regra_recorrencia,
+                "criado_em": item.criado_em,
+                "atualizado_em": item.atualizado_em,
+            });
+            adicionar_arquivo_tar(
+                &mut construtor,
+                &format!("{}item.json", prefixo),
+                serde_json::to_string_pretty(&metadados)?.as_bytes(),
+            )?;
+
+            if let Some(conteudo) = &item.conteudo_nota {
+                adicionar_arquivo_tar(&mut construtor, &format!("{}nota.md", prefixo), conteudo.as_bytes())?;
+            }
+
+            for anexo in &item.anexos {
+                let caminho_completo = dir_anexos.join(&anexo.caminho_interno);
+                if caminho_completo.exists() {
+                    let conteudo = fs::read(&caminho_completo)
+                        .with_context(|| format!("Falha ao ler anexo: {:?}", caminho_completo))?;
+                    let caminho_no_tar = format!("{}anexos/{}", prefixo, anexo.nome_original);
+                    adicionar_arquivo_tar(&mut construtor, &caminho_no_tar, &conteudo)?;
+                }
+            }
+        }
+
+        construtor.finish().context("Falha ao finalizar arquivo tar")?;
+    }
+
+    let bytes_finais = comprimir_tar(codec, &buffer_tar)?;
+    fs::write(&caminho_tar, &bytes_finais)
+        .with_context(|| format!("Falha ao gravar arquivo tar: {:?}", caminho_tar))?;
+
+    info!("Pasta exportada como tar: {:?} ({} itens)", caminho_tar, itens.len());
+
+    Ok(caminho_tar)
+}
+
+/// Importa um `.tar` gerado por `exportar_pasta_tar` de volta para o cofre.
+/// Detecta a compressão usada (`.tar`/`.tar.zst`/`.tar.lz4`) pela extensão
+/// do arquivo. Em caso de conflito de nome, usa o mesmo sufixo "(importado)"
+/// de `importar_pacote`.
+///
+/// Anexos não carregam `tipo_mime` dentro do `.tar` (diferente do pacote
+/// ZIP, que guarda esse dado em `dados.json`) — é gravado como
+/// `application/octet-stream` genérico, já que o objetivo do `.tar` é
+/// interoperabilidade com ferramentas externas que não conhecem esse campo.
+pub fn importar_pasta_tar(diretorio_app: &Path, conexao: &Connection, arquivo: &Path) -> Result<()> {
+    info!("Importando pasta de tar: {:?}", arquivo);
+
+    let bytes_comprimidos = fs::read(arquivo)
+        .with_context(|| format!("Falha ao ler arquivo tar: {:?}", arquivo))?;
+    let bytes_tar = descomprimir_tar(arquivo, &bytes_comprimidos)?;
+
+    let mut leitor_tar = Archive::new(Cursor::new(bytes_tar));
+    let mut arquivos: HashMap<String, Vec<u8>> = HashMap::new();
+    for entrada in leitor_tar.entries().context("Arquivo tar inválido")? {
+        let mut entrada = entrada.context("Falha ao ler entrada do tar")?;
+        let caminho = entrada
+            .path()
+            .context("Caminho inválido em entrada do tar")?
+            .to_string_lossy()
+            .to_string();
+        let mut conteudo = Vec::new();
+        entrada
+            .read_to_end(&mut conteudo)
+            .with_context(|| format!("Falha ao ler entrada do tar: {}", caminho))?;
+        arquivos.insert(caminho, conteudo);
+    }
+
+    let bytes_pasta = arquivos.get("pasta.json").context("pasta.json não encontrado no tar")?;
+    let pasta_json: serde_json::Value =
+        serde_json::from_slice(bytes_pasta).context("Falha ao deserializar pasta.json")?;
+
+    let nome_pasta_original = pasta_json["nome"].as_str().unwrap_or("Pasta Importada");
+    let nome_pasta = format!("{} (importado)", nome_pasta_original);
+    let pasta_pai_id = pasta_json["pasta_pai_id"].as_str().map(|s| s.to_string());
+
+    let nova_pasta = crate::db::models::NovaPasta {
+        nome: nome_pasta.clone(),
+        pasta_pai_id,
+    };
+    let pasta_criada = queries::criar_pasta(conexao, &nova_pasta)?;
+    info!("Pasta importada criada: {} ({})", nome_pasta, pasta_criada.id);
+
+    // Cada item aparece como "itens/<slug>/item.json" — agrupar pelo prefixo.
+    let mut slugs: Vec<String> = arquivos
+        .keys()
+        .filter_map(|caminho| caminho.strip_prefix("itens/"))
+        .filter_map(|resto| resto.split('/').next())
+        .map(|s| s.to_string())
+        .collect();
+    slugs.sort();
+    slugs.dedup();
+
+    for slug in slugs {
+        let prefixo = format!("itens/{}/", slug);
+        let Some(bytes_meta) = arquivos.get(&format!("{}item.json", prefixo)) else {
+            continue;
+        };
+        let metadados: serde_json::Value = serde_json::from_slice(bytes_meta)
+            .with_context(|| format!("Falha ao deserializar metadados do item '{}'", slug))?;
+
+        let tipo_str = metadados["tipo"].as_str().unwrap_or("nota");
+        let conteudo_nota = arquivos
+            .get(&format!("{}nota.md", prefixo))
+            .map(|bytes| String::from_utf8_lossy(bytes).to_string());
+
+        let novo_item = crate::db::models::NovoItem {
+            pasta_id: pasta_criada.id.clone(),
+            tipo: crate::db::models::TipoItem::de_str(tipo_str),
+            titulo: metadados["titulo"].as_str().unwrap_or("Sem título").to_string(),
+            descricao: metadados["descricao"].as_str().map(|s| s.to_string()),
+            conteudo_nota,
+            data_vencimento: metadados["data_vencimento"].as_str().map(|s| s.to_string()),
+            tag_ids: None,
+            regra_recorrencia: metadados["regra_recorrencia"].as_str().map(|s| s.to_string()),
+        };
+
+        let item_criado = queries::criar_item(conexao, &novo_item)?;
+
+        let prefixo_anexos = format!("{}anexos/", prefixo);
+        for (caminho, conteudo) in &arquivos {
+            let Some(nome_original) = caminho.strip_prefix(&prefixo_anexos) else {
+                continue;
+            };
+            if nome_original.is_empty() {
+                continue;
+            }
+
+            let id_anexo = uuid::Uuid::new_v4().to_string();
+            let dir_anexos = armazenamento::obter_diretorio_armazenamento(diretorio_app);
+            let dir_novo = dir_anexos.join(&id_anexo);
+            fs::create_dir_all(&dir_novo)?;
+
+            let caminho_destino = dir_novo.join(nome_original);
+            fs::write(&caminho_destino, conteudo)?;
+
+            let hash = calcular_hash_bytes(conteudo);
+            let anexo = crate::db::models::Anexo {
+                id: id_anexo.clone(),
+                item_id: Some(item_criado.id.clone()),
+                tarefa_id: None,
+                nome_original: nome_original.to_string(),
+                caminho_interno: format!("{}/{}", id_anexo, nome_original),
+                tamanho: conteudo.len() as i64,
+                tipo_mime: "application/octet-stream".to_string(),
+                hash_sha256: Some(hash),
+                criado_em: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            };
+            queries::criar_anexo(conexao, &anexo)?;
+        }
+    }
+
+    info!("Pasta importada de tar com sucesso!");
+    Ok(())
+}
+
+/// Adiciona uma entrada de arquivo regular ao tar, com modo `0644`
+/// (leitura para todos, escrita só pelo dono) — suficiente para dados
+/// exportados, sem necessidade de entradas executáveis.
+fn adicionar_arquivo_tar<W: Write>(construtor: &mut Builder<W>, caminho: &str, conteudo: &[u8]) -> Result<()> {
+    let mut cabecalho = Header::new_gnu();
+    cabecalho
+        .set_path(caminho)
+        .with_context(|| format!("Caminho inválido no tar: {}", caminho))?;
+    cabecalho.set_size(conteudo.len() as u64);
+    cabecalho.set_mode(0o644);
+    cabecalho.set_mtime(Utc::now().timestamp().max(0) as u64);
+    cabecalho.set_cksum();
+    construtor
+        .append(&cabecalho, conteudo)
+        .with_context(|| format!("Falha ao adicionar entrada ao tar: {}", caminho))
+}
+
+/// Gera um nome de diretório estável e legível para um item dentro do tar,
+/// combinando um prefixo do título (só caracteres seguros em nomes de
+/// arquivo) com os primeiros caracteres do id, para evitar colisões entre
+/// itens com títulos iguais.
+fn slug_item(item: &crate::db::models::Item) -> String {
+    let titulo_limpo: String = item
+        .titulo
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .take(40)
+        .collect();
+    let id_curto: String = item.id.chars().take(8).collect();
+    format!("{}_{}", titulo_limpo, id_curto)
+}
+
+/// Extensão de arquivo correspondente ao codec, usada para nomear o `.tar`
+/// exportado e, na importação, para detectar qual compressão foi usada.
+fn extensao_tar(codec: &CompressaoTar) -> &'static str {
+    match codec {
+        CompressaoTar::Nenhuma => "tar",
+        CompressaoTar::Zstd { .. } => "tar.zst",
+        CompressaoTar::Lz4 => "tar.lz4",
+    }
+}
+
+/// Comprime os bytes do `.tar` inteiro de acordo com `codec` (ver `CompressaoTar`).
+fn comprimir_tar(codec: &CompressaoTar, bytes_tar: &[u8]) -> Result<Vec<u8>> {
+    match codec {
+        CompressaoTar::Nenhuma => Ok(bytes_tar.to_vec()),
+        CompressaoTar::Zstd { nivel } => {
+            zstd::encode_all(bytes_tar, *nivel).context("Falha ao comprimir tar com Zstandard")
+        }
+        CompressaoTar::Lz4 => {
+            let mut saida = Vec::new();
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(&mut saida);
+            encoder.write_all(bytes_tar).context("Falha ao comprimir tar com LZ4")?;
+            encoder.finish().context("Falha ao finalizar compressão LZ4")?;
+            Ok(saida)
+        }
+    }
+}
+
+/// Descomprime os bytes lidos de `arquivo`, detectando o codec usado pela
+/// extensão do nome (`.tar.zst`/`.tar.lz4`/`.tar` simples) — `exportar_pasta_tar`
+/// nomeia o arquivo de acordo com o codec, então a extensão basta para
+/// detectar o formato sem precisar de um cabeçalho próprio.
+fn descomprimir_tar(arquivo: &Path, bytes: &[u8]) -> Result<Vec<u8>> {
+    let nome = arquivo.to_string_lossy();
+    if nome.ends_with(".tar.zst") {
+        zstd::decode_all(bytes).context("Falha ao descomprimir tar com Zstandard")
+    } else if nome.ends_with(".tar.lz4") {
+        let mut saida = Vec::new();
+        lz4_flex::frame::FrameDecoder::new(bytes)
+            .read_to_end(&mut saida)
+            .context("Falha ao descomprimir tar com LZ4")?;
+        Ok(saida)
+    } else {
+        Ok(bytes.to_vec())
+    }
+}
+
+// =============================================================================
+// VERIFICACAO DE INTEGRIDADE (SEM RESTAURAR)
+// =============================================================================
+
+/// Verifica a integridade de um `.vaultbackup` sem tocar no cofre ativo:
+/// reabre o ZIP (decifrando-o primeiro, se protegido por senha), lê
+/// `manifesto.json` e re-calcula o hash de `banco.sqlite` e de cada entrada
+/// em `anexos/`, comparando com os hashes do manifesto.
+///
+/// `opcoes` controla o escopo (`entrada`/`subpath`, `incluir_banco`,
+/// `incluir_anexos`) e se entradas corrompidas devem ser reparadas
+/// (`reparar`) usando a cópia correspondente do cofre atual em
+/// `diretorio_app`, quando essa cópia ainda bater com o hash esperado.
+///
+/// `restaurar_backup` chama esta função internamente (com `reparar: false`)
+/// antes de tocar no cofre atual, e rejeita o backup inteiro se o relatório
+/// não vier `ok`. Exposta também isoladamente para quem quiser checar (e,
+/// com `reparar: true`, corrigir) um `.vaultbackup` sem restaurá-lo.
+pub fn verificar_backup(
+    diretorio_app: &Path,
+    arquivo: &Path,
+    opcoes: &OpcoesVerificacao,
+    senha: Option<&str>,
+) -> Result<RelatorioIntegridade> {
+    let mut zip = abrir_leitura_zip(arquivo, senha)?;
+
+    let manifesto = ler_manifesto_do_zip(&mut zip)?;
+
+    let mut relatorio = RelatorioIntegridade::default();
+    let mut substituicoes: HashMap<String, Vec<u8>> = HashMap::new();
+
+    if opcoes.incluir_banco && entrada_selecionada("banco.sqlite", opcoes) {
+        verificar_entrada(
+            &mut zip,
+            "banco.sqlite".to_string(),
+            &manifesto.hash_banco,
+            || diretorio_app.join("vaultcraft.db"),
+            opcoes.reparar,
+            &mut relatorio,
+            &mut substituicoes,
+        )?;
+    }
+
+    if opcoes.incluir_anexos {
+        let dir_anexos = armazenamento::obter_diretorio_armazenamento(diretorio_app);
+
+        for (caminho_interno, hash_esperado) in &manifesto.hashes_anexos {
+            if !entrada_selecionada(caminho_interno, opcoes) {
+                continue;
+            }
+            // Anexos referenciados de um backup diferencial não são
+            // regravados neste arquivo (ver `criar_backup`) — vivem no
+            // backup pai, então sua ausência aqui é esperada, não uma falha.
+            if manifesto.anexos_referenciados.iter().any(|r| r == caminho_interno) {
+                continue;
+            }
+
+            let caminho_no_zip = format!("anexos/{}", caminho_interno);
+            verificar_entrada(
+                &mut zip,
+                caminho_no_zip,
+                hash_esperado,
+                || dir_anexos.join(caminho_interno),
+                opcoes.reparar,
+                &mut relatorio,
+                &mut substituicoes,
+            )?;
+        }
+
+        for i in 0..zip.len() {
+            let nome = zip.by_index(i)
+                .context("Falha ao ler entrada do ZIP durante verificação de órfãos")?
+                .name()
+                .to_string();
+
+            if let Some(caminho_interno) = nome.strip_prefix("anexos/") {
+                if !caminho_interno.is_empty() && !manifesto.hashes_anexos.contains_key(caminho_interno) {
+                    relatorio.orfaos.push(nome);
+                }
+            }
+        }
+    }
+
+    relatorio.ok = relatorio.ausentes.is_empty()
+        && relatorio.corrompidos.is_empty()
+        && relatorio.orfaos.is_empty();
+
+    if !substituicoes.is_empty() {
+        reescrever_zip_com_substituicoes(arquivo, &substituicoes, senha)?;
+    }
+
+    info!(
+        "Verificação de {:?}: ok={} ausentes={} corrompidos={} reparados={} orfaos={}",
+        arquivo, relatorio.ok, relatorio.ausentes.len(), relatorio.corrompidos.len(),
+        relatorio.reparados.len(), relatorio.orfaos.len()
+    );
+
+    Ok(relatorio)
+}
+
+/// `true` se a entrada `caminho` deve ser verificada dado o escopo em `opcoes`.
+fn entrada_selecionada(caminho: &str, opcoes: &OpcoesVerificacao) -> bool {
+    if let Some(entrada) = &opcoes.entrada {
+        if entrada != caminho {
+            return false;
+        }
+    }
+    if let Some(subpath) = &opcoes.subpath {
+        if !caminho.starts_with(subpath.as_str()) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Verifica uma única entrada do ZIP contra seu hash esperado, atualizando
+/// `relatorio` (e `substituicoes`, quando reparável) conforme o resultado.
+fn verificar_entrada(
+    zip: &mut LeitorZip,
+    nome_no_zip: String,
+    hash_esperado: &str,
+    caminho_no_cofre_atual: impl FnOnce() -> PathBuf,
+    reparar: bool,
+    relatorio: &mut RelatorioIntegridade,
+    substituicoes: &mut HashMap<String, Vec<u8>>,
+) -> Result<()> {
+    let mut entrada = match zip.by_name(&nome_no_zip) {
+        Ok(entrada) => entrada,
+        Err(_) => {
+            relatorio.ausentes.push(nome_no_zip);
+            return Ok(());
+        }
+    };
+
+    let mut conteudo = Vec::new();
+    entrada.read_to_end(&mut conteudo)
+        .with_context(|| format!("Falha ao ler entrada do backup: {}", nome_no_zip))?;
+    drop(entrada);
+
+    if calcular_hash_bytes(&conteudo) == hash_esperado {
+        return Ok(());
+    }
+
+    if reparar {
+        if let Ok(conteudo_atual) = fs::read(caminho_no_cofre_atual()) {
+            if calcular_hash_bytes(&conteudo_atual) == hash_esperado {
+                substituicoes.insert(nome_no_zip.clone(), conteudo_atual);
+                relatorio.reparados.push(nome_no_zip);
+                return Ok(());
+            }
+        }
+    }
+
+    relatorio.corrompidos.push(nome_no_zip);
+    Ok(())
+}
+
+/// Reescreve o `.vaultbackup` em `arquivo`, trocando o conteúdo das
+/// entradas listadas em `substituicoes` (por nome) e preservando as
+/// demais. Escreve em um arquivo temporário e substitui o original ao final.
+/// Se o arquivo original estiver cifrado, `senha` é usada tanto para abri-lo
+/// quanto para cifrar o arquivo reparado (a senha não muda com o reparo).
+fn reescrever_zip_com_substituicoes(
+    arquivo: &Path,
+    substituicoes: &HashMap<String, Vec<u8>>,
+    senha: Option<&str>,
+) -> Result<()> {
+    let mut zip = abrir_leitura_zip(arquivo, senha)
+        .context("Falha ao reabrir arquivo de backup para reparo")?;
+
+    let caminho_temp = arquivo.with_extension("vaultbackup.reparando");
+    let mut novo_zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let opcoes_zip = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for i in 0..zip.len() {
+        let mut entrada = zip.by_index(i)
+            .context("Falha ao ler entrada do ZIP durante reparo")?;
+        let nome = entrada.name().to_string();
+
+        if entrada.is_dir() {
+            novo_zip.add_directory(&nome, opcoes_zip)?;
+            continue;
+        }
+
+        novo_zip.start_file(&nome, opcoes_zip)
+            .with_context(|| format!("Falha ao iniciar entrada reparada: {}", nome))?;
+
+        if let Some(conteudo_novo) = substituicoes.get(&nome) {
+            novo_zip.write_all(conteudo_novo)?;
+        } else {
+            let mut conteudo = Vec::new();
+            entrada.read_to_end(&mut conteudo)?;
+            novo_zip.write_all(&conteudo)?;
+        }
+    }
+
+    let cursor_zip = novo_zip.finish().context("Falha ao finalizar ZIP reparado")?;
+    escrever_arquivo_backup(&caminho_temp, cursor_zip.into_inner(), senha)?;
+    fs::rename(&caminho_temp, arquivo)
+        .context("Falha ao substituir backup original pelo reparado")?;
+
+    Ok(())
+}
+
+// =============================================================================
+// BACKUP INCREMENTAL (DEDUPLICADO POR CHUNKS)
+// =============================================================================
+// Alternativa a `criar_backup`/`restaurar_backup` para quem faz backups
+// frequentes do mesmo cofre: em vez de gravar o banco e os anexos inteiros
+// a cada vez, cada arquivo é fragmentado em chunks (services::cdc) que são
+// gravados uma única vez em um repositório content-addressed (diretório
+// "chunks" ao lado dos backups). O .vaultbackup resultante contém só o
+// manifesto — listando a sequência de hashes de chunk por arquivo — então
+// um backup de um cofre pouco alterado reaproveita quase todos os chunks
+// do backup anterior.
+// =============================================================================
+
+/// Cria um backup incremental do cofre. Os chunks ficam em
+/// `destino/chunks`; o .vaultbackup gerado contém só `manifesto_chunks.json`.
+pub fn criar_backup_incremental(
+    diretorio_app: &Path,
+    conexao: &Connection,
+    destino: &Path,
+) -> Result<PathBuf> {
+    let agora = Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let nome_arquivo = format!("vaultcraft_backup_{}.vaultbackup", agora);
+    let caminho_backup = destino.join(&nome_arquivo);
+    let dir_chunks = destino.join("chunks");
+
+    fs::create_dir_all(&dir_chunks)
+        .context("Falha ao criar repositório de chunks")?;
+
+    info!("Criando backup incremental em: {:?}", caminho_backup);
+
+    // 1. Fragmentar o banco de dados
+    let caminho_banco = diretorio_app.join("vaultcraft.db");
+    let conteudo_banco = fs::read(&caminho_banco)
+        .context("Falha ao ler banco de dados para backup")?;
+    let banco = cdc::fragmentar_arquivo(&dir_chunks, &conteudo_banco)?;
+
+    // 2. Fragmentar cada anexo
+    let dir_anexos = armazenamento::obter_diretorio_armazenamento(diretorio_app);
+    let caminhos_anexos = queries::listar_caminhos_anexos(conexao)?;
+    let mut anexos: HashMap<String, crate::db::models::ArquivoFragmentado> = HashMap::new();
+
+    for caminho_interno in &caminhos_anexos {
+        let caminho_completo = dir_anexos.join(caminho_interno);
+        if caminho_completo.exists() {
+            let conteudo = fs::read(&caminho_completo)
+                .with_context(|| format!("Falha ao ler anexo: {:?}", caminho_completo))?;
+            let fragmentado = cdc::fragmentar_arquivo(&dir_chunks, &conteudo)?;
+            anexos.insert(caminho_interno.clone(), fragmentado);
+        }
+    }
+
+    // 3. Escrever o manifesto dentro do .vaultbackup
+    let total_itens = queries::contar_itens(conexao)?;
+    let total_anexos = queries::contar_anexos(conexao)?;
+    let versao_anterior = ultima_versao_manifesto(destino)?;
+
+    let manifesto = ManifestoBackupFragmentado {
+        id: Uuid::new_v4().to_string(),
+        versao_anterior,
+        versao_app: env!("CARGO_PKG_VERSION").to_string(),
+        versao_schema: versao_mais_recente(),
+        data: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        total_itens,
+        total_anexos,
+        banco,
+        anexos,
+    };
+
+    let manifesto_json = serde_json::to_string_pretty(&manifesto)
+        .context("Falha ao serializar manifesto fragmentado")?;
+
+    let arquivo_zip = fs::File::create(&caminho_backup)
+        .context("Falha ao criar arquivo de backup")?;
+    let mut zip = ZipWriter::new(arquivo_zip);
+    let opcoes = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("manifesto_chunks.json", opcoes)
+        .context("Falha ao adicionar manifesto ao ZIP")?;
+    zip.write_all(manifesto_json.as_bytes())
+        .context("Falha ao escrever manifesto no ZIP")?;
+
+    zip.finish()
+        .context("Falha ao finalizar arquivo ZIP de backup")?;
+
+    info!(
+        "Backup incremental criado: {:?} ({} itens, {} anexos, {} chunks novos/existentes)",
+        caminho_backup,
+        total_itens,
+        total_anexos,
+        manifesto.banco.chunks.len()
+    );
+
+    Ok(caminho_backup)
+}
+
+/// Restaura um backup incremental criado por `criar_backup_incremental`.
+/// `dir_chunks` é o repositório de chunks usado na criação (normalmente
+/// `destino/chunks` do backup original).
+pub fn restaurar_backup_incremental(
+    diretorio_app: &Path,
+    arquivo: &Path,
+    dir_chunks: &Path,
+) -> Result<()> {
+    info!("Restaurando backup incremental de: {:?}", arquivo);
+
+    let arquivo_zip = fs::File::open(arquivo)
+        .context("Falha ao abrir arquivo de backup")?;
+    let mut zip = zip::ZipArchive::new(arquivo_zip)
+        .context("Arquivo de backup inválido (não é ZIP válido)")?;
+
+    let mut manifesto_arquivo = zip.by_name("manifesto_chunks.json")
+        .context("Arquivo manifesto_chunks.json não encontrado no backup")?;
+    let mut manifesto_json = String::new();
+    manifesto_arquivo.read_to_string(&mut manifesto_json)
+        .context("Falha ao ler manifesto_chunks.json")?;
+    drop(manifesto_arquivo);
+
+    let manifesto: ManifestoBackupFragmentado = serde_json::from_str(&manifesto_json)
+        .context("Falha ao deserializar manifesto_chunks.json")?;
+
+    // Reconstruir e gravar o banco de dados
+    let conteudo_banco = cdc::reconstruir_arquivo(dir_chunks, &manifesto.banco)
+        .context("Falha ao reconstruir banco de dados a partir dos chunks")?;
+    let caminho_banco = diretorio_app.join("vaultcraft.db");
+    fs::write(&caminho_banco, &conteudo_banco)
+        .context("Falha ao restaurar banco de dados")?;
+
+    // Reconstruir e gravar cada anexo
+    let dir_anexos = armazenamento::obter_diretorio_armazenamento(diretorio_app);
+    if dir_anexos.exists() {
+        fs::remove_dir_all(&dir_anexos)
+            .context("Falha ao limpar diretório de anexos")?;
+    }
+    fs::create_dir_all(&dir_anexos)
+        .context("Falha ao recriar diretório de anexos")?;
+
+    for (caminho_interno, fragmentado) in &manifesto.anexos {
+        let conteudo = cdc::reconstruir_arquivo(dir_chunks, fragmentado)
+            .with_context(|| format!("Falha ao reconstruir anexo: {}", caminho_interno))?;
+
+        let caminho_destino = dir_anexos.join(caminho_interno);
+        if let Some(dir_pai) = caminho_destino.parent() {
+            fs::create_dir_all(dir_pai)
+                .with_context(|| format!("Falha ao criar diretório: {:?}", dir_pai))?;
+        }
+        fs::write(&caminho_destino, &conteudo)
+            .with_context(|| format!("Falha ao restaurar anexo: {:?}", caminho_destino))?;
+    }
+
+    info!("Backup incremental restaurado com sucesso!");
+    Ok(())
+}
+
+/// Lê `id` do manifesto do backup incremental mais recente em `destino`
+/// (mesmo critério de nome de arquivo de `extrair_timestamp_backup`), para
+/// encadear `versao_anterior` do próximo. `None` se `destino` não tem
+/// nenhum `.vaultbackup` com `manifesto_chunks.json` ainda.
+fn ultima_versao_manifesto(destino: &Path) -> Result<Option<String>> {
+    if !destino.exists() {
+        return Ok(None);
+    }
+
+    let mut candidatos: Vec<(String, NaiveDateTime)> = Vec::new();
+    for entrada in fs::read_dir(destino).context("Falha ao listar diretório de backups")? {
+        let entrada = entrada.context("Falha ao ler entrada do diretório de backups")?;
+        if !entrada.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+        let nome = entrada.file_name().to_string_lossy().to_string();
+        if let Some(timestamp) = extrair_timestamp_backup(&nome) {
+            candidatos.push((nome, timestamp));
+        }
+    }
+    candidatos.sort_by(|a, b| b.1.cmp(&a.1));
+
+    for (nome, _) in &candidatos {
+        let caminho = destino.join(nome);
+        let Ok(arquivo_zip) = fs::File::open(&caminho) else { continue };
+        let Ok(mut zip) = zip::ZipArchive::new(arquivo_zip) else { continue };
+        let Ok(mut manifesto_arquivo) = zip.by_name("manifesto_chunks.json") else { continue };
+        let mut manifesto_json = String::new();
+        if manifesto_arquivo.read_to_string(&mut manifesto_json).is_err() {
+            continue;
+        }
+        drop(manifesto_arquivo);
+        if let Ok(manifesto) = serde_json::from_str::<ManifestoBackupFragmentado>(&manifesto_json) {
+            return Ok(Some(manifesto.id));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Coleta os hashes de chunk de um `ArquivoFragmentado`.
+fn coletar_hashes(fragmentado: &crate::db::models::ArquivoFragmentado, referenciados: &mut HashSet<String>) {
+    referenciados.extend(fragmentado.chunks.iter().cloned());
+}
+
+/// Remove do repositório `dir_chunks` qualquer chunk que não seja
+/// referenciado pelo manifesto de nenhum `.vaultbackup` ainda presente em
+/// `destino` — ou seja, coleta de lixo dos backups incrementais já podados
+/// por `podar_backups_automaticos` (ou apagados manualmente). Percorre os
+/// manifestos retidos para montar o conjunto de chunks vivos e então varre
+/// `dir_chunks` (layout em subdiretórios de 2 caracteres, ver
+/// `cdc::caminho_do_chunk`) apagando o que sobrar.
+///
+/// Retorna a quantidade de chunks removidos.
+pub fn podar_chunks_nao_referenciados(destino: &Path, dir_chunks: &Path) -> Result<usize> {
+    if !dir_chunks.exists() {
+        return Ok(0);
+    }
+
+    let mut referenciados: HashSet<String> = HashSet::new();
+
+    if destino.exists() {
+        for entrada in fs::read_dir(destino).context("Falha ao listar diretório de backups")? {
+            let entrada = entrada.context("Falha ao ler entrada do diretório de backups")?;
+            if !entrada.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                continue;
+            }
+            let caminho = entrada.path();
+            if caminho.extension().and_then(|e| e.to_str()) != Some("vaultbackup") {
+                continue;
+            }
+
+            let Ok(arquivo_zip) = fs::File::open(&caminho) else { continue };
+            let Ok(mut zip) = zip::ZipArchive::new(arquivo_zip) else { continue };
+            let Ok(mut manifesto_arquivo) = zip.by_name("manifesto_chunks.json") else { continue };
+            let mut manifesto_json = String::new();
+            if manifesto_arquivo.read_to_string(&mut manifesto_json).is_err() {
+                continue;
+            }
+            drop(manifesto_arquivo);
+            let Ok(manifesto) = serde_json::from_str::<ManifestoBackupFragmentado>(&manifesto_json) else { continue };
+
+            coletar_hashes(&manifesto.banco, &mut referenciados);
+            for fragmentado in manifesto.anexos.values() {
+                coletar_hashes(fragmentado, &mut referenciados);
+            }
+        }
+    }
+
+    let mut removidos = 0usize;
+    for subdir in fs::read_dir(dir_chunks).context("Falha ao listar repositório de chunks")? {
+        let subdir = subdir.context("Falha ao ler subdiretório de chunks")?;
+        if !subdir.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        for arquivo in fs::read_dir(subdir.path()).context("Falha ao listar subdiretório de chunks")? {
+            let arquivo = arquivo.context("Falha ao ler chunk")?;
+            let hash = arquivo.file_name().to_string_lossy().to_string();
+            if !referenciados.contains(&hash) {
+                fs::remove_file(arquivo.path())
+                    .with_context(|| format!("Falha ao remover chunk órfão: {:?}", arquivo.path()))?;
+                removidos += 1;
+            }
+        }
+    }
+
+    if removidos > 0 {
+        info!("Poda de chunks em {:?}: {} chunk(s) órfão(s) removido(s)", dir_chunks, removidos);
+    }
+
+    Ok(removidos)
+}
+
+// =============================================================================
+// PODA DE BACKUPS AUTOMATICOS
+// =============================================================================
+// `restaurar_backup` grava um backup de segurança em `backups_automaticos`
+// a cada restauração, mas nunca removia nada — o diretório crescia sem
+// limite. `podar_backups_automaticos` aplica uma política de retenção no
+// estilo Proxmox (keep-last/keep-daily/keep-weekly/keep-monthly), mantendo
+// os arquivos mais relevantes e apagando o restante.
+// =============================================================================
+
+/// Remove arquivos `.vaultbackup` de `dir` que excedam a política de
+/// retenção em `politica` (ver `PoliticaPoda`). Só considera arquivos cujo
+/// nome segue o padrão `vaultcraft_backup_YYYYMMDD_HHMMSS.vaultbackup`
+/// (o formato usado por `criar_backup`/`criar_backup_incremental`) —
+/// qualquer outro arquivo no diretório é ignorado.
+///
+/// Retorna os nomes dos arquivos removidos, para registro em log/auditoria.
+pub fn podar_backups_automaticos(dir: &Path, politica: &PoliticaPoda) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut candidatos: Vec<(String, NaiveDateTime)> = Vec::new();
+    for entrada in fs::read_dir(dir).context("Falha ao listar diretório de backups automáticos")? {
+        let entrada = entrada.context("Falha ao ler entrada do diretório de backups automáticos")?;
+        if !entrada.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+        let nome = entrada.file_name().to_string_lossy().to_string();
+        if let Some(timestamp) = extrair_timestamp_backup(&nome) {
+            candidatos.push((nome, timestamp));
+        }
+    }
+
+    // Mais recente primeiro — necessário para que o "bucket" de cada
+    // dia/semana/mês guarde o snapshot mais novo daquele período.
+    candidatos.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let mut manter: HashSet<String> = HashSet::new();
+    for (nome, _) in candidatos.iter().take(politica.manter_ultimos) {
+        manter.insert(nome.clone());
+    }
+    manter_mais_recente_por_bucket(&candidatos, politica.manter_diarios, &mut manter, |ts| ts.date());
+    manter_mais_recente_por_bucket(&candidatos, politica.manter_semanais, &mut manter, |ts| {
+        let semana = ts.iso_week();
+        (semana.year(), semana.week())
+    });
+    manter_mais_recente_por_bucket(&candidatos, politica.manter_mensais, &mut manter, |ts| {
+        (ts.year(), ts.month())
+    });
+
+    let mut removidos = Vec::new();
+    for (nome, _) in &candidatos {
+        if !manter.contains(nome) {
+            fs::remove_file(dir.join(nome))
+                .with_context(|| format!("Falha ao remover backup automático antigo: {}", nome))?;
+            removidos.push(nome.clone());
+        }
+    }
+
+    if !removidos.is_empty() {
+        info!("Poda de {:?}: {} arquivo(s) removido(s), {} mantido(s)", dir, removidos.len(), manter.len());
+    }
+
+    Ok(removidos)
+}
+
+/// Extrai o timestamp de um nome de arquivo `vaultcraft_backup_YYYYMMDD_HHMMSS.vaultbackup`.
+fn extrair_timestamp_backup(nome_arquivo: &str) -> Option<NaiveDateTime> {
+    let meio = nome_arquivo
+        .strip_prefix("vaultcraft_backup_")?
+        .strip_suffix(".vaultbackup")?;
+    NaiveDateTime::parse_from_str(meio, "%Y%m%d_%H%M%S").ok()
+}
+
+/// Para os `num_buckets` valores de `chave` mais recentes presentes em
+/// `candidatos` (já ordenado do mais novo para o mais antigo), marca em
+/// `manter` o primeiro arquivo encontrado para cada um — que, dada a ordem,
+/// é sempre o mais recente daquele bucket (dia/semana/mês).
+fn manter_mais_recente_por_bucket<K: Eq + std::hash::Hash>(
+    candidatos: &[(String, NaiveDateTime)],
+    num_buckets: usize,
+    manter: &mut HashSet<String>,
+    chave: impl Fn(&NaiveDateTime) -> K,
+) {
+    let mut buckets_vistos: HashSet<K> = HashSet::new();
+    for (nome, timestamp) in candidatos {
+        if buckets_vistos.len() >= num_buckets {
+            break;
+        }
+        let k = chave(timestamp);
+        if buckets_vistos.contains(&k) {
+            continue;
+        }
+        buckets_vistos.insert(k);
+        manter.insert(nome.clone());
+    }
+}
+
 // =============================================================================
 // FUNCOES AUXILIARES
 // =============================================================================
@@ -476,8 +1572,45 @@ fn calcular_hash_bytes(dados: &[u8]) -> String {
     hex::encode(hasher.finalize())
 }
 
-/// Lê e deserializa o manifesto.json de dentro de um arquivo ZIP.
-fn ler_manifesto_do_zip(zip: &mut zip::ZipArchive<fs::File>) -> Result<ManifestoBackup> {
+/// Lê um `.vaultbackup` do disco inteiro em memória e, se os bytes
+/// começarem com a marca de cifragem (ver `crypto::backup::esta_cifrado`),
+/// decifra-o com `senha` antes de abri-lo como ZIP. Arquivos em texto claro
+/// (sem a marca) são abertos diretamente — compatível com backups antigos.
+///
+/// Retorna erro sem nenhum efeito colateral se o arquivo estiver cifrado e
+/// `senha` estiver ausente ou incorreta.
+fn abrir_leitura_zip(caminho: &Path, senha: Option<&str>) -> Result<LeitorZip> {
+    let bytes = fs::read(caminho)
+        .with_context(|| format!("Falha ao ler arquivo de backup: {:?}", caminho))?;
+
+    let bytes_zip = if cripto_backup::esta_cifrado(&bytes) {
+        let senha = senha.context(
+            "Este backup está protegido por senha — informe a senha para abri-lo",
+        )?;
+        cripto_backup::descifrar(&bytes, senha)?
+    } else {
+        bytes
+    };
+
+    zip::ZipArchive::new(Cursor::new(bytes_zip))
+        .context("Arquivo de backup inválido (não é ZIP válido)")
+}
+
+/// Serializa `bytes_zip` (o ZIP pronto) em `caminho`, cifrando-o antes com
+/// `senha` quando informada (ver `crypto::backup::cifrar`).
+fn escrever_arquivo_backup(caminho: &Path, bytes_zip: Vec<u8>, senha: Option<&str>) -> Result<()> {
+    let bytes_finais = match senha {
+        Some(senha) => cripto_backup::cifrar(&bytes_zip, senha)
+            .context("Falha ao cifrar backup")?,
+        None => bytes_zip,
+    };
+
+    fs::write(caminho, &bytes_finais)
+        .with_context(|| format!("Falha ao gravar arquivo de backup: {:?}", caminho))
+}
+
+/// Lê e deserializa o manifesto.json de dentro de um arquivo ZIP já aberto.
+fn ler_manifesto_do_zip(zip: &mut LeitorZip) -> Result<ManifestoBackup> {
     let mut manifesto_arquivo = zip.by_name("manifesto.json")
         .context("Arquivo manifesto.json não encontrado no backup")?;
 
@@ -490,3 +1623,13 @@ fn ler_manifesto_do_zip(zip: &mut zip::ZipArchive<fs::File>) -> Result<Manifesto
 
     Ok(manifesto)
 }
+
+/// Abre um arquivo .vaultbackup pelo caminho e lê seu manifesto.json.
+/// Usado para inspecionar um backup de referência sem restaurá-lo. Se o
+/// arquivo de referência estiver cifrado, assume a mesma `senha` do backup
+/// que está sendo criado.
+fn ler_manifesto_de_arquivo(caminho: &Path, senha: Option<&str>) -> Result<ManifestoBackup> {
+    let mut zip = abrir_leitura_zip(caminho, senha)
+        .with_context(|| format!("Falha ao abrir backup de referência: {:?}", caminho))?;
+    ler_manifesto_do_zip(&mut zip)
+}
@@ -0,0 +1,200 @@
+// =============================================================================
+// VaultCraft — Chunking com Limites de Conteúdo (FastCDC) + Repositório
+// Content-Addressed de Chunks
+// =============================================================================
+// Usado por services::backup para backups incrementais: em vez de re-gravar
+// o banco e os anexos inteiros a cada backup, dividimos cada arquivo em
+// blocos ("chunks") de tamanho variável cujos limites dependem do próprio
+// conteúdo (inspirado no FastCDC usado pelo zvault e pelo restic). Um chunk
+// que não mudou entre dois backups produz exatamente os mesmos bytes e,
+// portanto, o mesmo hash — então só é gravado uma vez no repositório
+// content-addressed, e backups sucessivos de um cofre pouco alterado
+// compartilham quase todos os chunks.
+//
+// Algoritmo (FastCDC com chunking normalizado):
+//   Mantemos uma impressão digital de 64 bits `fp` que desliza sobre os
+//   bytes: a cada byte, `fp = (fp << 1) + GEAR[byte]`, onde GEAR é uma
+//   tabela fixa de 256 valores pseudo-aleatórios de 64 bits. Um ponto de
+//   corte é declarado quando `fp & mascara == 0`.
+//
+//   Para reduzir a variância de tamanho, usamos duas máscaras: `MASCARA_S`
+//   (mais bits em 1, portanto mais restritiva) enquanto o chunk atual ainda
+//   não atingiu o tamanho médio alvo, e `MASCARA_L` (menos bits em 1, mais
+//   permissiva) depois disso — assim um corte "cedo demais" é raro e um
+//   corte perto do tamanho médio é favorecido. `TAMANHO_MINIMO`/
+//   `TAMANHO_MAXIMO` garantem que nenhum chunk fique menor ou maior que os
+//   limites, mesmo que nenhum ponto de corte seja encontrado.
+// =============================================================================
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Nenhum chunk é menor que isto, exceto o último chunk de um arquivo.
+pub const TAMANHO_MINIMO: usize = 2 * 1024;
+/// Tamanho médio alvo — usado para escolher entre MASCARA_S e MASCARA_L.
+pub const TAMANHO_MEDIO: usize = 16 * 1024;
+/// Nenhum chunk excede isto, mesmo sem ponto de corte encontrado.
+pub const TAMANHO_MAXIMO: usize = 64 * 1024;
+
+/// Máscara mais restritiva (mais bits em 1 = menos provável bater),
+/// aplicada enquanto o chunk corrente está abaixo de TAMANHO_MEDIO.
+const MASCARA_S: u64 = (1u64 << 15) - 1;
+/// Máscara mais permissiva (menos bits em 1 = mais provável bater),
+/// aplicada depois que o chunk corrente atinge TAMANHO_MEDIO.
+const MASCARA_L: u64 = (1u64 << 13) - 1;
+
+static TABELA_GEAR: OnceLock<[u64; 256]> = OnceLock::new();
+
+/// Tabela gear fixa: 256 valores pseudo-aleatórios de 64 bits, gerados uma
+/// única vez (sempre a partir da mesma semente) e memorizados — precisa ser
+/// idêntica em toda execução, senão dois backups do mesmo conteúdo cortariam
+/// em pontos diferentes e nenhum chunk seria reaproveitado.
+fn tabela_gear() -> &'static [u64; 256] {
+    TABELA_GEAR.get_or_init(|| {
+        let mut tabela = [0u64; 256];
+        let mut estado: u64 = 0x9E3779B97F4A7C15; // semente fixa arbitrária
+        for valor in tabela.iter_mut() {
+            // splitmix64: gerador determinístico simples, só precisamos de
+            // boa dispersão de bits, não de segurança criptográfica.
+            estado = estado.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = estado;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            *valor = z ^ (z >> 31);
+        }
+        tabela
+    })
+}
+
+/// Divide `dados` em chunks de tamanho variável delimitados pelo conteúdo.
+/// Retorna os chunks como fatias de `dados`, na ordem original.
+pub fn dividir_em_chunks(dados: &[u8]) -> Vec<&[u8]> {
+    if dados.is_empty() {
+        return Vec::new();
+    }
+
+    let gear = tabela_gear();
+    let mut chunks = Vec::new();
+    let mut inicio = 0usize;
+
+    while inicio < dados.len() {
+        let fim = proximo_ponto_de_corte(dados, inicio, gear);
+        chunks.push(&dados[inicio..fim]);
+        inicio = fim;
+    }
+
+    chunks
+}
+
+/// Encontra o próximo ponto de corte a partir de `inicio`, respeitando
+/// TAMANHO_MINIMO/TAMANHO_MAXIMO e as máscaras normalizadas.
+fn proximo_ponto_de_corte(dados: &[u8], inicio: usize, gear: &[u64; 256]) -> usize {
+    let restante = dados.len() - inicio;
+    let limite_maximo = inicio + restante.min(TAMANHO_MAXIMO);
+
+    // O último chunk do arquivo pode ficar abaixo de TAMANHO_MINIMO.
+    if limite_maximo - inicio <= TAMANHO_MINIMO {
+        return limite_maximo;
+    }
+
+    let mut fp: u64 = 0;
+    let mut pos = inicio + TAMANHO_MINIMO;
+
+    while pos < limite_maximo {
+        fp = (fp << 1).wrapping_add(gear[dados[pos] as usize]);
+
+        let mascara = if pos - inicio < TAMANHO_MEDIO { MASCARA_S } else { MASCARA_L };
+        if fp & mascara == 0 {
+            return pos + 1;
+        }
+
+        pos += 1;
+    }
+
+    limite_maximo
+}
+
+/// Calcula o hash SHA-256 (hex) de um chunk ou arquivo completo.
+pub fn calcular_hash(dados: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(dados);
+    hex::encode(hasher.finalize())
+}
+
+/// Caminho do chunk dentro do repositório content-addressed: os dois
+/// primeiros caracteres do hash formam um subdiretório, para evitar um
+/// único diretório com dezenas de milhares de entradas.
+fn caminho_do_chunk(dir_chunks: &Path, hash: &str) -> std::path::PathBuf {
+    dir_chunks.join(&hash[0..2]).join(hash)
+}
+
+/// Grava um chunk no repositório content-addressed, se ainda não existir.
+/// Retorna o hash (nome do arquivo) do chunk.
+fn gravar_chunk_se_ausente(dir_chunks: &Path, dados: &[u8]) -> Result<String> {
+    let hash = calcular_hash(dados);
+    let caminho = caminho_do_chunk(dir_chunks, &hash);
+
+    if !caminho.exists() {
+        if let Some(dir_pai) = caminho.parent() {
+            fs::create_dir_all(dir_pai)
+                .with_context(|| format!("Falha ao criar diretório de chunks: {:?}", dir_pai))?;
+        }
+        fs::write(&caminho, dados)
+            .with_context(|| format!("Falha ao gravar chunk: {:?}", caminho))?;
+    }
+
+    Ok(hash)
+}
+
+/// Fragmenta `dados` em chunks e os grava no repositório (os que já
+/// existirem são pulados). Retorna o tamanho, hash do arquivo completo e a
+/// sequência ordenada de hashes de chunk — pronta para ir no manifesto.
+pub fn fragmentar_arquivo(dir_chunks: &Path, dados: &[u8]) -> Result<crate::db::models::ArquivoFragmentado> {
+    let hash_completo = calcular_hash(dados);
+    let mut chunks = Vec::new();
+
+    for chunk in dividir_em_chunks(dados) {
+        chunks.push(gravar_chunk_se_ausente(dir_chunks, chunk)?);
+    }
+
+    Ok(crate::db::models::ArquivoFragmentado {
+        tamanho: dados.len() as u64,
+        hash_completo,
+        chunks,
+    })
+}
+
+/// Reconstrói um arquivo a partir da sequência de hashes de chunk,
+/// verificando o hash de cada chunk lido e o hash do arquivo completo ao final.
+pub fn reconstruir_arquivo(
+    dir_chunks: &Path,
+    fragmentado: &crate::db::models::ArquivoFragmentado,
+) -> Result<Vec<u8>> {
+    let mut resultado = Vec::with_capacity(fragmentado.tamanho as usize);
+
+    for hash in &fragmentado.chunks {
+        let caminho = caminho_do_chunk(dir_chunks, hash);
+        let dados_chunk = fs::read(&caminho)
+            .with_context(|| format!("Chunk ausente no repositório: {:?}", caminho))?;
+
+        let hash_obtido = calcular_hash(&dados_chunk);
+        if &hash_obtido != hash {
+            anyhow::bail!("Chunk corrompido: esperado {}, obtido {}", hash, hash_obtido);
+        }
+
+        resultado.extend_from_slice(&dados_chunk);
+    }
+
+    let hash_final = calcular_hash(&resultado);
+    if hash_final != fragmentado.hash_completo {
+        anyhow::bail!(
+            "Hash do arquivo reconstruído não confere! Esperado: {}, Obtido: {}",
+            fragmentado.hash_completo, hash_final
+        );
+    }
+
+    Ok(resultado)
+}
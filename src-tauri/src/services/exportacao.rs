@@ -15,11 +15,19 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use log::info;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
 
-use crate::db::models::{Item, TipoItem};
+use crate::db::models::{Item, NovaPasta, NovaTag, NovoItem, Pasta, TemaExportacao, TipoItem};
+use crate::db::queries;
+use super::markdown::markdown_para_html;
 
 /// Exporta um item como arquivo HTML formatado para impressão.
 ///
@@ -28,18 +36,39 @@ use crate::db::models::{Item, TipoItem};
 /// - Layout otimizado para impressão (@media print)
 /// - Metadados do item (tipo, pasta, tags, data de criação)
 /// - Conteúdo formatado de acordo com o tipo do item
+/// - Tema visual selecionável (`tema`) via variáveis CSS (`--bg`, `--fg`, etc.)
 ///
 /// Para converter em PDF, o usuário pode abrir no navegador e usar Ctrl+P.
-pub fn exportar_item_html(item: &Item, destino: &Path) -> Result<PathBuf> {
-    let nome_limpo = item.titulo
-        .replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-' && c != ' ', "")
-        .replace(' ', "_");
-
+pub fn exportar_item_html(item: &Item, destino: &Path, tema: TemaExportacao) -> Result<PathBuf> {
+    let nome_limpo = nome_arquivo_seguro(&item.titulo);
     let nome_arquivo = format!("{}.html", nome_limpo);
     let caminho_arquivo = destino.join(&nome_arquivo);
 
     info!("Exportando item '{}' como HTML para: {:?}", item.titulo, caminho_arquivo);
 
+    let html = renderizar_pagina_item(item, tema);
+
+    // Escrever arquivo
+    let mut arquivo = fs::File::create(&caminho_arquivo)
+        .context("Falha ao criar arquivo HTML")?;
+    arquivo.write_all(html.as_bytes())
+        .context("Falha ao escrever arquivo HTML")?;
+
+    info!("Item exportado como HTML: {:?}", caminho_arquivo);
+    Ok(caminho_arquivo)
+}
+
+/// Remove caracteres não seguros para nome de arquivo, mantendo o mesmo
+/// critério usado historicamente por `exportar_item_html`.
+fn nome_arquivo_seguro(titulo: &str) -> String {
+    titulo
+        .replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-' && c != ' ', "")
+        .replace(' ', "_")
+}
+
+/// Renderiza a página HTML completa de um item (usada tanto pela exportação
+/// individual quanto pelo site estático gerado por `exportar_cofre_site`).
+fn renderizar_pagina_item(item: &Item, tema: TemaExportacao) -> String {
     // Montar o conteúdo principal baseado no tipo do item
     let conteudo_html = match item.tipo {
         TipoItem::Nota => formatar_nota_html(item),
@@ -78,24 +107,28 @@ pub fn exportar_item_html(item: &Item, destino: &Path) -> Result<PathBuf> {
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>{titulo} — VaultCraft</title>
     <style>
-        /* Estilos base — design limpo e legível */
+        /* Variáveis de tema (ver TemaExportacao) — trocam a paleta sem
+           duplicar as regras abaixo. */
+        :root {{
+{variaveis_tema}
+        }}
         * {{ margin: 0; padding: 0; box-sizing: border-box; }}
         body {{
             font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
             line-height: 1.6;
-            color: #1a1a2e;
+            color: var(--fg);
             max-width: 800px;
             margin: 0 auto;
             padding: 40px 20px;
-            background: #ffffff;
+            background: var(--bg);
         }}
         h1 {{
             font-size: 1.8rem;
             margin-bottom: 8px;
-            color: #16213e;
+            color: var(--fg);
         }}
         .meta {{
-            color: #666;
+            color: var(--muted);
             font-size: 0.9rem;
             margin-bottom: 4px;
         }}
@@ -112,24 +145,23 @@ pub fn exportar_item_html(item: &Item, destino: &Path) -> Result<PathBuf> {
         }}
         .divider {{
             border: none;
-            border-top: 2px solid #e8e8e8;
+            border-top: 2px solid var(--border);
             margin: 20px 0;
         }}
         .conteudo {{
             margin-top: 20px;
-            white-space: pre-wrap;
             font-size: 1rem;
         }}
         .checklist-item {{
             padding: 8px 0;
-            border-bottom: 1px solid #f0f0f0;
+            border-bottom: 1px solid var(--border);
             display: flex;
             align-items: center;
         }}
         .checklist-item .checkbox {{
             width: 18px;
             height: 18px;
-            border: 2px solid #ccc;
+            border: 2px solid var(--muted);
             border-radius: 3px;
             margin-right: 12px;
             flex-shrink: 0;
@@ -140,34 +172,50 @@ pub fn exportar_item_html(item: &Item, destino: &Path) -> Result<PathBuf> {
         }}
         .checklist-item.concluida .titulo-tarefa {{
             text-decoration: line-through;
-            color: #999;
+            color: var(--muted);
         }}
         .anexos {{
             margin-top: 16px;
             padding: 12px;
-            background: #f8f9fa;
+            background: var(--border);
             border-radius: 8px;
         }}
         .anexos h3 {{
             font-size: 0.95rem;
             margin-bottom: 8px;
-            color: #444;
+            color: var(--fg);
         }}
         .anexos li {{
             font-size: 0.85rem;
             margin-bottom: 4px;
         }}
+        /* Realce de sintaxe para blocos de código (ver servicos::realce) */
+        pre {{
+            background: #1a1a2e;
+            color: #e8e8e8;
+            padding: 16px;
+            border-radius: 8px;
+            overflow-x: auto;
+            font-family: 'Consolas', 'Monaco', monospace;
+            font-size: 0.9rem;
+        }}
+        .tok-kw {{ color: var(--accent); font-weight: 600; }}
+        .tok-str {{ color: #22c55e; }}
+        .tok-com {{ color: #888; font-style: italic; }}
+        .tok-num {{ color: #f59e0b; }}
+        .tok-type {{ color: #38bdf8; }}
         .rodape {{
             margin-top: 40px;
             padding-top: 16px;
-            border-top: 1px solid #e8e8e8;
+            border-top: 1px solid var(--border);
             font-size: 0.75rem;
-            color: #999;
+            color: var(--muted);
             text-align: center;
         }}
-        /* Estilos para impressão */
+        /* Impressão sempre em fundo branco, independente do tema escolhido
+           na tela, para economizar tinta e manter legibilidade no papel. */
         @media print {{
-            body {{ padding: 20px; }}
+            body {{ padding: 20px; background: #ffffff; color: #1a1a2e; }}
             .tag {{ border: 1px solid #ccc; }}
         }}
     </style>
@@ -187,6 +235,7 @@ pub fn exportar_item_html(item: &Item, destino: &Path) -> Result<PathBuf> {
     </div>
 </body>
 </html>"#,
+        variaveis_tema = variaveis_css_tema(tema),
         titulo = html_escape(&item.titulo),
         tipo = item.tipo,
         criado_em = item.criado_em,
@@ -198,14 +247,7 @@ pub fn exportar_item_html(item: &Item, destino: &Path) -> Result<PathBuf> {
         data_exportacao = Utc::now().format("%d/%m/%Y %H:%M UTC"),
     );
 
-    // Escrever arquivo
-    let mut arquivo = fs::File::create(&caminho_arquivo)
-        .context("Falha ao criar arquivo HTML")?;
-    arquivo.write_all(html.as_bytes())
-        .context("Falha ao escrever arquivo HTML")?;
-
-    info!("Item exportado como HTML: {:?}", caminho_arquivo);
-    Ok(caminho_arquivo)
+    html
 }
 
 /// Exporta uma lista de itens como arquivo CSV.
@@ -256,16 +298,834 @@ pub fn exportar_lista_csv(itens: &[Item], destino: &Path) -> Result<PathBuf> {
     Ok(caminho_arquivo)
 }
 
+// =============================================================================
+// IMPORTACAO DE LISTA CSV
+// =============================================================================
+// Inverso de `exportar_lista_csv`: permite migrar planilhas de
+// logins/documentos para o cofre, com tipagem correta de datas e números
+// via `Conversion` em vez de tratar toda célula como texto cru.
+// =============================================================================
+
+/// Campos de `NovoItem` que uma coluna do CSV pode preencher em
+/// `importar_lista_csv`. Campos fora desta lista (ex.: tags) não são
+/// suportados por este caminho de importação.
+const CAMPOS_CSV_SUPORTADOS: &[&str] = &["titulo", "descricao", "conteudo_nota", "data_vencimento"];
+
+/// Conversão de uma célula de CSV (sempre uma string bruta) para o tipo de
+/// destino esperado por `importar_lista_csv`. Os campos do item continuam
+/// sendo strings — a conversão serve para validar o formato da célula e
+/// rejeitar dados malformados linha a linha, em vez de deixá-los entrar no
+/// cofre sem checagem (ex.: uma data fora do padrão RFC3339).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    /// Mantém a célula como está, sem nenhuma validação.
+    Bytes,
+    /// Mesmo que `Bytes` — mantém a célula como string.
+    String,
+    /// Valida que a célula é um inteiro (ex.: "42").
+    Integer,
+    /// Valida que a célula é um número de ponto flutuante (ex.: "3.14").
+    Float,
+    /// Valida que a célula é um booleano ("true"/"false", "1"/"0", "sim"/"não").
+    Boolean,
+    /// Data/hora em RFC3339 (ex.: "2024-01-15T10:00:00Z").
+    Timestamp,
+    /// Data/hora em um formato `strftime` específico desta coluna (ex.:
+    /// "%d/%m/%Y"), convertida para RFC3339 antes de ser gravada no item.
+    TimestampFmt(String),
+}
+
+impl FromStr for Conversion {
+    type Err = String;
+
+    fn from_str(valor: &str) -> std::result::Result<Self, Self::Err> {
+        if let Some(formato) = valor.strip_prefix("timestamp_fmt:") {
+            return Ok(Conversion::TimestampFmt(formato.to_string()));
+        }
+        match valor {
+            "bytes" => Ok(Conversion::Bytes),
+            "string" => Ok(Conversion::String),
+            "integer" => Ok(Conversion::Integer),
+            "float" => Ok(Conversion::Float),
+            "boolean" => Ok(Conversion::Boolean),
+            "timestamp" => Ok(Conversion::Timestamp),
+            outro => Err(format!("conversão desconhecida: '{}'", outro)),
+        }
+    }
+}
+
+/// Importa itens de um CSV (ver `exportar_lista_csv` para o formato
+/// produzido/esperado, incluindo o escape de células com `csv_escape`).
+///
+/// `mapeamento` associa, para cada coluna usada, seu índice (0-based), o
+/// campo de destino em `NovoItem` (ver `CAMPOS_CSV_SUPORTADOS`) e a
+/// `Conversion` a aplicar na célula. Colunas não listadas em `mapeamento`
+/// são ignoradas. Células vazias viram `None` para campos opcionais
+/// (ex.: `data_vencimento`).
+///
+/// Se qualquer célula falhar na conversão, a importação é interrompida e o
+/// erro identifica a linha (1-based, contando o cabeçalho) e a coluna de
+/// destino causadoras — nenhuma linha é inserida silenciosamente com dados
+/// inválidos. Itens importados são sempre criados como `TipoItem::Nota`.
+///
+/// Retorna o total de itens criados.
+pub fn importar_lista_csv(
+    conexao: &Connection,
+    arquivo: &Path,
+    pasta_id: &str,
+    mapeamento: &[(usize, String, Conversion)],
+) -> Result<i64> {
+    info!("Importando CSV de {:?} para pasta {}", arquivo, pasta_id);
+
+    for (_, campo, _) in mapeamento {
+        if !CAMPOS_CSV_SUPORTADOS.contains(&campo.as_str()) {
+            anyhow::bail!("Campo de destino desconhecido: '{}'", campo);
+        }
+    }
+
+    let conteudo = fs::read_to_string(arquivo).context("Falha ao ler arquivo CSV")?;
+    let conteudo_sem_bom = conteudo.strip_prefix('\u{feff}').unwrap_or(&conteudo);
+
+    let mut total_importados: i64 = 0;
+
+    for (indice, linha) in conteudo_sem_bom.lines().enumerate() {
+        // A primeira linha é o cabeçalho (ver `exportar_lista_csv`).
+        if indice == 0 || linha.trim().is_empty() {
+            continue;
+        }
+        let numero_linha = indice + 1;
+        let celulas = analisar_linha_csv(linha);
+
+        let mut titulo: Option<String> = None;
+        let mut descricao: Option<String> = None;
+        let mut conteudo_nota: Option<String> = None;
+        let mut data_vencimento: Option<String> = None;
+
+        for (coluna, campo, conversao) in mapeamento {
+            let celula = celulas.get(*coluna).map(|s| s.as_str()).unwrap_or("");
+            let valor = aplicar_conversao(celula, conversao).map_err(|e| {
+                anyhow::anyhow!("Linha {}, coluna '{}': {}", numero_linha, campo, e)
+            })?;
+
+            match campo.as_str() {
+                "titulo" => titulo = valor,
+                "descricao" => descricao = valor,
+                "conteudo_nota" => conteudo_nota = valor,
+                "data_vencimento" => data_vencimento = valor,
+                _ => unreachable!("campo já validado em CAMPOS_CSV_SUPORTADOS"),
+            }
+        }
+
+        let novo_item = NovoItem {
+            pasta_id: pasta_id.to_string(),
+            tipo: TipoItem::Nota,
+            titulo: titulo.unwrap_or_else(|| format!("Item importado (linha {})", numero_linha)),
+            descricao,
+            conteudo_nota,
+            data_vencimento,
+            tag_ids: None,
+            regra_recorrencia: None,
+        };
+
+        queries::criar_item(conexao, &novo_item)
+            .with_context(|| format!("Falha ao criar item da linha {}", numero_linha))?;
+        total_importados += 1;
+    }
+
+    info!("CSV importado: {} itens criados na pasta {}", total_importados, pasta_id);
+    Ok(total_importados)
+}
+
+/// Aplica `conversao` a uma célula crua do CSV. Células vazias retornam
+/// `None` (campo opcional ausente); caso contrário, valida o formato e
+/// retorna o valor já normalizado (datas sempre como RFC3339, já que é o
+/// formato usado pelos campos de data do item).
+fn aplicar_conversao(celula: &str, conversao: &Conversion) -> std::result::Result<Option<String>, String> {
+    if celula.is_empty() {
+        return Ok(None);
+    }
+
+    let valor = match conversao {
+        Conversion::Bytes | Conversion::String => celula.to_string(),
+        Conversion::Integer => celula
+            .parse::<i64>()
+            .map_err(|e| format!("'{}' não é um inteiro válido: {}", celula, e))?
+            .to_string(),
+        Conversion::Float => celula
+            .parse::<f64>()
+            .map_err(|e| format!("'{}' não é um número válido: {}", celula, e))?
+            .to_string(),
+        Conversion::Boolean => match celula.to_lowercase().as_str() {
+            "true" | "1" | "sim" | "verdadeiro" => "true".to_string(),
+            "false" | "0" | "nao" | "não" | "falso" => "false".to_string(),
+            _ => return Err(format!("'{}' não é um booleano válido", celula)),
+        },
+        Conversion::Timestamp => {
+            chrono::DateTime::parse_from_rfc3339(celula)
+                .map_err(|e| format!("'{}' não é uma data RFC3339 válida: {}", celula, e))?;
+            celula.to_string()
+        }
+        Conversion::TimestampFmt(formato) => {
+            let data = chrono::NaiveDateTime::parse_from_str(celula, formato)
+                .map_err(|e| format!("'{}' não bate com o formato '{}': {}", celula, formato, e))?;
+            data.and_utc().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+        }
+    };
+
+    Ok(Some(valor))
+}
+
+/// Interpreta uma linha de CSV separada por ponto-e-vírgula, desfazendo o
+/// escape de `csv_escape` (campos entre aspas podem conter `;` e aspas
+/// duplicadas). Não trata campos com quebra de linha embutida — como
+/// `exportar_lista_csv` nunca produz esse caso, a importação assume uma
+/// célula por linha física do arquivo.
+fn analisar_linha_csv(linha: &str) -> Vec<String> {
+    let mut campos = Vec::new();
+    let mut atual = String::new();
+    let mut dentro_aspas = false;
+    let mut chars = linha.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if dentro_aspas {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    atual.push('"');
+                    chars.next();
+                } else {
+                    dentro_aspas = false;
+                }
+            } else {
+                atual.push(c);
+            }
+        } else if c == '"' {
+            dentro_aspas = true;
+        } else if c == ';' {
+            campos.push(std::mem::take(&mut atual));
+        } else {
+            atual.push(c);
+        }
+    }
+    campos.push(atual);
+    campos
+}
+
+// =============================================================================
+// IMPORTACAO/EXPORTACAO JSON COMPATIVEL COM BITWARDEN/VAULTWARDEN
+// =============================================================================
+// Formato: o JSON de exportação "sem senha mestra" (`encrypted: false`) do
+// Bitwarden/vaultwarden — `{ encrypted, folders: [...], items: [...] }`, com
+// `items[].type` 1 (login) ou 2 (nota segura), `login.{username,password,
+// totp,uris}` e `fields` (campos customizados).
+//
+// NOTA DE ESCOPO: o schema de `Item` do VaultCraft não tem colunas para
+// credenciais de login — ele foi desenhado para notas/documentos/checklists,
+// não para um cofre de senhas. Em vez de inventar colunas novas só para
+// hospedar dados de outro produto, um item importado do Bitwarden guarda seu
+// `login`/`fields`/`organizationId`/`favorite` originais num bloco de
+// metadados hex-codificado (`MetadadosBitwarden`, ver `embutir_metadados_bitwarden`)
+// anexado ao final de `conteudo_nota` — hex para não colidir com o Markdown
+// ao redor nem com o delimitador do próprio bloco. `exportar_lista_bitwarden_json`
+// lê esse bloco de volta para reconstituir o JSON original, e também grava
+// nele o `tipo`/`descricao`/tags atuais do item, sempre atualizados a partir
+// do estado do item (diferente de login/fields, que só mudam através de uma
+// nova importação). O efeito: exportar → importar em outro cliente
+// Bitwarden-compatível → exportar de volta → reimportar no VaultCraft
+// reproduz o mesmo conjunto de itens (mesmo tipo, notas, pasta e campos
+// customizados), sem que o VaultCraft precise entender login/senha/TOTP
+// como conceitos próprios — ele só os carrega de um lado para o outro.
+// Pastas do Bitwarden viram pastas VaultCraft pelo nome completo (`caminho`
+// do Bitwarden usa "/" para aninhamento, ex. "Trabalho/Financeiro"); não
+// decompomos isso numa árvore de pastas pai/filho, só criamos uma pasta por
+// nome de pasta do Bitwarden na raiz do cofre.
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportacaoBitwarden {
+    encrypted: bool,
+    folders: Vec<PastaBitwarden>,
+    items: Vec<ItemBitwarden>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PastaBitwarden {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemBitwarden {
+    id: String,
+    organization_id: Option<String>,
+    folder_id: Option<String>,
+    #[serde(rename = "type")]
+    tipo: i32,
+    name: String,
+    notes: Option<String>,
+    #[serde(default)]
+    favorite: bool,
+    #[serde(default)]
+    login: Option<LoginBitwarden>,
+    #[serde(default)]
+    fields: Vec<CampoBitwarden>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginBitwarden {
+    username: Option<String>,
+    password: Option<String>,
+    totp: Option<String>,
+    #[serde(default)]
+    uris: Vec<UriBitwarden>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UriBitwarden {
+    uri: String,
+    #[serde(rename = "match")]
+    correspondencia: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CampoBitwarden {
+    name: String,
+    value: Option<String>,
+    #[serde(rename = "type")]
+    tipo: i32,
+}
+
+/// Item do tipo "login" no schema do Bitwarden.
+const TIPO_BITWARDEN_LOGIN: i32 = 1;
+/// Item do tipo "nota segura" no schema do Bitwarden — usado para qualquer
+/// item VaultCraft que não carregue dados de `login` preservados.
+const TIPO_BITWARDEN_NOTA_SEGURA: i32 = 2;
+
+/// Dados que não têm coluna própria em `Item` mas precisam sobreviver a um
+/// ciclo exportar/importar — ver a NOTA DE ESCOPO desta seção.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct MetadadosBitwarden {
+    tipo_vaultcraft: String,
+    descricao: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    organization_id: Option<String>,
+    #[serde(default)]
+    favorite: bool,
+    login: Option<LoginBitwarden>,
+    #[serde(default)]
+    fields: Vec<CampoBitwarden>,
+}
+
+/// Início/fim do bloco de metadados embutido no final de `conteudo_nota`.
+const MARCADOR_BITWARDEN_INICIO: &str = "<!-- vaultcraft:bitwarden:";
+const MARCADOR_BITWARDEN_FIM: &str = " -->";
+
+/// Acrescenta o bloco de metadados (hex-codificado) de `metadados` ao final
+/// de `conteudo_base`. Usado por `importar_lista_bitwarden_json` (para
+/// guardar o que veio de fora) e por `exportar_lista_bitwarden_json` (para
+/// manter o bloco atualizado após reexportar o mesmo item).
+fn embutir_metadados_bitwarden(conteudo_base: &str, metadados: &MetadadosBitwarden) -> String {
+    let json = serde_json::to_string(metadados).unwrap_or_default();
+    let hex_json = hex::encode(json.as_bytes());
+    if conteudo_base.is_empty() {
+        format!("{}{}{}", MARCADOR_BITWARDEN_INICIO, hex_json, MARCADOR_BITWARDEN_FIM)
+    } else {
+        format!("{}\n\n{}{}{}", conteudo_base, MARCADOR_BITWARDEN_INICIO, hex_json, MARCADOR_BITWARDEN_FIM)
+    }
+}
+
+/// Inverso de `embutir_metadados_bitwarden`: separa o conteúdo visível (para
+/// `notes`) dos metadados embutidos, se houver algum. Conteúdo sem o
+/// marcador (item criado nativamente no VaultCraft) retorna `(conteudo, None)`
+/// sem modificação.
+fn extrair_metadados_bitwarden(conteudo_nota: &str) -> (String, Option<MetadadosBitwarden>) {
+    let Some(inicio) = conteudo_nota.find(MARCADOR_BITWARDEN_INICIO) else {
+        return (conteudo_nota.to_string(), None);
+    };
+    let apos_marcador = inicio + MARCADOR_BITWARDEN_INICIO.len();
+    let Some(fim_relativo) = conteudo_nota[apos_marcador..].find(MARCADOR_BITWARDEN_FIM) else {
+        return (conteudo_nota.to_string(), None);
+    };
+    let hex_json = &conteudo_nota[apos_marcador..apos_marcador + fim_relativo];
+
+    let metadados = hex::decode(hex_json)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|json| serde_json::from_str::<MetadadosBitwarden>(&json).ok());
+
+    let conteudo_visivel = conteudo_nota[..inicio].trim_end().to_string();
+    (conteudo_visivel, metadados)
+}
+
+/// Exporta `itens` (com suas `pastas`) como JSON compatível com o formato de
+/// exportação "sem senha mestra" do Bitwarden/vaultwarden.
+///
+/// Itens importados anteriormente de um Bitwarden/vaultwarden (ver
+/// `importar_lista_bitwarden_json`) reexportam seu `login`/`fields`/
+/// `organizationId`/`favorite` originais; itens nativos do VaultCraft
+/// exportam como nota segura (`type: 2`), com `notes` = `conteudo_nota`.
+pub fn exportar_lista_bitwarden_json(pastas: &[Pasta], itens: &[Item], destino: &Path) -> Result<PathBuf> {
+    let agora = Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let nome_arquivo = format!("vaultcraft_bitwarden_{}.json", agora);
+    let caminho_arquivo = destino.join(&nome_arquivo);
+
+    info!("Exportando {} itens como JSON Bitwarden para: {:?}", itens.len(), caminho_arquivo);
+
+    let folders: Vec<PastaBitwarden> = pastas
+        .iter()
+        .map(|pasta| PastaBitwarden { id: pasta.id.clone(), name: pasta.caminho.clone() })
+        .collect();
+
+    let items: Vec<ItemBitwarden> = itens
+        .iter()
+        .map(|item| {
+            let (conteudo_visivel, metadados_previos) =
+                extrair_metadados_bitwarden(item.conteudo_nota.as_deref().unwrap_or(""));
+
+            // login/fields/organizationId/favorite só existem se o item veio
+            // de uma importação anterior; tipo/descricao/tags sempre refletem
+            // o estado atual do item, não o que estava gravado no bloco.
+            let metadados = MetadadosBitwarden {
+                tipo_vaultcraft: item.tipo.to_string(),
+                descricao: item.descricao.clone(),
+                tags: item.tags.iter().map(|tag| tag.nome.clone()).collect(),
+                ..metadados_previos.unwrap_or_default()
+            };
+
+            ItemBitwarden {
+                id: item.id.clone(),
+                organization_id: metadados.organization_id.clone(),
+                folder_id: Some(item.pasta_id.clone()),
+                tipo: if metadados.login.is_some() { TIPO_BITWARDEN_LOGIN } else { TIPO_BITWARDEN_NOTA_SEGURA },
+                name: item.titulo.clone(),
+                notes: (!conteudo_visivel.is_empty()).then_some(conteudo_visivel),
+                favorite: metadados.favorite,
+                login: metadados.login.clone(),
+                fields: metadados.fields.clone(),
+            }
+        })
+        .collect();
+
+    let exportacao = ExportacaoBitwarden { encrypted: false, folders, items };
+    let json = serde_json::to_string_pretty(&exportacao)
+        .context("Falha ao serializar exportação Bitwarden")?;
+
+    fs::write(&caminho_arquivo, json).context("Falha ao escrever arquivo JSON Bitwarden")?;
+
+    info!("JSON Bitwarden exportado: {:?} ({} itens, {} pastas)", caminho_arquivo, itens.len(), pastas.len());
+    Ok(caminho_arquivo)
+}
+
+/// Importa um JSON de exportação do Bitwarden/vaultwarden (ver
+/// `exportar_lista_bitwarden_json` para o formato). Cada pasta vira uma
+/// pasta VaultCraft (pelo nome completo — sem decompor "/" em hierarquia);
+/// itens sem `folderId` (ou cuja pasta não constava em `folders`) vão para
+/// `pasta_id_padrao`. Tags gravadas num bloco de metadados de uma
+/// reimportação (ver `MetadadosBitwarden`) são recriadas por nome se ainda
+/// não existirem no cofre.
+///
+/// Retorna o total de itens criados.
+pub fn importar_lista_bitwarden_json(
+    conexao: &Connection,
+    arquivo: &Path,
+    pasta_id_padrao: &str,
+) -> Result<i64> {
+    info!("Importando JSON Bitwarden de {:?} para pasta padrão {}", arquivo, pasta_id_padrao);
+
+    let conteudo = fs::read_to_string(arquivo).context("Falha ao ler arquivo JSON do Bitwarden")?;
+    let exportacao: ExportacaoBitwarden = serde_json::from_str(&conteudo)
+        .context("JSON não está no formato de exportação do Bitwarden (esperado { encrypted, folders, items })")?;
+
+    if exportacao.encrypted {
+        anyhow::bail!(
+            "Este arquivo é uma exportação cifrada do Bitwarden — só exportações \"sem senha mestra\" \
+             (encrypted: false) são suportadas"
+        );
+    }
+
+    let mut mapa_pastas: HashMap<String, String> = HashMap::new();
+    for pasta_bw in &exportacao.folders {
+        let nova = queries::criar_pasta(conexao, &NovaPasta { nome: pasta_bw.name.clone(), pasta_pai_id: None })
+            .with_context(|| format!("Falha ao criar pasta '{}' importada do Bitwarden", pasta_bw.name))?;
+        mapa_pastas.insert(pasta_bw.id.clone(), nova.id);
+    }
+
+    let mut tags_existentes: HashMap<String, String> = queries::listar_tags(conexao)?
+        .into_iter()
+        .map(|tag| (tag.nome, tag.id))
+        .collect();
+
+    let mut total_importados: i64 = 0;
+    for item_bw in &exportacao.items {
+        let pasta_id = item_bw
+            .folder_id
+            .as_ref()
+            .and_then(|id| mapa_pastas.get(id))
+            .cloned()
+            .unwrap_or_else(|| pasta_id_padrao.to_string());
+
+        let notas_brutas = item_bw.notes.as_deref().unwrap_or("");
+        let (conteudo_visivel, metadados_previos) = extrair_metadados_bitwarden(notas_brutas);
+
+        // Sem bloco de metadados: item genuinamente vindo do Bitwarden, sem
+        // histórico prévio no VaultCraft — entra como Nota comum.
+        let tipo = metadados_previos
+            .as_ref()
+            .map(|m| TipoItem::de_str(&m.tipo_vaultcraft))
+            .unwrap_or(TipoItem::Nota);
+        let descricao = metadados_previos.as_ref().and_then(|m| m.descricao.clone());
+        let nomes_tags = metadados_previos.as_ref().map(|m| m.tags.clone()).unwrap_or_default();
+
+        let mut tag_ids = Vec::with_capacity(nomes_tags.len());
+        for nome_tag in &nomes_tags {
+            let id_tag = match tags_existentes.get(nome_tag) {
+                Some(id) => id.clone(),
+                None => {
+                    let tag = queries::criar_tag(conexao, &NovaTag { nome: nome_tag.clone(), cor: None })
+                        .with_context(|| format!("Falha ao criar tag '{}' importada do Bitwarden", nome_tag))?;
+                    tags_existentes.insert(nome_tag.clone(), tag.id.clone());
+                    tag.id
+                }
+            };
+            tag_ids.push(id_tag);
+        }
+
+        let metadados = MetadadosBitwarden {
+            tipo_vaultcraft: tipo.to_string(),
+            descricao: descricao.clone(),
+            tags: nomes_tags,
+            organization_id: item_bw.organization_id.clone(),
+            favorite: item_bw.favorite,
+            login: item_bw.login.clone(),
+            fields: item_bw.fields.clone(),
+        };
+        let conteudo_nota = embutir_metadados_bitwarden(&conteudo_visivel, &metadados);
+
+        let novo_item = NovoItem {
+            pasta_id,
+            tipo,
+            titulo: item_bw.name.clone(),
+            descricao,
+            conteudo_nota: Some(conteudo_nota),
+            data_vencimento: None,
+            tag_ids: (!tag_ids.is_empty()).then_some(tag_ids),
+            regra_recorrencia: None,
+        };
+
+        queries::criar_item(conexao, &novo_item)
+            .with_context(|| format!("Falha ao criar item '{}' importado do Bitwarden", item_bw.name))?;
+        total_importados += 1;
+    }
+
+    info!(
+        "JSON Bitwarden importado: {} item(ns), {} pasta(s)",
+        total_importados, exportacao.folders.len()
+    );
+    Ok(total_importados)
+}
+
+// =============================================================================
+// EXPORTACAO DO COFRE COMO SITE ESTATICO NAVEGAVEL
+// =============================================================================
+
+/// Exporta o cofre inteiro como um site estático autocontido (ZIP), com uma
+/// página HTML por item, um `index.html` em árvore seguindo `Pasta.caminho`
+/// e um `search-index.json` consumido por um `search.js` embutido (sem rede).
+///
+/// Diferente de `criar_backup` (que mira restauração fiel), este pacote é
+/// para navegação/consulta: um arquivo que o usuário pode extrair e abrir
+/// direto no navegador, inclusive anos depois, sem precisar do VaultCraft.
+pub fn exportar_cofre_site(pastas: &[Pasta], itens: &[Item], destino: &Path) -> Result<PathBuf> {
+    let agora = Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let nome_arquivo = format!("vaultcraft_site_{}.zip", agora);
+    let caminho_zip = destino.join(&nome_arquivo);
+
+    info!("Exportando site estático do cofre para: {:?}", caminho_zip);
+
+    if let Some(dir_pai) = caminho_zip.parent() {
+        fs::create_dir_all(dir_pai).context("Falha ao criar diretório de destino do site")?;
+    }
+
+    let arquivo_zip = fs::File::create(&caminho_zip)
+        .context("Falha ao criar arquivo ZIP do site")?;
+    let mut zip = ZipWriter::new(arquivo_zip);
+    let opcoes = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    // Uma página HTML por item, nomeada pelo id (estável e sem colisões).
+    let mut registros_busca = Vec::with_capacity(itens.len());
+    for item in itens {
+        let pagina = renderizar_pagina_item(item, TemaExportacao::Claro);
+        zip.start_file(format!("itens/{}.html", item.id), opcoes)
+            .context("Falha ao iniciar página de item no ZIP")?;
+        zip.write_all(pagina.as_bytes())
+            .context("Falha ao escrever página de item no ZIP")?;
+
+        registros_busca.push(registro_busca(item, pastas));
+    }
+
+    // index.html: árvore de pastas seguindo Pasta.caminho
+    let indice_html = renderizar_indice_site(pastas, itens);
+    zip.start_file("index.html", opcoes)
+        .context("Falha ao iniciar index.html no ZIP")?;
+    zip.write_all(indice_html.as_bytes())
+        .context("Falha ao escrever index.html no ZIP")?;
+
+    // search-index.json
+    let indice_json = serde_json::to_string(&registros_busca)
+        .context("Falha ao serializar search-index.json")?;
+    zip.start_file("search-index.json", opcoes)
+        .context("Falha ao iniciar search-index.json no ZIP")?;
+    zip.write_all(indice_json.as_bytes())
+        .context("Falha ao escrever search-index.json no ZIP")?;
+
+    // search.js: busca client-side sem dependências de rede
+    zip.start_file("search.js", opcoes)
+        .context("Falha ao iniciar search.js no ZIP")?;
+    zip.write_all(SEARCH_JS.as_bytes())
+        .context("Falha ao escrever search.js no ZIP")?;
+
+    zip.finish().context("Falha ao finalizar ZIP do site")?;
+
+    info!(
+        "Site estático exportado: {:?} ({} itens, {} pastas)",
+        caminho_zip, itens.len(), pastas.len()
+    );
+
+    Ok(caminho_zip)
+}
+
+/// Registro de um item no `search-index.json`: `texto` é um excerto em
+/// texto puro (sem marcação Markdown/HTML) de `conteudo_nota`/`descricao`,
+/// limitado em tamanho para manter o índice leve.
+#[derive(serde::Serialize)]
+struct RegistroBusca {
+    id: String,
+    titulo: String,
+    tipo: String,
+    tags: Vec<String>,
+    caminho: String,
+    texto: String,
+}
+
+const TAMANHO_MAXIMO_TEXTO: usize = 500;
+
+fn registro_busca(item: &Item, pastas: &[Pasta]) -> RegistroBusca {
+    let caminho = pastas
+        .iter()
+        .find(|p| p.id == item.pasta_id)
+        .map(|p| p.caminho.clone())
+        .unwrap_or_default();
+
+    let bruto = item.conteudo_nota.as_deref()
+        .or(item.descricao.as_deref())
+        .unwrap_or("");
+
+    RegistroBusca {
+        id: item.id.clone(),
+        titulo: item.titulo.clone(),
+        tipo: item.tipo.to_string(),
+        tags: item.tags.iter().map(|t| t.nome.clone()).collect(),
+        caminho,
+        texto: texto_puro_limitado(bruto, TAMANHO_MAXIMO_TEXTO),
+    }
+}
+
+/// Remove marcação Markdown/HTML grosseiramente (mantém apenas o texto) e
+/// corta no limite de caracteres informado, adicionando reticências.
+fn texto_puro_limitado(bruto: &str, limite: usize) -> String {
+    let sem_marcacao: String = bruto
+        .chars()
+        .filter(|c| !matches!(c, '#' | '*' | '`' | '>' | '_' | '|'))
+        .collect();
+    let normalizado = sem_marcacao.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if normalizado.chars().count() <= limite {
+        normalizado
+    } else {
+        let cortado: String = normalizado.chars().take(limite).collect();
+        format!("{}…", cortado)
+    }
+}
+
+/// Renderiza o `index.html` do site: uma árvore de pastas (por `caminho`)
+/// com links para cada item, e a caixa de busca que usa `search.js`.
+fn renderizar_indice_site(pastas: &[Pasta], itens: &[Item]) -> String {
+    let mut pastas_ordenadas = pastas.to_vec();
+    pastas_ordenadas.sort_by(|a, b| a.caminho.cmp(&b.caminho));
+
+    let mut arvore = String::new();
+    for pasta in &pastas_ordenadas {
+        let itens_da_pasta: Vec<&Item> = itens.iter().filter(|i| i.pasta_id == pasta.id).collect();
+        arvore.push_str(&format!(
+            "<li><strong>{}</strong><ul>\n",
+            html_escape(&pasta.caminho)
+        ));
+        for item in itens_da_pasta {
+            arvore.push_str(&format!(
+                r#"<li><a href="itens/{}.html">{}</a></li>"#,
+                item.id,
+                html_escape(&item.titulo)
+            ));
+            arvore.push('\n');
+        }
+        arvore.push_str("</ul></li>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="pt-BR">
+<head>
+    <meta charset="UTF-8">
+    <title>VaultCraft — Cofre Exportado</title>
+    <style>
+        body {{ font-family: sans-serif; max-width: 800px; margin: 0 auto; padding: 40px 20px; color: #1a1a2e; }}
+        h1 {{ color: #16213e; }}
+        #busca {{ width: 100%; padding: 10px; font-size: 1rem; margin: 20px 0; box-sizing: border-box; }}
+        #resultados a, .arvore a {{ color: #6366f1; text-decoration: none; }}
+        #resultados li, .arvore li {{ margin: 4px 0; }}
+    </style>
+</head>
+<body>
+    <h1>VaultCraft — Cofre Exportado</h1>
+    <input id="busca" type="search" placeholder="Buscar no cofre…" autocomplete="off">
+    <ul id="resultados"></ul>
+    <h2>Todas as pastas</h2>
+    <ul class="arvore">
+{arvore}
+    </ul>
+    <script src="search.js"></script>
+</body>
+</html>"#,
+        arvore = arvore
+    )
+}
+
+/// `search.js` embutido no site estático: busca 100% client-side, sem rede.
+/// Tokeniza a consulta, pontua cada registro por frequência de termos em
+/// `titulo` (peso maior) e `texto`, com bônus de prefixo/fuzzy via distância
+/// de Levenshtein limitada (≤2) para tolerar pequenos erros de digitação.
+const SEARCH_JS: &str = r#"
+(function () {
+  var indice = null;
+  var campoBusca = document.getElementById('busca');
+  var listaResultados = document.getElementById('resultados');
+
+  fetch('search-index.json')
+    .then(function (resp) { return resp.json(); })
+    .then(function (dados) { indice = dados; })
+    .catch(function () { indice = []; });
+
+  function distanciaLevenshtein(a, b, maximo) {
+    if (Math.abs(a.length - b.length) > maximo) return maximo + 1;
+    var dp = [];
+    for (var i = 0; i <= a.length; i++) dp.push([i]);
+    for (var j = 0; j <= b.length; j++) dp[0][j] = j;
+    for (var i = 1; i <= a.length; i++) {
+      for (var j = 1; j <= b.length; j++) {
+        var custo = a[i - 1] === b[j - 1] ? 0 : 1;
+        dp[i][j] = Math.min(
+          dp[i - 1][j] + 1,
+          dp[i][j - 1] + 1,
+          dp[i - 1][j - 1] + custo
+        );
+      }
+    }
+    return dp[a.length][b.length];
+  }
+
+  function pontuarTermo(termo, textoAlvo, peso) {
+    var palavras = textoAlvo.toLowerCase().split(/\s+/);
+    var pontos = 0;
+    palavras.forEach(function (palavra) {
+      if (palavra === termo) {
+        pontos += peso * 3;
+      } else if (palavra.indexOf(termo) === 0) {
+        pontos += peso * 2;
+      } else if (termo.length >= 3 && distanciaLevenshtein(termo, palavra, 2) <= 2) {
+        pontos += peso;
+      }
+    });
+    return pontos;
+  }
+
+  function pontuarRegistro(termos, registro) {
+    var pontos = 0;
+    termos.forEach(function (termo) {
+      pontos += pontuarTermo(termo, registro.titulo || '', 5);
+      pontos += pontuarTermo(termo, registro.texto || '', 1);
+    });
+    return pontos;
+  }
+
+  function buscar(consulta) {
+    if (!indice || !consulta.trim()) {
+      listaResultados.innerHTML = '';
+      return;
+    }
+    var termos = consulta.toLowerCase().split(/\s+/).filter(Boolean);
+    var pontuados = indice
+      .map(function (registro) { return { registro: registro, pontos: pontuarRegistro(termos, registro) }; })
+      .filter(function (r) { return r.pontos > 0; })
+      .sort(function (a, b) { return b.pontos - a.pontos; })
+      .slice(0, 20);
+
+    listaResultados.innerHTML = pontuados
+      .map(function (r) {
+        return '<li><a href="itens/' + r.registro.id + '.html">' + r.registro.titulo + '</a> — ' + r.registro.caminho + '</li>';
+      })
+      .join('');
+  }
+
+  campoBusca.addEventListener('input', function (ev) { buscar(ev.target.value); });
+})();
+"#;
+
+/// Retorna as declarações CSS custom properties (`--bg`, `--fg`, `--muted`,
+/// `--accent`, `--border`) para o `:root` do HTML exportado, de acordo com
+/// o tema escolhido.
+fn variaveis_css_tema(tema: TemaExportacao) -> &'static str {
+    match tema {
+        TemaExportacao::Claro => {
+            "            --bg: #ffffff;\n\
+             \x20           --fg: #1a1a2e;\n\
+             \x20           --muted: #666666;\n\
+             \x20           --accent: #6366f1;\n\
+             \x20           --border: #e8e8e8;"
+        }
+        TemaExportacao::Escuro => {
+            "            --bg: #0f0f1a;\n\
+             \x20           --fg: #e8e8f0;\n\
+             \x20           --muted: #9a9ab0;\n\
+             \x20           --accent: #818cf8;\n\
+             \x20           --border: #2a2a3e;"
+        }
+        TemaExportacao::AltoContraste => {
+            "            --bg: #000000;\n\
+             \x20           --fg: #ffffff;\n\
+             \x20           --muted: #cccccc;\n\
+             \x20           --accent: #ffd700;\n\
+             \x20           --border: #ffffff;"
+        }
+    }
+}
+
 // =============================================================================
 // FUNCOES AUXILIARES DE FORMATACAO
 // =============================================================================
 
 /// Formata o conteúdo de uma nota como HTML.
+///
+/// `conteudo_nota` guarda Markdown livre, então renderizamos CommonMark real
+/// (veja `servicos::markdown`) em vez de apenas escapar o texto — caso
+/// contrário o HTML exportado mostraria `#`, `*` e `` ` `` literais.
 fn formatar_nota_html(item: &Item) -> String {
     let conteudo = item.conteudo_nota.as_deref().unwrap_or("(sem conteúdo)");
     format!(
         r#"<div class="conteudo">{}</div>"#,
-        html_escape(conteudo)
+        markdown_para_html(conteudo)
     )
 }
 
@@ -327,7 +1187,7 @@ fn formatar_anexos_html(item: &Item) -> String {
 }
 
 /// Escapa caracteres especiais do HTML para prevenir XSS.
-fn html_escape(texto: &str) -> String {
+pub(crate) fn html_escape(texto: &str) -> String {
     texto
         .replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -0,0 +1,226 @@
+// =============================================================================
+// VaultCraft — Fachada HTTP Local do Motor de Segredos (services::api_segredos)
+// =============================================================================
+// Expõe `services::api_segredos` como `GET/PUT/DELETE /v1/secret/<path>` em
+// `127.0.0.1:<porta>`, autenticado por token bearer (`api_segredos::criar_token`/
+// `validar_token`) — a mesma decisão de transporte registrada em
+// `services::rpc` (ver DECISÃO DE TRANSPORTE lá): o motor de segredos em si
+// já era código pronto desde o chunk original, o que faltava era o listener.
+//
+// Como `services::rpc`, o listener não inicia sozinho: só passa a escutar
+// depois que `commands::iniciar_servidor_http_segredos` é chamado
+// explicitamente. O app continua offline-first por padrão (ver topo de
+// `lib.rs`).
+//
+// Cada requisição precisa do cabeçalho `Authorization: Bearer <token>` com
+// um token emitido por `api_segredos::criar_token` — não há sessão de
+// cookie/CSRF aqui porque o único cliente esperado é um script/CI local,
+// não um navegador.
+// =============================================================================
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Context, Result};
+use axum::extract::{Path as ExtractPath, State as ExtractState};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Json};
+use axum::routing::{delete, get, put};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+
+use crate::db::connection::PoolConexoes;
+use crate::services::{api_segredos, cifragem::EstadoSessao};
+
+#[derive(Clone)]
+struct EstadoHttp {
+    banco: Arc<PoolConexoes>,
+    sessao_cifragem: Arc<EstadoSessao>,
+}
+
+/// Controla o ciclo de vida do listener HTTP local da API de segredos.
+/// Mesmo formato de `services::rpc::ServidorRpc`.
+pub struct ServidorHttpSegredos {
+    tarefa: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+impl ServidorHttpSegredos {
+    pub fn novo() -> Self {
+        Self { tarefa: Mutex::new(None) }
+    }
+
+    pub fn esta_em_execucao(&self) -> bool {
+        self.tarefa.lock().map(|t| t.is_some()).unwrap_or(false)
+    }
+
+    /// Inicia o listener em `127.0.0.1:porta`, se ainda não estiver rodando.
+    pub fn iniciar(&self, banco: Arc<PoolConexoes>, sessao_cifragem: Arc<EstadoSessao>, porta: u16) -> Result<()> {
+        let mut tarefa = self
+            .tarefa
+            .lock()
+            .map_err(|_| anyhow::anyhow!("lock da tarefa do servidor HTTP de segredos envenenado"))?;
+        if tarefa.is_some() {
+            bail!("Servidor HTTP de segredos já está em execução");
+        }
+
+        *tarefa = Some(tauri::async_runtime::spawn(async move {
+            if let Err(erro) = servir_http(banco, sessao_cifragem, porta).await {
+                log::error!("Servidor HTTP de segredos encerrado com erro: {:#}", erro);
+            }
+        }));
+        Ok(())
+    }
+
+    /// Encerra o listener, se estiver em execução.
+    pub fn parar(&self) {
+        if let Ok(mut tarefa) = self.tarefa.lock() {
+            if let Some(handle) = tarefa.take() {
+                handle.abort();
+            }
+        }
+    }
+}
+
+impl Default for ServidorHttpSegredos {
+    fn default() -> Self {
+        Self::novo()
+    }
+}
+
+async fn servir_http(banco: Arc<PoolConexoes>, sessao_cifragem: Arc<EstadoSessao>, porta: u16) -> Result<()> {
+    let estado = EstadoHttp { banco, sessao_cifragem };
+
+    let app = Router::new()
+        .route(
+            "/v1/secret/*caminho",
+            get(handler_ler_segredo)
+                .put(handler_escrever_segredo)
+                .delete(handler_excluir_segredo),
+        )
+        .with_state(estado);
+
+    let endereco = SocketAddr::from(([127, 0, 0, 1], porta));
+    let listener = TcpListener::bind(endereco)
+        .await
+        .with_context(|| format!("Falha ao abrir listener HTTP em {}", endereco))?;
+    log::info!("Servidor HTTP de segredos escutando em {}", endereco);
+
+    axum::serve(listener, app)
+        .await
+        .context("Servidor HTTP de segredos falhou")
+}
+
+#[derive(Debug, Serialize)]
+struct RespostaErro {
+    erro: String,
+}
+
+fn resposta_erro(status: StatusCode, mensagem: impl Into<String>) -> axum::response::Response {
+    (status, Json(RespostaErro { erro: mensagem.into() })).into_response()
+}
+
+/// Confere `Authorization: Bearer <token>` contra `api_segredos::validar_token`.
+/// Retorna o próprio erro HTTP já pronto para devolver quando a autenticação falhar.
+fn autenticar(estado: &EstadoHttp, cabecalhos: &HeaderMap) -> Result<(), axum::response::Response> {
+    let Some(cabecalho) = cabecalhos.get(axum::http::header::AUTHORIZATION) else {
+        return Err(resposta_erro(StatusCode::UNAUTHORIZED, "cabeçalho Authorization ausente"));
+    };
+    let cabecalho = cabecalho
+        .to_str()
+        .map_err(|_| resposta_erro(StatusCode::UNAUTHORIZED, "cabeçalho Authorization inválido"))?;
+    let Some(token) = cabecalho.strip_prefix("Bearer ") else {
+        return Err(resposta_erro(StatusCode::UNAUTHORIZED, "esperado 'Authorization: Bearer <token>'"));
+    };
+
+    let conexao = estado
+        .banco
+        .leitor()
+        .map_err(|e| resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    let valido = api_segredos::validar_token(&conexao, token)
+        .map_err(|e| resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    drop(conexao);
+
+    match valido {
+        Some(_) => Ok(()),
+        None => Err(resposta_erro(StatusCode::UNAUTHORIZED, "token inválido, expirado ou revogado")),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RespostaSegredo {
+    caminho: String,
+    valor: String,
+}
+
+async fn handler_ler_segredo(
+    ExtractState(estado): ExtractState<EstadoHttp>,
+    ExtractPath(caminho): ExtractPath<String>,
+    cabecalhos: HeaderMap,
+) -> axum::response::Response {
+    if let Err(resposta) = autenticar(&estado, &cabecalhos) {
+        return resposta;
+    }
+
+    let conexao = match estado.banco.leitor() {
+        Ok(c) => c,
+        Err(erro) => return resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", erro)),
+    };
+
+    match api_segredos::ler_segredo(&conexao, &estado.sessao_cifragem, &caminho) {
+        Ok(Some(valor)) => Json(RespostaSegredo { caminho, valor }).into_response(),
+        Ok(None) => resposta_erro(StatusCode::NOT_FOUND, format!("nenhum segredo em '{}'", caminho)),
+        Err(erro) => resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", erro)),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CorpoEscreverSegredo {
+    valor: String,
+}
+
+async fn handler_escrever_segredo(
+    ExtractState(estado): ExtractState<EstadoHttp>,
+    ExtractPath(caminho): ExtractPath<String>,
+    cabecalhos: HeaderMap,
+    Json(corpo): Json<CorpoEscreverSegredo>,
+) -> axum::response::Response {
+    if let Err(resposta) = autenticar(&estado, &cabecalhos) {
+        return resposta;
+    }
+
+    let conexao = match estado.banco.escritor() {
+        Ok(c) => c,
+        Err(erro) => return resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", erro)),
+    };
+
+    match api_segredos::escrever_segredo(&conexao, &estado.sessao_cifragem, &caminho, &corpo.valor) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(erro) => resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", erro)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RespostaExclusao {
+    removido: bool,
+}
+
+async fn handler_excluir_segredo(
+    ExtractState(estado): ExtractState<EstadoHttp>,
+    ExtractPath(caminho): ExtractPath<String>,
+    cabecalhos: HeaderMap,
+) -> axum::response::Response {
+    if let Err(resposta) = autenticar(&estado, &cabecalhos) {
+        return resposta;
+    }
+
+    let conexao = match estado.banco.escritor() {
+        Ok(c) => c,
+        Err(erro) => return resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", erro)),
+    };
+
+    match api_segredos::excluir_segredo(&conexao, &caminho) {
+        Ok(removido) => Json(RespostaExclusao { removido }).into_response(),
+        Err(erro) => resposta_erro(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", erro)),
+    }
+}
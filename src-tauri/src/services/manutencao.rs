@@ -0,0 +1,109 @@
+// =============================================================================
+// VaultCraft — Manutenção Programada do Cofre
+// =============================================================================
+// Até aqui, a única ação de manutenção era o VACUUM ad-hoc disparado pelo
+// comando `compactar_banco` (registrado no log como `{"acao": "vacuum"}`).
+// Sem retenção, `log_auditoria` ("nunca é deletado", ver `db::models::LogAuditoria`)
+// cresce sem limite.
+//
+// `ConfiguracaoManutencao` (ver db::models) expõe os limites ajustáveis:
+// - dias_retencao_auditoria / max_linhas_auditoria: até quando/quanto o log
+//   de auditoria pode crescer antes de ser podado
+// - intervalo_auto_vacuum_dias: de quanto em quanto tempo VACUUM +
+//   `PRAGMA optimize` rodam automaticamente
+//
+// `executar` é pensado para ser chamado periodicamente (ex.: na
+// inicialização do app) — cada etapa só age quando necessário, então
+// chamar de novo antes do intervalo configurado não tem efeito.
+// =============================================================================
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+
+use crate::db::models::{ConfiguracaoManutencao, RelatorioManutencao};
+use crate::db::queries;
+use crate::services::auditoria;
+
+/// Chave de configuração onde fica a data/hora da última execução
+/// automática de VACUUM + `PRAGMA optimize` (ver `executar_vacuum_se_devido`).
+const CONFIG_ULTIMO_AUTO_VACUUM: &str = "manutencao_ultimo_auto_vacuum";
+
+/// Executa a manutenção programada do cofre de acordo com `config`:
+/// 1. Poda linhas do log de auditoria além da janela de retenção/limite de
+///    linhas (ver `queries::podar_log_auditoria`), registrando a poda de
+///    volta no próprio log (se algo foi removido)
+/// 2. Roda VACUUM + `PRAGMA optimize` se já se passou
+///    `config.intervalo_auto_vacuum_dias` desde a última vez (ou nunca rodou)
+pub fn executar(conexao: &Connection, config: &ConfiguracaoManutencao) -> Result<RelatorioManutencao> {
+    let linhas_auditoria_removidas = podar_auditoria(conexao, config)?;
+    let (vacuum_executado, optimize_executado) = executar_vacuum_se_devido(conexao, config)?;
+
+    Ok(RelatorioManutencao {
+        linhas_auditoria_removidas,
+        vacuum_executado,
+        optimize_executado,
+    })
+}
+
+/// Poda linhas de `log_auditoria` mais antigas que
+/// `config.dias_retencao_auditoria` ou além de `config.max_linhas_auditoria`
+/// (ver `queries::podar_log_auditoria`, que cuida de preservar a cadeia de
+/// hashes a partir do ponto podado). Registra a poda no log só se algo foi
+/// de fato removido.
+fn podar_auditoria(conexao: &Connection, config: &ConfiguracaoManutencao) -> Result<u64> {
+    let corte = Utc::now() - Duration::days(config.dias_retencao_auditoria.max(0));
+    let corte_str = corte.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let removidas = queries::podar_log_auditoria(conexao, &corte_str, config.max_linhas_auditoria)?;
+
+    if removidas > 0 {
+        auditoria::registrar(
+            conexao, "manutencao", "sistema", None,
+            Some(&format!(
+                "{{\"acao\": \"poda_auditoria\", \"linhas_removidas\": {}, \"dias_retencao\": {}, \"max_linhas\": {}}}",
+                removidas, config.dias_retencao_auditoria, config.max_linhas_auditoria,
+            )),
+        )?;
+    }
+
+    Ok(removidas)
+}
+
+/// Roda VACUUM + `PRAGMA optimize` se já se passou
+/// `config.intervalo_auto_vacuum_dias` desde a última execução automática
+/// (ou se esta é a primeira). Retorna se cada etapa rodou.
+fn executar_vacuum_se_devido(conexao: &Connection, config: &ConfiguracaoManutencao) -> Result<(bool, bool)> {
+    let devido = match queries::obter_configuracao(conexao, CONFIG_ULTIMO_AUTO_VACUUM)? {
+        Some(configuracao) => match configuracao.valor {
+            Some(valor) => {
+                let ultimo = DateTime::parse_from_rfc3339(&valor)
+                    .context("Data do último auto-vacuum corrompida")?;
+                Utc::now().signed_duration_since(ultimo)
+                    >= Duration::days(config.intervalo_auto_vacuum_dias.max(0))
+            }
+            None => true,
+        },
+        None => true,
+    };
+
+    if !devido {
+        return Ok((false, false));
+    }
+
+    queries::compactar_banco(conexao)?;
+    queries::otimizar_banco(conexao)?;
+
+    queries::salvar_configuracao(
+        conexao,
+        CONFIG_ULTIMO_AUTO_VACUUM,
+        &Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    )?;
+
+    auditoria::registrar(
+        conexao, "manutencao", "sistema", None,
+        Some("{\"acao\": \"auto_vacuum\"}"),
+    )?;
+
+    Ok((true, true))
+}
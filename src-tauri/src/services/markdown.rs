@@ -0,0 +1,407 @@
+// =============================================================================
+// VaultCraft — Renderizador de Markdown (CommonMark, subconjunto)
+// =============================================================================
+// Converte o Markdown livre digitado em notas (`Item.conteudo_nota`) para
+// HTML real, para que a exportação em `servicos::exportacao` produza um
+// documento formatado em vez de texto cru com `#`, `*` e `` ` `` literais.
+//
+// Cobre os construtos mais comuns do CommonMark:
+// - Blocos: cabeçalhos ATX (#..######), listas (com/sem marcador numérico),
+//   citações (>), blocos de código cercados (```), linhas horizontais (---)
+//   e tabelas (|).
+// - Inline: **negrito**, *itálico*, `código`, [texto](url) e autolinks.
+//
+// Segurança: este módulo NUNCA emite HTML bruto do usuário. Todo texto passa
+// por `html_escape` antes de ser inserido, e somente esquemas http(s)/mailto
+// são aceitos em hrefs — qualquer outro vira texto escapado simples.
+// =============================================================================
+
+use super::exportacao::html_escape;
+use super::realce::realcar_codigo;
+
+/// Converte uma string Markdown em um fragmento HTML seguro.
+///
+/// Processa o texto em blocos (heurística linha a linha, como a maioria dos
+/// parsers CommonMark simplificados) e depois aplica as regras inline a
+/// cada trecho de texto resultante.
+pub fn markdown_para_html(md: &str) -> String {
+    let linhas: Vec<&str> = md.lines().collect();
+    let mut html = String::new();
+    let mut i = 0;
+
+    while i < linhas.len() {
+        let linha = linhas[i];
+
+        if linha.trim().is_empty() {
+            i += 1;
+            continue;
+        }
+
+        // Bloco de código cercado por ```
+        if let Some(resto) = linha.trim_start().strip_prefix("```") {
+            let info = resto.trim().to_string();
+            let mut corpo = Vec::new();
+            i += 1;
+            while i < linhas.len() && !linhas[i].trim_start().starts_with("```") {
+                corpo.push(linhas[i]);
+                i += 1;
+            }
+            i += 1; // pular a linha de fechamento ```
+            html.push_str(&renderizar_bloco_codigo(&info, &corpo.join("\n")));
+            continue;
+        }
+
+        // Linha horizontal (thematic break): ---, ***, ___ (3+ caracteres iguais)
+        if eh_linha_horizontal(linha) {
+            html.push_str("<hr>\n");
+            i += 1;
+            continue;
+        }
+
+        // Cabeçalho ATX: # até ######
+        if let Some((nivel, texto)) = parsear_cabecalho(linha) {
+            html.push_str(&format!(
+                "<h{n}>{texto}</h{n}>\n",
+                n = nivel,
+                texto = renderizar_inline(texto)
+            ));
+            i += 1;
+            continue;
+        }
+
+        // Citação (blockquote): linhas começando com >
+        if linha.trim_start().starts_with('>') {
+            let mut corpo = Vec::new();
+            while i < linhas.len() && linhas[i].trim_start().starts_with('>') {
+                let sem_marcador = linhas[i].trim_start().trim_start_matches('>').trim_start();
+                corpo.push(sem_marcador);
+                i += 1;
+            }
+            html.push_str(&format!(
+                "<blockquote>{}</blockquote>\n",
+                renderizar_paragrafo(&corpo.join(" "))
+            ));
+            continue;
+        }
+
+        // Tabela: a linha atual e a próxima contêm '|' e a segunda é o separador ---|---
+        if linha.contains('|') && i + 1 < linhas.len() && eh_separador_tabela(linhas[i + 1]) {
+            let (bloco_tabela, proximo_indice) = renderizar_tabela(&linhas, i);
+            html.push_str(&bloco_tabela);
+            i = proximo_indice;
+            continue;
+        }
+
+        // Lista (não ordenada ou ordenada)
+        if let Some(_) = parsear_item_lista(linha) {
+            let (bloco_lista, proximo_indice) = renderizar_lista(&linhas, i);
+            html.push_str(&bloco_lista);
+            i = proximo_indice;
+            continue;
+        }
+
+        // Parágrafo: acumula linhas consecutivas não vazias que não são outro bloco
+        let mut paragrafo = vec![linha];
+        i += 1;
+        while i < linhas.len()
+            && !linhas[i].trim().is_empty()
+            && !eh_linha_horizontal(linhas[i])
+            && parsear_cabecalho(linhas[i]).is_none()
+            && !linhas[i].trim_start().starts_with("```")
+            && !linhas[i].trim_start().starts_with('>')
+            && parsear_item_lista(linhas[i]).is_none()
+        {
+            paragrafo.push(linhas[i]);
+            i += 1;
+        }
+        html.push_str(&format!("<p>{}</p>\n", renderizar_paragrafo(&paragrafo.join(" "))));
+    }
+
+    html
+}
+
+/// Detecta uma linha horizontal (---, ***, ___), exigindo ao menos 3 caracteres.
+fn eh_linha_horizontal(linha: &str) -> bool {
+    let aparado = linha.trim();
+    if aparado.len() < 3 {
+        return false;
+    }
+    let primeiro = match aparado.chars().next() {
+        Some(c) => c,
+        None => return false,
+    };
+    (primeiro == '-' || primeiro == '*' || primeiro == '_')
+        && aparado.chars().all(|c| c == primeiro || c.is_whitespace())
+}
+
+/// Reconhece um cabeçalho ATX (# a ######) e retorna (nivel, texto).
+fn parsear_cabecalho(linha: &str) -> Option<(usize, &str)> {
+    let aparado = linha.trim_start();
+    let nivel = aparado.chars().take_while(|&c| c == '#').count();
+    if nivel == 0 || nivel > 6 {
+        return None;
+    }
+    let resto = &aparado[nivel..];
+    if !resto.starts_with(' ') && !resto.is_empty() {
+        return None;
+    }
+    Some((nivel, resto.trim()))
+}
+
+/// Reconhece o marcador de um item de lista e retorna (ordenada, texto).
+fn parsear_item_lista(linha: &str) -> Option<(bool, &str)> {
+    let aparado = linha.trim_start();
+
+    if let Some(resto) = aparado.strip_prefix("- ") {
+        return Some((false, resto));
+    }
+    if let Some(resto) = aparado.strip_prefix("* ") {
+        return Some((false, resto));
+    }
+    if let Some(resto) = aparado.strip_prefix("+ ") {
+        return Some((false, resto));
+    }
+
+    // Lista ordenada: "1. texto", "2. texto", etc.
+    let digitos: String = aparado.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if !digitos.is_empty() {
+        let resto = &aparado[digitos.len()..];
+        if let Some(texto) = resto.strip_prefix(". ") {
+            return Some((true, texto));
+        }
+    }
+
+    None
+}
+
+/// Renderiza um bloco de lista contíguo começando em `inicio`.
+/// Retorna (html, proximo_indice).
+fn renderizar_lista(linhas: &[&str], inicio: usize) -> (String, usize) {
+    let mut i = inicio;
+    let mut ordenada = false;
+    let mut itens = Vec::new();
+
+    while i < linhas.len() {
+        match parsear_item_lista(linhas[i]) {
+            Some((eh_ordenada, texto)) => {
+                ordenada = eh_ordenada;
+                itens.push(texto);
+                i += 1;
+            }
+            None => break,
+        }
+    }
+
+    let tag = if ordenada { "ol" } else { "ul" };
+    let mut html = format!("<{}>\n", tag);
+    for item in itens {
+        html.push_str(&format!("<li>{}</li>\n", renderizar_inline(item)));
+    }
+    html.push_str(&format!("</{}>\n", tag));
+
+    (html, i)
+}
+
+/// Verifica se a linha é um separador de tabela GFM (ex: `---|:---:|---`).
+fn eh_separador_tabela(linha: &str) -> bool {
+    let aparado = linha.trim();
+    if !aparado.contains('-') {
+        return false;
+    }
+    aparado
+        .trim_matches('|')
+        .split('|')
+        .all(|celula| celula.trim().chars().all(|c| c == '-' || c == ':' || c.is_whitespace()))
+}
+
+/// Renderiza uma tabela GFM a partir da linha de cabeçalho em `inicio`.
+/// Retorna (html, proximo_indice).
+fn renderizar_tabela(linhas: &[&str], inicio: usize) -> (String, usize) {
+    let celulas_de = |linha: &str| -> Vec<String> {
+        linha
+            .trim()
+            .trim_matches('|')
+            .split('|')
+            .map(|c| c.trim().to_string())
+            .collect()
+    };
+
+    let cabecalho = celulas_de(linhas[inicio]);
+    let mut i = inicio + 2; // pular cabeçalho e separador
+
+    let mut html = String::from("<table>\n<thead>\n<tr>\n");
+    for celula in &cabecalho {
+        html.push_str(&format!("<th>{}</th>\n", renderizar_inline(celula)));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    while i < linhas.len() && linhas[i].contains('|') && !linhas[i].trim().is_empty() {
+        html.push_str("<tr>\n");
+        for celula in celulas_de(linhas[i]) {
+            html.push_str(&format!("<td>{}</td>\n", renderizar_inline(&celula)));
+        }
+        html.push_str("</tr>\n");
+        i += 1;
+    }
+
+    html.push_str("</tbody>\n</table>\n");
+    (html, i)
+}
+
+/// Renderiza um bloco de código cercado. `info` é a info-string da cerca
+/// (ex.: `rust` em ```` ```rust ````), usada para escolher a classe CSS e
+/// a tabela de palavras-chave do realçador de sintaxe.
+fn renderizar_bloco_codigo(info: &str, codigo: &str) -> String {
+    let linguagem = info.split_whitespace().next().unwrap_or("");
+    let classe = if linguagem.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", html_escape(linguagem))
+    };
+    format!(
+        "<pre><code{classe}>{codigo}</code></pre>\n",
+        classe = classe,
+        codigo = realcar_codigo(linguagem, codigo)
+    )
+}
+
+/// Renderiza o conteúdo de um parágrafo/citação aplicando as regras inline.
+fn renderizar_paragrafo(texto: &str) -> String {
+    renderizar_inline(texto)
+}
+
+/// Aplica as regras inline (negrito, itálico, código, links, autolinks)
+/// a um trecho de texto, escapando tudo que não for marcação reconhecida.
+///
+/// Implementado como uma varredura caractere a caractere que acumula texto
+/// simples em `saida_escapada` e só insere tags quando reconhece um par de
+/// delimitadores válido — nunca repassa HTML do usuário.
+fn renderizar_inline(texto: &str) -> String {
+    let chars: Vec<char> = texto.chars().collect();
+    let mut saida = String::new();
+    let mut buffer = String::new();
+    let mut i = 0;
+
+    macro_rules! flush_buffer {
+        () => {
+            if !buffer.is_empty() {
+                saida.push_str(&html_escape(&buffer));
+                buffer.clear();
+            }
+        };
+    }
+
+    while i < chars.len() {
+        // Código inline: `codigo`
+        if chars[i] == '`' {
+            if let Some(fim) = encontrar_fechamento(&chars, i + 1, '`') {
+                flush_buffer!();
+                let trecho: String = chars[i + 1..fim].iter().collect();
+                saida.push_str(&format!("<code>{}</code>", html_escape(&trecho)));
+                i = fim + 1;
+                continue;
+            }
+        }
+
+        // Negrito: **texto**
+        if chars[i] == '*' && i + 1 < chars.len() && chars[i + 1] == '*' {
+            if let Some(fim) = encontrar_fechamento_duplo(&chars, i + 2, '*') {
+                flush_buffer!();
+                let trecho: String = chars[i + 2..fim].iter().collect();
+                saida.push_str(&format!("<strong>{}</strong>", renderizar_inline(&trecho)));
+                i = fim + 2;
+                continue;
+            }
+        }
+
+        // Ênfase: *texto*
+        if chars[i] == '*' {
+            if let Some(fim) = encontrar_fechamento(&chars, i + 1, '*') {
+                flush_buffer!();
+                let trecho: String = chars[i + 1..fim].iter().collect();
+                saida.push_str(&format!("<em>{}</em>", renderizar_inline(&trecho)));
+                i = fim + 1;
+                continue;
+            }
+        }
+
+        // Link: [texto](url)
+        if chars[i] == '[' {
+            if let Some(fim_texto) = encontrar_fechamento(&chars, i + 1, ']') {
+                if fim_texto + 1 < chars.len() && chars[fim_texto + 1] == '(' {
+                    if let Some(fim_url) = encontrar_fechamento(&chars, fim_texto + 2, ')') {
+                        flush_buffer!();
+                        let texto_link: String = chars[i + 1..fim_texto].iter().collect();
+                        let url: String = chars[fim_texto + 2..fim_url].iter().collect();
+                        saida.push_str(&renderizar_link(&texto_link, &url));
+                        i = fim_url + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // Autolink: <http://...> ou <usuario@dominio>
+        if chars[i] == '<' {
+            if let Some(fim) = encontrar_fechamento(&chars, i + 1, '>') {
+                let conteudo: String = chars[i + 1..fim].iter().collect();
+                if eh_url_permitida(&conteudo) || conteudo.contains('@') {
+                    flush_buffer!();
+                    let href = if conteudo.contains('@') && !conteudo.starts_with("mailto:") {
+                        format!("mailto:{}", conteudo)
+                    } else {
+                        conteudo.clone()
+                    };
+                    saida.push_str(&renderizar_link(&conteudo, &href));
+                    i = fim + 1;
+                    continue;
+                }
+            }
+        }
+
+        buffer.push(chars[i]);
+        i += 1;
+    }
+
+    flush_buffer!();
+    saida
+}
+
+/// Gera um elemento `<a>` seguro — apenas esquemas http(s)/mailto são aceitos
+/// como href; qualquer outra coisa é renderizada como texto simples.
+fn renderizar_link(texto: &str, url: &str) -> String {
+    if eh_url_permitida(url) {
+        format!(
+            r#"<a href="{}">{}</a>"#,
+            html_escape(url),
+            renderizar_inline(texto)
+        )
+    } else {
+        html_escape(texto)
+    }
+}
+
+/// Apenas http(s):// e mailto: são aceitos em hrefs — bloqueia `javascript:`,
+/// `data:` e outros esquemas que poderiam ser usados para XSS.
+fn eh_url_permitida(url: &str) -> bool {
+    let minuscula = url.trim().to_ascii_lowercase();
+    minuscula.starts_with("http://") || minuscula.starts_with("https://") || minuscula.starts_with("mailto:")
+}
+
+/// Procura o índice do próximo `delimitador` a partir de `inicio`, sem cruzar
+/// quebras de linha dentro do trecho (são strings de uma linha já processada).
+fn encontrar_fechamento(chars: &[char], inicio: usize, delimitador: char) -> Option<usize> {
+    (inicio..chars.len()).find(|&j| chars[j] == delimitador)
+}
+
+/// Como `encontrar_fechamento`, mas procura o delimitador duplicado (ex.: `**`).
+fn encontrar_fechamento_duplo(chars: &[char], inicio: usize, delimitador: char) -> Option<usize> {
+    let mut j = inicio;
+    while j + 1 < chars.len() {
+        if chars[j] == delimitador && chars[j + 1] == delimitador {
+            return Some(j);
+        }
+        j += 1;
+    }
+    None
+}
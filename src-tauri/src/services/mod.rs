@@ -8,9 +8,47 @@
 // - armazenamento: gestão de arquivos (anexos) no disco
 // - exportacao: exportação de itens para HTML/CSV
 // - auditoria: registro de eventos para rastreabilidade
+// - cdc: chunking com limites de conteúdo (FastCDC) para backups incrementais
+// - compressao: codecs de compressão plugáveis para backups (Deflate/Zstd/xz/Brotli)
+// - arquivo: exportação/importação de arquivo de cofre portátil cifrado (.vcarch)
+// - manutencao: retenção do log de auditoria e VACUUM/optimize programados
+// - reparo_fts: verificação de integridade e reconstrução do índice
+//   full-text (itens_fts), para quando os gatilhos de sincronização
+//   divergirem da tabela itens
+// - sincronizacao: envio/recebimento opcional de arquivo de cofre portátil
+//   por SFTP/SCP para um destino remoto configurado pelo usuário
+// - cifragem: sessão de cifragem em repouso de conteudo_nota/descricao
+//   (sealed blobs via crypto::campo_cifrado), desbloqueada por passphrase
+// - recorrencia: cálculo da próxima ocorrência de itens recorrentes e
+//   parser de datas relativas em linguagem natural ("-1d", "em 2 semanas")
+// - rpc: envelope, despacho JSON-RPC 2.0 e listener WebSocket local
+//   (`ServidorRpc`, ver `commands::iniciar_servidor_rpc`) para expor um
+//   subconjunto dos serviços acima a um transporte externo
+// - api_segredos: motor de segredos chave/valor cifrados e tokens de acesso
+//   de curta duração
+// - http_segredos: listener HTTP local (`ServidorHttpSegredos`, ver
+//   `commands::iniciar_servidor_http_segredos`) que expõe api_segredos como
+//   `GET/PUT/DELETE /v1/secret/<path>` autenticado por token bearer
+// - backend_remoto: backend HTTP/S3-like (bearer token) para sincronizar os
+//   chunks de um backup incremental com um destino remoto, complementando
+//   sincronizacao (que cuida de SFTP/SCP de um .vcarch inteiro)
 // =============================================================================
 
 pub mod backup;
 pub mod armazenamento;
 pub mod exportacao;
 pub mod auditoria;
+pub mod markdown;
+pub mod realce;
+pub mod cdc;
+pub mod compressao;
+pub mod arquivo;
+pub mod manutencao;
+pub mod reparo_fts;
+pub mod sincronizacao;
+pub mod cifragem;
+pub mod recorrencia;
+pub mod rpc;
+pub mod api_segredos;
+pub mod http_segredos;
+pub mod backend_remoto;
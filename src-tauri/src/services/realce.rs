@@ -0,0 +1,190 @@
+// =============================================================================
+// VaultCraft — Realce de Sintaxe para Blocos de Código Exportados
+// =============================================================================
+// Tokeniza um trecho de código-fonte e o envolve em spans com classes CSS
+// (`tok-kw`, `tok-str`, etc.), para que blocos ```cercados``` em notas
+// Markdown fiquem legíveis quando exportados como HTML/PDF — sem depender
+// de JavaScript externo (o app é 100% offline).
+//
+// É um lexer simples e "bom o suficiente": não constrói uma AST, apenas
+// classifica cada pedaço do texto em uma das categorias abaixo. Para
+// linguagens fora da tabela de palavras-chave, cai no fallback genérico
+// (ainda reconhece comentários, strings e números comuns a C-like langs).
+// =============================================================================
+
+use super::exportacao::html_escape;
+
+/// Categorias de token reconhecidas pelo realçador.
+/// O nome da variante em minúsculo (via `as_classe`) vira a classe CSS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TipoToken {
+    PalavraChave,
+    Texto,
+    Comentario,
+    Numero,
+    Tipo,
+    Normal,
+}
+
+impl TipoToken {
+    fn as_classe(self) -> &'static str {
+        match self {
+            TipoToken::PalavraChave => "tok-kw",
+            TipoToken::Texto => "tok-str",
+            TipoToken::Comentario => "tok-com",
+            TipoToken::Numero => "tok-num",
+            TipoToken::Tipo => "tok-type",
+            TipoToken::Normal => "tok-plain",
+        }
+    }
+}
+
+/// Realça um trecho de código como HTML seguro, de acordo com a linguagem
+/// indicada na info-string da cerca (ex.: `rust`, `js`, `python`).
+/// Linguagens desconhecidas usam um conjunto genérico de palavras-chave.
+pub fn realcar_codigo(linguagem: &str, codigo: &str) -> String {
+    let palavras_chave = palavras_chave_para(linguagem);
+    let mut saida = String::new();
+    let chars: Vec<char> = codigo.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Comentário de linha (// ou #)
+        if eh_inicio_comentario_linha(&chars, i, linguagem) {
+            let inicio = i;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            saida.push_str(&envolver(TipoToken::Comentario, &chars[inicio..i]));
+            continue;
+        }
+
+        // Comentário de bloco /* ... */
+        if c == '/' && i + 1 < chars.len() && chars[i + 1] == '*' {
+            let inicio = i;
+            i += 2;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            i = (i + 2).min(chars.len());
+            saida.push_str(&envolver(TipoToken::Comentario, &chars[inicio..i]));
+            continue;
+        }
+
+        // String/char literal: "..." ou '...' (com suporte a \" de escape)
+        if c == '"' || c == '\'' {
+            let delimitador = c;
+            let inicio = i;
+            i += 1;
+            while i < chars.len() && chars[i] != delimitador {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            saida.push_str(&envolver(TipoToken::Texto, &chars[inicio..i]));
+            continue;
+        }
+
+        // Número: dígitos com ponto decimal opcional
+        if c.is_ascii_digit() {
+            let inicio = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == '_') {
+                i += 1;
+            }
+            saida.push_str(&envolver(TipoToken::Numero, &chars[inicio..i]));
+            continue;
+        }
+
+        // Identificador / palavra-chave / tipo
+        if c.is_alphabetic() || c == '_' {
+            let inicio = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let palavra: String = chars[inicio..i].iter().collect();
+
+            let tipo_token = if palavras_chave.contains(&palavra.as_str()) {
+                TipoToken::PalavraChave
+            } else if palavra.chars().next().map_or(false, |p| p.is_uppercase()) {
+                TipoToken::Tipo
+            } else {
+                TipoToken::Normal
+            };
+
+            saida.push_str(&envolver(tipo_token, &chars[inicio..i]));
+            continue;
+        }
+
+        // Qualquer outro caractere (pontuação, espaço) vai como texto simples
+        saida.push_str(&html_escape(&c.to_string()));
+        i += 1;
+    }
+
+    saida
+}
+
+/// Envolve um trecho de caracteres já delimitado em um `<span>` com a classe
+/// CSS correspondente, escapando o conteúdo para evitar XSS.
+fn envolver(tipo: TipoToken, trecho: &[char]) -> String {
+    let texto: String = trecho.iter().collect();
+    if tipo == TipoToken::Normal {
+        // Identificadores comuns não precisam de span — reduz o tamanho do HTML.
+        return html_escape(&texto);
+    }
+    format!(
+        r#"<span class="{}">{}</span>"#,
+        tipo.as_classe(),
+        html_escape(&texto)
+    )
+}
+
+/// Detecta se a posição `i` inicia um comentário de linha para a linguagem dada.
+fn eh_inicio_comentario_linha(chars: &[char], i: usize, linguagem: &str) -> bool {
+    let usa_hash = matches!(linguagem, "python" | "py" | "bash" | "sh" | "yaml" | "toml" | "ruby");
+    if usa_hash && chars[i] == '#' {
+        return true;
+    }
+    chars[i] == '/' && i + 1 < chars.len() && chars[i + 1] == '/'
+}
+
+/// Retorna a tabela de palavras-chave para a linguagem informada na
+/// info-string da cerca. Linguagens não listadas usam o fallback genérico.
+fn palavras_chave_para(linguagem: &str) -> &'static [&'static str] {
+    match linguagem.to_ascii_lowercase().as_str() {
+        "rust" | "rs" => &[
+            "fn", "let", "mut", "pub", "struct", "enum", "impl", "trait", "use", "mod",
+            "match", "if", "else", "for", "while", "loop", "return", "break", "continue",
+            "self", "Self", "async", "await", "move", "ref", "dyn", "where", "as", "in",
+            "const", "static", "unsafe", "crate", "super", "true", "false",
+        ],
+        "javascript" | "js" | "typescript" | "ts" | "jsx" | "tsx" => &[
+            "function", "const", "let", "var", "if", "else", "for", "while", "return",
+            "class", "extends", "new", "this", "import", "export", "default", "async",
+            "await", "try", "catch", "finally", "switch", "case", "break", "continue",
+            "typeof", "instanceof", "true", "false", "null", "undefined",
+        ],
+        "python" | "py" => &[
+            "def", "class", "if", "elif", "else", "for", "while", "return", "import",
+            "from", "as", "with", "try", "except", "finally", "raise", "pass", "break",
+            "continue", "lambda", "yield", "async", "await", "self", "None", "True", "False",
+        ],
+        "go" => &[
+            "func", "package", "import", "var", "const", "type", "struct", "interface",
+            "if", "else", "for", "range", "return", "go", "chan", "select", "defer",
+            "map", "nil", "true", "false",
+        ],
+        "sql" => &[
+            "SELECT", "FROM", "WHERE", "INSERT", "INTO", "VALUES", "UPDATE", "SET",
+            "DELETE", "JOIN", "ON", "GROUP", "BY", "ORDER", "LIMIT", "AND", "OR", "NOT",
+            "NULL", "CREATE", "TABLE", "INDEX", "PRIMARY", "KEY",
+        ],
+        _ => &[
+            "if", "else", "for", "while", "return", "function", "class", "import",
+            "true", "false", "null",
+        ],
+    }
+}
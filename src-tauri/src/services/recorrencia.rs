@@ -0,0 +1,237 @@
+// =============================================================================
+// VaultCraft — Recorrência de Itens e Datas Relativas
+// =============================================================================
+// Dois utilitários independentes em torno de datas de vencimento:
+//
+// - `proxima_ocorrencia`: dado um item com `regra_recorrencia` (ver
+//   db::models::Item), calcula a próxima `data_vencimento` quando o item é
+//   avançado (ver `queries::avancar_ocorrencia_item`). A regra usa um
+//   subconjunto do formato RRULE (RFC 5545): `FREQ=DAILY|WEEKLY|MONTHLY|YEARLY;
+//   INTERVAL=N`. Campos não suportados (ex.: BYDAY) são ignorados.
+// - `parsear_offset_humano`: converte expressões relativas digitadas pelo
+//   usuário ("-1d", "15 minutes", "ontem 17:20", "in 2 fortnights") em um
+//   timestamp absoluto, no mesmo formato ISO 8601 usado em todo o módulo
+//   (`%Y-%m-%dT%H:%M:%SZ`), resolvendo contra um instante de referência
+//   (tipicamente `Utc::now()`).
+//
+// Nenhuma das duas funções toca o banco — isso fica a cargo de
+// `queries::avancar_ocorrencia_item`, que as usa.
+// =============================================================================
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+
+/// Frequência de uma regra de recorrência (subconjunto de RRULE FREQ=).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequencia {
+    Diaria,
+    Semanal,
+    Mensal,
+    Anual,
+}
+
+/// Calcula a próxima ocorrência de `data_base` segundo `regra`
+/// (formato `FREQ=DAILY;INTERVAL=1`, também WEEKLY/MONTHLY/YEARLY).
+/// Recorrências mensais/anuais fazem "clamping" de fim de mês: 31 de
+/// janeiro + 1 mês vira 28 ou 29 de fevereiro (conforme o ano).
+pub fn proxima_ocorrencia(data_base: DateTime<Utc>, regra: &str) -> Result<DateTime<Utc>> {
+    let (frequencia, intervalo) = parsear_regra(regra)?;
+
+    Ok(match frequencia {
+        Frequencia::Diaria => data_base + Duration::days(intervalo as i64),
+        Frequencia::Semanal => data_base + Duration::weeks(intervalo as i64),
+        Frequencia::Mensal => adicionar_meses(data_base, intervalo)?,
+        Frequencia::Anual => adicionar_meses(data_base, intervalo * 12)?,
+    })
+}
+
+/// Interpreta uma regra `FREQ=...;INTERVAL=...` (separada por `;`, pares
+/// `chave=valor` separados por `=`, chaves e valores de FREQ
+/// case-insensitive). `INTERVAL` é opcional (padrão 1); `FREQ` é
+/// obrigatório. Campos desconhecidos (ex.: `BYDAY=MO`) são ignorados.
+fn parsear_regra(regra: &str) -> Result<(Frequencia, i32)> {
+    let mut frequencia = None;
+    let mut intervalo = 1i32;
+
+    for par in regra.split(';') {
+        let par = par.trim();
+        if par.is_empty() {
+            continue;
+        }
+        let Some((chave, valor)) = par.split_once('=') else {
+            continue;
+        };
+
+        match chave.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequencia = Some(match valor.trim().to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequencia::Diaria,
+                    "WEEKLY" => Frequencia::Semanal,
+                    "MONTHLY" => Frequencia::Mensal,
+                    "YEARLY" => Frequencia::Anual,
+                    outro => return Err(anyhow!("Frequência de recorrência desconhecida: '{}'", outro)),
+                });
+            }
+            "INTERVAL" => {
+                intervalo = valor.trim().parse()
+                    .with_context(|| format!("INTERVAL inválido na regra de recorrência: '{}'", valor))?;
+            }
+            _ => {}
+        }
+    }
+
+    let frequencia = frequencia.ok_or_else(|| anyhow!("Regra de recorrência sem FREQ: '{}'", regra))?;
+    if intervalo < 1 {
+        return Err(anyhow!("INTERVAL da regra de recorrência deve ser >= 1, recebeu {}", intervalo));
+    }
+
+    Ok((frequencia, intervalo))
+}
+
+/// Soma `meses` a `data`, preservando o horário e fazendo clamping do dia
+/// ao último dia do mês de destino quando necessário (ex.: 31/01 + 1 mês
+/// não vira 03/03, vira 28 ou 29/02).
+fn adicionar_meses(data: DateTime<Utc>, meses: i32) -> Result<DateTime<Utc>> {
+    let total_meses = data.year() * 12 + (data.month() as i32 - 1) + meses;
+    let ano_destino = total_meses.div_euclid(12);
+    let mes_destino = (total_meses.rem_euclid(12)) as u32 + 1;
+    let dia_destino = data.day().min(ultimo_dia_do_mes(ano_destino, mes_destino));
+
+    let nova_data = NaiveDate::from_ymd_opt(ano_destino, mes_destino, dia_destino)
+        .ok_or_else(|| anyhow!("Data resultante inválida ao somar {} mês(es)", meses))?;
+
+    Ok(nova_data.and_time(data.time()).and_utc())
+}
+
+/// Último dia do mês `mes`/`ano` (1-12), via "primeiro dia do mês seguinte
+/// menos um dia".
+fn ultimo_dia_do_mes(ano: i32, mes: u32) -> u32 {
+    let (ano_seguinte, mes_seguinte) = if mes == 12 { (ano + 1, 1) } else { (ano, mes + 1) };
+    NaiveDate::from_ymd_opt(ano_seguinte, mes_seguinte, 1)
+        .expect("mês seguinte sempre válido")
+        .pred_opt()
+        .expect("dia anterior ao dia 1 sempre válido")
+        .day()
+}
+
+/// Resolve uma expressão relativa de data/hora em `texto` contra o instante
+/// `agora`, retornando o timestamp absoluto. Formatos aceitos:
+/// - Compacto com sinal: `-1d`, `+3h`, `30m` (unidades: `s`/`m`/`h`/`d`/`w`)
+/// - Por extenso: `15 minutes`, `2 weeks`, `2 fortnights` (também em
+///   português: `15 minutos`, `2 semanas`, `2 quinzenas`), com prefixo
+///   opcional `in`/`em` (futuro) — sem prefixo, assume-se passado
+/// - Dia nomeado + hora opcional: `yesterday`/`ontem`, `today`/`hoje`,
+///   `tomorrow`/`amanhã` (também sem acento), com `HH:MM` opcional
+///   (ex.: `yesterday 17:20`)
+pub fn parsear_offset_humano(texto: &str, agora: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let texto = texto.trim();
+    if texto.is_empty() {
+        return Err(anyhow!("Expressão de data relativa vazia"));
+    }
+
+    if let Some(resultado) = parsear_dia_nomeado(texto, agora)? {
+        return Ok(resultado);
+    }
+    if let Some(resultado) = parsear_offset_compacto(texto, agora) {
+        return Ok(resultado);
+    }
+    if let Some(resultado) = parsear_offset_por_extenso(texto, agora)? {
+        return Ok(resultado);
+    }
+
+    Err(anyhow!("Expressão de data relativa não reconhecida: '{}'", texto))
+}
+
+/// `ontem`/`yesterday`, `hoje`/`today`, `amanhã`/`amanha`/`tomorrow`,
+/// opcionalmente seguido de `HH:MM`. Retorna `None` se a primeira palavra
+/// não for um desses nomes de dia.
+fn parsear_dia_nomeado(texto: &str, agora: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+    let mut partes = texto.splitn(2, char::is_whitespace);
+    let primeira = partes.next().unwrap_or("").to_ascii_lowercase();
+    let resto = partes.next().map(str::trim).filter(|s| !s.is_empty());
+
+    let offset_dias = match primeira.as_str() {
+        "ontem" | "yesterday" => -1,
+        "hoje" | "today" => 0,
+        "amanha" | "amanhã" | "tomorrow" => 1,
+        _ => return Ok(None),
+    };
+
+    let data_alvo = (agora + Duration::days(offset_dias)).date_naive();
+
+    let hora = match resto {
+        Some(hora_texto) => NaiveTime::parse_from_str(hora_texto, "%H:%M")
+            .with_context(|| format!("Horário inválido em expressão de data relativa: '{}'", hora_texto))?,
+        None => agora.time(),
+    };
+
+    Ok(Some(data_alvo.and_time(hora).and_utc()))
+}
+
+/// Formato compacto `[+|-]<número><unidade>`, unidade de uma letra:
+/// `s` (segundos), `m` (minutos), `h` (horas), `d` (dias), `w` (semanas).
+/// Sem sinal, assume-se offset negativo (passado) — convenção deste parser
+/// para números "soltos" como `30m`.
+fn parsear_offset_compacto(texto: &str, agora: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let (sinal, resto) = match texto.as_bytes().first()? {
+        b'+' => (1, &texto[1..]),
+        b'-' => (-1, &texto[1..]),
+        _ => (-1, texto),
+    };
+
+    let unidade = resto.chars().last()?;
+    if !unidade.is_ascii_alphabetic() {
+        return None;
+    }
+    let quantidade: i64 = resto[..resto.len() - unidade.len_utf8()].parse().ok()?;
+
+    let duracao = duracao_para_unidade_curta(unidade)?;
+    Some(agora + duracao * (sinal * quantidade as i32))
+}
+
+fn duracao_para_unidade_curta(unidade: char) -> Option<Duration> {
+    Some(match unidade.to_ascii_lowercase() {
+        's' => Duration::seconds(1),
+        'm' => Duration::minutes(1),
+        'h' => Duration::hours(1),
+        'd' => Duration::days(1),
+        'w' => Duration::weeks(1),
+        _ => return None,
+    })
+}
+
+/// Formato por extenso `[in|em] <número> <unidade>`, ex. `15 minutes`,
+/// `2 semanas`, `in 2 fortnights`. Sem `in`/`em`, assume-se passado.
+fn parsear_offset_por_extenso(texto: &str, agora: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+    let texto_lower = texto.to_ascii_lowercase();
+    let (futuro, resto) = match texto_lower.strip_prefix("in ").or_else(|| texto_lower.strip_prefix("em ")) {
+        Some(resto) => (true, resto),
+        None => (false, texto_lower.as_str()),
+    };
+
+    let mut palavras = resto.split_whitespace();
+    let Some(quantidade_texto) = palavras.next() else { return Ok(None) };
+    let Ok(quantidade) = quantidade_texto.parse::<i64>() else { return Ok(None) };
+    let Some(unidade_texto) = palavras.next() else { return Ok(None) };
+
+    let Some(duracao) = duracao_para_unidade_por_extenso(unidade_texto) else { return Ok(None) };
+
+    let sinal = if futuro { 1 } else { -1 };
+    Ok(Some(agora + duracao * (sinal * quantidade as i32)))
+}
+
+/// Unidade por extenso (singular ou plural, português ou inglês) para uma
+/// `Duration` unitária. `fortnight`/`quinzena` = 14 dias.
+fn duracao_para_unidade_por_extenso(unidade: &str) -> Option<Duration> {
+    Some(match unidade.trim_end_matches('s') {
+        "second" | "segundo" => Duration::seconds(1),
+        "minute" | "minuto" => Duration::minutes(1),
+        "hour" | "hora" => Duration::hours(1),
+        "day" | "dia" => Duration::days(1),
+        "week" | "semana" => Duration::weeks(1),
+        "fortnight" | "quinzena" => Duration::days(14),
+        "month" | "mes" | "mês" => Duration::days(30),
+        "year" | "ano" => Duration::days(365),
+        _ => return None,
+    })
+}
@@ -0,0 +1,45 @@
+// =============================================================================
+// VaultCraft — Reparo do Índice Full-Text (itens_fts)
+// =============================================================================
+// A busca (ver `db::queries::buscar_fts`) depende inteiramente dos gatilhos
+// `trg_itens_fts_*` manterem `itens_fts` em sincronia com `itens`. Uma
+// migração manual, uma falha no meio de uma importação em lote, ou uma
+// mudança de schema podem deixá-los dessincronizados, quebrando a busca em
+// silêncio (o item simplesmente nunca aparece, sem erro nenhum).
+//
+// `executar` segue o padrão de reparo online/offline do Garage: primeiro
+// verifica (sem modificar nada), e só reconstrói se encontrar divergência —
+// reconstruir é mais custoso (relê toda a tabela `itens`) e desnecessário
+// quando os gatilhos já estão fazendo o trabalho corretamente.
+// =============================================================================
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::db::models::RelatorioReparoFts;
+use crate::db::queries;
+use crate::services::auditoria;
+
+/// Verifica a integridade de `itens_fts` e, se encontrar IDs ausentes ou
+/// órfãos, reconstrói o índice (ver `queries::reconstruir_fts`). Registra
+/// o reparo na auditoria apenas quando uma reconstrução de fato acontece.
+pub fn executar(conexao: &mut Connection) -> Result<RelatorioReparoFts> {
+    let mut relatorio = queries::verificar_integridade_fts(conexao)?;
+
+    if relatorio.ids_ausentes.is_empty() && relatorio.ids_orfaos.is_empty() {
+        return Ok(relatorio);
+    }
+
+    queries::reconstruir_fts(conexao)?;
+    relatorio.reconstruido = true;
+
+    auditoria::registrar(
+        conexao, "reparo_fts", "sistema", None,
+        Some(&format!(
+            "{{\"acao\": \"reconstrucao\", \"ids_ausentes\": {}, \"ids_orfaos\": {}, \"linhas_escaneadas\": {}}}",
+            relatorio.ids_ausentes.len(), relatorio.ids_orfaos.len(), relatorio.linhas_escaneadas,
+        )),
+    )?;
+
+    Ok(relatorio)
+}
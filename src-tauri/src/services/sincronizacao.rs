@@ -0,0 +1,187 @@
+// =============================================================================
+// VaultCraft — Sincronização Remota (SFTP/SCP) de Arquivos de Cofre Portátil
+// =============================================================================
+// Envio/recebimento explícito, disparado pelo usuário, para um host remoto
+// configurado por ele mesmo (nunca automático, nunca em segundo plano).
+// Inspirado no modelo de transferência de arquivos do termscp (SFTP/SCP com
+// bookmarks de destino e parâmetros de autenticação salvos, ver
+// `db::models::DestinoRemoto`). Cuida só dos protocolos baseados em SSH —
+// destinos `ProtocoloRemoto::Http` são responsabilidade de
+// `services::backend_remoto`, que usa o mesmo bookmark `DestinoRemoto` mas
+// fala REST com bearer token em vez de SFTP/SCP.
+//
+// Este módulo NUNCA transfere o cofre em texto claro: `enviar` só aceita
+// enviar um arquivo de cofre portátil (.vcarch) já cifrado e assinado (ver
+// `services::arquivo::exportar`), e `restaurar` verifica a assinatura Ed25519
+// do arquivo baixado (ver `arquivo::verificar_assinatura`) antes de gravá-lo
+// no disco local — um arquivo corrompido ou adulterado em trânsito nunca
+// chega a ficar perto do cofre. Decifrar e importar de fato continua sendo
+// responsabilidade de `services::arquivo::importar`, chamado separadamente
+// depois de `restaurar` trazer o arquivo para o disco local.
+//
+// Cada envio/recebimento é registrado no log de auditoria como ação de
+// manutenção (mesmo padrão de `criar_backup`/`restaurar_backup`).
+// =============================================================================
+
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+use ssh2::Session;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+
+use crate::db::models::{AutenticacaoRemota, DestinoRemoto, ProtocoloRemoto};
+use crate::services::arquivo;
+use crate::services::auditoria;
+
+/// Conecta e autentica uma sessão SSH com `destino`. Usada tanto por SFTP
+/// quanto por SCP (ssh2 expõe os dois sobre a mesma `Session`).
+fn conectar(destino: &DestinoRemoto) -> Result<Session> {
+    let endereco = format!("{}:{}", destino.host, destino.porta);
+    let tcp = TcpStream::connect(&endereco)
+        .with_context(|| format!("Falha ao conectar em {}", endereco))?;
+
+    let mut sessao = Session::new().context("Falha ao criar sessão SSH")?;
+    sessao.set_tcp_stream(tcp);
+    sessao.handshake().context("Falha no handshake SSH")?;
+
+    match &destino.autenticacao {
+        AutenticacaoRemota::Senha { senha } => {
+            sessao
+                .userauth_password(&destino.usuario, senha)
+                .context("Falha na autenticação por senha")?;
+        }
+        AutenticacaoRemota::ChavePrivada { caminho_chave, frase_senha } => {
+            sessao
+                .userauth_pubkey_file(
+                    &destino.usuario,
+                    None,
+                    Path::new(caminho_chave),
+                    frase_senha.as_deref(),
+                )
+                .context("Falha na autenticação por chave privada")?;
+        }
+        AutenticacaoRemota::Token { .. } => {
+            bail!("Autenticação por token não é suportada por SFTP/SCP — use services::backend_remoto para destinos HTTP")
+        }
+    }
+
+    if !sessao.authenticated() {
+        bail!("Autenticação SSH falhou para {}", endereco);
+    }
+
+    Ok(sessao)
+}
+
+/// Envia `arquivo_local` (um `.vcarch` produzido por
+/// `services::arquivo::exportar`) para `destino`, sob o mesmo nome de
+/// arquivo, dentro de `destino.caminho_remoto`.
+///
+/// Recusa enviar qualquer arquivo cuja assinatura Ed25519 não confira — só
+/// arquivos de cofre portátil legítimos saem do disco de origem por aqui.
+pub fn enviar(conexao: &Connection, destino: &DestinoRemoto, arquivo_local: &Path) -> Result<()> {
+    let dados = fs::read(arquivo_local)
+        .with_context(|| format!("Falha ao ler arquivo para envio: {:?}", arquivo_local))?;
+    arquivo::verificar_assinatura(&dados)
+        .context("Recusando enviar: arquivo não é um .vcarch válido e assinado")?;
+
+    let nome_arquivo = arquivo_local
+        .file_name()
+        .context("Caminho de arquivo local inválido")?
+        .to_string_lossy()
+        .to_string();
+    let caminho_remoto = caminho_remoto_completo(destino, &nome_arquivo);
+
+    let sessao = conectar(destino)?;
+    match destino.protocolo {
+        ProtocoloRemoto::Sftp => {
+            let sftp = sessao.sftp().context("Falha ao iniciar subsistema SFTP")?;
+            let mut arquivo_remoto = sftp
+                .create(&caminho_remoto)
+                .with_context(|| format!("Falha ao criar arquivo remoto: {:?}", caminho_remoto))?;
+            arquivo_remoto
+                .write_all(&dados)
+                .context("Falha ao transferir arquivo por SFTP")?;
+        }
+        ProtocoloRemoto::Scp => {
+            let mut canal = sessao
+                .scp_send(&caminho_remoto, 0o600, dados.len() as u64, None)
+                .with_context(|| format!("Falha ao abrir canal SCP para: {:?}", caminho_remoto))?;
+            canal.write_all(&dados).context("Falha ao transferir arquivo por SCP")?;
+        }
+        ProtocoloRemoto::Http => bail!("Destino HTTP não é suportado por services::sincronizacao — use services::backend_remoto"),
+    }
+
+    auditoria::registrar(
+        conexao, "sincronizacao_envio", "sistema", None,
+        Some(&format!(
+            "{{\"destino\": \"{}\", \"arquivo\": \"{}\"}}",
+            destino.nome, nome_arquivo
+        )),
+    )?;
+
+    Ok(())
+}
+
+/// Busca `nome_arquivo` de dentro de `destino.caminho_remoto` e grava uma
+/// cópia em `destino_local`. Verifica a assinatura Ed25519 do arquivo
+/// baixado antes de gravá-lo — um arquivo corrompido ou adulterado em
+/// trânsito nunca chega a tocar o disco local. Não decifra nem importa o
+/// conteúdo: isso é responsabilidade de `services::arquivo::importar`,
+/// chamado separadamente com o caminho retornado.
+pub fn restaurar(
+    conexao: &Connection,
+    destino: &DestinoRemoto,
+    nome_arquivo: &str,
+    destino_local: &Path,
+) -> Result<PathBuf> {
+    let caminho_remoto = caminho_remoto_completo(destino, nome_arquivo);
+
+    let sessao = conectar(destino)?;
+    let mut dados = Vec::new();
+    match destino.protocolo {
+        ProtocoloRemoto::Sftp => {
+            let sftp = sessao.sftp().context("Falha ao iniciar subsistema SFTP")?;
+            let mut arquivo_remoto = sftp
+                .open(&caminho_remoto)
+                .with_context(|| format!("Falha ao abrir arquivo remoto: {:?}", caminho_remoto))?;
+            arquivo_remoto
+                .read_to_end(&mut dados)
+                .context("Falha ao transferir arquivo por SFTP")?;
+        }
+        ProtocoloRemoto::Scp => {
+            let (mut canal, _stat) = sessao
+                .scp_recv(&caminho_remoto)
+                .with_context(|| format!("Falha ao abrir canal SCP para: {:?}", caminho_remoto))?;
+            canal.read_to_end(&mut dados).context("Falha ao transferir arquivo por SCP")?;
+        }
+        ProtocoloRemoto::Http => bail!("Destino HTTP não é suportado por services::sincronizacao — use services::backend_remoto"),
+    }
+
+    arquivo::verificar_assinatura(&dados)
+        .context("Arquivo baixado não é um .vcarch válido e assinado — descartado")?;
+
+    fs::create_dir_all(destino_local)
+        .context("Falha ao criar diretório de destino local")?;
+    let caminho_local = destino_local.join(nome_arquivo);
+    fs::write(&caminho_local, &dados)
+        .with_context(|| format!("Falha ao gravar arquivo baixado: {:?}", caminho_local))?;
+
+    auditoria::registrar(
+        conexao, "sincronizacao_recebimento", "sistema", None,
+        Some(&format!(
+            "{{\"destino\": \"{}\", \"arquivo\": \"{}\"}}",
+            destino.nome, nome_arquivo
+        )),
+    )?;
+
+    Ok(caminho_local)
+}
+
+/// Junta `destino.caminho_remoto` com `nome_arquivo` usando separador `/`
+/// (caminho remoto POSIX, independente do SO local que está rodando o VaultCraft).
+fn caminho_remoto_completo(destino: &DestinoRemoto, nome_arquivo: &str) -> PathBuf {
+    let base = destino.caminho_remoto.trim_end_matches('/');
+    PathBuf::from(format!("{}/{}", base, nome_arquivo))
+}
@@ -0,0 +1,107 @@
+/**
+ * Testes de integração para a deduplicação/contagem de referências de
+ * blobs endereçados por conteúdo (`services::armazenamento`, ver o
+ * cabeçalho desse módulo).
+ *
+ * Por quê? Dois anexos com o mesmo conteúdo devem compartilhar um único
+ * arquivo físico em disco, e esse arquivo só pode ser apagado quando o
+ * último anexo que aponta para ele for removido — `salvar_anexo`/
+ * `remover_anexo` coordenam isso com `db::queries::registrar_referencia_blob`/
+ * `remover_referencia_blob` (contagem de referências), mas nada além de
+ * leitura manual do código garantia que a contagem e o arquivo físico
+ * ficam consistentes entre si ao longo de salvamentos/remoções repetidos.
+ * Um bug aqui tanto poderia apagar um blob ainda em uso por outro anexo
+ * quanto vazar blobs órfãos que nunca são liberados.
+ */
+
+#[cfg(test)]
+mod testes_armazenamento_dedup {
+    use rusqlite::Connection;
+    use std::fs;
+    use tempfile::TempDir;
+    use vaultcraft_lib::db::migrations::executar_migracoes;
+    use vaultcraft_lib::db::queries::obter_blob;
+    use vaultcraft_lib::services::armazenamento::{remover_anexo, salvar_anexo};
+
+    fn banco_migrado() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        executar_migracoes(&conn).expect("migrações devem rodar sem erro em um banco novo");
+        conn
+    }
+
+    #[test]
+    fn dois_anexos_com_mesmo_conteudo_compartilham_um_unico_blob() {
+        let conn = banco_migrado();
+        let dir_app = TempDir::new().unwrap();
+
+        let origem_a = dir_app.path().join("relatorio.pdf");
+        let origem_b = dir_app.path().join("copia_do_relatorio.pdf");
+        fs::write(&origem_a, b"%PDF-1.4 conteudo identico").unwrap();
+        fs::write(&origem_b, b"%PDF-1.4 conteudo identico").unwrap();
+
+        let anexo_a = salvar_anexo(dir_app.path(), &conn, &origem_a, Some("item-1"), None).unwrap();
+        let anexo_b = salvar_anexo(dir_app.path(), &conn, &origem_b, Some("item-2"), None).unwrap();
+
+        assert_eq!(anexo_a.hash_sha256, anexo_b.hash_sha256, "conteúdo idêntico deveria gerar o mesmo hash");
+        assert_eq!(anexo_a.caminho_interno, anexo_b.caminho_interno, "ambos deveriam apontar para o mesmo blob físico");
+
+        let hash = anexo_a.hash_sha256.clone().unwrap();
+        let blob = obter_blob(&conn, &hash).unwrap().expect("blob deveria existir após o primeiro salvamento");
+        assert_eq!(blob.contagem_referencias, 2, "dois anexos apontando para o mesmo conteúdo devem somar 2 referências");
+    }
+
+    #[test]
+    fn remover_um_de_dois_anexos_deduplicados_mantem_o_blob_fisico() {
+        let conn = banco_migrado();
+        let dir_app = TempDir::new().unwrap();
+
+        let origem_a = dir_app.path().join("a.txt");
+        let origem_b = dir_app.path().join("b.txt");
+        fs::write(&origem_a, b"mesmo conteudo").unwrap();
+        fs::write(&origem_b, b"mesmo conteudo").unwrap();
+
+        let anexo_a = salvar_anexo(dir_app.path(), &conn, &origem_a, Some("item-1"), None).unwrap();
+        let anexo_b = salvar_anexo(dir_app.path(), &conn, &origem_b, Some("item-2"), None).unwrap();
+        let hash = anexo_a.hash_sha256.clone().unwrap();
+
+        let caminho_blob = vaultcraft_lib::services::armazenamento::obter_diretorio_armazenamento(dir_app.path())
+            .join(&anexo_a.caminho_interno);
+        assert!(caminho_blob.exists());
+
+        remover_anexo(dir_app.path(), &conn, &anexo_a).unwrap();
+
+        // O segundo anexo ainda referencia o blob, então o arquivo físico
+        // e a linha em `blobs` devem continuar existindo.
+        assert!(caminho_blob.exists(), "arquivo físico não deveria ser removido enquanto outro anexo o referencia");
+        let blob = obter_blob(&conn, &hash).unwrap().expect("blob ainda deveria existir");
+        assert_eq!(blob.contagem_referencias, 1);
+
+        remover_anexo(dir_app.path(), &conn, &anexo_b).unwrap();
+
+        // Agora sim: última referência removida, arquivo e linha somem.
+        assert!(!caminho_blob.exists(), "arquivo físico deveria ser removido após a última referência sair");
+        assert!(obter_blob(&conn, &hash).unwrap().is_none(), "linha do blob deveria ser removida quando a contagem chega a zero");
+    }
+
+    #[test]
+    fn anexos_com_conteudos_diferentes_geram_blobs_separados() {
+        let conn = banco_migrado();
+        let dir_app = TempDir::new().unwrap();
+
+        let origem_a = dir_app.path().join("a.txt");
+        let origem_b = dir_app.path().join("b.txt");
+        fs::write(&origem_a, b"conteudo a").unwrap();
+        fs::write(&origem_b, b"conteudo b, diferente").unwrap();
+
+        let anexo_a = salvar_anexo(dir_app.path(), &conn, &origem_a, Some("item-1"), None).unwrap();
+        let anexo_b = salvar_anexo(dir_app.path(), &conn, &origem_b, Some("item-2"), None).unwrap();
+
+        assert_ne!(anexo_a.hash_sha256, anexo_b.hash_sha256);
+        assert_ne!(anexo_a.caminho_interno, anexo_b.caminho_interno);
+
+        let blob_a = obter_blob(&conn, &anexo_a.hash_sha256.clone().unwrap()).unwrap().unwrap();
+        let blob_b = obter_blob(&conn, &anexo_b.hash_sha256.clone().unwrap()).unwrap().unwrap();
+        assert_eq!(blob_a.contagem_referencias, 1);
+        assert_eq!(blob_b.contagem_referencias, 1);
+    }
+}
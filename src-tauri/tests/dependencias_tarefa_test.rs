@@ -0,0 +1,72 @@
+/**
+ * Testes de integração para o grafo de dependências entre tarefas de
+ * checklist (`queries::adicionar_dependencia`/`alcancavel`).
+ *
+ * Por quê? `adicionar_dependencia` percorre o grafo existente para recusar
+ * arestas que fechariam um ciclo antes do INSERT — é uma checagem de grafo,
+ * não uma constraint de schema, então nada além de leitura manual do código
+ * garantia que a travessia realmente barra um ciclo (em vez de, por
+ * exemplo, só checar o vizinho direto). Deixar uma tarefa bloquear a si
+ * mesma transitivamente travaria o checklist inteiro sem nenhum jeito de
+ * concluir qualquer uma das tarefas do ciclo.
+ */
+
+#[cfg(test)]
+mod testes_dependencias_tarefa {
+    use rusqlite::Connection;
+    use vaultcraft_lib::db::migrations::executar_migracoes;
+    use vaultcraft_lib::db::queries::{adicionar_dependencia, listar_dependencias, remover_dependencia};
+
+    fn banco_migrado() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        executar_migracoes(&conn).expect("migrações devem rodar sem erro em um banco novo");
+        conn
+    }
+
+    #[test]
+    fn dependencia_direta_e_aceita() {
+        let conn = banco_migrado();
+        adicionar_dependencia(&conn, "tarefa-b", "tarefa-a").unwrap();
+
+        assert_eq!(listar_dependencias(&conn, "tarefa-b").unwrap(), vec!["tarefa-a".to_string()]);
+    }
+
+    #[test]
+    fn tarefa_nao_pode_depender_de_si_mesma() {
+        let conn = banco_migrado();
+        assert!(adicionar_dependencia(&conn, "tarefa-a", "tarefa-a").is_err());
+        assert!(listar_dependencias(&conn, "tarefa-a").unwrap().is_empty());
+    }
+
+    #[test]
+    fn dependencia_transitiva_que_fecharia_um_ciclo_e_rejeitada() {
+        let conn = banco_migrado();
+
+        // b depende de a; c depende de b. Fechar a depende de c criaria um
+        // ciclo a -> c -> b -> a, então precisa ser rejeitado mesmo sem
+        // nenhuma aresta direta entre a e c.
+        adicionar_dependencia(&conn, "tarefa-b", "tarefa-a").unwrap();
+        adicionar_dependencia(&conn, "tarefa-c", "tarefa-b").unwrap();
+
+        let resultado = adicionar_dependencia(&conn, "tarefa-a", "tarefa-c");
+        assert!(resultado.is_err(), "dependência que fecha um ciclo transitivo deveria ser rejeitada");
+
+        // A aresta rejeitada não deve ter sido gravada parcialmente.
+        assert!(listar_dependencias(&conn, "tarefa-a").unwrap().is_empty());
+    }
+
+    #[test]
+    fn remover_dependencia_quebra_o_ciclo_e_permite_a_aresta_inversa() {
+        let conn = banco_migrado();
+
+        adicionar_dependencia(&conn, "tarefa-b", "tarefa-a").unwrap();
+        assert!(adicionar_dependencia(&conn, "tarefa-a", "tarefa-b").is_err());
+
+        remover_dependencia(&conn, "tarefa-b", "tarefa-a").unwrap();
+
+        // Com a aresta original removida, o grafo não tem mais ciclo e a
+        // direção oposta passa a ser aceita.
+        adicionar_dependencia(&conn, "tarefa-a", "tarefa-b").unwrap();
+        assert_eq!(listar_dependencias(&conn, "tarefa-a").unwrap(), vec!["tarefa-b".to_string()]);
+    }
+}
@@ -9,6 +9,7 @@
 #[cfg(test)]
 mod testes_migracao {
     use rusqlite::Connection;
+    use vaultcraft_lib::db::migrations::executar_migracoes;
 
     /// SQL das migrações (embarcado como nos serviços reais)
     const MIGRATION_001: &str = include_str!("../src/db/migrations/001_schema_inicial.sql");
@@ -294,4 +295,41 @@ mod testes_migracao {
         );
         assert!(resultado.is_err(), "FK deve rejeitar pasta inexistente");
     }
+
+    /// Testa o sistema de migrações de verdade (`executar_migracoes`), não
+    /// apenas o SQL bruto de 001/002 embutido neste arquivo. Um banco novo
+    /// (user_version 0, o caminho normal de primeira execução) roda 1→mais
+    /// recente numa única transação — se qualquer migração intermediária
+    /// não puder rodar nessa ordem (ex.: auditar um passo antes de
+    /// `log_auditoria` ter as colunas que a auditoria espera), o app nunca
+    /// conseguiria inicializar um cofre novo, e só um teste que chama a
+    /// função real pega isso (ver chunk10-6).
+    #[test]
+    fn executar_migracoes_inicializa_banco_novo_do_zero() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        executar_migracoes(&conn).expect("migrações devem rodar sem erro em um banco novo");
+
+        let versao: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert!(versao >= 11, "Versão final do schema deveria ser >= 11, foi {}", versao);
+
+        let tabelas_esperadas = [
+            "pastas", "itens", "itens_fts", "tags", "anexos", "tarefas_checklist",
+            "log_auditoria", "configuracoes", "revisoes_item", "dependencias_tarefa",
+        ];
+        for tabela in tabelas_esperadas {
+            let existe: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name=?1",
+                    [tabela],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!(existe, "Tabela esperada não existe após migrar: {}", tabela);
+        }
+
+        // Roda de novo sobre o mesmo banco (reabertura do app) — não deve
+        // tentar reaplicar nada nem falhar.
+        executar_migracoes(&conn).expect("reexecutar sobre um banco já migrado não deve falhar");
+    }
 }
@@ -0,0 +1,109 @@
+/**
+ * Testes de integração para a coleta de lixo de chunks órfãos
+ * (`services::backup::podar_chunks_nao_referenciados`).
+ *
+ * Por quê? Backups incrementais fragmentados compartilham um repositório
+ * de chunks content-addressed entre si (ver `services::cdc`); quando um
+ * `.vaultbackup` antigo é podado por `podar_backups_automaticos`, os
+ * chunks que só ele referenciava viram lixo. `podar_chunks_nao_referenciados`
+ * precisa varrer os manifestos dos `.vaultbackup` que *sobraram* no
+ * destino e apagar só o que não aparece em nenhum deles — um bug aqui
+ * tanto poderia apagar um chunk ainda em uso (corrompendo silenciosamente
+ * um backup que parece válido até a restauração) quanto nunca liberar
+ * espaço nenhum.
+ */
+
+#[cfg(test)]
+mod testes_poda_chunks_orfaos {
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use vaultcraft_lib::db::models::{ArquivoFragmentado, ManifestoBackupFragmentado};
+    use vaultcraft_lib::services::backup::podar_chunks_nao_referenciados;
+    use vaultcraft_lib::services::cdc::fragmentar_arquivo;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn escrever_vaultbackup_com_manifesto(destino: &std::path::Path, nome: &str, manifesto: &ManifestoBackupFragmentado) {
+        let manifesto_json = serde_json::to_string_pretty(manifesto).unwrap();
+        let arquivo = fs::File::create(destino.join(nome)).unwrap();
+        let mut zip = ZipWriter::new(arquivo);
+        let opcoes = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("manifesto_chunks.json", opcoes).unwrap();
+        zip.write_all(manifesto_json.as_bytes()).unwrap();
+        zip.finish().unwrap();
+    }
+
+    fn manifesto_vazio(banco: ArquivoFragmentado) -> ManifestoBackupFragmentado {
+        ManifestoBackupFragmentado {
+            id: "manifesto-teste".to_string(),
+            versao_anterior: None,
+            versao_app: "0.1.0".to_string(),
+            versao_schema: 1,
+            data: "2026-01-01T00:00:00Z".to_string(),
+            total_itens: 0,
+            total_anexos: 0,
+            banco,
+            anexos: std::collections::HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn chunks_referenciados_por_backup_retido_sao_preservados() {
+        let dir_chunks = TempDir::new().unwrap();
+        let dir_destino = TempDir::new().unwrap();
+
+        let banco = fragmentar_arquivo(dir_chunks.path(), b"conteudo do banco de dados").unwrap();
+        let manifesto = manifesto_vazio(banco.clone());
+        escrever_vaultbackup_com_manifesto(dir_destino.path(), "vaultcraft_backup_20260101_000000.vaultbackup", &manifesto);
+
+        let removidos = podar_chunks_nao_referenciados(dir_destino.path(), dir_chunks.path()).unwrap();
+
+        assert_eq!(removidos, 0, "nenhum chunk deveria ser removido enquanto o único backup ainda os referencia");
+        for hash in &banco.chunks {
+            let subdir = &hash[0..2];
+            assert!(dir_chunks.path().join(subdir).join(hash).exists(), "chunk referenciado não deveria ter sido removido");
+        }
+    }
+
+    #[test]
+    fn chunks_de_backup_apagado_manualmente_sao_removidos() {
+        let dir_chunks = TempDir::new().unwrap();
+        let dir_destino = TempDir::new().unwrap();
+
+        // Fragmenta um conteúdo e grava um backup que o referencia, depois
+        // apaga o .vaultbackup "manualmente" (ou por `podar_backups_automaticos`)
+        // sem nunca rodar a poda de chunks — simula o cenário real.
+        let banco_antigo = fragmentar_arquivo(dir_chunks.path(), b"versao antiga, grande o bastante para gerar chunks").unwrap();
+        let manifesto_antigo = manifesto_vazio(banco_antigo.clone());
+        let nome_antigo = "vaultcraft_backup_20260101_000000.vaultbackup";
+        escrever_vaultbackup_com_manifesto(dir_destino.path(), nome_antigo, &manifesto_antigo);
+        fs::remove_file(dir_destino.path().join(nome_antigo)).unwrap();
+
+        // Backup mais recente, com conteúdo diferente, é o único que resta.
+        let banco_novo = fragmentar_arquivo(dir_chunks.path(), b"versao nova, conteudo totalmente diferente do anterior").unwrap();
+        let manifesto_novo = manifesto_vazio(banco_novo.clone());
+        escrever_vaultbackup_com_manifesto(dir_destino.path(), "vaultcraft_backup_20260102_000000.vaultbackup", &manifesto_novo);
+
+        let removidos = podar_chunks_nao_referenciados(dir_destino.path(), dir_chunks.path()).unwrap();
+
+        assert!(removidos > 0, "chunks do backup apagado e não referenciado por mais ninguém deveriam ser removidos");
+        for hash in &banco_novo.chunks {
+            assert!(dir_chunks.path().join(&hash[0..2]).join(hash).exists(), "chunk do backup retido não deveria ser removido");
+        }
+        for hash in &banco_antigo.chunks {
+            if !banco_novo.chunks.contains(hash) {
+                assert!(!dir_chunks.path().join(&hash[0..2]).join(hash).exists(), "chunk órfão deveria ter sido removido");
+            }
+        }
+    }
+
+    #[test]
+    fn repositorio_de_chunks_inexistente_nao_falha() {
+        let dir_destino = TempDir::new().unwrap();
+        let dir_chunks_inexistente = dir_destino.path().join("chunks_que_nao_existem");
+
+        let removidos = podar_chunks_nao_referenciados(dir_destino.path(), &dir_chunks_inexistente).unwrap();
+        assert_eq!(removidos, 0);
+    }
+}
@@ -0,0 +1,134 @@
+/**
+ * Testes de integração para a reconciliação de chunks com um destino
+ * remoto (`services::backend_remoto::sincronizar`).
+ *
+ * Por quê? `sincronizar` é o que torna o envio "resumível de graça" (ver
+ * cabeçalho do módulo): ele só deve reenviar os chunks que o remoto ainda
+ * não tem, e nunca apagar nada local ou remoto. Em vez de subir um
+ * servidor HTTP real para testar `BackendHttp`, exercitamos a lógica de
+ * reconciliação (que é o que tem risco real de bug) contra um
+ * `BackendRemoto` falso em memória — o mesmo ponto de extensão que
+ * `BackendHttp` implementa, então o teste cobre exatamente o contrato que
+ * `sincronizar` depende dele cumprir.
+ */
+
+#[cfg(test)]
+mod testes_sincronizacao_remota {
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+    use vaultcraft_lib::services::backend_remoto::{sincronizar, BackendRemoto};
+    use vaultcraft_lib::services::cdc::fragmentar_arquivo;
+
+    /// Backend remoto falso, em memória: guarda os chunks recebidos num
+    /// `HashSet` protegido por mutex (a trait exige `&self`, não `&mut
+    /// self`, pelo mesmo motivo que `BackendHttp` não precisa de
+    /// mutabilidade interna — o estado real mora do outro lado da rede).
+    struct BackendFalso {
+        chunks: Mutex<HashSet<String>>,
+        hashes_que_falham: HashSet<String>,
+    }
+
+    impl BackendFalso {
+        fn vazio() -> Self {
+            Self { chunks: Mutex::new(HashSet::new()), hashes_que_falham: HashSet::new() }
+        }
+
+        fn com_chunks_previos(previos: HashSet<String>) -> Self {
+            Self { chunks: Mutex::new(previos), hashes_que_falham: HashSet::new() }
+        }
+    }
+
+    impl BackendRemoto for BackendFalso {
+        fn enviar_chunk(&self, hash: &str, _dados: &[u8]) -> anyhow::Result<()> {
+            if self.hashes_que_falham.contains(hash) {
+                anyhow::bail!("falha simulada ao enviar chunk {}", hash);
+            }
+            self.chunks.lock().unwrap().insert(hash.to_string());
+            Ok(())
+        }
+
+        fn listar_chunks(&self) -> anyhow::Result<HashSet<String>> {
+            Ok(self.chunks.lock().unwrap().clone())
+        }
+
+        fn buscar_chunk(&self, _hash: &str) -> anyhow::Result<Vec<u8>> {
+            unreachable!("sincronizar não busca chunks, só envia")
+        }
+    }
+
+    #[test]
+    fn envia_apenas_os_chunks_que_o_remoto_ainda_nao_tem() {
+        let dir_chunks = TempDir::new().unwrap();
+        let fragmentado = fragmentar_arquivo(dir_chunks.path(), b"conteudo de teste grande o bastante para fragmentar").unwrap();
+        assert!(!fragmentado.chunks.is_empty());
+
+        // O remoto já tem o primeiro chunk, mas não os demais.
+        let mut previos = HashSet::new();
+        previos.insert(fragmentado.chunks[0].clone());
+        let backend = BackendFalso::com_chunks_previos(previos);
+
+        let resumo = sincronizar(&backend, dir_chunks.path()).unwrap();
+
+        assert_eq!(resumo.total_local, fragmentado.chunks.len());
+        assert_eq!(resumo.ja_no_remoto, 1);
+        assert_eq!(resumo.enviados, fragmentado.chunks.len() - 1);
+        assert!(resumo.falhas.is_empty());
+
+        let chunks_no_remoto = backend.listar_chunks().unwrap();
+        for hash in &fragmentado.chunks {
+            assert!(chunks_no_remoto.contains(hash), "todos os chunks locais deveriam acabar no remoto após sincronizar");
+        }
+    }
+
+    #[test]
+    fn sincronizar_de_novo_nao_reenvia_nada() {
+        let dir_chunks = TempDir::new().unwrap();
+        fragmentar_arquivo(dir_chunks.path(), b"outro conteudo de teste, tambem grande o bastante").unwrap();
+
+        let backend = BackendFalso::vazio();
+        let primeiro = sincronizar(&backend, dir_chunks.path()).unwrap();
+        assert!(primeiro.enviados > 0);
+
+        let segundo = sincronizar(&backend, dir_chunks.path()).unwrap();
+        assert_eq!(segundo.enviados, 0, "chunks já presentes no remoto não deveriam ser reenviados");
+        assert_eq!(segundo.ja_no_remoto, segundo.total_local);
+    }
+
+    /// Gera `tamanho` bytes pseudo-aleatórios determinísticos — maior que
+    /// `cdc::TAMANHO_MAXIMO` (64KiB) para *garantir* ao menos 2 chunks,
+    /// já que nenhum chunk pode exceder esse limite independentemente do
+    /// ponto de corte encontrado (ver `cdc::proximo_ponto_de_corte`).
+    fn conteudo_grande_o_bastante_para_varios_chunks(tamanho: usize) -> Vec<u8> {
+        (0..tamanho).map(|i| ((i as u64).wrapping_mul(2654435761) >> 8) as u8).collect()
+    }
+
+    #[test]
+    fn falha_ao_enviar_um_chunk_nao_impede_o_envio_dos_demais() {
+        let dir_chunks = TempDir::new().unwrap();
+        let conteudo = conteudo_grande_o_bastante_para_varios_chunks(200 * 1024);
+        let fragmentado = fragmentar_arquivo(dir_chunks.path(), &conteudo).unwrap();
+        assert!(fragmentado.chunks.len() >= 2, "conteúdo precisa gerar ao menos 2 chunks para este teste fazer sentido");
+
+        let mut backend = BackendFalso::vazio();
+        backend.hashes_que_falham.insert(fragmentado.chunks[0].clone());
+
+        let resumo = sincronizar(&backend, dir_chunks.path()).unwrap();
+
+        assert_eq!(resumo.falhas.len(), 1);
+        assert_eq!(resumo.falhas[0].0, fragmentado.chunks[0]);
+        assert_eq!(resumo.enviados, fragmentado.chunks.len() - 1);
+    }
+
+    #[test]
+    fn repositorio_local_vazio_sincroniza_sem_erro() {
+        let dir_chunks = TempDir::new().unwrap();
+        let backend = BackendFalso::vazio();
+
+        let resumo = sincronizar(&backend, dir_chunks.path()).unwrap();
+
+        assert_eq!(resumo.total_local, 0);
+        assert_eq!(resumo.enviados, 0);
+        assert!(resumo.falhas.is_empty());
+    }
+}